@@ -0,0 +1,78 @@
+//! Benchmarks of the code run for every entity in every update or frame.
+//! Run with `cargo bench`, criterion compares each run with the previous one
+
+#[macro_use]
+extern crate criterion;
+extern crate rustac;
+
+use criterion::{black_box, BatchSize, Criterion};
+
+use rustac::builder;
+use rustac::ecs::ECS;
+use rustac::gamestate::LocationVec;
+use rustac::gamestate::movement::MoveIntent;
+use rustac::headless::{self, HeadlessOptions};
+use rustac::render;
+
+// a room of 100 x 100 fields: 10000 floor tiles, their walls and the actors on them
+const ROOM_SIZE: u64 = 100;
+const DUMMY_COUNT: u64 = 200;
+const INTENT_COUNT: usize = 5000;
+
+/// A large floor with the player in the middle and attack dummies spread over it
+fn large_world() -> ECS {
+    let mut ecs_ = ECS::new();
+    builder::dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, ROOM_SIZE, ROOM_SIZE);
+    for i in 0..DUMMY_COUNT {
+        let x = 1 + i * 7 % (ROOM_SIZE - 2);
+        let y = 1 + i * 13 % (ROOM_SIZE - 2);
+        builder::dungeon::create_attack_dummy(&mut ecs_, x as f64, y as f64);
+    }
+    builder::dungeon::create_test_player(&mut ecs_, (ROOM_SIZE / 2) as f64, (ROOM_SIZE / 2) as f64);
+    ecs_
+}
+
+fn bench_move_intents(c: &mut Criterion) {
+    let intents: Vec<(MoveIntent, LocationVec)> = (0..INTENT_COUNT).map(|i| {
+        let location = LocationVec { x: (i % 100) as f64, y: (i / 100) as f64 };
+        let intent = if i % 2 == 0 {
+            MoveIntent::Vector(LocationVec { x: 1.0, y: 0.0 }, 1.0)
+        } else {
+            MoveIntent::Position(LocationVec { x: location.x, y: location.y + 1.0 }, 2.0)
+        };
+        (intent, location)
+    }).collect();
+
+    c.bench_function("move_from 5000 intents", |b| b.iter_batched_ref(
+        || intents.clone(),
+        |intents| {
+            for (intent, location) in intents.iter_mut() {
+                *location = intent.move_from(location);
+            }
+        },
+        BatchSize::SmallInput
+    ));
+}
+
+fn bench_render_order(c: &mut Criterion) {
+    let ecs_ = large_world();
+    c.bench_function("render order of 10k entities", |b| b.iter(|| render::render_order(black_box(&ecs_), 1.0)));
+}
+
+fn bench_spatial_queries(c: &mut Criterion) {
+    let ecs_ = large_world();
+    let center = (ROOM_SIZE / 2) as f64;
+    c.bench_function("entities at a location in 10k entities", |b| b.iter(|| {
+        ecs_.get_entities_by_location(black_box(LocationVec { x: center, y: center }))
+    }));
+}
+
+fn bench_update(c: &mut Criterion) {
+    // the same world keeps running, the player waits whenever it is its turn
+    let mut ecs_ = large_world();
+    let options = HeadlessOptions { ticks: Some(1), realtime: false, player_waits: true };
+    c.bench_function("update with 10k entities", |b| b.iter(|| headless::run(&mut ecs_, None, None, options)));
+}
+
+criterion_group!(benches, bench_move_intents, bench_render_order, bench_spatial_queries, bench_update);
+criterion_main!(benches);
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+
+/// Refers to an asset of an `Assets` collection, whether it is loaded or not. Cheaper
+/// to keep and look up than the asset's name
+pub struct Handle<T> {
+    index: usize,
+    _asset: PhantomData<fn() -> T>
+}
+
+// derived impls would require T to implement the traits as well
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Handle<T> {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Handle<T>) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> std::fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Handle({})", self.index)
+    }
+}
+
+/// How long an asset is needed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetScope {
+    // used everywhere, e.g. the player
+    Global,
+    // used by the contents of floors, unloaded when the floor changes and loaded
+    // again once a floor uses it
+    Floor,
+}
+
+enum AssetState<T> {
+    Unloaded,
+    Loaded(T),
+    // loading failed, it is not tried again until the file changes
+    Missing,
+}
+
+struct Entry<T> {
+    name: String,
+    file: String,
+    scope: AssetScope,
+    state: AssetState<T>
+}
+
+/// Assets of one type, e.g. textures, registered by name and loaded from their file
+/// when they are first used. Assets that can not be loaded are replaced by a
+/// placeholder, so a missing file shows up in the game instead of stopping it
+pub struct Assets<T> {
+    dir: PathBuf,
+    loader: fn(&Path) -> Result<T>,
+    entries: Vec<Entry<T>>,
+    by_name: HashMap<String, Handle<T>>,
+    placeholder: Option<Handle<T>>
+}
+
+impl<T> Assets<T> {
+    /// ### Arguments
+    /// * `dir`    - The directory the files of the assets are in
+    /// * `loader` - Loads an asset from its file
+    ///
+    pub fn new(dir: &Path, loader: fn(&Path) -> Result<T>) -> Assets<T> {
+        Assets {
+            dir: dir.to_path_buf(),
+            loader,
+            entries: Vec::new(),
+            by_name: HashMap::new(),
+            placeholder: None
+        }
+    }
+
+    /// Makes an asset known without loading it. Registering a name again replaces
+    /// the file of the asset, its handle stays the same
+    ///
+    /// ### Arguments
+    /// * `name`  - The name the asset is used by, e.g. a `SpriteId`
+    /// * `file`  - Path of the file within the directory of the assets
+    /// * `scope` - How long the asset is needed once loaded
+    ///
+    pub fn register(&mut self, name: &str, file: &str, scope: AssetScope) -> Handle<T> {
+        if let Some(handle) = self.by_name.get(name) {
+            let entry = &mut self.entries[handle.index];
+            entry.file = file.to_string();
+            entry.scope = scope;
+            entry.state = AssetState::Unloaded;
+            return *handle;
+        }
+        let handle = Handle { index: self.entries.len(), _asset: PhantomData };
+        self.entries.push(Entry {
+            name: name.to_string(),
+            file: file.to_string(),
+            scope,
+            state: AssetState::Unloaded
+        });
+        self.by_name.insert(name.to_string(), handle);
+        handle
+    }
+
+    /// Uses a registered asset in place of missing ones
+    pub fn set_placeholder(&mut self, handle: Handle<T>) {
+        self.placeholder = Some(handle);
+    }
+
+    pub fn handle(&self, name: &str) -> Option<Handle<T>> {
+        self.by_name.get(name).copied()
+    }
+
+    /// The asset of a handle, loaded if it is not yet. The placeholder if it can not
+    /// be loaded, None if there is no placeholder either
+    pub fn get(&mut self, handle: Handle<T>) -> Option<&T> {
+        let placeholder = self.placeholder;
+        let index = if self.load(handle) {
+            handle.index
+        } else {
+            match placeholder {
+                Some(placeholder) if placeholder != handle && self.load(placeholder) => placeholder.index,
+                _ => return None
+            }
+        };
+        match &self.entries[index].state {
+            AssetState::Loaded(asset) => Some(asset),
+            _ => None
+        }
+    }
+
+    /// Like `get`, but None for names that were never registered. Callers decide how
+    /// to show them, e.g. warn and draw the placeholder
+    pub fn get_by_name(&mut self, name: &str) -> Option<&T> {
+        let handle = self.handle(name)?;
+        self.get(handle)
+    }
+
+    /// True if loading the asset failed
+    pub fn is_missing(&self, handle: Handle<T>) -> bool {
+        matches!(self.entries[handle.index].state, AssetState::Missing)
+    }
+
+    /// Loads a changed file again for all loaded assets using it. Assets that are not
+    /// loaded load the new file when they are used. If loading fails, the assets keep
+    /// their previous version
+    ///
+    /// ### Returns
+    /// The number of assets using the file
+    pub fn reload(&mut self, file: &str) -> usize {
+        let mut count = 0;
+        for i in 0..self.entries.len() {
+            if self.entries[i].file != file {
+                continue;
+            }
+            count += 1;
+            if !matches!(self.entries[i].state, AssetState::Loaded(_)) {
+                self.entries[i].state = AssetState::Unloaded;
+                continue;
+            }
+            match (self.loader)(&self.dir.join(file)) {
+                Ok(asset) => {
+                    info!("Reloaded {} from {:?}", self.entries[i].name, file);
+                    self.entries[i].state = AssetState::Loaded(asset);
+                },
+                Err(err) => warn!("Kept the previous version of {}: {}", self.entries[i].name, err)
+            }
+        }
+        count
+    }
+
+    /// Frees all loaded assets of a scope, they are loaded again when used
+    pub fn unload(&mut self, scope: AssetScope) {
+        for entry in self.entries.iter_mut().filter(|entry| entry.scope == scope) {
+            entry.state = AssetState::Unloaded;
+        }
+    }
+
+    /// Frees all loaded assets, e.g. because the graphics context they belong to is
+    /// replaced. Missing assets are tried again
+    pub fn unload_all(&mut self) {
+        for entry in self.entries.iter_mut() {
+            entry.state = AssetState::Unloaded;
+        }
+    }
+
+    /// Number of assets currently loaded
+    pub fn loaded_count(&self) -> usize {
+        self.entries.iter().filter(|entry| matches!(entry.state, AssetState::Loaded(_))).count()
+    }
+
+    /// Loads the asset if needed
+    ///
+    /// ### Returns
+    /// True if it is loaded
+    fn load(&mut self, handle: Handle<T>) -> bool {
+        let entry = &mut self.entries[handle.index];
+        if let AssetState::Unloaded = entry.state {
+            entry.state = match (self.loader)(&self.dir.join(&entry.file)) {
+                Ok(asset) => {
+                    debug!("Loaded {} from {:?}", entry.name, entry.file);
+                    AssetState::Loaded(asset)
+                },
+                Err(err) => {
+                    warn!("{} is missing, a placeholder is used: {}", entry.name, err);
+                    AssetState::Missing
+                }
+            };
+        }
+        matches!(entry.state, AssetState::Loaded(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    // "loads" the file name, files starting with "missing" fail
+    fn fake_loader(path: &Path) -> Result<String> {
+        let name = path.file_name().unwrap().to_str().unwrap().to_string();
+        if name.starts_with("missing") {
+            Err(Error::Asset { path: path.to_path_buf(), reason: "not found".to_string() })
+        } else {
+            Ok(name)
+        }
+    }
+
+    #[test]
+    fn test_lazy_loading_and_placeholder() {
+        let mut assets = Assets::new(Path::new("textures"), fake_loader);
+        let default = assets.register("default", "default.png", AssetScope::Global);
+        let tile = assets.register("tile", "tile.png", AssetScope::Floor);
+        let broken = assets.register("broken", "missing.png", AssetScope::Floor);
+        assets.set_placeholder(default);
+        assert_eq!(assets.loaded_count(), 0);
+
+        assert_eq!(assets.get(tile).unwrap(), "tile.png");
+        assert_eq!(assets.get(broken).unwrap(), "default.png");
+        assert!(assets.is_missing(broken));
+        assert!(assets.get_by_name("unknown").is_none());
+        assert_eq!(assets.loaded_count(), 2);
+
+        // leaving the floor frees the tile, it is loaded again when used
+        assets.unload(AssetScope::Floor);
+        assert_eq!(assets.loaded_count(), 1);
+        assert_eq!(assets.get(tile).unwrap(), "tile.png");
+
+        // a fixed file is picked up once it changes
+        assert_eq!(assets.register("broken", "fixed.png", AssetScope::Floor), broken);
+        assert_eq!(assets.get(broken).unwrap(), "fixed.png");
+    }
+}
@@ -0,0 +1,289 @@
+pub mod music;
+pub mod sound;
+
+extern crate rodio;
+
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use rodio::{Decoder, OutputStream, OutputStreamHandle, SpatialSink};
+
+use crate::config::VolumeSettings;
+use crate::ecs;
+use crate::event::bus::GameEvent;
+use crate::gamestate::LocationVec;
+use sound::{SoundCategory, SoundRegistry};
+
+// hard limit of sounds playing at the same time, further sounds are dropped
+const MAX_VOICES: usize = 16;
+// distance in fields at which sounds become inaudible
+const HEARING_DISTANCE: f64 = 16.0;
+// ears of the listener, sounds are placed on a circle around them to pan them
+const LEFT_EAR: [f32; 3] = [-0.2, 0.0, 0.0];
+const RIGHT_EAR: [f32; 3] = [0.2, 0.0, 0.0];
+const CENTER: [f32; 3] = [0.0, 0.0, 1.0];
+
+/// A playing sound
+struct Voice {
+    category: SoundCategory,
+    // volume factor due to the distance of the emitter
+    attenuation: f32,
+    sink: SpatialSink
+}
+
+/// Plays sound effects in reaction to game events and the music fitting the current
+/// situation. Without an audio device the system stays silent but can still be used
+pub struct AudioSystem {
+    // the stream has to be kept alive for anything to be heard
+    _stream: Option<OutputStream>,
+    handle: Option<OutputStreamHandle>,
+    registry: SoundRegistry,
+    // currently playing sounds
+    voices: Vec<Voice>,
+    music: music::MusicPlayer,
+    master_volume: f32,
+    volumes: HashMap<SoundCategory, f32>
+}
+
+impl AudioSystem {
+    pub fn new(registry: SoundRegistry) -> AudioSystem {
+        let (stream, handle) = match OutputStream::try_default() {
+            Ok((stream, handle)) => (Some(stream), Some(handle)),
+            Err(err) => {
+                warn!("No audio output available, sound is disabled: {}", err);
+                (None, None)
+            }
+        };
+
+        AudioSystem {
+            _stream: stream,
+            handle,
+            registry,
+            voices: Vec::new(),
+            music: music::MusicPlayer::new(),
+            master_volume: 1.0,
+            volumes: HashMap::new()
+        }
+    }
+
+    /// The sounds that can be played, e.g. to load changed files again
+    pub fn registry_mut(&mut self) -> &mut SoundRegistry {
+        &mut self.registry
+    }
+
+    pub fn master_volume(&self) -> f32 {
+        self.master_volume
+    }
+
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.master_volume = volume.clamp(0.0, 1.0);
+        self.apply_volumes();
+    }
+
+    /// Volume of a category between 0 and 1, categories start at full volume
+    pub fn volume(&self, category: SoundCategory) -> f32 {
+        *self.volumes.get(&category).unwrap_or(&1.0)
+    }
+
+    /// Sets the volume of a category, also for sounds which are already playing
+    pub fn set_volume(&mut self, category: SoundCategory, volume: f32) {
+        self.volumes.insert(category, volume.clamp(0.0, 1.0));
+        self.apply_volumes();
+    }
+
+    /// Takes over all volumes from the settings
+    pub fn set_volumes(&mut self, volume: &VolumeSettings) {
+        let volumes = [
+            (SoundCategory::Effects, volume.effects),
+            (SoundCategory::Footsteps, volume.footsteps),
+            (SoundCategory::Music, volume.music),
+            (SoundCategory::Interface, volume.interface)
+        ];
+        let changed = self.master_volume != volume.master
+            || volumes.iter().any(|(category, volume)| self.volume(*category) != *volume);
+        if !changed {
+            return;
+        }
+
+        self.master_volume = volume.master.clamp(0.0, 1.0);
+        for (category, volume) in volumes.iter() {
+            self.volumes.insert(*category, volume.clamp(0.0, 1.0));
+        }
+        self.apply_volumes();
+    }
+
+    /// Plays a registered sound once, centered and at full volume
+    ///
+    /// ### Arguments
+    /// * `id` - The id the sound was registered with
+    ///
+    /// ### Returns
+    /// True if the sound is playing, false if it is unknown, could not be decoded
+    /// or all voices are in use
+    pub fn play(&mut self, id: &str) -> bool {
+        self.play_spatial(id, CENTER, 1.0)
+    }
+
+    /// Plays a registered sound once, coming from a location in the dungeon. It is
+    /// attenuated by the distance to the listener and panned by its direction
+    ///
+    /// ### Arguments
+    /// * `id`       - The id the sound was registered with
+    /// * `emitter`  - Location the sound comes from
+    /// * `listener` - Location the sound is heard at
+    ///
+    /// ### Returns
+    /// True if the sound is playing, false if it is out of hearing distance or could
+    /// not be played
+    pub fn play_at(&mut self, id: &str, emitter: LocationVec, listener: LocationVec) -> bool {
+        let offset = emitter - listener;
+        let distance = (offset.x * offset.x + offset.y * offset.y).sqrt();
+        let attenuation = attenuation(distance);
+        if attenuation <= 0.0 {
+            return false;
+        }
+        self.play_spatial(id, direction(offset), attenuation)
+    }
+
+    fn play_spatial(&mut self, id: &str, position: [f32; 3], attenuation: f32) -> bool {
+        self.voices.retain(|voice| !voice.sink.empty());
+        if self.voices.len() >= MAX_VOICES {
+            debug!("All voices in use, dropping sound {}", id);
+            return false;
+        }
+
+        let handle = match &self.handle {
+            Some(handle) => handle,
+            None => return false
+        };
+        let sound = match self.registry.get(id) {
+            Some(sound) => sound,
+            None => {
+                debug!("Unknown sound {}", id);
+                return false;
+            }
+        };
+        let source = match Decoder::new(Cursor::new(sound.data.clone())) {
+            Ok(source) => source,
+            Err(err) => {
+                warn!("Could not decode sound {}: {}", id, err);
+                return false;
+            }
+        };
+        let sink = match SpatialSink::try_new(handle, position, LEFT_EAR, RIGHT_EAR) {
+            Ok(sink) => sink,
+            Err(err) => {
+                warn!("Could not play sound {}: {}", id, err);
+                return false;
+            }
+        };
+
+        sink.set_volume(self.master_volume * self.volume(sound.category) * attenuation);
+        sink.append(source);
+        self.voices.push(Voice { category: sound.category, attenuation, sink });
+        true
+    }
+
+    /// Plays the sounds belonging to the events of one update. Sounds of entities are
+    /// heard from the location of the listener
+    ///
+    /// ### Arguments
+    /// * `ecs_`     - The entity component system the events happened in
+    /// * `events`   - The events as returned by `gamelogic::process_events`
+    /// * `listener` - The entity hearing the sounds, usually the one the camera follows
+    ///
+    pub fn play_events(&mut self, ecs_: &mut ecs::ECS, events: &[GameEvent], listener: Option<ecs::Entity>) {
+        let listener_location = listener.and_then(|listener| ecs_.location_component.get(listener))
+                                        .map(|location_c| location_c.location);
+        for event in events {
+            let (id, emitter) = match sound_for_event(event) {
+                Some(sound) => sound,
+                None => continue
+            };
+            let emitter_location = emitter.and_then(|emitter| ecs_.location_component.get(emitter))
+                                          .map(|location_c| location_c.location);
+            match (emitter_location, listener_location) {
+                (Some(emitter), Some(listener)) => self.play_at(id, emitter, listener),
+                _ => self.play(id)
+            };
+        }
+    }
+
+    /// Switches the music to the track fitting the current situation, pauses it together
+    /// with the game and advances crossfades
+    ///
+    /// ### Arguments
+    /// * `ecs_` - The entity component system to select the track for
+    /// * `dt`   - Passed time in seconds
+    ///
+    pub fn update_music(&mut self, ecs_: &mut ecs::ECS, dt: f32) {
+        if let Some(handle) = &self.handle {
+            let track = music::select_track(ecs_);
+            self.music.play(handle, &self.registry, track);
+        }
+        self.music.set_paused(ecs_.paused);
+        self.music.update(dt, self.master_volume * self.volume(SoundCategory::Music));
+    }
+
+    fn apply_volumes(&self) {
+        for voice in &self.voices {
+            voice.sink.set_volume(self.master_volume * self.volume(voice.category) * voice.attenuation);
+        }
+    }
+}
+
+/// Maps an event to the id of the sound played for it and the entity the sound comes from,
+/// None if the event has no sound
+fn sound_for_event(event: &GameEvent) -> Option<(&'static str, Option<ecs::Entity>)> {
+    match event {
+        GameEvent::Damaged { target, .. } => Some(("hit", Some(*target))),
+        GameEvent::Blocked { target, .. } => Some(("block", Some(*target))),
+        GameEvent::Died { entity, .. } => Some(("death", Some(*entity))),
+        GameEvent::Stepped { entity } => Some(("footstep", Some(*entity))),
+        GameEvent::Looted { looter, .. } => Some(("pickup", Some(*looter))),
+        GameEvent::PickedUp { entity } | GameEvent::Dropped { entity } => Some(("pickup", Some(*entity))),
+        GameEvent::UiClick => Some(("click", None)),
+        GameEvent::ItemUsed { .. } | GameEvent::FloorChanged | GameEvent::Descended { .. } | GameEvent::RunEnded { .. } => None
+    }
+}
+
+/// Volume factor for a sound at a distance in fields, falling to zero at the hearing
+/// distance. Off-screen sounds are still heard, but quietly
+pub fn attenuation(distance: f64) -> f32 {
+    let linear = (1.0 - distance / HEARING_DISTANCE).clamp(0.0, 1.0);
+    (linear * linear) as f32
+}
+
+/// Position of an emitter around the listener's ears, in the direction of the offset
+fn direction(offset: LocationVec) -> [f32; 3] {
+    let length = (offset.x * offset.x + offset.y * offset.y).sqrt();
+    if length == 0.0 {
+        return CENTER;
+    }
+    // left and right of the screen pan between the ears, sounds above or below the
+    // listener are placed in front and behind it
+    [(offset.x / length) as f32, 0.0, (-offset.y / length) as f32]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attenuation() {
+        assert_eq!(attenuation(0.0), 1.0);
+        assert!(attenuation(4.0) > attenuation(8.0));
+        assert!(attenuation(HEARING_DISTANCE - 1.0) > 0.0);
+        assert_eq!(attenuation(HEARING_DISTANCE), 0.0);
+        assert_eq!(attenuation(HEARING_DISTANCE * 2.0), 0.0);
+    }
+
+    #[test]
+    fn test_direction_pans() {
+        let right = direction(LocationVec { x: 3.0, y: 0.0 });
+        let left = direction(LocationVec { x: -3.0, y: 0.0 });
+        assert!(right[0] > 0.0);
+        assert!(left[0] < 0.0);
+        assert_eq!(direction(LocationVec { x: 0.0, y: 0.0 }), CENTER);
+    }
+}
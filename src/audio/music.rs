@@ -0,0 +1,180 @@
+extern crate rodio;
+
+use std::io::Cursor;
+
+use rodio::{Decoder, OutputStreamHandle, Sink, Source};
+
+use crate::ecs;
+use crate::gamelogic;
+use crate::gamestate::{dungeon::FloorType, mode::{GameMode, TransitionTarget}};
+use crate::audio::sound::{SoundId, SoundRegistry};
+
+// seconds it takes to fade from one track to the next
+const CROSSFADE_SECONDS: f32 = 2.0;
+
+/// A playing music track
+struct Track {
+    id: SoundId,
+    sink: Sink,
+    // fade level between 0.0 (silent) and 1.0
+    level: f32
+}
+
+/// Plays looping music tracks and crossfades between them. The current track fades in
+/// while all previous tracks fade out
+pub struct MusicPlayer {
+    current: Option<Track>,
+    fading: Vec<Track>,
+    paused: bool
+}
+
+impl MusicPlayer {
+    pub fn new() -> MusicPlayer {
+        MusicPlayer {
+            current: None,
+            fading: Vec::new(),
+            paused: false
+        }
+    }
+
+    /// Id of the track currently playing or fading in
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_ref().map(|track| track.id.as_str())
+    }
+
+    /// Switches to another track, crossfading from the current one. Switching to the
+    /// track already playing does nothing
+    ///
+    /// ### Arguments
+    /// * `handle`   - The output to play on
+    /// * `registry` - The registry the track is looked up in
+    /// * `id`       - Id of the track
+    ///
+    pub fn play(&mut self, handle: &OutputStreamHandle, registry: &SoundRegistry, id: &str) {
+        if self.current() == Some(id) {
+            return;
+        }
+        self.stop();
+
+        // a track which is still fading out is faded in again instead of restarting it
+        if let Some(index) = self.fading.iter().position(|track| track.id == id) {
+            self.current = Some(self.fading.remove(index));
+            return;
+        }
+
+        let sound = match registry.get(id) {
+            Some(sound) => sound,
+            None => {
+                debug!("Unknown music track {}", id);
+                return;
+            }
+        };
+        let source = match Decoder::new(Cursor::new(sound.data.clone())) {
+            Ok(source) => source,
+            Err(err) => {
+                warn!("Could not decode music track {}: {}", id, err);
+                return;
+            }
+        };
+        let sink = match Sink::try_new(handle) {
+            Ok(sink) => sink,
+            Err(err) => {
+                warn!("Could not play music track {}: {}", id, err);
+                return;
+            }
+        };
+        sink.set_volume(0.0);
+        sink.append(source.repeat_infinite());
+        if self.paused {
+            sink.pause();
+        }
+        self.current = Some(Track { id: id.to_string(), sink, level: 0.0 });
+    }
+
+    /// Fades out the current track
+    pub fn stop(&mut self) {
+        if let Some(track) = self.current.take() {
+            self.fading.push(track);
+        }
+    }
+
+    /// Pauses or resumes all tracks, fading is paused as well
+    pub fn set_paused(&mut self, paused: bool) {
+        if self.paused == paused {
+            return;
+        }
+        self.paused = paused;
+        for track in self.current.iter().chain(self.fading.iter()) {
+            if paused {
+                track.sink.pause();
+            } else {
+                track.sink.play();
+            }
+        }
+    }
+
+    /// Advances the crossfade
+    ///
+    /// ### Arguments
+    /// * `dt`     - Passed time in seconds
+    /// * `volume` - Volume of the music at full fade level
+    ///
+    pub fn update(&mut self, dt: f32, volume: f32) {
+        let step = if self.paused { 0.0 } else { dt / CROSSFADE_SECONDS };
+
+        if let Some(track) = &mut self.current {
+            track.level = (track.level + step).min(1.0);
+            track.sink.set_volume(track.level * volume);
+        }
+        for track in &mut self.fading {
+            track.level = (track.level - step).max(0.0);
+            track.sink.set_volume(track.level * volume);
+        }
+        self.fading.retain(|track| {
+            if track.level <= 0.0 {
+                track.sink.stop();
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+impl Default for MusicPlayer {
+    fn default() -> MusicPlayer {
+        MusicPlayer::new()
+    }
+}
+
+/// Selects the track fitting the current situation. During a transition to another
+/// mode the track of that mode is selected, so the music fades along with the screen
+///
+/// ### Arguments
+/// * `ecs_` - The entity component system to select for
+///
+/// ### Returns
+/// Id of the track
+pub fn select_track(ecs_: &mut ecs::ECS) -> &'static str {
+    let mode = match ecs_.transition.as_ref().and_then(|transition| transition.target.as_ref()) {
+        Some(TransitionTarget::Mode(mode)) => *mode,
+        _ => ecs_.game_mode
+    };
+    if mode != GameMode::Dungeon {
+        return "music_menu";
+    }
+
+    if let Some(player) = ecs_.get_player_entity() {
+        let boss_in_view = gamelogic::visible_hostiles(ecs_, player).into_iter()
+            .any(|entity| ecs_.boss_component.get(entity).is_some());
+        if boss_in_view {
+            return "music_boss";
+        }
+    }
+
+    match ecs_.floor_type {
+        FloorType::Dungeon => "music_dungeon",
+        FloorType::Overworld => "music_overworld",
+        FloorType::Town => "music_town"
+    }
+}
@@ -0,0 +1,109 @@
+extern crate serde;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Serialize, Deserialize};
+
+pub type SoundId = String;
+
+/// Sounds are grouped into categories, each with its own volume
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SoundCategory {
+    // combat and other things happening in the dungeon
+    Effects,
+    Footsteps,
+    Music,
+    // menus, maps and other parts of the interface
+    Interface
+}
+
+/// A loaded sound file. The data is kept encoded and decoded again for every playback,
+/// which keeps short sound effects cheap to store
+pub struct Sound {
+    pub data: Arc<[u8]>,
+    pub category: SoundCategory
+}
+
+/// All sounds which can be played, looked up by id
+pub struct SoundRegistry {
+    sounds: HashMap<SoundId, Sound>,
+    // file and category every sound was registered with, to load it again
+    files: HashMap<SoundId, (String, SoundCategory)>
+}
+
+impl SoundRegistry {
+    pub fn new() -> SoundRegistry {
+        SoundRegistry {
+            sounds: HashMap::new(),
+            files: HashMap::new()
+        }
+    }
+
+    /// Loads a sound file from `assets/sounds/` and registers it. Missing files are
+    /// only logged, the sound then simply stays silent
+    ///
+    /// ### Arguments
+    /// * `id`       - The id the sound is played by
+    /// * `file`     - Name of the file within `assets/sounds/`
+    /// * `category` - The category whose volume applies to the sound
+    ///
+    pub fn register(&mut self, id: &str, file: &str, category: SoundCategory) {
+        self.files.insert(id.to_string(), (file.to_string(), category));
+        let path = Path::new("./assets/sounds/").join(file);
+        match fs::read(&path) {
+            Ok(data) => {
+                self.sounds.insert(id.to_string(), Sound { data: data.into(), category });
+            },
+            Err(err) => warn!("Could not load sound {:?}: {}", path, err)
+        }
+    }
+
+    /// Loads a sound file again for all sounds registered with it. Sounds already
+    /// playing are not affected
+    ///
+    /// ### Arguments
+    /// * `file` - Name of the changed file within `assets/sounds/`
+    ///
+    pub fn reload(&mut self, file: &str) {
+        let sounds: Vec<(SoundId, SoundCategory)> = self.files.iter()
+            .filter(|(_, (sound_file, _))| sound_file == file)
+            .map(|(id, (_, category))| (id.clone(), *category))
+            .collect();
+        for (id, category) in sounds {
+            info!("Reloading sound {}", id);
+            self.register(&id, file, category);
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Sound> {
+        self.sounds.get(id)
+    }
+}
+
+impl Default for SoundRegistry {
+    fn default() -> SoundRegistry {
+        SoundRegistry::new()
+    }
+}
+
+pub fn setup_sound_registry() -> SoundRegistry {
+    let mut registry = SoundRegistry::new();
+
+    registry.register("hit", "hit.wav", SoundCategory::Effects);
+    registry.register("block", "block.wav", SoundCategory::Effects);
+    registry.register("death", "death.wav", SoundCategory::Effects);
+    registry.register("pickup", "pickup.wav", SoundCategory::Effects);
+    registry.register("footstep", "footstep.wav", SoundCategory::Footsteps);
+    registry.register("click", "click.wav", SoundCategory::Interface);
+
+    registry.register("music_menu", "music/menu.wav", SoundCategory::Music);
+    registry.register("music_dungeon", "music/dungeon.wav", SoundCategory::Music);
+    registry.register("music_overworld", "music/overworld.wav", SoundCategory::Music);
+    registry.register("music_town", "music/town.wav", SoundCategory::Music);
+    registry.register("music_boss", "music/boss.wav", SoundCategory::Music);
+
+    registry
+}
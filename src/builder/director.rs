@@ -0,0 +1,229 @@
+extern crate rand;
+extern crate serde;
+
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+
+use crate::builder::{dungeon, spawn::{self, SpawnFn, SpawnTable}};
+use crate::ecs;
+use crate::gamelogic::{self, collision::CollisionMap};
+use crate::gamestate::{dungeon::{DungeonElement, FloorType}, LocationVec};
+
+// fields of floor per creature spawned with a new floor, at normal difficulty
+const FIELDS_PER_CREATURE: f64 = 20.0;
+// creatures never spawn this close to a player, in fields
+const SAFE_DISTANCE: f64 = 4.0;
+// turns between two reinforcements
+const REINFORCEMENT_TURNS: u32 = 40;
+// reinforcements arriving on one floor at most, at normal difficulty
+const MAX_REINFORCEMENTS: u32 = 4;
+
+/// A creature of an `EncounterTable`, whose weight changes with the depth of the floor
+pub struct Encounter {
+    pub spawn: SpawnFn,
+    // weight on the first floor it appears on
+    pub weight: u32,
+    // weight added for every floor below that, negative for creatures getting rarer
+    pub weight_per_floor: i32,
+    // the first floor it appears on, 0 is the first floor of the dungeon
+    pub min_floor: i32
+}
+
+impl Encounter {
+    /// The weight of the encounter on a floor, 0 where it does not appear
+    pub fn weight_at(&self, floor: i32) -> u32 {
+        if floor < self.min_floor {
+            return 0;
+        }
+        (self.weight as i64 + self.weight_per_floor as i64 * (floor - self.min_floor) as i64).max(0) as u32
+    }
+}
+
+/// The creatures that can be encountered in the dungeon, weighted by depth
+pub struct EncounterTable {
+    pub encounters: Vec<Encounter>
+}
+
+impl EncounterTable {
+    pub fn new() -> EncounterTable {
+        EncounterTable {
+            encounters: Vec::new()
+        }
+    }
+
+    pub fn add(&mut self, spawn: SpawnFn, weight: u32, weight_per_floor: i32, min_floor: i32) {
+        self.encounters.push(Encounter {
+            spawn,
+            weight,
+            weight_per_floor,
+            min_floor
+        });
+    }
+
+    /// The spawn table of a floor, with the weights of the encounters at its depth
+    pub fn for_floor(&self, floor: i32) -> SpawnTable {
+        let mut table = SpawnTable::new();
+        for encounter in &self.encounters {
+            table.add(encounter.spawn, encounter.weight_at(floor), None);
+        }
+        table
+    }
+}
+
+impl Default for EncounterTable {
+    fn default() -> EncounterTable {
+        EncounterTable::new()
+    }
+}
+
+/// The encounters of the dungeon: rats get rarer the deeper the players go, skeletons
+/// appear from the second floor on and become the most common creature
+pub fn dungeon_encounters() -> EncounterTable {
+    let mut table = EncounterTable::new();
+    table.add(dungeon::create_rat, 10, -2, 0);
+    table.add(dungeon::create_skeleton, 2, 3, 1);
+    table
+}
+
+/// Decides when the dungeon sends reinforcements to the current floor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpawnDirector {
+    // creatures arrive over time, out of sight of the players
+    pub reinforcements: bool,
+    // turns until the next reinforcement arrives
+    pub countdown: u32,
+    // reinforcements arrived on the current floor
+    pub arrived: u32
+}
+
+impl Default for SpawnDirector {
+    fn default() -> SpawnDirector {
+        SpawnDirector {
+            reinforcements: true,
+            countdown: REINFORCEMENT_TURNS,
+            arrived: 0
+        }
+    }
+}
+
+/// The depth of the current floor, the deepest any player went
+pub fn current_floor(ecs_: &ecs::ECS) -> i32 {
+    ecs_.allocator.live_indices()
+        .into_iter()
+        .filter_map(|entity| ecs_.player_component.get(entity).map(|player_c| player_c.stage_level))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Finds the empty fields of floor creatures can be placed on, away from the players
+///
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `hidden` - Only fields out of view of all players
+///
+fn spawn_fields(ecs_: &ecs::ECS, hidden: bool) -> Vec<LocationVec> {
+    let players = gamelogic::lod::focus(ecs_);
+    let collision_map = CollisionMap::build(ecs_);
+    let min_distance = if hidden { gamelogic::view_radius(ecs_).max(SAFE_DISTANCE) } else { SAFE_DISTANCE };
+    ecs_.allocator.live_indices()
+        .into_iter()
+        .filter(|entity| ecs_.dungeon_component.get(*entity).is_some_and(|dungeon_c| dungeon_c.type_ == DungeonElement::Floor))
+        .filter_map(|entity| ecs_.location_component.get(entity).map(|location_c| location_c.location))
+        .filter(|field| collision_map.is_free(*field))
+        .filter(|field| players.iter().all(|player| ecs_.diagonal_rule.distance(player, field) > min_distance))
+        .collect()
+}
+
+/// Places creatures of a spawn table onto random fields
+///
+/// ### Returns
+/// The number of creatures spawned
+fn spawn_on_fields(ecs_: &mut ecs::ECS, table: &SpawnTable, mut fields: Vec<LocationVec>, count: usize) -> usize {
+    let mut spawned = 0;
+    while spawned < count && !fields.is_empty() {
+        let field = fields.swap_remove(ecs_.rng.gen_range(0..fields.len()));
+        if spawn::spawn_from_table(ecs_, table, field.x, field.y).is_none() {
+            break;
+        }
+        spawned += 1;
+    }
+    spawned
+}
+
+/// Populates a new floor with creatures of the dungeon's encounters at its depth. How
+/// many depends on the size of the floor and the difficulty, none spawn close to the
+/// players. The reinforcements of the floor start over
+///
+/// ### Returns
+/// The number of creatures spawned
+pub fn populate_floor(ecs_: &mut ecs::ECS) -> usize {
+    ecs_.spawn_director = SpawnDirector { reinforcements: ecs_.spawn_director.reinforcements, ..SpawnDirector::default() };
+    let floor = current_floor(ecs_);
+    let fields = spawn_fields(ecs_, false);
+    let count = (fields.len() as f64 / FIELDS_PER_CREATURE * ecs_.difficulty.spawn_density()).round() as usize;
+    let spawned = spawn_on_fields(ecs_, &dungeon_encounters().for_floor(floor), fields, count);
+    debug!("Spawned {} creatures on floor {}", spawned, floor);
+    spawned
+}
+
+/// Counts down to the next reinforcement by one turn and lets it arrive somewhere the
+/// players can not see. Only dungeons send reinforcements, a limited number per floor
+pub fn trickle_reinforcements(ecs_: &mut ecs::ECS) {
+    let max_arrivals = (MAX_REINFORCEMENTS as f64 * ecs_.difficulty.spawn_density()).round() as u32;
+    let director = &mut ecs_.spawn_director;
+    if !director.reinforcements || director.arrived >= max_arrivals || ecs_.floor_type != FloorType::Dungeon {
+        return;
+    }
+    director.countdown = director.countdown.saturating_sub(1);
+    if director.countdown > 0 {
+        return;
+    }
+    director.countdown = REINFORCEMENT_TURNS;
+    let fields = spawn_fields(ecs_, true);
+    let table = dungeon_encounters().for_floor(current_floor(ecs_));
+    if spawn_on_fields(ecs_, &table, fields, 1) > 0 {
+        ecs_.spawn_director.arrived += 1;
+        debug!("Reinforcement {} arrived", ecs_.spawn_director.arrived);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Difficulty;
+
+    #[test]
+    fn test_deeper_floors_spawn_other_creatures() {
+        let table = dungeon_encounters();
+        assert_eq!(table.encounters[1].weight_at(0), 0);
+        assert!(table.encounters[1].weight_at(4) > table.encounters[0].weight_at(4));
+        assert_eq!(table.encounters[0].weight_at(10), 0);
+
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 30, 20);
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        let spawned = populate_floor(&mut ecs_);
+        let creatures: Vec<ecs::Entity> = ecs_.allocator.live_indices()
+            .into_iter()
+            .filter(|entity| ecs_.npc_behavior_component.get(*entity).is_some_and(|npc_c| npc_c.hostile))
+            .collect();
+        assert!(spawned > 0);
+        assert_eq!(creatures.len(), spawned);
+        assert!(creatures.iter().all(|creature| {
+            let location = ecs_.location_component.get(*creature).unwrap().location;
+            ecs_.diagonal_rule.distance(&location, &LocationVec { x: 1.0, y: 1.0 }) > SAFE_DISTANCE
+        }));
+
+        // more on hard, reinforcements arrive out of view
+        ecs_.difficulty = Difficulty::Hard;
+        assert!(populate_floor(&mut ecs_) > spawned);
+        let before = ecs_.allocator.live_indices();
+        for _ in 0..REINFORCEMENT_TURNS {
+            trickle_reinforcements(&mut ecs_);
+        }
+        let arrived: Vec<ecs::Entity> = ecs_.allocator.live_indices().into_iter().filter(|entity| !before.contains(entity)).collect();
+        assert_eq!((arrived.len(), ecs_.spawn_director.arrived), (1, 1));
+        assert!(!gamelogic::visible_hostiles(&ecs_, player).contains(&arrived[0]));
+    }
+}
@@ -1,107 +1,182 @@
+use std::collections::HashMap;
+
+use crate::builder::director;
 use crate::ecs;
-use crate::gamestate::{components, movement, dungeon, LocationVec};
+use crate::gamelogic::{new_game_plus, party};
+use crate::gamestate::{actor, components, hotbar, interaction::Interaction, item, movement, dungeon, spell, LocationVec};
+use crate::gamestate::party::PartyOrder;
+use crate::gamestate::schedule::{Activity, Schedule, ScheduleEntry};
 use crate::event::{Hitbox, HitboxType};
+use crate::render::{animation::AnimationController, layer::RenderLayer, sprite};
+
+// items a player can carry
+pub const PLAYER_INVENTORY_CAPACITY: i32 = 10;
 
 pub fn tear_down_level(ecs_: &mut ecs::ECS) {
     for entity in ecs_.allocator.live_indices() {
-        if ecs_.dungeon_component.get(entity).is_some() {
+        // doors, chests and items lying around belong to the floor as well
+        if ecs_.dungeon_component.get(entity).is_some() || ecs_.ground_item_component.get(entity).is_some()
+            || (ecs_.interactable_component.get(entity).is_some() && ecs_.health_component.get(entity).is_none()) {
             ecs_.allocator.deallocate(entity);
         }
     }
 }
 
-pub fn create_floor_tile(ecs_: &mut ecs::ECS, x: f64, y: f64) -> ecs::Entity {
-    let entity = ecs_.allocator.allocate();
-    ecs_.dungeon_component.set(entity, components::DungeonComponent {
-        type_: dungeon::DungeonElement::Floor
-    });
-    ecs_.location_component.set(entity, components::LocationComponent {
+fn location(x: f64, y: f64, hitbox: Option<Hitbox>) -> components::LocationComponent {
+    components::LocationComponent {
         location: LocationVec { x, y },
+        previous_location: None,
         direction: movement::Direction::Down,
         move_intent: None,
-        hitbox: None
-    });
-    ecs_.render_component.set(entity, components::RenderComponent {
-        base_sprite: "floor_tile".to_string(),
+        hitbox
+    }
+}
+
+fn render(sprite: &str, render_layer: RenderLayer) -> components::RenderComponent {
+    components::RenderComponent {
+        base_sprite: sprite.to_string(),
         base_sprite_size: 1.0,
-        animation: None,
+        animation: AnimationController::new(),
         visible: true,
-        render_layer: 0
-    });
-    entity
+        render_layer,
+        tint: None,
+        opacity: 1.0,
+        flash: None
+    }
+}
+
+/// Seed of the variant and idle phase of a tile, the same tile always looks the same
+fn tile_seed(x: f64, y: f64) -> u64 {
+    ((x.round() as i64 as u64) << 32) ^ (y.round() as i64 as u64 & 0xFFFF_FFFF)
+}
+
+fn create_tile(ecs_: &mut ecs::ECS, x: f64, y: f64, type_: dungeon::DungeonElement, hitbox: Option<Hitbox>, sprite: &str, render_layer: RenderLayer) -> ecs::Entity {
+    // tiles are everywhere, so their sprites vary and are not animated in lockstep
+    let animation = AnimationController::varied(tile_seed(x, y));
+    let render_c = components::RenderComponent {
+        base_sprite: sprite::variant(sprite, animation.variant()),
+        animation,
+        ..render(sprite, render_layer)
+    };
+    ecs_.spawn()
+        .with(components::DungeonComponent { type_ })
+        .with(location(x, y, hitbox))
+        .with(render_c)
+        .build()
+}
+
+pub fn create_floor_tile(ecs_: &mut ecs::ECS, x: f64, y: f64) -> ecs::Entity {
+    create_tile(ecs_, x, y, dungeon::DungeonElement::Floor, None, "floor_tile", RenderLayer::Floor)
 }
 
 pub fn create_wall_tile(ecs_: &mut ecs::ECS, x: f64, y: f64) -> ecs::Entity {
-    let entity = ecs_.allocator.allocate();
-    ecs_.dungeon_component.set(entity, components::DungeonComponent {
-        type_: dungeon::DungeonElement::Wall
-    });
-    ecs_.location_component.set(entity, components::LocationComponent {
-        location: LocationVec { x, y },
-        direction: movement::Direction::Down,
-        move_intent: None,
-        hitbox: Some(Hitbox::new_small(HitboxType::Wall))
-    });
-    ecs_.render_component.set(entity, components::RenderComponent {
-        base_sprite: "wall_tile".to_string(),
-        base_sprite_size: 1.0,
-        animation: None,
-        visible: true,
-        render_layer: 1
-    });
-    entity
+    create_tile(ecs_, x, y, dungeon::DungeonElement::Wall, Some(Hitbox::new_small(HitboxType::Wall)), "wall_tile", RenderLayer::Floor)
+}
+
+pub fn create_water_tile(ecs_: &mut ecs::ECS, x: f64, y: f64) -> ecs::Entity {
+    create_tile(ecs_, x, y, dungeon::DungeonElement::Water, None, "water_tile", RenderLayer::Floor)
 }
 
 pub fn create_connector_tile(ecs_: &mut ecs::ECS, x: f64, y: f64) -> ecs::Entity {
-    let entity = ecs_.allocator.allocate();
-    ecs_.dungeon_component.set(entity, components::DungeonComponent {
-        type_: dungeon::DungeonElement::Connector
-    });
-    ecs_.location_component.set(entity, components::LocationComponent {
-        location: LocationVec { x, y },
-        direction: movement::Direction::Down,
-        move_intent: None,
-        hitbox: None
-    });
-    entity
+    ecs_.spawn()
+        .with(components::DungeonComponent { type_: dungeon::DungeonElement::Connector })
+        .with(location(x, y, None))
+        .with(components::InteractableComponent { interaction: Interaction::Stairs })
+        .build()
+}
+
+/// Creates a closed door, it blocks like a wall until it is opened
+pub fn create_door(ecs_: &mut ecs::ECS, x: f64, y: f64) -> ecs::Entity {
+    create_interactable(ecs_, x, y, "door", Some(Hitbox::new_small(HitboxType::Wall)), Interaction::Door { open: false })
+}
+
+pub fn create_chest(ecs_: &mut ecs::ECS, x: f64, y: f64, gold: i32, items: Vec<item::Item>) -> ecs::Entity {
+    create_interactable(ecs_, x, y, "chest", Some(Hitbox::new_small(HitboxType::Creature)), Interaction::Chest { gold, items, opened: false })
+}
+
+/// Creates a lever switching a flag of the global state table
+pub fn create_lever(ecs_: &mut ecs::ECS, x: f64, y: f64, flag: &str) -> ecs::Entity {
+    create_interactable(ecs_, x, y, "lever", None, Interaction::Lever { flag: flag.to_string(), pulled: false })
+}
+
+fn create_interactable(ecs_: &mut ecs::ECS, x: f64, y: f64, sprite: &str, hitbox: Option<Hitbox>, interaction: Interaction) -> ecs::Entity {
+    ecs_.spawn()
+        .with(location(x, y, hitbox))
+        .with(render(sprite, RenderLayer::Items))
+        .with(components::InteractableComponent { interaction })
+        .build()
 }
 
 pub fn create_attack_dummy(ecs_: &mut ecs::ECS, x: f64, y: f64) -> ecs::Entity {
-    let entity = ecs_.allocator.allocate();
-    ecs_.location_component.set(entity, components::LocationComponent {
-        location: LocationVec { x, y},
-        direction: movement::Direction::Down,
-        move_intent: None,
-        hitbox: None
-    });
-
-    ecs_.name_component.set(entity, components::NameComponent {
-        name: "Dummy".to_string()
-    });
-
-    ecs_.health_component.set(entity, components::HealthComponent {
-        current: 100,
-        maximum: 100
-    });
-
-    ecs_.basestats_component.set(entity, components::BaseStatsComponent {
-        attack: 5,
-        defense: 5,
-        magic: 5,
-        resistence: 5
-    });
-
-    ecs_.render_component.set(entity, components::RenderComponent {
-        base_sprite: "dummy".to_string(),
-        base_sprite_size: 1.0,
-        animation: None,
-        visible: true,
-        render_layer: 2
-    });
+    ecs_.spawn()
+        .with(location(x, y, None))
+        .with(components::NameComponent { name: "creature.dummy".to_string() })
+        .with(components::HealthComponent { current: 100, maximum: 100 })
+        .with(components::BaseStatsComponent { attack: 5, defense: 5, magic: 5, resistence: 5 })
+        .with(render("dummy", RenderLayer::Actors))
+        .build()
+}
 
-    entity
+/// Creates a hostile creature of the dungeon, see `director::dungeon_encounters`
+fn create_monster(ecs_: &mut ecs::ECS, x: f64, y: f64, name: &str, health: i32, basestats: components::BaseStatsComponent) -> ecs::Entity {
+    // stronger in new-game-plus runs
+    let health = new_game_plus::scale(ecs_, health);
+    let basestats = components::BaseStatsComponent {
+        attack: new_game_plus::scale(ecs_, basestats.attack),
+        defense: new_game_plus::scale(ecs_, basestats.defense),
+        magic: new_game_plus::scale(ecs_, basestats.magic),
+        resistence: new_game_plus::scale(ecs_, basestats.resistence)
+    };
+    ecs_.spawn()
+        .with(location(x, y, Some(Hitbox::new_small(HitboxType::Creature))))
+        .with(components::NameComponent { name: format!("creature.{}", name) })
+        .with(components::HealthComponent { current: health, maximum: health })
+        .with(basestats)
+        .with(components::NpcBehaviorComponent { hostile: true })
+        .with(render(name, RenderLayer::Actors))
+        .build()
+}
+
+/// Creates a rat, weak and common on the first floors
+pub fn create_rat(ecs_: &mut ecs::ECS, x: f64, y: f64) -> ecs::Entity {
+    create_monster(ecs_, x, y, "rat", 20, components::BaseStatsComponent { attack: 4, defense: 1, magic: 0, resistence: 1 })
+}
 
+/// Creates a skeleton, found on deeper floors
+pub fn create_skeleton(ecs_: &mut ecs::ECS, x: f64, y: f64) -> ecs::Entity {
+    create_monster(ecs_, x, y, "skeleton", 50, components::BaseStatsComponent { attack: 8, defense: 4, magic: 0, resistence: 6 })
+}
 
+/// Creates a mercenary fighting alongside the players, following them until ordered
+/// otherwise (see `gamelogic::party`)
+pub fn create_companion(ecs_: &mut ecs::ECS, x: f64, y: f64) -> ecs::Entity {
+    ecs_.spawn()
+        .with(location(x, y, Some(Hitbox::new_small(HitboxType::Creature))))
+        .with(components::NameComponent { name: "creature.mercenary".to_string() })
+        .with(components::HealthComponent { current: 60, maximum: 60 })
+        .with(components::BaseStatsComponent { attack: 6, defense: 6, magic: 0, resistence: 4 })
+        .with(components::NpcBehaviorComponent { hostile: false })
+        .with(components::CompanionComponent { order: PartyOrder::Follow, hold: None, target: None })
+        .with(render("mercenary", RenderLayer::Actors))
+        .build()
+}
+
+/// Creates a shopkeeper, who stands at the counter by day and sleeps at home at night.
+/// The shop is open while they are at the counter during its opening hours
+pub fn create_shopkeeper(ecs_: &mut ecs::ECS, counter: LocationVec, home: LocationVec) -> ecs::Entity {
+    let walking_speed = movement::DEFAULT_SPEED / 2.0;
+    let schedule = Schedule::new(vec![
+        ScheduleEntry { hour: 7.0, goal: counter, speed: walking_speed, activity: Activity::Work },
+        ScheduleEntry { hour: 20.0, goal: home, speed: walking_speed, activity: Activity::Sleep },
+    ]);
+    ecs_.spawn()
+        .with(location(home.x, home.y, Some(Hitbox::new_small(HitboxType::Creature))))
+        .with(render("shopkeeper", RenderLayer::Actors))
+        .with(components::NameComponent { name: "creature.shopkeeper".to_string() })
+        .with(components::NpcBehaviorComponent { hostile: false })
+        .with(components::ShopComponent { open: false, opening_hour: 8.0, closing_hour: 19.0 })
+        .with(components::ScheduleComponent { schedule, current: None })
+        .build()
 }
 
 pub fn create_empty_room(ecs_: &mut ecs::ECS, x: f64, y: f64, xs: u64, ys: u64) {
@@ -133,4 +208,82 @@ pub fn delete_dungeon_entities(ecs_: &mut ecs::ECS, type_: dungeon::DungeonEleme
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Builds the floor used for testing: two connected rooms with an attack dummy
+pub fn create_test_floor(ecs_: &mut ecs::ECS) {
+    create_empty_room(ecs_, 0.0, 0.0, 10, 8);
+    create_empty_room(ecs_, 0.0, 8.0, 8, 10);
+    delete_dungeon_entities(ecs_, dungeon::DungeonElement::Wall, LocationVec { x: 4.0, y: 7.0 });
+    delete_dungeon_entities(ecs_, dungeon::DungeonElement::Wall, LocationVec { x: 4.0, y: 8.0 });
+    create_attack_dummy(ecs_, 3.0, 4.0);
+}
+
+/// Places the players at the entrance of a new floor, their companions in the row
+/// behind them
+fn place_arrivals(ecs_: &mut ecs::ECS, x: f64, y: f64) {
+    let players: Vec<ecs::Entity> = ecs_.allocator.live_indices()
+                                        .into_iter()
+                                        .filter(|entity| ecs_.player_component.get(*entity).is_some())
+                                        .collect();
+    let companions = party::companions(ecs_);
+    for (row, entities) in [players, companions].iter().enumerate() {
+        for (i, entity) in entities.iter().enumerate() {
+            if let Some(location_c) = ecs_.location_component.get_mut(*entity) {
+                location_c.location = LocationVec { x: x + i as f64, y: y + row as f64 };
+                location_c.previous_location = None;
+                location_c.move_intent = None;
+            }
+        }
+    }
+}
+
+/// Builds the floor below the current one, the players arrive at its entrance with
+/// their companions behind them
+pub fn create_next_floor(ecs_: &mut ecs::ECS) {
+    ecs_.floor_type = dungeon::FloorType::Dungeon;
+    create_test_floor(ecs_);
+    create_connector_tile(ecs_, 5.0, 15.0);
+    place_arrivals(ecs_, 1.0, 1.0);
+    director::populate_floor(ecs_);
+}
+
+/// Builds a town: a square with a shop, whose road out leads back to the overworld
+pub fn create_town(ecs_: &mut ecs::ECS) {
+    ecs_.floor_type = dungeon::FloorType::Town;
+    create_empty_room(ecs_, 0.0, 0.0, 14, 10);
+    create_shopkeeper(ecs_, LocationVec { x: 10.0, y: 3.0 }, LocationVec { x: 12.0, y: 8.0 });
+    create_connector_tile(ecs_, 1.0, 8.0);
+    place_arrivals(ecs_, 1.0, 1.0);
+}
+
+/// Creates the player used for testing at the given location
+pub fn create_test_player(ecs_: &mut ecs::ECS, x: f64, y: f64) -> ecs::Entity {
+    let mut slots = vec![None; hotbar::HOTBAR_SLOTS];
+    slots[0] = Some(hotbar::HotbarSlot::Ability(spell::Ability::Shoot));
+    slots[1] = Some(hotbar::HotbarSlot::Consumable);
+
+    ecs_.spawn()
+        .with(components::ActorComponent {
+            state: actor::ActorState::WaitingForTurn,
+            turn: 0,
+            max_actions: 1,
+            performed_actions: 0,
+            cooldown: 0
+        })
+        .with(location(x, y, Some(Hitbox::new_small(HitboxType::Creature))))
+        .with(render("player", RenderLayer::Actors))
+        .with(components::PlayerComponent {
+            stage_level: 0,
+            gold: 0,
+            progression_flags: HashMap::new(),
+            resting: None
+        })
+        .with(components::HealthComponent { current: 100, maximum: 100 })
+        .with(components::HungerComponent { current: 1000, maximum: 1000 })
+        .with(components::BaseStatsComponent { attack: 10, defense: 10, magic: 10, resistence: 10 })
+        .with(components::NameComponent { name: "creature.player".to_string() })
+        .with(components::HotbarComponent { slots })
+        .with(components::InventoryComponent { items: Vec::new(), capacity: PLAYER_INVENTORY_CAPACITY })
+        .build()
+}
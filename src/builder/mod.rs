@@ -1 +1,3 @@
-pub mod dungeon;
\ No newline at end of file
+pub mod director;
+pub mod dungeon;
+pub mod spawn;
\ No newline at end of file
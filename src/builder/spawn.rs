@@ -0,0 +1,95 @@
+use crate::ecs;
+use crate::gamestate::{clock::DayTime, dungeon::FloorType};
+
+extern crate rand;
+use rand::Rng;
+
+/// Creates an entity at the given location (x, y)
+pub type SpawnFn = fn(&mut ecs::ECS, f64, f64) -> ecs::Entity;
+
+/// A possible spawn of a `SpawnTable`
+pub struct SpawnEntry {
+    pub spawn: SpawnFn,
+    pub weight: u32,
+    // only spawn during these times of the day, None for always.
+    // Ignored in dungeons as there is no day and night
+    pub day_times: Option<Vec<DayTime>>
+}
+
+/// Weighted list of entities that can spawn on a floor
+pub struct SpawnTable {
+    pub entries: Vec<SpawnEntry>
+}
+
+impl SpawnTable {
+    pub fn new() -> SpawnTable {
+        SpawnTable {
+            entries: Vec::new()
+        }
+    }
+
+    pub fn add(&mut self, spawn: SpawnFn, weight: u32, day_times: Option<Vec<DayTime>>) {
+        self.entries.push(SpawnEntry {
+            spawn,
+            weight,
+            day_times
+        });
+    }
+
+    /// Picks a random entry of the table weighted by their weight. Outside of dungeons
+    /// only entries allowed at the current time of day are considered
+    ///
+    /// ### Arguments
+    /// * `ecs_` - The entity component system holding world clock, floor type and rng
+    ///
+    /// ### Returns
+    /// The spawn function of the chosen entry, None if no entry is eligible
+    ///
+    pub fn choose(&self, ecs_: &mut ecs::ECS) -> Option<SpawnFn> {
+        let day_time = ecs_.world_clock.day_time();
+        let outside = ecs_.floor_type != FloorType::Dungeon;
+
+        let eligible: Vec<&SpawnEntry> = self.entries.iter()
+            .filter(|entry| entry.weight > 0)
+            .filter(|entry| match &entry.day_times {
+                Some(day_times) if outside => day_times.contains(&day_time),
+                _ => true
+            })
+            .collect();
+
+        let total_weight: u32 = eligible.iter().map(|entry| entry.weight).sum();
+        if total_weight == 0 {
+            return None;
+        }
+
+        let mut roll = ecs_.rng.gen_range(0..total_weight);
+        for entry in eligible {
+            if roll < entry.weight {
+                return Some(entry.spawn);
+            }
+            roll -= entry.weight;
+        }
+        None
+    }
+}
+
+impl Default for SpawnTable {
+    fn default() -> SpawnTable {
+        SpawnTable::new()
+    }
+}
+
+/// Spawns a random entity of the spawn table at the given location
+///
+/// ### Arguments
+/// * `ecs_`  - The entity component system to perform on
+/// * `table` - The table to choose from
+/// * `x`     - The x coordinate of the spawn location
+/// * `y`     - The y coordinate of the spawn location
+///
+/// ### Returns
+/// The spawned entity, None if nothing was eligible to spawn
+///
+pub fn spawn_from_table(ecs_: &mut ecs::ECS, table: &SpawnTable, x: f64, y: f64) -> Option<ecs::Entity> {
+    table.choose(ecs_).map(|spawn| spawn(ecs_, x, y))
+}
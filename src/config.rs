@@ -0,0 +1,843 @@
+extern crate dirs;
+extern crate log;
+extern crate piston;
+extern crate serde;
+extern crate serde_yaml;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use log::LevelFilter;
+use piston::Key;
+use serde::{Serialize, Deserialize};
+
+use crate::error::Result;
+use crate::gamestate::actor::Scheduling;
+use crate::gamestate::item::{Item, ItemType};
+use crate::gamestate::movement::{DiagonalRule, Direction};
+use crate::gamestate::party::PartyOrder;
+use crate::gamestate::spell::Ability;
+use crate::locale::Locale;
+use crate::persistence::format::SaveFormat;
+use crate::render::palette::Palette;
+
+// lowest and highest values of the settings adjusted in steps, the same in the menu
+// and in a settings file edited by hand
+const SCALE_LIMITS: (f64, f64) = (20.0, 300.0);
+const MAX_FPS_LIMITS: (u64, u64) = (10, 240);
+const PICKUP_WEIGHT_LIMITS: (f64, f64) = (0.0, 10.0);
+const MOVE_REPEAT_DELAY_LIMITS: (u64, u64) = (0, 1000);
+
+/// Everything the player can do with a key, keys are bound to actions in the settings
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    MoveUpLeft,
+    MoveUpRight,
+    MoveDownLeft,
+    MoveDownRight,
+    Attack,
+    Wait,
+    Rest,
+    // saved by older versions as `Loot`, which only looted corpses
+    #[serde(alias = "Loot")]
+    Interact,
+    PickUp,
+    Drop,
+    Shoot,
+    CastFirebolt,
+    ConfirmTarget,
+    CancelTarget,
+    Hotbar1,
+    Hotbar2,
+    Hotbar3,
+    Hotbar4,
+    Hotbar5,
+    Hotbar6,
+    Hotbar7,
+    Hotbar8,
+    Hotbar9,
+    Hotbar10,
+    OrderFollow,
+    OrderHold,
+    OrderFocusTarget,
+    OrderRetreat,
+    ToggleMap,
+    ToggleMinimap,
+    Pause,
+    Settings,
+    LoadMenu,
+    ToggleProfiler,
+    ToggleInspector,
+    UndoTurn,
+    CheatGodMode,
+    CheatRevealMap,
+    CheatTeleportToStairs,
+    CheatGrantExperience,
+    CheatGrantGold,
+    CheatGrantItems,
+    CheatSpawnWave,
+    ToggleCheatConsole,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 48] = [
+        InputAction::MoveUp,
+        InputAction::MoveDown,
+        InputAction::MoveLeft,
+        InputAction::MoveRight,
+        InputAction::MoveUpLeft,
+        InputAction::MoveUpRight,
+        InputAction::MoveDownLeft,
+        InputAction::MoveDownRight,
+        InputAction::Attack,
+        InputAction::Wait,
+        InputAction::Rest,
+        InputAction::Interact,
+        InputAction::PickUp,
+        InputAction::Drop,
+        InputAction::Shoot,
+        InputAction::CastFirebolt,
+        InputAction::ConfirmTarget,
+        InputAction::CancelTarget,
+        InputAction::Hotbar1,
+        InputAction::Hotbar2,
+        InputAction::Hotbar3,
+        InputAction::Hotbar4,
+        InputAction::Hotbar5,
+        InputAction::Hotbar6,
+        InputAction::Hotbar7,
+        InputAction::Hotbar8,
+        InputAction::Hotbar9,
+        InputAction::Hotbar10,
+        InputAction::OrderFollow,
+        InputAction::OrderHold,
+        InputAction::OrderFocusTarget,
+        InputAction::OrderRetreat,
+        InputAction::ToggleMap,
+        InputAction::ToggleMinimap,
+        InputAction::Pause,
+        InputAction::Settings,
+        InputAction::LoadMenu,
+        InputAction::ToggleProfiler,
+        InputAction::ToggleInspector,
+        InputAction::UndoTurn,
+        InputAction::CheatGodMode,
+        InputAction::CheatRevealMap,
+        InputAction::CheatTeleportToStairs,
+        InputAction::CheatGrantExperience,
+        InputAction::CheatGrantGold,
+        InputAction::CheatGrantItems,
+        InputAction::CheatSpawnWave,
+        InputAction::ToggleCheatConsole,
+    ];
+
+    /// True for actions of the player's character, which are performed by the server in
+    /// multiplayer games. All other actions only change the local view or menus
+    pub fn is_game_action(&self) -> bool {
+        matches!(self,
+            InputAction::MoveUp | InputAction::MoveDown | InputAction::MoveLeft | InputAction::MoveRight |
+            InputAction::MoveUpLeft | InputAction::MoveUpRight | InputAction::MoveDownLeft | InputAction::MoveDownRight |
+            InputAction::Attack | InputAction::Wait | InputAction::Rest | InputAction::Interact |
+            InputAction::PickUp | InputAction::Drop |
+            InputAction::OrderFollow | InputAction::OrderHold | InputAction::OrderFocusTarget | InputAction::OrderRetreat |
+            InputAction::CheatGodMode | InputAction::CheatRevealMap | InputAction::CheatTeleportToStairs |
+            InputAction::CheatGrantExperience | InputAction::CheatGrantGold | InputAction::CheatGrantItems |
+            InputAction::CheatSpawnWave)
+    }
+
+    /// The ability whose targeting an action starts, None for other actions
+    pub fn ability(&self) -> Option<Ability> {
+        match self {
+            InputAction::Shoot => Some(Ability::Shoot),
+            InputAction::CastFirebolt => Some(Ability::Firebolt),
+            _ => None
+        }
+    }
+
+    /// The hotbar slot an action uses, 0 for the first one, None for other actions
+    pub fn hotbar_slot(&self) -> Option<usize> {
+        match self {
+            InputAction::Hotbar1 => Some(0),
+            InputAction::Hotbar2 => Some(1),
+            InputAction::Hotbar3 => Some(2),
+            InputAction::Hotbar4 => Some(3),
+            InputAction::Hotbar5 => Some(4),
+            InputAction::Hotbar6 => Some(5),
+            InputAction::Hotbar7 => Some(6),
+            InputAction::Hotbar8 => Some(7),
+            InputAction::Hotbar9 => Some(8),
+            InputAction::Hotbar10 => Some(9),
+            _ => None
+        }
+    }
+
+    /// The order to the companions an action gives, None for other actions
+    pub fn party_order(&self) -> Option<PartyOrder> {
+        match self {
+            InputAction::OrderFollow => Some(PartyOrder::Follow),
+            InputAction::OrderHold => Some(PartyOrder::Hold),
+            InputAction::OrderFocusTarget => Some(PartyOrder::FocusTarget),
+            InputAction::OrderRetreat => Some(PartyOrder::Retreat),
+            _ => None
+        }
+    }
+
+    /// The direction of a move action, None for other actions
+    pub fn move_direction(&self) -> Option<Direction> {
+        match self {
+            InputAction::MoveUp => Some(Direction::Up),
+            InputAction::MoveDown => Some(Direction::Down),
+            InputAction::MoveLeft => Some(Direction::Left),
+            InputAction::MoveRight => Some(Direction::Right),
+            InputAction::MoveUpLeft => Some(Direction::UpLeft),
+            InputAction::MoveUpRight => Some(Direction::UpRight),
+            InputAction::MoveDownLeft => Some(Direction::DownLeft),
+            InputAction::MoveDownRight => Some(Direction::DownRight),
+            _ => None
+        }
+    }
+
+    /// The move action into a direction
+    pub fn move_to(direction: Direction) -> InputAction {
+        match direction {
+            Direction::Up => InputAction::MoveUp,
+            Direction::Down => InputAction::MoveDown,
+            Direction::Left => InputAction::MoveLeft,
+            Direction::Right => InputAction::MoveRight,
+            Direction::UpLeft => InputAction::MoveUpLeft,
+            Direction::UpRight => InputAction::MoveUpRight,
+            Direction::DownLeft => InputAction::MoveDownLeft,
+            Direction::DownRight => InputAction::MoveDownRight,
+        }
+    }
+
+    /// Key of the action's name in the locale
+    pub fn locale_key(&self) -> String {
+        format!("action.{:?}", self)
+    }
+
+    fn default_key(&self) -> Key {
+        match self {
+            InputAction::MoveUp => Key::Up,
+            InputAction::MoveDown => Key::Down,
+            InputAction::MoveLeft => Key::Left,
+            InputAction::MoveRight => Key::Right,
+            InputAction::MoveUpLeft => Key::NumPad7,
+            InputAction::MoveUpRight => Key::NumPad9,
+            InputAction::MoveDownLeft => Key::NumPad1,
+            InputAction::MoveDownRight => Key::NumPad3,
+            InputAction::Attack => Key::Space,
+            InputAction::Wait => Key::Period,
+            InputAction::Rest => Key::R,
+            InputAction::Interact => Key::E,
+            InputAction::PickUp => Key::G,
+            InputAction::Drop => Key::D,
+            InputAction::Shoot => Key::F,
+            InputAction::CastFirebolt => Key::C,
+            InputAction::ConfirmTarget => Key::Return,
+            InputAction::CancelTarget => Key::Backspace,
+            InputAction::Hotbar1 => Key::D1,
+            InputAction::Hotbar2 => Key::D2,
+            InputAction::Hotbar3 => Key::D3,
+            InputAction::Hotbar4 => Key::D4,
+            InputAction::Hotbar5 => Key::D5,
+            InputAction::Hotbar6 => Key::D6,
+            InputAction::Hotbar7 => Key::D7,
+            InputAction::Hotbar8 => Key::D8,
+            InputAction::Hotbar9 => Key::D9,
+            InputAction::Hotbar10 => Key::D0,
+            InputAction::OrderFollow => Key::Z,
+            InputAction::OrderHold => Key::X,
+            InputAction::OrderFocusTarget => Key::T,
+            InputAction::OrderRetreat => Key::Q,
+            InputAction::ToggleMap => Key::M,
+            InputAction::ToggleMinimap => Key::Tab,
+            InputAction::Pause => Key::P,
+            InputAction::Settings => Key::O,
+            InputAction::LoadMenu => Key::F9,
+            InputAction::ToggleProfiler => Key::F3,
+            InputAction::ToggleInspector => Key::F4,
+            InputAction::UndoTurn => Key::F5,
+            InputAction::CheatGodMode => Key::F1,
+            InputAction::CheatRevealMap => Key::F2,
+            InputAction::CheatTeleportToStairs => Key::F6,
+            InputAction::CheatGrantExperience => Key::F7,
+            InputAction::CheatGrantGold => Key::F8,
+            InputAction::CheatGrantItems => Key::F10,
+            InputAction::CheatSpawnWave => Key::F11,
+            InputAction::ToggleCheatConsole => Key::Backquote,
+        }
+    }
+}
+
+// keys moving besides the bound ones: the numpad and, if enabled, the keys of vi
+const NUMPAD_KEYS: &[(Key, Direction)] = &[
+    (Key::NumPad8, Direction::Up),
+    (Key::NumPad2, Direction::Down),
+    (Key::NumPad4, Direction::Left),
+    (Key::NumPad6, Direction::Right),
+];
+const VI_KEYS: &[(Key, Direction)] = &[
+    (Key::K, Direction::Up),
+    (Key::J, Direction::Down),
+    (Key::H, Direction::Left),
+    (Key::L, Direction::Right),
+    (Key::Y, Direction::UpLeft),
+    (Key::U, Direction::UpRight),
+    (Key::B, Direction::DownLeft),
+    (Key::N, Direction::DownRight),
+];
+
+/// Difficulty of a game, the setting is the default for new games
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// Multiplier for the damage the player takes
+    pub fn damage_taken_factor(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    /// Multiplier for the number of creatures spawned, see `builder::director`
+    pub fn spawn_density(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.7,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.4,
+        }
+    }
+
+    pub fn next(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+}
+
+/// Volumes between 0.0 and 1.0, all but the master volume belong to one `audio::sound::SoundCategory`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VolumeSettings {
+    pub master: f32,
+    pub effects: f32,
+    pub footsteps: f32,
+    pub music: f32,
+    pub interface: f32
+}
+
+impl Default for VolumeSettings {
+    fn default() -> VolumeSettings {
+        VolumeSettings {
+            master: 1.0,
+            effects: 1.0,
+            footsteps: 0.6,
+            music: 0.5,
+            interface: 0.8
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    // in pixels
+    pub window_width: u32,
+    pub window_height: u32,
+    // pixels per field
+    pub scale: f64,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    // upper limit of rendered frames per second, independent of the game updates
+    pub max_fps: u64,
+    // show an fps and ups counter
+    pub show_fps: bool
+}
+
+impl Default for DisplaySettings {
+    fn default() -> DisplaySettings {
+        DisplaySettings {
+            window_width: 1000,
+            window_height: 1000,
+            scale: 100.0,
+            fullscreen: false,
+            vsync: false,
+            max_fps: 60,
+            show_fps: false
+        }
+    }
+}
+
+impl DisplaySettings {
+    /// True if changing from other settings to these requires a new window
+    pub fn needs_new_window(&self, other: &DisplaySettings) -> bool {
+        self.fullscreen != other.fullscreen || self.vsync != other.vsync
+    }
+}
+
+/// Which items players pick up by walking over them, see `gamelogic::items::auto_pick_up`.
+/// The pick up action takes everything regardless
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PickupSettings {
+    pub gold: bool,
+    pub consumables: bool,
+    // heaviest stack of consumables picked up
+    pub max_consumable_weight: f64,
+    pub equipment: bool,
+    pub ignore_junk: bool
+}
+
+impl Default for PickupSettings {
+    fn default() -> PickupSettings {
+        PickupSettings {
+            gold: true,
+            consumables: true,
+            max_consumable_weight: 2.0,
+            equipment: true,
+            ignore_junk: true
+        }
+    }
+}
+
+impl PickupSettings {
+    /// Tests if an item is picked up by walking over it. Key items always are
+    pub fn allows(&self, item: &Item) -> bool {
+        match item.item {
+            ItemType::Gold => self.gold,
+            ItemType::Consumable => self.consumables && item.weight() <= self.max_consumable_weight,
+            ItemType::Equipment(_) => self.equipment,
+            ItemType::KeyItem => true,
+            ItemType::Junk => !self.ignore_junk
+        }
+    }
+}
+
+/// Settings making the game easier to see and control
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    // colors of health, friend and foe and the like, see `render::palette`
+    pub palette: Palette,
+    // milliseconds a move key is held before the player keeps walking
+    pub move_repeat_delay: u64,
+    // weaker screen shake, see `render::shake`
+    pub reduce_screen_shake: bool
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> AccessibilitySettings {
+        AccessibilitySettings {
+            palette: Palette::Standard,
+            move_repeat_delay: 0,
+            reduce_screen_shake: false
+        }
+    }
+}
+
+impl AccessibilitySettings {
+    /// The move repeat delay in event loop updates
+    pub fn move_repeat_updates(&self) -> u64 {
+        self.move_repeat_delay * crate::UPDATES_PER_SECOND / 1000
+    }
+}
+
+/// What is logged and where to, see `logging`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogSettings {
+    // level of all modules without their own
+    pub level: LevelFilter,
+    // levels of modules and the modules within them, e.g. `rustac::net: debug`
+    pub modules: BTreeMap<String, LevelFilter>,
+    // also write to the log file in the platform's data directory
+    pub to_file: bool
+}
+
+impl Default for LogSettings {
+    fn default() -> LogSettings {
+        LogSettings {
+            level: LevelFilter::Info,
+            modules: BTreeMap::new(),
+            to_file: true
+        }
+    }
+}
+
+/// Settings of the player, stored in a yaml file in the platform's config directory.
+/// Missing values fall back to their defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub key_bindings: BTreeMap<InputAction, Key>,
+    pub volume: VolumeSettings,
+    pub display: DisplaySettings,
+    pub difficulty: Difficulty,
+    // defaults for new games like the difficulty
+    pub permadeath: bool,
+    pub diagonal_rule: DiagonalRule,
+    pub scheduling: Scheduling,
+    // move with h, j, k, l and y, u, b, n, before the keys bound to other actions
+    pub vi_keys: bool,
+    pub save_format: SaveFormat,
+    pub log: LogSettings,
+    pub pickup: PickupSettings,
+    pub accessibility: AccessibilitySettings
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            key_bindings: InputAction::ALL.iter().map(|action| (*action, action.default_key())).collect(),
+            volume: VolumeSettings::default(),
+            display: DisplaySettings::default(),
+            difficulty: Difficulty::Normal,
+            permadeath: false,
+            diagonal_rule: DiagonalRule::default(),
+            scheduling: Scheduling::default(),
+            vi_keys: false,
+            save_format: SaveFormat::default(),
+            log: LogSettings::default(),
+            pickup: PickupSettings::default(),
+            accessibility: AccessibilitySettings::default()
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the settings file, falling back to the default settings if there is none
+    /// or it can not be read
+    pub fn load() -> Settings {
+        let path = settings_path();
+        let mut settings: Settings = match fs::read_to_string(&path) {
+            Ok(content) => serde_yaml::from_str(&content).unwrap_or_else(|err| {
+                warn!("Could not parse settings {:?}: {}", path, err);
+                Settings::default()
+            }),
+            Err(err) => {
+                info!("No settings loaded from {:?}: {}", path, err);
+                Settings::default()
+            }
+        };
+
+        // actions added since the file was written get their default key
+        for action in InputAction::ALL.iter() {
+            if !settings.key_bindings.contains_key(action) {
+                let key = action.default_key();
+                if settings.action_for(key).is_none() {
+                    settings.key_bindings.insert(*action, key);
+                }
+            }
+        }
+        settings.limit_values();
+        settings
+    }
+
+    /// Brings every value into the range the game can handle, e.g. a scale of zero
+    /// written into the file by hand
+    pub fn limit_values(&mut self) {
+        let limit_volume = |volume: &mut f32| *volume = volume.clamp(0.0, 1.0);
+        limit_volume(&mut self.volume.master);
+        limit_volume(&mut self.volume.effects);
+        limit_volume(&mut self.volume.footsteps);
+        limit_volume(&mut self.volume.music);
+        limit_volume(&mut self.volume.interface);
+        self.display.window_width = self.display.window_width.max(1);
+        self.display.window_height = self.display.window_height.max(1);
+        self.display.scale = self.display.scale.clamp(SCALE_LIMITS.0, SCALE_LIMITS.1);
+        self.display.max_fps = self.display.max_fps.clamp(MAX_FPS_LIMITS.0, MAX_FPS_LIMITS.1);
+        self.pickup.max_consumable_weight = self.pickup.max_consumable_weight.clamp(PICKUP_WEIGHT_LIMITS.0, PICKUP_WEIGHT_LIMITS.1);
+        self.accessibility.move_repeat_delay = self.accessibility.move_repeat_delay.clamp(MOVE_REPEAT_DELAY_LIMITS.0, MOVE_REPEAT_DELAY_LIMITS.1);
+    }
+
+    /// Writes the settings file, creating its directory if needed
+    pub fn save(&self) -> Result<()> {
+        let path = settings_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_yaml::to_string(self)?;
+        Ok(fs::write(&path, content)?)
+    }
+
+    /// The action bound to a key. The numpad and vi keys always move unless they are
+    /// bound to an action, vi keys are preferred over bindings if they are enabled
+    pub fn action_for(&self, key: Key) -> Option<InputAction> {
+        let movement_key = |keys: &[(Key, Direction)]| keys.iter()
+                                                           .find(|(movement_key, _)| *movement_key == key)
+                                                           .map(|(_, direction)| InputAction::move_to(*direction));
+        if self.vi_keys {
+            if let Some(action) = movement_key(VI_KEYS) {
+                return Some(action);
+            }
+        }
+        self.key_bindings.iter()
+            .find(|(_, bound)| **bound == key)
+            .map(|(action, _)| *action)
+            .or_else(|| movement_key(NUMPAD_KEYS))
+    }
+
+    /// Binds a key to an action. The key is taken away from any other action, except
+    /// from move actions: they get the old key of the action instead, so they stay bound
+    pub fn bind(&mut self, action: InputAction, key: Key) {
+        let previous = self.key_bindings.get(&action).copied();
+        let displaced = self.key_bindings.iter()
+                                         .find(|(other, bound)| **other != action && **bound == key)
+                                         .map(|(other, _)| *other);
+        if let Some(displaced) = displaced {
+            match (displaced.move_direction(), previous) {
+                (Some(_), Some(previous)) => {
+                    self.key_bindings.insert(displaced, previous);
+                },
+                (Some(_), None) => {
+                    warn!("{:?} is bound to {:?}, which has to stay bound", key, displaced);
+                    return;
+                },
+                (None, _) => {
+                    self.key_bindings.remove(&displaced);
+                }
+            }
+        }
+        self.key_bindings.insert(action, key);
+    }
+}
+
+/// Location of the settings file, `<config dir>/rustac/settings.yaml`
+pub fn settings_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rustac")
+        .join("settings.yaml")
+}
+
+/// A line of the settings menu
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SettingsEntry {
+    MasterVolume,
+    EffectsVolume,
+    FootstepsVolume,
+    MusicVolume,
+    InterfaceVolume,
+    Scale,
+    Fullscreen,
+    Vsync,
+    MaxFps,
+    ShowFps,
+    Difficulty,
+    Permadeath,
+    DiagonalRule,
+    Scheduling,
+    ViKeys,
+    PickupGold,
+    PickupConsumables,
+    PickupWeight,
+    PickupEquipment,
+    IgnoreJunk,
+    Palette,
+    MoveRepeatDelay,
+    ReduceScreenShake,
+    Binding(InputAction),
+}
+
+/// All lines of the settings menu in the order they are shown
+pub fn settings_entries() -> Vec<SettingsEntry> {
+    let mut entries = vec![
+        SettingsEntry::MasterVolume,
+        SettingsEntry::EffectsVolume,
+        SettingsEntry::FootstepsVolume,
+        SettingsEntry::MusicVolume,
+        SettingsEntry::InterfaceVolume,
+        SettingsEntry::Scale,
+        SettingsEntry::Fullscreen,
+        SettingsEntry::Vsync,
+        SettingsEntry::MaxFps,
+        SettingsEntry::ShowFps,
+        SettingsEntry::Difficulty,
+        SettingsEntry::Permadeath,
+        SettingsEntry::DiagonalRule,
+        SettingsEntry::Scheduling,
+        SettingsEntry::ViKeys,
+        SettingsEntry::PickupGold,
+        SettingsEntry::PickupConsumables,
+        SettingsEntry::PickupWeight,
+        SettingsEntry::PickupEquipment,
+        SettingsEntry::IgnoreJunk,
+        SettingsEntry::Palette,
+        SettingsEntry::MoveRepeatDelay,
+        SettingsEntry::ReduceScreenShake,
+    ];
+    entries.extend(InputAction::ALL.iter().map(|action| SettingsEntry::Binding(*action)));
+    entries
+}
+
+/// State of the settings menu
+#[derive(Debug, Default)]
+pub struct SettingsMenu {
+    // index into `settings_entries`
+    pub selected: usize,
+    // the next pressed key is bound to the selected action
+    pub rebinding: bool
+}
+
+impl Settings {
+    /// Changes the value of a settings entry. Key bindings are changed by `bind` instead
+    ///
+    /// ### Arguments
+    /// * `entry` - The entry to change
+    /// * `steps` - How far to change it, negative to decrease
+    ///
+    pub fn adjust(&mut self, entry: SettingsEntry, steps: i32) {
+        let volume_step = 0.1 * steps as f32;
+        let adjust_volume = |volume: &mut f32| *volume = (*volume + volume_step).clamp(0.0, 1.0);
+        match entry {
+            SettingsEntry::MasterVolume => adjust_volume(&mut self.volume.master),
+            SettingsEntry::EffectsVolume => adjust_volume(&mut self.volume.effects),
+            SettingsEntry::FootstepsVolume => adjust_volume(&mut self.volume.footsteps),
+            SettingsEntry::MusicVolume => adjust_volume(&mut self.volume.music),
+            SettingsEntry::InterfaceVolume => adjust_volume(&mut self.volume.interface),
+            SettingsEntry::Scale => {
+                self.display.scale = (self.display.scale + 10.0 * steps as f64).clamp(SCALE_LIMITS.0, SCALE_LIMITS.1);
+            },
+            SettingsEntry::Fullscreen => self.display.fullscreen = !self.display.fullscreen,
+            SettingsEntry::Vsync => self.display.vsync = !self.display.vsync,
+            SettingsEntry::MaxFps => {
+                self.display.max_fps = (self.display.max_fps as i64 + 10 * steps as i64).clamp(MAX_FPS_LIMITS.0 as i64, MAX_FPS_LIMITS.1 as i64) as u64;
+            },
+            SettingsEntry::ShowFps => self.display.show_fps = !self.display.show_fps,
+            SettingsEntry::Difficulty => self.difficulty = self.difficulty.next(),
+            SettingsEntry::Permadeath => self.permadeath = !self.permadeath,
+            SettingsEntry::DiagonalRule => self.diagonal_rule = self.diagonal_rule.next(),
+            SettingsEntry::Scheduling => self.scheduling = self.scheduling.next(),
+            SettingsEntry::ViKeys => self.vi_keys = !self.vi_keys,
+            SettingsEntry::PickupGold => self.pickup.gold = !self.pickup.gold,
+            SettingsEntry::PickupConsumables => self.pickup.consumables = !self.pickup.consumables,
+            SettingsEntry::PickupWeight => {
+                self.pickup.max_consumable_weight = (self.pickup.max_consumable_weight + 0.5 * steps as f64).clamp(PICKUP_WEIGHT_LIMITS.0, PICKUP_WEIGHT_LIMITS.1);
+            },
+            SettingsEntry::PickupEquipment => self.pickup.equipment = !self.pickup.equipment,
+            SettingsEntry::IgnoreJunk => self.pickup.ignore_junk = !self.pickup.ignore_junk,
+            SettingsEntry::Palette => self.accessibility.palette = self.accessibility.palette.next(),
+            SettingsEntry::MoveRepeatDelay => {
+                self.accessibility.move_repeat_delay = (self.accessibility.move_repeat_delay as i64 + 50 * steps as i64).clamp(MOVE_REPEAT_DELAY_LIMITS.0 as i64, MOVE_REPEAT_DELAY_LIMITS.1 as i64) as u64;
+            },
+            SettingsEntry::ReduceScreenShake => self.accessibility.reduce_screen_shake = !self.accessibility.reduce_screen_shake,
+            SettingsEntry::Binding(_) => {}
+        }
+    }
+
+    /// Name and current value of a settings entry as shown in the menu
+    pub fn describe(&self, entry: SettingsEntry, locale: &Locale) -> (String, String) {
+        let percent = |volume: f32| format!("{}%", (volume * 100.0).round() as i32);
+        let on_off = |on: bool| locale.text(if on { "settings.on" } else { "settings.off" });
+        match entry {
+            SettingsEntry::MasterVolume => (locale.text("settings.master_volume"), percent(self.volume.master)),
+            SettingsEntry::EffectsVolume => (locale.text("settings.effects_volume"), percent(self.volume.effects)),
+            SettingsEntry::FootstepsVolume => (locale.text("settings.footsteps_volume"), percent(self.volume.footsteps)),
+            SettingsEntry::MusicVolume => (locale.text("settings.music_volume"), percent(self.volume.music)),
+            SettingsEntry::InterfaceVolume => (locale.text("settings.interface_volume"), percent(self.volume.interface)),
+            SettingsEntry::Scale => (locale.text("settings.scale"), format!("{}", self.display.scale)),
+            SettingsEntry::Fullscreen => (locale.text("settings.fullscreen"), on_off(self.display.fullscreen)),
+            SettingsEntry::Vsync => (locale.text("settings.vsync"), on_off(self.display.vsync)),
+            SettingsEntry::MaxFps => (locale.text("settings.max_fps"), format!("{}", self.display.max_fps)),
+            SettingsEntry::ShowFps => (locale.text("settings.show_fps"), on_off(self.display.show_fps)),
+            SettingsEntry::Difficulty => (locale.text("settings.difficulty"),
+                                          locale.text(&format!("difficulty.{:?}", self.difficulty))),
+            SettingsEntry::Permadeath => (locale.text("settings.permadeath"), on_off(self.permadeath)),
+            SettingsEntry::DiagonalRule => (locale.text("settings.diagonal_rule"),
+                                            locale.text(&format!("diagonal.{:?}", self.diagonal_rule))),
+            SettingsEntry::Scheduling => (locale.text("settings.scheduling"),
+                                          locale.text(&format!("scheduling.{:?}", self.scheduling))),
+            SettingsEntry::ViKeys => (locale.text("settings.vi_keys"), on_off(self.vi_keys)),
+            SettingsEntry::PickupGold => (locale.text("settings.pickup_gold"), on_off(self.pickup.gold)),
+            SettingsEntry::PickupConsumables => (locale.text("settings.pickup_consumables"), on_off(self.pickup.consumables)),
+            SettingsEntry::PickupWeight => (locale.text("settings.pickup_weight"), format!("{}", self.pickup.max_consumable_weight)),
+            SettingsEntry::PickupEquipment => (locale.text("settings.pickup_equipment"), on_off(self.pickup.equipment)),
+            SettingsEntry::IgnoreJunk => (locale.text("settings.ignore_junk"), on_off(self.pickup.ignore_junk)),
+            SettingsEntry::Palette => (locale.text("settings.palette"),
+                                       locale.text(&format!("palette.{:?}", self.accessibility.palette))),
+            SettingsEntry::MoveRepeatDelay => (locale.text("settings.move_repeat_delay"),
+                                               format!("{} ms", self.accessibility.move_repeat_delay)),
+            SettingsEntry::ReduceScreenShake => (locale.text("settings.reduce_screen_shake"), on_off(self.accessibility.reduce_screen_shake)),
+            SettingsEntry::Binding(action) => (locale.text(&action.locale_key()),
+                                               self.key_bindings.get(&action)
+                                                   .map(|key| format!("{:?}", key))
+                                                   .unwrap_or_else(|| "-".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_takes_key_from_other_action() {
+        let mut settings = Settings::default();
+        settings.bind(InputAction::Attack, Key::R);
+        assert_eq!(settings.action_for(Key::R), Some(InputAction::Attack));
+        assert_eq!(settings.key_bindings.get(&InputAction::Rest), None);
+    }
+
+    #[test]
+    fn test_bind_keeps_move_actions_bound() {
+        let mut settings = Settings::default();
+        settings.bind(InputAction::Attack, Key::Up);
+        assert_eq!(settings.action_for(Key::Up), Some(InputAction::Attack));
+        assert_eq!(settings.action_for(Key::Space), Some(InputAction::MoveUp));
+
+        // without a key to hand over the move action keeps its key
+        settings.key_bindings.remove(&InputAction::Rest);
+        settings.bind(InputAction::Rest, Key::Down);
+        assert_eq!(settings.action_for(Key::Down), Some(InputAction::MoveDown));
+        assert_eq!(settings.key_bindings.get(&InputAction::Rest), None);
+    }
+
+    #[test]
+    fn test_movement_keys() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.action_for(Key::NumPad8), Some(InputAction::MoveUp));
+        settings.bind(InputAction::Interact, Key::L);
+        assert_eq!(settings.action_for(Key::L), Some(InputAction::Interact));
+        settings.vi_keys = true;
+        assert_eq!(settings.action_for(Key::L), Some(InputAction::MoveRight));
+        assert_eq!(settings.action_for(Key::B), Some(InputAction::MoveDownLeft));
+    }
+
+    #[test]
+    fn test_partial_file_uses_defaults() {
+        let settings: Settings = serde_yaml::from_str("volume:\n  music: 0.2\n").unwrap();
+        assert_eq!(settings.volume.music, 0.2);
+        assert_eq!(settings.volume.master, 1.0);
+        assert_eq!(settings.action_for(Key::Space), Some(InputAction::Attack));
+
+        let saved = serde_yaml::to_string(&settings).unwrap();
+        let loaded: Settings = serde_yaml::from_str(&saved).unwrap();
+        assert_eq!(loaded.key_bindings, settings.key_bindings);
+        assert_eq!(loaded.display, settings.display);
+    }
+
+    #[test]
+    fn test_values_edited_by_hand_are_limited() {
+        let mut settings: Settings = serde_yaml::from_str("volume:\n  music: 3.0\ndisplay:\n  scale: 0.0\n  max_fps: 0\n").unwrap();
+        settings.limit_values();
+        assert_eq!(settings.volume.music, 1.0);
+        assert_eq!(settings.display.scale, SCALE_LIMITS.0);
+        assert_eq!(settings.display.max_fps, MAX_FPS_LIMITS.0);
+        assert_eq!(settings.volume.master, 1.0);
+    }
+}
@@ -1,10 +1,20 @@
+extern crate rand;
 extern crate recs;
 use recs::allocation;
 
 use std::collections::HashMap;
 
 use crate::gamestate::components::*;
-use crate::gamestate::LocationVec;
+use crate::gamestate::{LocationVec, actor, clock, dungeon, explored, mode, movement, overworld, rng, run, time, weather};
+use crate::gamestate::profile::FrameProfiler;
+use crate::builder::director;
+use crate::config;
+use crate::error::{Error, Result};
+use crate::event::bus;
+use crate::gamelogic::{cheats, cutscene, lod, scheduling, targeting, validation};
+use crate::locale::Locale;
+use crate::persistence::{delta, history, profile, slots};
+use crate::render::{animation, inspector, particle, shake, toast};
 
 pub type Entity = allocation::GenerationalIndex;
 pub type EntityMap<T> = allocation::GenerationalIndexArray<T>;
@@ -14,22 +24,107 @@ pub struct ECS {
 
     pub actor_component: EntityMap<ActorComponent>,
     pub basestats_component: EntityMap<BaseStatsComponent>,
+    pub boss_component: EntityMap<BossComponent>,
     pub caster_component: EntityMap<CasterComponent>,
     pub class_component: EntityMap<ClassComponent>,
+    pub companion_component: EntityMap<CompanionComponent>,
+    pub corpse_component: EntityMap<CorpseComponent>,
     pub dungeon_component: EntityMap<DungeonComponent>,
+    pub dying_component: EntityMap<DyingComponent>,
     pub health_component: EntityMap<HealthComponent>,
+    pub ground_item_component: EntityMap<GroundItemComponent>,
+    pub hotbar_component: EntityMap<HotbarComponent>,
     pub humanoid_component: EntityMap<HumanoidComponent>,
+    pub hunger_component: EntityMap<HungerComponent>,
+    pub interactable_component: EntityMap<InteractableComponent>,
     pub inventory_component: EntityMap<InventoryComponent>,
     pub item_drop_component: EntityMap<ItemDropComponent>,
     pub location_component: EntityMap<LocationComponent>,
     pub name_component: EntityMap<NameComponent>,
     pub npc_behavior_component: EntityMap<NpcBehaviorComponent>,
     pub player_component: EntityMap<PlayerComponent>,
+    pub quest_target_component: EntityMap<QuestTargetComponent>,
     pub render_component: EntityMap<RenderComponent>,
+    pub schedule_component: EntityMap<ScheduleComponent>,
+    pub shop_component: EntityMap<ShopComponent>,
     pub status_component: EntityMap<StatusComponent>,
+    pub sync_id_component: EntityMap<SyncIdComponent>,
 
     pub global_state_table: HashMap<String, String>,
     pub index_cache: HashMap<String, allocation::GenerationalIndex>,
+
+    pub events: bus::EventBus,
+    // all randomness affecting the game goes through this, so a game can be repeated from its seed
+    pub rng: rng::GameRng,
+    pub locale: Locale,
+    pub settings: config::Settings,
+    pub settings_menu: config::SettingsMenu,
+    pub load_menu: slots::LoadMenu,
+    pub difficulty: config::Difficulty,
+    // every save of a lost run is deleted, see `slots::on_run_ended`
+    pub permadeath: bool,
+    pub diagonal_rule: movement::DiagonalRule,
+    pub scheduling: actor::Scheduling,
+    // updates since the last turn passed in real-time games
+    pub realtime_updates: u64,
+    pub action_queue: scheduling::ActionQueue,
+    // the ability the player is aiming, if any
+    pub targeting: Option<targeting::Targeting>,
+    // the cutscene playing, the player has no control meanwhile
+    pub cutscene: Option<cutscene::Cutscene>,
+    // enables tools for finding bugs, like undoing turns
+    pub debug_mode: bool,
+    // enables cheats for testers
+    pub dev_mode: bool,
+    pub cheat_console: cheats::CheatConsole,
+    pub turn_history: history::TurnHistory,
+
+    pub game_mode: mode::GameMode,
+    pub transition: Option<mode::Transition>,
+    // paused by the player, nothing is simulated
+    pub paused: bool,
+    pub time_scale: time::TimeScale,
+    // seconds the dungeon was simulated, shown with saves
+    pub playtime: f64,
+    // identifies the run, every save of it has the same, see `slots::on_run_ended`
+    pub run_id: u64,
+    // next free `SyncIdComponent` id
+    pub next_sync_id: delta::SyncId,
+    // what the autosave contains, later autosaves only write the changes since.
+    // None until the first autosave of the game wrote a full save
+    pub autosave_tracker: Option<delta::ChangeTracker>,
+
+    pub world_clock: clock::WorldClock,
+    pub floor_type: dungeon::FloorType,
+    pub weather: weather::Weather,
+    pub explored: explored::ExploredMap,
+
+    pub run_stats: run::RunStats,
+    pub spawn_director: director::SpawnDirector,
+    // where the players are between dungeons and towns
+    pub overworld: overworld::Overworld,
+    // creatures are stronger, see `gamelogic::new_game_plus`
+    pub new_game_plus: bool,
+    // set once the run ended, shown on the game over screen
+    pub run_summary: Option<run::RunSummary>,
+    // lifetime statistics and achievements, independent of the saves
+    pub profile: profile::PlayerProfile,
+
+    // how often entities far from the players are updated
+    pub lod: lod::SimulationLod,
+
+    // cosmetic only, not part of the game state
+    pub particles: particle::ParticleSystem,
+    // clips of the animated sprites, loaded from `assets/textures/`
+    pub animations: animation::AnimationLibrary,
+    // timings of the game loop, shown with F3
+    pub profiler: FrameProfiler,
+    // components of an entity, shown with F4
+    pub inspector: inspector::Inspector,
+    pub toasts: toast::Toasts,
+    pub screen_shake: shake::ScreenShake,
+    // invariant violations logged in debug builds
+    pub violations: validation::Violations,
 }
 
 impl ECS {
@@ -39,22 +134,85 @@ impl ECS {
 
             actor_component: EntityMap::new(),
             basestats_component: EntityMap::new(),
+            boss_component: EntityMap::new(),
             caster_component: EntityMap::new(),
             class_component: EntityMap::new(),
+            companion_component: EntityMap::new(),
+            corpse_component: EntityMap::new(),
             dungeon_component: EntityMap::new(),
+            dying_component: EntityMap::new(),
             health_component: EntityMap::new(),
+            ground_item_component: EntityMap::new(),
+            hotbar_component: EntityMap::new(),
             humanoid_component: EntityMap::new(),
+            hunger_component: EntityMap::new(),
+            interactable_component: EntityMap::new(),
             inventory_component: EntityMap::new(),
             item_drop_component: EntityMap::new(),
             location_component: EntityMap::new(),
             name_component: EntityMap::new(),
             npc_behavior_component: EntityMap::new(),
             player_component: EntityMap::new(),
+            quest_target_component: EntityMap::new(),
             render_component: EntityMap::new(),
+            schedule_component: EntityMap::new(),
+            shop_component: EntityMap::new(),
             status_component: EntityMap::new(),
+            sync_id_component: EntityMap::new(),
 
             global_state_table: HashMap::new(),
-            index_cache: HashMap::new()
+            index_cache: HashMap::new(),
+
+            events: bus::EventBus::new(),
+            rng: rng::GameRng::new(0),
+            locale: Locale::default(),
+            settings: config::Settings::default(),
+            settings_menu: config::SettingsMenu::default(),
+            load_menu: slots::LoadMenu::default(),
+            difficulty: config::Difficulty::Normal,
+            permadeath: false,
+            diagonal_rule: movement::DiagonalRule::default(),
+            scheduling: actor::Scheduling::default(),
+            realtime_updates: 0,
+            action_queue: scheduling::ActionQueue::default(),
+            targeting: None,
+            cutscene: None,
+            debug_mode: false,
+            dev_mode: false,
+            cheat_console: cheats::CheatConsole::default(),
+            turn_history: history::TurnHistory::default(),
+
+            game_mode: mode::GameMode::Dungeon,
+            transition: None,
+            paused: false,
+            time_scale: time::TimeScale::new(),
+            playtime: 0.0,
+            run_id: rand::random(),
+            next_sync_id: 0,
+            autosave_tracker: None,
+
+            world_clock: clock::WorldClock::new(clock::START_HOUR),
+            floor_type: dungeon::FloorType::Dungeon,
+            weather: weather::Weather::Clear,
+            explored: explored::ExploredMap::new(),
+
+
+            run_stats: run::RunStats::default(),
+            spawn_director: director::SpawnDirector::default(),
+            overworld: overworld::Overworld::default(),
+            new_game_plus: false,
+            run_summary: None,
+            profile: profile::PlayerProfile::default(),
+
+            lod: lod::SimulationLod::default(),
+
+            particles: particle::ParticleSystem::new(),
+            animations: animation::AnimationLibrary::default(),
+            profiler: FrameProfiler::new(),
+            inspector: inspector::Inspector::default(),
+            toasts: toast::Toasts::default(),
+            screen_shake: shake::ScreenShake::default(),
+            violations: validation::Violations::default()
         }
     }
 
@@ -75,6 +233,13 @@ impl ECS {
         result
     }
 
+    /// Starts building a new entity, e.g.
+    /// `ecs_.spawn().with(LocationComponent { .. }).with(HealthComponent { .. }).build()`
+    pub fn spawn(&mut self) -> EntityBuilder<'_> {
+        let entity = self.allocator.allocate();
+        EntityBuilder { ecs: self, entity }
+    }
+
     pub fn get_player_entity(&mut self) -> Option<Entity> {
         if let Some(index) = self.index_cache.get("Player") {
             if self.allocator.is_live(*index){
@@ -82,6 +247,7 @@ impl ECS {
             }
         }
         if let Some(index) = self.allocator.live_indices().into_iter().find(|&e| self.player_component.get(e).is_some()) {
+            trace!("Player entity is now {:?}", index);
             self.index_cache.insert("Player".to_string(), index);
             Some(index)
         } else {
@@ -90,7 +256,193 @@ impl ECS {
     }
 }
 
+impl Default for ECS {
+    fn default() -> ECS {
+        ECS::new()
+    }
+}
 
+/// A component stored in one of the `EntityMap`s of the ECS
+pub trait Component: Sized {
+    // shown in errors about missing components
+    const NAME: &'static str;
 
+    fn store(ecs_: &ECS) -> &EntityMap<Self>;
+    fn store_mut(ecs_: &mut ECS) -> &mut EntityMap<Self>;
+}
 
+/// Implements `Component` for the components of the ECS and their fields
+macro_rules! components {
+    ($($field:ident: $type_:ty),* $(,)?) => {
+        $(
+            impl Component for $type_ {
+                const NAME: &'static str = stringify!($type_);
 
+                fn store(ecs_: &ECS) -> &EntityMap<Self> {
+                    &ecs_.$field
+                }
+
+                fn store_mut(ecs_: &mut ECS) -> &mut EntityMap<Self> {
+                    &mut ecs_.$field
+                }
+            }
+        )*
+    };
+}
+
+components! {
+    actor_component: ActorComponent,
+    basestats_component: BaseStatsComponent,
+    boss_component: BossComponent,
+    caster_component: CasterComponent,
+    class_component: ClassComponent,
+    companion_component: CompanionComponent,
+    corpse_component: CorpseComponent,
+    dungeon_component: DungeonComponent,
+    dying_component: DyingComponent,
+    health_component: HealthComponent,
+    ground_item_component: GroundItemComponent,
+    hotbar_component: HotbarComponent,
+    humanoid_component: HumanoidComponent,
+    hunger_component: HungerComponent,
+    interactable_component: InteractableComponent,
+    inventory_component: InventoryComponent,
+    item_drop_component: ItemDropComponent,
+    location_component: LocationComponent,
+    name_component: NameComponent,
+    npc_behavior_component: NpcBehaviorComponent,
+    player_component: PlayerComponent,
+    quest_target_component: QuestTargetComponent,
+    render_component: RenderComponent,
+    schedule_component: ScheduleComponent,
+    shop_component: ShopComponent,
+    status_component: StatusComponent,
+    sync_id_component: SyncIdComponent,
+}
+
+/// Tests if an entity has a component
+pub fn has<T: Component>(ecs_: &ECS, entity: Entity) -> bool {
+    T::store(ecs_).get(entity).is_some()
+}
+
+/// Finds a component an entity has although it only works together with another
+/// one the entity lacks, e.g. only entities with a location can be rendered
+///
+/// ### Returns
+/// The name of the component and of the one it needs, None if all are paired
+pub fn missing_pairing(ecs_: &ECS, entity: Entity) -> Option<(&'static str, &'static str)> {
+    let located = has::<LocationComponent>(ecs_, entity);
+    let needing_location = [
+        (has::<RenderComponent>(ecs_, entity), RenderComponent::NAME),
+        (has::<ActorComponent>(ecs_, entity), ActorComponent::NAME),
+        (has::<CorpseComponent>(ecs_, entity), CorpseComponent::NAME),
+        (has::<GroundItemComponent>(ecs_, entity), GroundItemComponent::NAME),
+        (has::<InteractableComponent>(ecs_, entity), InteractableComponent::NAME),
+        (has::<ScheduleComponent>(ecs_, entity), ScheduleComponent::NAME),
+    ];
+    if let Some((_, name)) = needing_location.iter().find(|(present, _)| *present && !located) {
+        return Some((name, LocationComponent::NAME));
+    }
+    // players have to be able to lose
+    if has::<PlayerComponent>(ecs_, entity) && !has::<HealthComponent>(ecs_, entity) {
+        return Some((PlayerComponent::NAME, HealthComponent::NAME));
+    }
+    None
+}
+
+/// Builds an entity component by component, see `ECS::spawn`. Building checks that
+/// components needing each other were added together (see `missing_pairing`)
+pub struct EntityBuilder<'a> {
+    ecs: &'a mut ECS,
+    entity: Entity
+}
+
+impl<'a> EntityBuilder<'a> {
+    pub fn with<T: Component>(self, component: T) -> EntityBuilder<'a> {
+        T::store_mut(self.ecs).set(self.entity, component);
+        self
+    }
+
+    /// Adds a component if there is one, e.g. a sprite only some entities have
+    pub fn with_some<T: Component>(self, component: Option<T>) -> EntityBuilder<'a> {
+        match component {
+            Some(component) => self.with(component),
+            None => self
+        }
+    }
+
+    /// Finishes the entity. A missing pairing is a bug, so it fails debug builds and is
+    /// logged otherwise, the entity is kept either way
+    pub fn build(self) -> Entity {
+        if let Some((component, needed)) = missing_pairing(self.ecs, self.entity) {
+            error!("{:?} was built with a {} but without a {}", self.entity, component, needed);
+            debug_assert!(false, "{} needs a {}", component, needed);
+        }
+        self.entity
+    }
+
+    /// Like `build`, but an entity with a missing pairing is removed again
+    ///
+    /// ### Returns
+    /// The entity, the missing component if a pairing is missing
+    pub fn try_build(self) -> Result<Entity> {
+        match missing_pairing(self.ecs, self.entity) {
+            Some((_, needed)) => {
+                self.ecs.allocator.deallocate(self.entity);
+                Err(Error::MissingComponent { entity: self.entity, component: needed })
+            },
+            None => Ok(self.entity)
+        }
+    }
+}
+
+/// Gets a component an entity is expected to have, e.g. because it was selected for
+/// having it. Not having it is reported as an error instead of a panic
+///
+/// ### Arguments
+/// * `components` - The components of one type, e.g. `&ecs_.render_component`
+/// * `entity`     - The entity to get the component of
+///
+pub fn require<T>(components: &EntityMap<T>, entity: Entity) -> Result<&T> {
+    components.get(entity).ok_or_else(|| Error::MissingComponent {
+        entity,
+        component: std::any::type_name::<T>().rsplit("::").next().unwrap_or("component")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::movement::Direction;
+
+    #[test]
+    fn test_builder_checks_pairings() {
+        let mut ecs_ = ECS::new();
+        let entity = ecs_.spawn()
+                         .with(LocationComponent {
+                             location: LocationVec { x: 1.0, y: 2.0 },
+                             previous_location: None,
+                             direction: Direction::Down,
+                             move_intent: None,
+                             hitbox: None
+                         })
+                         .with(HealthComponent { current: 5, maximum: 5 })
+                         .build();
+        assert_eq!(ecs_.health_component.get(entity).unwrap().current, 5);
+        assert_eq!(ecs_.get_entities_by_location(LocationVec { x: 1.0, y: 2.0 }), vec![entity]);
+
+        let unlocated = ecs_.spawn()
+                            .with(NameComponent { name: "creature.dummy".to_string() })
+                            .with(DungeonComponent { type_: dungeon::DungeonElement::Floor })
+                            .with_some(None::<LocationComponent>)
+                            .with(ActorComponent { state: actor::ActorState::WaitingForTurn, turn: 0, max_actions: 1, performed_actions: 0, cooldown: 0 })
+                            .try_build();
+        match unlocated {
+            Err(Error::MissingComponent { entity, component }) => {
+                assert_eq!(component, "LocationComponent");
+                assert!(!ecs_.allocator.is_live(entity));
+            },
+            other => panic!("expected a missing location, got {:?}", other)
+        }
+    }
+}
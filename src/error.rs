@@ -0,0 +1,74 @@
+extern crate bincode;
+extern crate serde_yaml;
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use crate::ecs::Entity;
+use crate::persistence::slots::SaveSlot;
+
+/// Everything that can go wrong in the game without it being a bug. Callers decide
+/// whether to go on without the result, e.g. with a missing texture, or to give up
+#[derive(Debug)]
+pub enum Error {
+    // reading or writing a file or connection failed
+    Io(io::Error),
+    // a yaml file like the settings, a replay or a save is malformed
+    Yaml(serde_yaml::Error),
+    // binary data like a save or the autosave journal is corrupt
+    Binary(bincode::Error),
+    // an asset could not be loaded, e.g. an image in a format that is not supported
+    Asset { path: PathBuf, reason: String },
+    // there is no save in the slot
+    EmptySlot(SaveSlot),
+    // an entity lacks a component it is expected to have
+    MissingComponent { entity: Entity, component: &'static str },
+    // the window or its graphics context could not be created
+    Window(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Yaml(err) => write!(f, "invalid yaml: {}", err),
+            Error::Binary(err) => write!(f, "invalid data: {}", err),
+            Error::Asset { path, reason } => write!(f, "could not load {:?}: {}", path, reason),
+            Error::EmptySlot(slot) => write!(f, "{:?} is empty", slot),
+            Error::MissingComponent { entity, component } => write!(f, "{:?} has no {}", entity, component),
+            Error::Window(reason) => write!(f, "could not create the window: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Yaml(err) => Some(err),
+            Error::Binary(err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(err: serde_yaml::Error) -> Error {
+        Error::Yaml(err)
+    }
+}
+
+impl From<bincode::Error> for Error {
+    fn from(err: bincode::Error) -> Error {
+        Error::Binary(err)
+    }
+}
@@ -0,0 +1,64 @@
+use crate::ecs::Entity;
+use crate::gamestate::run::RunOutcome;
+
+/// Something that happened in the game which other systems may react to
+#[derive(Debug, Clone)]
+pub enum GameEvent {
+    // an entity lost health
+    Damaged { target: Entity, source: Option<Entity>, amount: i32 },
+    // an attack did not do anything, e.g. because of `StatusType::Invincible`
+    Blocked { target: Entity, source: Option<Entity> },
+    // an entity's health reached zero
+    Died { entity: Entity, killer: Option<Entity> },
+    // an entity arrived at the field it was moving to
+    Stepped { entity: Entity },
+    // an entity took the loot of a corpse
+    Looted { looter: Entity, corpse: Entity },
+    // an entity picked up an item from the ground
+    PickedUp { entity: Entity },
+    // an entity dropped an item onto the ground
+    Dropped { entity: Entity },
+    // an entity used up an item of its inventory, e.g. drank a potion
+    ItemUsed { entity: Entity },
+    // the player used the interface, e.g. toggled a map
+    UiClick,
+    // a new floor was built
+    FloorChanged,
+    // an entity took the stairs down to the next floor of a dungeon
+    Descended { entity: Entity },
+    // the run ended, see `gamelogic::run::end_run`
+    RunEnded { outcome: RunOutcome },
+}
+
+/// Queue of game events. Events are published by the game logic during an update
+/// and drained once per update by `gamelogic::process_events`
+pub struct EventBus {
+    events: Vec<GameEvent>
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        EventBus {
+            events: Vec::new()
+        }
+    }
+
+    pub fn publish(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    /// Takes all events published since the last drain, in publishing order
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> EventBus {
+        EventBus::new()
+    }
+}
@@ -1,3 +1,5 @@
+pub mod bus;
+
 extern crate serde;
 
 use serde::{Serialize, Deserialize};
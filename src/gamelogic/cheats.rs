@@ -0,0 +1,257 @@
+use crate::builder::{director, spawn};
+use crate::ecs;
+use crate::gamelogic::{self, collision::CollisionMap};
+use crate::gamestate::components::{InventoryComponent, StatusComponent};
+use crate::gamestate::{dungeon::DungeonElement, duration::Duration, item, status, LocationVec};
+
+// gold and experience granted at once
+const GRANTED_GOLD: i32 = 1000;
+const GRANTED_EXPERIENCE: i32 = 1000;
+// hostile creatures spawned around the player at once
+const WAVE_SIZE: usize = 6;
+// fields around the player a wave spawns within
+const WAVE_RADIUS: i64 = 3;
+const GRANTED_INVENTORY_CAPACITY: i32 = 20;
+
+/// Shortcuts for testers to reach late-game content quickly. Only performed in
+/// games started with `--dev`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cheat {
+    // the player takes no damage, toggled
+    GodMode,
+    // the whole floor is explored
+    RevealMap,
+    TeleportToStairs,
+    GrantExperience,
+    GrantGold,
+    // one of each equipment and some consumables
+    GrantItems,
+    // hostile creatures appear around the player
+    SpawnWave,
+}
+
+impl Cheat {
+    pub const ALL: [Cheat; 7] = [
+        Cheat::GodMode,
+        Cheat::RevealMap,
+        Cheat::TeleportToStairs,
+        Cheat::GrantExperience,
+        Cheat::GrantGold,
+        Cheat::GrantItems,
+        Cheat::SpawnWave,
+    ];
+
+    /// Name of the cheat in commands, e.g. `--cheat god,gold`
+    pub fn command(&self) -> &'static str {
+        match self {
+            Cheat::GodMode => "god",
+            Cheat::RevealMap => "reveal",
+            Cheat::TeleportToStairs => "stairs",
+            Cheat::GrantExperience => "xp",
+            Cheat::GrantGold => "gold",
+            Cheat::GrantItems => "items",
+            Cheat::SpawnWave => "wave",
+        }
+    }
+
+    pub fn parse(command: &str) -> Option<Cheat> {
+        Cheat::ALL.iter().copied().find(|cheat| cheat.command() == command.trim())
+    }
+}
+
+/// A line testers type the command of a cheat into, e.g. `gold`, instead of pressing its
+/// function key. Only opened in games started with `--dev`, see `input::keys::button_action`
+#[derive(Debug, Clone, Default)]
+pub struct CheatConsole {
+    pub open: bool,
+    pub input: String
+}
+
+/// Performs a cheat for a player, if the game runs in developer mode
+///
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `player` - The player the cheat is for
+/// * `cheat`  - The cheat to perform
+///
+/// ### Returns
+/// True if the cheat was performed, else false
+pub fn perform_cheat(ecs_: &mut ecs::ECS, player: ecs::Entity, cheat: Cheat) -> bool {
+    if !ecs_.dev_mode {
+        debug!("Cheats are only allowed in games started with --dev");
+        return false;
+    }
+    let performed = match cheat {
+        Cheat::GodMode => toggle_god_mode(ecs_, player),
+        Cheat::RevealMap => reveal_map(ecs_),
+        Cheat::TeleportToStairs => teleport_to_stairs(ecs_, player),
+        Cheat::GrantExperience => match ecs_.class_component.get_mut(player) {
+            Some(class_c) => {
+                class_c.experience += GRANTED_EXPERIENCE;
+                true
+            },
+            None => false
+        },
+        Cheat::GrantGold => match ecs_.player_component.get_mut(player) {
+            Some(player_c) => {
+                player_c.gold += GRANTED_GOLD;
+                true
+            },
+            None => false
+        },
+        Cheat::GrantItems => grant_items(ecs_, player),
+        Cheat::SpawnWave => spawn_wave(ecs_, player) > 0
+    };
+    info!("Cheat {} for {}: {}", cheat.command(), crate::ut::name_or_id(ecs_, player), if performed { "done" } else { "not possible" });
+    performed
+}
+
+/// Makes the player invincible until the cheat is used again
+fn toggle_god_mode(ecs_: &mut ecs::ECS, player: ecs::Entity) -> bool {
+    if ecs_.status_component.get(player).is_none() {
+        ecs_.status_component.set(player, StatusComponent { status: Vec::new() });
+    }
+    if let Some(status_c) = ecs_.status_component.get_mut(player) {
+        let is_god = |status: &status::Status| matches!(status.type_, status::StatusType::Invincible) && status.duration == Duration::Infinite;
+        if status_c.status.iter().any(is_god) {
+            status_c.status.retain(|status| !is_god(status));
+        } else {
+            status_c.status.push(status::Status { type_: status::StatusType::Invincible, duration: Duration::Infinite });
+        }
+    }
+    true
+}
+
+fn reveal_map(ecs_: &mut ecs::ECS) -> bool {
+    let mut tiles = Vec::new();
+    for entity in ecs_.allocator.live_indices() {
+        if let (Some(dungeon_c), Some(location_c)) = (ecs_.dungeon_component.get(entity), ecs_.location_component.get(entity)) {
+            tiles.push((location_c.location, dungeon_c.type_));
+        }
+    }
+    for (location, element) in tiles {
+        ecs_.explored.explore(&location, element);
+    }
+    true
+}
+
+/// Moves the player onto the nearest stairs of the floor
+fn teleport_to_stairs(ecs_: &mut ecs::ECS, player: ecs::Entity) -> bool {
+    let from = match ecs_.location_component.get(player) {
+        Some(location_c) => location_c.location,
+        None => return false
+    };
+    let distance = |location: &LocationVec| (location.x - from.x).abs() + (location.y - from.y).abs();
+    let stairs = ecs_.allocator.live_indices()
+                     .into_iter()
+                     .filter(|entity| ecs_.dungeon_component.get(*entity).is_some_and(|dungeon_c| dungeon_c.type_ == DungeonElement::Connector))
+                     .filter_map(|entity| ecs_.location_component.get(entity).map(|location_c| location_c.location))
+                     .min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap_or(std::cmp::Ordering::Equal));
+    match stairs {
+        Some(stairs) => {
+            if let Some(location_c) = ecs_.location_component.get_mut(player) {
+                location_c.move_intent = None;
+            }
+            gamelogic::force_move(ecs_, player, stairs.x, stairs.y)
+        },
+        None => false
+    }
+}
+
+fn grant_items(ecs_: &mut ecs::ECS, player: ecs::Entity) -> bool {
+    if ecs_.inventory_component.get(player).is_none() {
+        ecs_.inventory_component.set(player, InventoryComponent { items: Vec::new(), capacity: GRANTED_INVENTORY_CAPACITY });
+    }
+    let equipment = vec![
+        item::Equipment::OneHandWeapon,
+        item::Equipment::TwoHandWeapon,
+        item::Equipment::Helm,
+        item::Equipment::ChestArmor,
+        item::Equipment::Gloves,
+        item::Equipment::Pants,
+        item::Equipment::Boots,
+    ];
+    if let Some(inventory_c) = ecs_.inventory_component.get_mut(player) {
+        for equipment in equipment {
+            inventory_c.items.push(item::Item { item: item::ItemType::Equipment(equipment), amount: 1 });
+        }
+        inventory_c.items.push(item::Item { item: item::ItemType::Consumable, amount: 10 });
+    }
+    true
+}
+
+/// Spawns creatures of the dungeon's encounters at the current depth on free fields
+/// around the player
+///
+/// ### Returns
+/// The number of creatures spawned
+fn spawn_wave(ecs_: &mut ecs::ECS, player: ecs::Entity) -> usize {
+    let center = match ecs_.location_component.get(player) {
+        Some(location_c) => LocationVec { x: location_c.location.x.round(), y: location_c.location.y.round() },
+        None => return 0
+    };
+    let collision_map = CollisionMap::build(ecs_);
+    let mut fields = Vec::new();
+    for dx in -WAVE_RADIUS..=WAVE_RADIUS {
+        for dy in -WAVE_RADIUS..=WAVE_RADIUS {
+            let field = LocationVec { x: center.x + dx as f64, y: center.y + dy as f64 };
+            if (dx != 0 || dy != 0) && collision_map.check_enter(ecs_, player, field).is_ok() {
+                fields.push(field);
+            }
+        }
+    }
+    // nearest fields first, so the wave surrounds the player
+    fields.sort_by_key(|field| ((field.x - center.x).abs() + (field.y - center.y).abs()) as i64);
+    let table = director::dungeon_encounters().for_floor(director::current_floor(ecs_));
+    fields.iter()
+          .take(WAVE_SIZE)
+          .filter(|field| spawn::spawn_from_table(ecs_, &table, field.x, field.y).is_some())
+          .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::config::InputAction;
+    use crate::input::keys;
+    use piston::{Button, Key};
+
+    #[test]
+    fn test_cheats_need_dev_mode() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_test_floor(&mut ecs_);
+        dungeon::create_connector_tile(&mut ecs_, 5.0, 12.0);
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        assert!(!perform_cheat(&mut ecs_, player, Cheat::GrantGold));
+
+        ecs_.dev_mode = true;
+        assert!(perform_cheat(&mut ecs_, player, Cheat::GrantGold));
+        assert_eq!(ecs_.player_component.get(player).unwrap().gold, GRANTED_GOLD);
+        assert!(perform_cheat(&mut ecs_, player, Cheat::TeleportToStairs));
+        assert_eq!(ecs_.location_component.get(player).unwrap().location, LocationVec { x: 5.0, y: 12.0 });
+        assert!(perform_cheat(&mut ecs_, player, Cheat::RevealMap));
+        assert!(ecs_.explored.is_explored(&LocationVec { x: 7.0, y: 17.0 }));
+
+        assert!(perform_cheat(&mut ecs_, player, Cheat::SpawnWave));
+        let wave = gamelogic::visible_hostiles(&ecs_, player);
+        assert_eq!(wave.len(), WAVE_SIZE);
+        // only rats are encountered on the first floor
+        assert!(wave.iter().all(|creature| ecs_.name_component.get(*creature).unwrap().name == "creature.rat"));
+        assert_eq!(Cheat::parse(" wave"), Some(Cheat::SpawnWave));
+    }
+
+    #[test]
+    fn test_console_types_cheats() {
+        let mut ecs_ = ecs::ECS::new();
+        let press = |ecs_: &mut ecs::ECS, key: Key| keys::button_action(&Button::Keyboard(key), ecs_);
+        assert_eq!(press(&mut ecs_, Key::Backquote), Some(InputAction::ToggleCheatConsole));
+        ecs_.cheat_console.open = true;
+        for key in [Key::G, Key::O, Key::X, Key::Backspace, Key::L, Key::D] {
+            assert_eq!(press(&mut ecs_, key), None);
+        }
+        assert_eq!(ecs_.cheat_console.input, "gold");
+        assert_eq!(press(&mut ecs_, Key::Return), Some(InputAction::CheatGrantGold));
+        assert!(!ecs_.cheat_console.open && ecs_.cheat_console.input.is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use crate::ecs;
+use crate::event::HitboxType;
+use crate::gamelogic::death;
+use crate::gamestate::{dungeon::DungeonElement, movement::Direction, LocationVec};
+
+type Field = (i64, i64);
+
+fn to_field(location: LocationVec) -> Field {
+    (location.x.round() as i64, location.y.round() as i64)
+}
+
+/// Why a field can not be entered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Blocked {
+    // there is nothing to stand on
+    Void,
+    // walls, water and other obstacles
+    Obstacle,
+    // a living creature stands on the field or is walking onto it
+    Occupied(ecs::Entity),
+}
+
+// a field is only known if there is something to stand on, e.g. a floor tile
+#[derive(Default)]
+struct FieldState {
+    water: bool,
+    hitboxes: Vec<(ecs::Entity, HitboxType)>,
+    creatures: Vec<ecs::Entity>
+}
+
+/// Which fields can be walked on, built once from all entities with a location so many
+/// fields can be checked without looking through all entities for each. Entities count
+/// for the field they stand on and the one they are moving to (see `ECS::get_entities_by_location`).
+/// A map is built once per update or turn and passed on, entities that step, arrive or
+/// die in the meantime are updated with `update_entity`
+pub struct CollisionMap {
+    fields: HashMap<Field, FieldState>,
+    // the fields every entity is on, to take it off them again
+    placed: ecs::EntityMap<Vec<Field>>
+}
+
+impl CollisionMap {
+    pub fn build(ecs_: &ecs::ECS) -> CollisionMap {
+        let mut collision_map = CollisionMap {
+            fields: HashMap::new(),
+            placed: ecs::EntityMap::new()
+        };
+        for entity in ecs_.allocator.live_indices() {
+            collision_map.place(ecs_, entity);
+        }
+        collision_map
+    }
+
+    /// Updates the fields of an entity after it changed, e.g. it started to move, arrived or died
+    pub fn update_entity(&mut self, ecs_: &ecs::ECS, entity: ecs::Entity) {
+        if let Some(placed) = self.placed.get_mut(entity) {
+            for field in std::mem::take(placed) {
+                if let Some(state) = self.fields.get_mut(&field) {
+                    state.hitboxes.retain(|(other, _)| *other != entity);
+                    state.creatures.retain(|other| *other != entity);
+                }
+            }
+        }
+        self.place(ecs_, entity);
+    }
+
+    fn place(&mut self, ecs_: &ecs::ECS, entity: ecs::Entity) {
+        let location_c = match ecs_.location_component.get(entity) {
+            Some(location_c) => location_c,
+            None => return
+        };
+        let water = ecs_.dungeon_component.get(entity).is_some_and(|dungeon_c| dungeon_c.type_ == DungeonElement::Water);
+        let creature = ecs_.health_component.get(entity).is_some_and(|health_c| health_c.current > 0) && !death::is_dying(ecs_, entity);
+
+        let mut locations = vec![location_c.location];
+        if let Some(move_intent) = &location_c.move_intent {
+            locations.push(move_intent.target_goal(&location_c.location));
+        }
+        let mut placed = Vec::new();
+        for location in locations {
+            // entities between two fields are only on the ones they started from and move to
+            if location.x.fract() != 0.0 || location.y.fract() != 0.0 || placed.contains(&to_field(location)) {
+                continue;
+            }
+            let field = self.fields.entry(to_field(location)).or_default();
+            field.water |= water;
+            if let Some(hitbox) = &location_c.hitbox {
+                field.hitboxes.push((entity, hitbox.type_.clone()));
+            }
+            if creature {
+                field.creatures.push(entity);
+            }
+            placed.push(to_field(location));
+        }
+        self.placed.set(entity, placed);
+    }
+
+    /// Tests if an entity can stand on a field. There must be something to stand on, no water, no colliding
+    /// `event::Hitbox` of equal or greater ordering layer than the entity's and, if the entity is a creature,
+    /// no other living creature
+    ///
+    /// ### Arguments
+    /// * `ecs_`     - The entity component system the map was built from
+    /// * `entity`   - The entity that wants to enter the field
+    /// * `location` - The location of the field
+    ///
+    /// ### Returns
+    /// Ok if the entity can enter the field, else why it can not
+    pub fn check_enter(&self, ecs_: &ecs::ECS, entity: ecs::Entity, location: LocationVec) -> Result<(), Blocked> {
+        let field = match self.fields.get(&to_field(location)) {
+            Some(field) => field,
+            None => return Err(Blocked::Void)
+        };
+        let hitbox = ecs_.location_component.get(entity).and_then(|location_c| location_c.hitbox.as_ref());
+        let is_creature = ecs_.health_component.get(entity).is_some();
+
+        if field.water && (is_creature || hitbox.is_some_and(|hitbox| hitbox.type_ >= HitboxType::Creature)) {
+            return Err(Blocked::Obstacle);
+        }
+        if let Some(hitbox) = hitbox {
+            if let Some((other, _)) = field.hitboxes.iter().find(|(other, type_)| *other != entity && *type_ >= hitbox.type_) {
+                // creatures in the way can be attacked, everything else can not
+                return Err(if field.creatures.contains(other) { Blocked::Occupied(*other) } else { Blocked::Obstacle });
+            }
+        }
+        if is_creature {
+            if let Some(other) = field.creatures.iter().find(|&&other| other != entity) {
+                return Err(Blocked::Occupied(*other));
+            }
+        }
+        Ok(())
+    }
+
+    /// Tests if a field is empty ground, e.g. to place a creature onto. There must be
+    /// something to stand on, no water and nothing with a hitbox or alive
+    pub fn is_free(&self, location: LocationVec) -> bool {
+        self.fields.get(&to_field(location))
+                   .is_some_and(|field| !field.water && field.hitboxes.is_empty() && field.creatures.is_empty())
+    }
+
+    /// Tests if an entity can take a step from a field into a direction. Diagonal steps can not cut the corners
+    /// of fields the entity can not enter
+    pub fn check_step(&self, ecs_: &ecs::ECS, entity: ecs::Entity, from: LocationVec, dir: Direction) -> Result<(), Blocked> {
+        let target = self.check_enter(ecs_, entity, from + dir.into());
+        // a creature right in the way is what the entity bumps into, even on diagonal steps
+        if let Err(Blocked::Occupied(_)) = target {
+            return target;
+        }
+        if let Some((vertical, horizontal)) = dir.split() {
+            for corner in [vertical, horizontal].iter() {
+                if let Err(blocked) = self.check_enter(ecs_, entity, from + (*corner).into()) {
+                    return Err(match blocked {
+                        Blocked::Occupied(_) => Blocked::Obstacle,
+                        blocked => blocked
+                    });
+                }
+            }
+        }
+        target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamestate::movement::MoveIntent;
+
+    #[test]
+    fn test_walls_water_and_creatures_block() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 6, 5);
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        let dummy = dungeon::create_attack_dummy(&mut ecs_, 3.0, 1.0);
+        dungeon::create_water_tile(&mut ecs_, 1.0, 3.0);
+        let map = CollisionMap::build(&ecs_);
+
+        assert_eq!(map.check_enter(&ecs_, player, LocationVec { x: 2.0, y: 1.0 }), Ok(()));
+        assert_eq!(map.check_enter(&ecs_, player, LocationVec { x: 0.0, y: 1.0 }), Err(Blocked::Obstacle));
+        assert_eq!(map.check_enter(&ecs_, player, LocationVec { x: 1.0, y: 3.0 }), Err(Blocked::Obstacle));
+        assert_eq!(map.check_enter(&ecs_, player, LocationVec { x: 3.0, y: 1.0 }), Err(Blocked::Occupied(dummy)));
+        assert_eq!(map.check_enter(&ecs_, player, LocationVec { x: 9.0, y: 1.0 }), Err(Blocked::Void));
+        // no corners are cut around walls or creatures
+        assert_eq!(map.check_step(&ecs_, player, LocationVec { x: 1.0, y: 1.0 }, Direction::UpRight), Err(Blocked::Obstacle));
+        assert_eq!(map.check_step(&ecs_, player, LocationVec { x: 2.0, y: 2.0 }, Direction::UpRight), Err(Blocked::Occupied(dummy)));
+        assert_eq!(map.check_step(&ecs_, player, LocationVec { x: 2.0, y: 1.0 }, Direction::DownRight), Err(Blocked::Obstacle));
+
+        // dead creatures are no longer in the way, stepping creatures are on both fields
+        ecs_.health_component.get_mut(dummy).unwrap().current = 0;
+        let mut map = CollisionMap::build(&ecs_);
+        assert_eq!(map.check_enter(&ecs_, player, LocationVec { x: 3.0, y: 1.0 }), Ok(()));
+        let rat = dungeon::create_rat(&mut ecs_, 2.0, 2.0);
+        assert!(map.is_free(LocationVec { x: 2.0, y: 3.0 }));
+        ecs_.location_component.get_mut(rat).unwrap().move_intent = Some(MoveIntent::Vector(Direction::Down.into(), 1.0));
+        map.update_entity(&ecs_, rat);
+        assert!(!map.is_free(LocationVec { x: 2.0, y: 2.0 }) && !map.is_free(LocationVec { x: 2.0, y: 3.0 }));
+        ecs_.location_component.get_mut(rat).unwrap().location = LocationVec { x: 2.0, y: 3.0 };
+        ecs_.location_component.get_mut(rat).unwrap().move_intent = None;
+        map.update_entity(&ecs_, rat);
+        assert!(map.is_free(LocationVec { x: 2.0, y: 2.0 }));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::ecs;
+use crate::gamelogic::{self, BURN_DAMAGE};
+use crate::gamestate::{spell::Ability, status::StatusType};
+
+// attacks vary between 90% and 110% of their damage
+pub const MIN_DAMAGE_ROLL: f32 = 0.9;
+pub const MAX_DAMAGE_ROLL: f32 = 1.1;
+
+/// The offensive and defensive stats of an entity with its status applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EffectiveStats {
+    pub attack: i32,
+    pub attack_multiplier: f32,
+    pub defense: i32,
+    pub defense_multiplier: f32,
+    // blocks all attacks, see `StatusType::Invincible`
+    pub invincible: bool
+}
+
+/// A prediction of what attacking a target does, e.g. to show it before attacking.
+/// There are no critical hits, so the range covers every possible hit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DamagePreview {
+    // lowest and highest damage of the hit, None if it is blocked
+    pub damage: Option<(i32, i32)>,
+    // damage taken each turn afterwards, e.g. while burning
+    pub damage_per_turn: i32,
+    // true if even the lowest damage kills a hostile target
+    pub lethal: bool
+}
+
+/// Computes the stats an entity fights with. Entities without a `BaseStatsComponent`
+/// neither attack nor defend and their status is ignored
+pub fn effective_stats(ecs_: &ecs::ECS, entity: ecs::Entity) -> EffectiveStats {
+    let mut stats = EffectiveStats { attack: 0, attack_multiplier: 2.0, defense: 0, defense_multiplier: 1.0, invincible: false };
+    if let Some(basestats_c) = ecs_.basestats_component.get(entity) {
+        stats.attack = basestats_c.attack;
+        stats.defense = basestats_c.defense;
+
+        // check for basestats status modifications
+        if let Some(status_c) = ecs_.status_component.get(entity) {
+            for status in &status_c.status {
+                match &status.type_ {
+                    StatusType::BaseStatusModifier(modifier) => {
+                        stats.attack += modifier.attack;
+                        stats.defense += modifier.defense;
+                    },
+                    StatusType::BaseStatusMuliplier(multiplier) => {
+                        stats.attack_multiplier += multiplier.attack;
+                        stats.defense_multiplier += multiplier.defense;
+                    },
+                    StatusType::Invincible => stats.invincible = true,
+                    _ => {}
+                }
+            }
+        }
+        //TODO check for equipment
+    }
+    stats
+}
+
+/// The damage formula: the attack of the attacker against the defense of the target,
+/// at least 1
+///
+/// ### Arguments
+/// * `attacker`   - Effective stats of the attacker
+/// * `target`     - Effective stats of the target
+/// * `roll`       - Random variation, between `MIN_DAMAGE_ROLL` and `MAX_DAMAGE_ROLL`
+/// * `difficulty` - Factor of the damage the target takes, see `damage_taken_factor`
+///
+pub fn damage(attacker: &EffectiveStats, target: &EffectiveStats, roll: f32, difficulty: f32) -> i32 {
+    let base = attacker.attack as f32 * attacker.attack_multiplier - target.defense as f32 * target.defense_multiplier;
+    std::cmp::max(1, (base * roll * difficulty) as i32)
+}
+
+/// Factor of the damage an entity takes, the difficulty only changes the damage players take
+pub fn damage_taken_factor(ecs_: &ecs::ECS, target: ecs::Entity) -> f32 {
+    if ecs_.player_component.get(target).is_some() {
+        ecs_.difficulty.damage_taken_factor()
+    } else {
+        1.0
+    }
+}
+
+/// The lowest and highest damage an attack can do
+///
+/// ### Returns
+/// The range, None if the target blocks the attack
+pub fn damage_range(ecs_: &ecs::ECS, attacker: ecs::Entity, target: ecs::Entity) -> Option<(i32, i32)> {
+    let (attacker_stats, target_stats) = (effective_stats(ecs_, attacker), effective_stats(ecs_, target));
+    if target_stats.invincible {
+        return None;
+    }
+    let difficulty = damage_taken_factor(ecs_, target);
+    Some((damage(&attacker_stats, &target_stats, MIN_DAMAGE_ROLL, difficulty),
+          damage(&attacker_stats, &target_stats, MAX_DAMAGE_ROLL, difficulty)))
+}
+
+/// Damage an ability deals each turn after hitting, e.g. the fire of a firebolt
+pub fn damage_per_turn(ecs_: &ecs::ECS, ability: Option<Ability>) -> i32 {
+    match ability {
+        // the rain douses the fire at once
+        Some(Ability::Firebolt) if !ecs_.weather.douses_fire() => BURN_DAMAGE,
+        _ => 0
+    }
+}
+
+/// Predicts what attacking a target does, in melee or with an ability
+///
+/// ### Arguments
+/// * `ecs_`     - The entity component system to perform on
+/// * `attacker` - The attacking entity
+/// * `target`   - The attacked entity
+/// * `ability`  - The ability used, None for a melee attack
+///
+pub fn preview(ecs_: &ecs::ECS, attacker: ecs::Entity, target: ecs::Entity, ability: Option<Ability>) -> DamagePreview {
+    let damage = damage_range(ecs_, attacker, target);
+    let health = ecs_.health_component.get(target).map_or(0, |health_c| health_c.current);
+    DamagePreview {
+        damage,
+        damage_per_turn: if damage.is_some() { damage_per_turn(ecs_, ability) } else { 0 },
+        lethal: damage.is_some_and(|(min, _)| min >= health) && gamelogic::is_hostile(ecs_, attacker, target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::config::Difficulty;
+    use crate::gamestate::{components::StatusComponent, duration::Duration, status::Status};
+
+    #[test]
+    fn test_preview_matches_the_damage_dealt() {
+        let mut ecs_ = ecs::ECS::new();
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        let dummy = dungeon::create_attack_dummy(&mut ecs_, 2.0, 1.0);
+
+        // 10 attack doubled against 5 defense
+        let (min, max) = damage_range(&ecs_, player, dummy).unwrap();
+        assert_eq!((min, max), (13, 16));
+        for _ in 0..20 {
+            ecs_.health_component.get_mut(dummy).unwrap().current = 100;
+            gamelogic::attack(&mut ecs_, player, dummy);
+            let dealt = 100 - ecs_.health_component.get(dummy).unwrap().current;
+            assert!(dealt >= min && dealt <= max, "{} outside of {}-{}", dealt, min, max);
+        }
+        assert_eq!(preview(&ecs_, player, dummy, Some(Ability::Firebolt)).damage_per_turn, BURN_DAMAGE);
+        assert!(!preview(&ecs_, player, dummy, None).lethal);
+
+        // only players take more damage on hard
+        ecs_.difficulty = Difficulty::Hard;
+        assert_eq!(damage_range(&ecs_, player, dummy), Some((min, max)));
+        assert_eq!(damage_taken_factor(&ecs_, player), 1.5);
+
+        ecs_.status_component.set(dummy, StatusComponent { status: vec![Status { type_: StatusType::Invincible, duration: Duration::Infinite }] });
+        let blocked = preview(&ecs_, player, dummy, Some(Ability::Firebolt));
+        assert_eq!((blocked.damage, blocked.damage_per_turn), (None, 0));
+    }
+}
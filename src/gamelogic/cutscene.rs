@@ -0,0 +1,316 @@
+use std::collections::VecDeque;
+
+use crate::ecs;
+use crate::event::bus::GameEvent;
+use crate::gamelogic::{self, stop_all_resting};
+use crate::gamestate::{movement::{self, MoveIntent}, LocationVec};
+
+// game updates the camera of the intro takes to glide down to the player
+const INTRO_PAN_UPDATES: u64 = 45;
+// fields above the player the intro starts at
+const INTRO_PAN_DISTANCE: f64 = 6.0;
+// game updates the camera rests on a boss before and after it speaks
+const BOSS_WAIT_UPDATES: u64 = 20;
+
+/// What the camera shows during a cutscene
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraFocus {
+    // follows an entity, like the player outside of cutscenes
+    Entity(ecs::Entity),
+    // stands still on a point, which may lie between fields
+    Point(LocationVec)
+}
+
+/// A step of a cutscene, the next one begins once it is done
+#[derive(Debug, Clone)]
+pub enum CutsceneStep {
+    // the camera follows an entity from now on
+    Focus(ecs::Entity),
+    // the camera glides to a point over a number of game updates and stays there
+    Pan(LocationVec, u64),
+    // an entity moves, done once it arrived
+    Move(ecs::Entity, MoveIntent),
+    // a text of the locale, shown until the player confirms it, with the name of its
+    // speaker if any
+    Dialogue(Option<ecs::Entity>, String),
+    // nothing happens for a number of game updates
+    Wait(u64),
+}
+
+/// A scripted scene taking control from the player, e.g. an intro or the entrance of a
+/// boss. Turns do not pass while it plays, only its moves go on
+#[derive(Debug, Clone)]
+pub struct Cutscene {
+    steps: VecDeque<CutsceneStep>,
+    // None while the camera follows whatever it followed before
+    pub camera: Option<CameraFocus>,
+    // the text and speaker shown, until the player confirms it
+    pub dialogue: Option<(Option<ecs::Entity>, String)>,
+    // game updates the current step has run
+    elapsed: u64,
+    // where the camera was when the current pan began
+    pan_from: Option<LocationVec>,
+    started: bool
+}
+
+impl Cutscene {
+    pub fn new(steps: Vec<CutsceneStep>) -> Cutscene {
+        Cutscene {
+            steps: steps.into(),
+            camera: None,
+            dialogue: None,
+            elapsed: 0,
+            pan_from: None,
+            started: false
+        }
+    }
+}
+
+/// Starts playing a cutscene: aiming and resting stop, queued actions are dropped
+///
+/// ### Returns
+/// True if the cutscene started, false if another one is playing
+pub fn start(ecs_: &mut ecs::ECS, cutscene: Cutscene) -> bool {
+    if ecs_.cutscene.is_some() {
+        return false;
+    }
+    debug!("Starting a cutscene of {} steps", cutscene.steps.len());
+    stop_all_resting(ecs_);
+    ecs_.targeting = None;
+    ecs_.action_queue.clear();
+    ecs_.cutscene = Some(cutscene);
+    true
+}
+
+pub fn is_playing(ecs_: &ecs::ECS) -> bool {
+    ecs_.cutscene.is_some()
+}
+
+/// Plays the cutscene on for one game update. Steps that are done at once, like
+/// refocusing the camera, are followed by the next one in the same update
+pub fn update_cutscene(ecs_: &mut ecs::ECS) {
+    let mut cutscene = match ecs_.cutscene.take() {
+        Some(cutscene) => cutscene,
+        None => return
+    };
+    while let Some(step) = cutscene.steps.front().cloned() {
+        if !play_step(ecs_, &mut cutscene, step) {
+            break;
+        }
+        cutscene.steps.pop_front();
+        cutscene.elapsed = 0;
+        cutscene.started = false;
+    }
+    if cutscene.steps.is_empty() {
+        debug!("Cutscene finished");
+    } else {
+        ecs_.cutscene = Some(cutscene);
+    }
+}
+
+/// Plays a step for one game update
+///
+/// ### Returns
+/// True if the step is done
+fn play_step(ecs_: &mut ecs::ECS, cutscene: &mut Cutscene, step: CutsceneStep) -> bool {
+    let starting = !cutscene.started;
+    cutscene.started = true;
+    match step {
+        CutsceneStep::Focus(entity) => {
+            cutscene.camera = Some(CameraFocus::Entity(entity));
+            true
+        },
+        CutsceneStep::Pan(to, updates) => {
+            if starting {
+                cutscene.pan_from = camera_location(ecs_, cutscene.camera);
+            }
+            cutscene.elapsed += 1;
+            let progress = (cutscene.elapsed as f64 / updates.max(1) as f64).min(1.0);
+            let from = cutscene.pan_from.unwrap_or(to);
+            cutscene.camera = Some(CameraFocus::Point(LocationVec {
+                x: from.x + (to.x - from.x) * progress,
+                y: from.y + (to.y - from.y) * progress
+            }));
+            progress >= 1.0
+        },
+        CutsceneStep::Move(entity, intent) => {
+            match ecs_.location_component.get_mut(entity) {
+                Some(location_c) => {
+                    if starting {
+                        location_c.move_intent = Some(intent);
+                    }
+                    location_c.move_intent.is_none()
+                },
+                // whoever should move is gone, e.g. killed meanwhile
+                None => true
+            }
+        },
+        CutsceneStep::Dialogue(speaker, text) => {
+            if starting {
+                cutscene.dialogue = Some((speaker, text));
+                ecs_.events.publish(GameEvent::UiClick);
+            }
+            cutscene.dialogue.is_none()
+        },
+        CutsceneStep::Wait(updates) => {
+            cutscene.elapsed += 1;
+            cutscene.elapsed >= updates
+        }
+    }
+}
+
+/// The point the camera shows, the player's location if the cutscene did not move it
+fn camera_location(ecs_: &mut ecs::ECS, camera: Option<CameraFocus>) -> Option<LocationVec> {
+    let entity = match camera {
+        Some(CameraFocus::Point(point)) => return Some(point),
+        Some(CameraFocus::Entity(entity)) => Some(entity),
+        None => ecs_.get_player_entity()
+    };
+    entity.and_then(|entity| ecs_.location_component.get(entity)).map(|location_c| location_c.location)
+}
+
+/// Closes the dialogue shown, the cutscene goes on with its next step
+pub fn confirm_dialogue(ecs_: &mut ecs::ECS) {
+    if let Some(cutscene) = &mut ecs_.cutscene {
+        cutscene.dialogue = None;
+    }
+}
+
+/// Ends the cutscene at once. Entities it would have moved are put where they would
+/// have arrived, so the world is as if it had played to the end
+pub fn skip(ecs_: &mut ecs::ECS) {
+    let cutscene = match ecs_.cutscene.take() {
+        Some(cutscene) => cutscene,
+        None => return
+    };
+    debug!("Skipping the cutscene with {} steps left", cutscene.steps.len());
+    let started = cutscene.started;
+    for (i, step) in cutscene.steps.into_iter().enumerate() {
+        if let CutsceneStep::Move(entity, intent) = step {
+            if let Some(location_c) = ecs_.location_component.get_mut(entity) {
+                // a move in progress may have been rerouted, its current intent knows best
+                let intent = match &location_c.move_intent {
+                    Some(current) if i == 0 && started => current.clone(),
+                    _ => intent
+                };
+                location_c.location = intent.target_goal(&location_c.location);
+                location_c.previous_location = None;
+                location_c.move_intent = None;
+            }
+        }
+    }
+}
+
+/// The intro of a new game: the camera glides down onto the player, who is welcomed
+pub fn intro(ecs_: &ecs::ECS, player: ecs::Entity) -> Cutscene {
+    let location = ecs_.location_component.get(player).map_or(LocationVec { x: 0.0, y: 0.0 }, |location_c| location_c.location);
+    Cutscene::new(vec![
+        CutsceneStep::Pan(LocationVec { x: location.x, y: location.y - INTRO_PAN_DISTANCE }, 0),
+        CutsceneStep::Pan(location, INTRO_PAN_UPDATES),
+        CutsceneStep::Focus(player),
+        CutsceneStep::Dialogue(None, "cutscene.intro".to_string())
+    ])
+}
+
+/// The entrance of a boss: the camera turns to the boss, which steps towards the player
+/// and speaks, then back to the player
+pub fn boss_entrance(ecs_: &ecs::ECS, boss: ecs::Entity, player: ecs::Entity) -> Cutscene {
+    let mut steps = vec![CutsceneStep::Focus(boss), CutsceneStep::Wait(BOSS_WAIT_UPDATES)];
+    let locations = (ecs_.location_component.get(boss), ecs_.location_component.get(player));
+    if let (Some(boss_c), Some(player_c)) = locations {
+        // one field towards the player, also diagonally
+        let sign = |difference: f64| if difference.abs() < 0.5 { 0.0 } else { difference.signum() };
+        let towards = LocationVec { x: sign(player_c.location.x - boss_c.location.x),
+                                    y: sign(player_c.location.y - boss_c.location.y) };
+        let step = boss_c.location + towards;
+        // a boss next to the player stays where it is
+        if step != player_c.location && step != boss_c.location {
+            steps.push(CutsceneStep::Move(boss, MoveIntent::Position(step, movement::DEFAULT_SPEED / 2.0)));
+        }
+    }
+    steps.push(CutsceneStep::Dialogue(Some(boss), "cutscene.boss".to_string()));
+    steps.push(CutsceneStep::Wait(BOSS_WAIT_UPDATES));
+    steps.push(CutsceneStep::Focus(player));
+    Cutscene::new(steps)
+}
+
+/// Plays the entrance of a boss the first time the player sees it
+pub fn check_boss_entrances(ecs_: &mut ecs::ECS) {
+    if is_playing(ecs_) {
+        return;
+    }
+    let player = match ecs_.get_player_entity() {
+        Some(player) => player,
+        None => return
+    };
+    let boss = gamelogic::visible_hostiles(ecs_, player)
+        .into_iter()
+        .find(|entity| ecs_.boss_component.get(*entity).is_some_and(|boss_c| !boss_c.introduced));
+    if let Some(boss) = boss {
+        if let Some(boss_c) = ecs_.boss_component.get_mut(boss) {
+            boss_c.introduced = true;
+        }
+        let cutscene = boss_entrance(ecs_, boss, player);
+        start(ecs_, cutscene);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamestate::components::{BossComponent, NpcBehaviorComponent};
+    use crate::render::RenderConfig;
+
+    #[test]
+    fn test_boss_entrance_plays_once() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 10, 6);
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 2.0);
+        let boss = dungeon::create_attack_dummy(&mut ecs_, 6.0, 2.0);
+        ecs_.npc_behavior_component.set(boss, NpcBehaviorComponent { hostile: true });
+        ecs_.boss_component.set(boss, BossComponent { final_boss: false, introduced: false });
+        let mut conf = RenderConfig { scale: 20.0, window_xs: 320, window_ys: 240, focused_entity: Some(player), focused_point: None, show_minimap: false };
+
+        gamelogic::update(&mut ecs_);
+        assert!(is_playing(&ecs_));
+        conf.update_focus(&mut ecs_);
+        assert_eq!(conf.focused_entity, Some(boss));
+
+        // the boss steps closer and speaks until confirmed
+        for _ in 0..crate::UPDATES_PER_SECOND * 3 {
+            gamelogic::update(&mut ecs_);
+        }
+        assert_eq!(ecs_.location_component.get(boss).unwrap().location, LocationVec { x: 5.0, y: 2.0 });
+        assert!(ecs_.cutscene.as_ref().unwrap().dialogue.is_some());
+
+        confirm_dialogue(&mut ecs_);
+        for _ in 0..=BOSS_WAIT_UPDATES {
+            gamelogic::update(&mut ecs_);
+        }
+        assert!(!is_playing(&ecs_));
+        conf.update_focus(&mut ecs_);
+        assert_eq!(conf.focused_entity, Some(player));
+        gamelogic::update(&mut ecs_);
+        assert!(!is_playing(&ecs_));
+    }
+
+    #[test]
+    fn test_skipping_finishes_moves() {
+        let mut ecs_ = ecs::ECS::new();
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        let goal = LocationVec { x: 4.0, y: 1.0 };
+        assert!(start(&mut ecs_, Cutscene::new(vec![
+            CutsceneStep::Pan(goal, 10),
+            CutsceneStep::Move(player, MoveIntent::Position(goal, movement::DEFAULT_SPEED))
+        ])));
+        let intro = intro(&ecs_, player);
+        assert!(!start(&mut ecs_, intro));
+
+        gamelogic::update(&mut ecs_);
+        assert!(matches!(ecs_.cutscene.as_ref().unwrap().camera, Some(CameraFocus::Point(point)) if point.x > 1.0 && point.x < 4.0));
+        skip(&mut ecs_);
+        assert!(!is_playing(&ecs_));
+        assert_eq!(ecs_.location_component.get(player).unwrap().location, goal);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::ecs;
+use crate::event::bus::GameEvent;
+use crate::gamelogic::items;
+use crate::gamestate::{actor, components, interaction::Interaction};
+use crate::render::{animation::AnimationController, layer::RenderLayer};
+use crate::ut;
+
+// game updates the death animation of an entity takes
+const DYING_UPDATES: u32 = 20;
+// turns until a corpse decays
+const CORPSE_DECAY_TURNS: u32 = 50;
+const CORPSE_TINT: [f32; 3] = [0.45, 0.4, 0.4];
+
+/// Lets an entity start dying. It stops acting and blocking other entities and is
+/// replaced by a corpse once its death animation has finished.
+/// The player does not die this way
+/// 
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `entity` - The entity that died
+/// 
+pub fn start_dying(ecs_: &mut ecs::ECS, entity: ecs::Entity) {
+    if ecs_.player_component.get(entity).is_some() || ecs_.dying_component.get(entity).is_some() {
+        return;
+    }
+
+    ecs_.dying_component.set(entity, components::DyingComponent {
+        remaining: DYING_UPDATES,
+        duration: DYING_UPDATES
+    });
+    // dying entities must not hold up the turn or block the way
+    if let Some(actor_c) = ecs_.actor_component.get_mut(entity) {
+        actor_c.state = actor::ActorState::DoneActing;
+    }
+    if let Some(location_c) = ecs_.location_component.get_mut(entity) {
+        location_c.hitbox = None;
+        location_c.move_intent = None;
+    }
+}
+
+/// Tests if the entity is currently dying
+pub fn is_dying(ecs_: &ecs::ECS, entity: ecs::Entity) -> bool {
+    ecs_.dying_component.get(entity).is_some()
+}
+
+/// Advances the death animations of all dying entities by one update and replaces
+/// entities with finished animations by their corpses
+/// 
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+/// 
+pub fn update_dying(ecs_: &mut ecs::ECS) {
+    for entity in ecs_.allocator.live_indices() {
+        let mut finished = false;
+        if let Some(dying_c) = ecs_.dying_component.get_mut(entity) {
+            dying_c.remaining = dying_c.remaining.saturating_sub(1);
+            finished = dying_c.remaining == 0;
+        }
+
+        if finished {
+            spawn_corpse(ecs_, entity);
+            ecs_.allocator.deallocate(entity);
+        }
+    }
+}
+
+/// Creates the corpse of an entity at its location, holding its gold and items
+/// 
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `entity` - The dead entity
+/// 
+/// ### Returns
+/// The corpse, None if the entity had no location
+/// 
+pub fn spawn_corpse(ecs_: &mut ecs::ECS, entity: ecs::Entity) -> Option<ecs::Entity> {
+    let (location, direction) = match ecs_.location_component.get(entity) {
+        Some(location_c) => (location_c.location, location_c.direction),
+        None => return None
+    };
+    let name = ecs_.locale.format("creature.corpse", &[("name", &ut::name_or_id(ecs_, entity))]);
+    let sprite = ecs_.render_component.get(entity).map(|render_c| (render_c.base_sprite.clone(), render_c.base_sprite_size));
+    let gold = ecs_.item_drop_component.get(entity).map_or(0, |drop_c| drop_c.gold);
+    //TODO drop ItemDropComponent items once items can be looked up by ItemId
+    let items = ecs_.inventory_component.get_mut(entity)
+                                        .map_or(Vec::new(), |inventory_c| inventory_c.items.drain(..).collect());
+
+    let corpse = ecs_.spawn()
+        .with(components::LocationComponent {
+            location,
+            previous_location: None,
+            direction,
+            move_intent: None,
+            // corpses never block
+            hitbox: None
+        })
+        .with(components::NameComponent { name })
+        .with(components::CorpseComponent {
+            decay_turns: CORPSE_DECAY_TURNS,
+            gold,
+            items,
+            looted: false
+        })
+        .with(components::InteractableComponent { interaction: Interaction::Corpse })
+        .with_some(sprite.map(|(base_sprite, base_sprite_size)| components::RenderComponent {
+            base_sprite,
+            base_sprite_size,
+            animation: AnimationController::new(),
+            visible: true,
+            render_layer: RenderLayer::Items,
+            tint: Some(CORPSE_TINT),
+            opacity: 0.8,
+            flash: None
+        }))
+        .build();
+    Some(corpse)
+}
+
+/// Lets all corpses decay by one turn and removes fully decayed ones
+/// 
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+/// 
+pub fn decay_corpses(ecs_: &mut ecs::ECS) {
+    for entity in ecs_.allocator.live_indices() {
+        let mut decayed = false;
+        if let Some(corpse_c) = ecs_.corpse_component.get_mut(entity) {
+            corpse_c.decay_turns = corpse_c.decay_turns.saturating_sub(1);
+            decayed = corpse_c.decay_turns == 0;
+        }
+        if decayed {
+            debug!("{} decayed", ut::name_or_id(ecs_, entity));
+            ecs_.allocator.deallocate(entity);
+        }
+    }
+}
+
+/// Moves gold and items of a corpse to the looting entity. Gold is only taken by
+/// players, items only by entities with an inventory with enough capacity left
+/// 
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `looter` - The entity looting
+/// * `corpse` - The corpse to loot
+/// 
+/// ### Returns
+/// True if the corpse was looted, false if it was no corpse or already looted
+/// 
+pub fn loot_corpse(ecs_: &mut ecs::ECS, looter: ecs::Entity, corpse: ecs::Entity) -> bool {
+    let (gold, items) = match ecs_.corpse_component.get_mut(corpse) {
+        Some(corpse_c) if !corpse_c.looted => {
+            let gold = corpse_c.gold;
+            corpse_c.gold = 0;
+            (gold, corpse_c.items.drain(..).collect::<Vec<_>>())
+        },
+        _ => return false
+    };
+
+    let items = items::give_loot(ecs_, looter, gold, items);
+
+    // whatever did not fit stays in the corpse
+    if let Some(corpse_c) = ecs_.corpse_component.get_mut(corpse) {
+        corpse_c.looted = items.is_empty();
+        corpse_c.items = items;
+    }
+    info!("{}", ecs_.locale.format("log.loot", &[
+        ("looter", &ut::name_or_id(ecs_, looter)),
+        ("gold", &gold),
+        ("corpse", &ut::name_or_id(ecs_, corpse))
+    ]));
+    ecs_.events.publish(GameEvent::Looted { looter, corpse });
+    true
+}
@@ -0,0 +1,132 @@
+use crate::ecs;
+use crate::gamelogic::{death, lod::{self, Throttled}};
+use crate::gamestate::{components::RenderComponent, LocationVec};
+use crate::render::{animation::{AnimationKind, HitFlash}, particle::Burst, shake};
+
+// game updates a hit flash lasts
+const FLASH_UPDATES: u32 = 6;
+// distance (in fields) an entity is knocked away by a hit
+const KNOCK_DISTANCE: f64 = 0.15;
+const DAMAGE_FLASH_COLOR: [f32; 3] = [0.8, 0.0, 0.0];
+const BLOCK_FLASH_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+// time scale and duration (in event loop updates) of the slow motion after a kill
+const KILL_SLOW_MOTION_SCALE: f64 = 0.3;
+const KILL_SLOW_MOTION_UPDATES: u32 = 20;
+// strength (in fields) and duration (in event loop updates) of the screen shake when a player is hit
+const HIT_SHAKE_STRENGTH: f64 = 0.12;
+const HIT_SHAKE_UPDATES: u32 = 8;
+
+/// Cosmetic reaction to an entity taking damage
+pub fn on_hit(ecs_: &mut ecs::ECS, entity: ecs::Entity, source: Option<ecs::Entity>) {
+    if let Some(location) = ecs_.location_component.get(entity).map(|location_c| location_c.location) {
+        ecs_.particles.spawn_burst(location, &Burst::hit());
+    }
+    flash(ecs_, entity, source, DAMAGE_FLASH_COLOR, KNOCK_DISTANCE);
+    play(ecs_, Some(entity), AnimationKind::Hurt);
+    play(ecs_, source, AnimationKind::Attack);
+    if ecs_.player_component.get(entity).is_some() {
+        let scale = if ecs_.settings.accessibility.reduce_screen_shake { shake::REDUCED_SHAKE_SCALE } else { 1.0 };
+        ecs_.screen_shake.start(HIT_SHAKE_STRENGTH * scale, HIT_SHAKE_UPDATES);
+    }
+}
+
+/// Cosmetic reaction to an attack not affecting an entity
+pub fn on_block(ecs_: &mut ecs::ECS, entity: ecs::Entity, source: Option<ecs::Entity>) {
+    flash(ecs_, entity, source, BLOCK_FLASH_COLOR, 0.0);
+    play(ecs_, source, AnimationKind::Attack);
+}
+
+/// Cosmetic reaction to a spell hitting a location
+pub fn on_spell_impact(ecs_: &mut ecs::ECS, location: LocationVec) {
+    ecs_.particles.spawn_burst(location, &Burst::spell_impact());
+}
+
+/// Cosmetic reaction to an entity dying
+pub fn on_death(ecs_: &mut ecs::ECS, entity: ecs::Entity, killer: Option<ecs::Entity>) {
+    if let Some(location) = ecs_.location_component.get(entity).map(|location_c| location_c.location) {
+        ecs_.particles.spawn_burst(location, &Burst::death());
+    }
+    // kills of the player are shown in slow motion
+    if killer.is_some_and(|killer| ecs_.player_component.get(killer).is_some()) {
+        ecs_.time_scale.set_effect(KILL_SLOW_MOTION_SCALE, KILL_SLOW_MOTION_UPDATES);
+    }
+}
+
+/// Lets the sprite of an entity flash in a color and knocks it away from the source
+/// 
+/// ### Arguments
+/// * `ecs_`     - The entity component system to perform on
+/// * `entity`   - The entity to flash
+/// * `source`   - The entity causing the flash, the knock points away from it
+/// * `color`    - The color to flash in
+/// * `distance` - The distance (in fields) of the knock
+/// 
+fn flash(ecs_: &mut ecs::ECS, entity: ecs::Entity, source: Option<ecs::Entity>, color: [f32; 3], distance: f64) {
+    let location = |e: ecs::Entity| ecs_.location_component.get(e).map(|location_c| location_c.location);
+    let mut knock = LocationVec { x: 0.0, y: 0.0 };
+    if let (Some(target_location), Some(source_location)) = (location(entity), source.and_then(location)) {
+        let direction = target_location - source_location;
+        let length = (direction.x * direction.x + direction.y * direction.y).sqrt();
+        if length > 0.0 {
+            knock = LocationVec { x: direction.x / length * distance, y: direction.y / length * distance };
+        }
+    }
+
+    if let Some(render_c) = ecs_.render_component.get_mut(entity) {
+        render_c.flash = Some(HitFlash::new(color, knock, FLASH_UPDATES));
+    }
+}
+
+/// Advances all hit flashes by one update and removes finished ones. Flashes far away
+/// from the players are throttled, see `lod`
+pub fn update_flashes(ecs_: &mut ecs::ECS) {
+    let focus = lod::focus(ecs_);
+    for entity in ecs_.allocator.live_indices() {
+        if ecs_.render_component.get(entity).is_none_or(|render_c| render_c.flash.is_none()) {
+            continue;
+        }
+        let ticks = lod::ticks(ecs_, &focus, entity, Throttled::Render);
+        if let Some(render_c) = ecs_.render_component.get_mut(entity) {
+            if let Some(flash) = &mut render_c.flash {
+                flash.remaining = flash.remaining.saturating_sub(ticks as u32);
+                if flash.remaining == 0 {
+                    render_c.flash = None;
+                }
+            }
+        }
+    }
+}
+
+/// Lets an entity play a one shot animation from the next update on
+fn play(ecs_: &mut ecs::ECS, entity: Option<ecs::Entity>, kind: AnimationKind) {
+    if let Some(render_c) = entity.and_then(|entity| ecs_.render_component.get_mut(entity)) {
+        render_c.animation.trigger(kind);
+    }
+}
+
+/// Advances the animations of all entities by one update. Dying entities play their die
+/// clip, moving ones their walk clip and all others idle, unless an attack or hurt
+/// clip is being played. Animations far away from the players are throttled, see `lod`
+pub fn update_animations(ecs_: &mut ecs::ECS) {
+    let dt_ms = 1000 / crate::UPDATES_PER_SECOND as u32;
+    let focus = lod::focus(ecs_);
+    for entity in ecs_.allocator.live_indices() {
+        let ticks = lod::ticks(ecs_, &focus, entity, Throttled::Render);
+        if ticks == 0 {
+            continue;
+        }
+        let state = if death::is_dying(ecs_, entity) {
+            AnimationKind::Die
+        } else if ecs_.location_component.get(entity).is_some_and(|location_c| location_c.move_intent.is_some()) {
+            AnimationKind::Walk
+        } else {
+            AnimationKind::Idle
+        };
+        let library = &ecs_.animations;
+        if let Some(RenderComponent { base_sprite, animation, .. }) = ecs_.render_component.get_mut(entity) {
+            let variant = animation.variant();
+            let duration = |kind| library.clip_for(base_sprite, kind, variant).map(|clip| clip.duration_ms());
+            animation.advance(state, &library.rules, &duration, dt_ms * ticks as u32);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use crate::ecs;
+use crate::event::bus::GameEvent;
+use crate::gamelogic::{targeting, PlayerAction};
+use crate::gamestate::{actor, hotbar::{HotbarSlot, HOTBAR_SLOTS}, item::ItemType, spell::Ability};
+use crate::gamestate::components::HotbarComponent;
+
+// health a consumable restores
+const CONSUMABLE_HEALING: i32 = 20;
+
+/// What the HUD shows of a slot
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotState {
+    // key of the name in the locale
+    pub name_key: String,
+    // uses left, None if unlimited
+    pub charges: Option<i32>,
+    // turns until the slot can be used again and the turns it cools down after use
+    pub cooldown: (u32, u32),
+    pub usable: bool
+}
+
+/// Fills a hotbar with everything an entity can use: the abilities of its class, its
+/// spells and, if it has an inventory, its consumables
+///
+/// ### Returns
+/// The slots, with empty ones after the used ones
+pub fn default_slots(ecs_: &ecs::ECS, entity: ecs::Entity) -> Vec<Option<HotbarSlot>> {
+    let mut slots: Vec<Option<HotbarSlot>> = Vec::new();
+    if let Some(class_c) = ecs_.class_component.get(entity) {
+        slots.extend(class_c.class.abilities().iter().map(|ability| Some(HotbarSlot::Ability(*ability))));
+    }
+    if let Some(caster_c) = ecs_.caster_component.get(entity) {
+        slots.extend((0..caster_c.spells.len()).map(|index| Some(HotbarSlot::Spell(index))));
+    }
+    if ecs_.inventory_component.get(entity).is_some() {
+        slots.push(Some(HotbarSlot::Consumable));
+    }
+    slots.resize(HOTBAR_SLOTS, None);
+    slots
+}
+
+/// Puts something into a slot of an entity's hotbar, the hotbar is created if the
+/// entity has none
+///
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `entity` - The entity owning the hotbar
+/// * `index`  - The slot, 0 for the first number key
+/// * `slot`   - What the slot uses, None empties it
+///
+/// ### Returns
+/// True if the slot was set, false if there is no such slot
+pub fn bind_slot(ecs_: &mut ecs::ECS, entity: ecs::Entity, index: usize, slot: Option<HotbarSlot>) -> bool {
+    if index >= HOTBAR_SLOTS {
+        return false;
+    }
+    if ecs_.hotbar_component.get(entity).is_none() {
+        ecs_.hotbar_component.set(entity, HotbarComponent { slots: vec![None; HOTBAR_SLOTS] });
+    }
+    match ecs_.hotbar_component.get_mut(entity) {
+        Some(hotbar_c) => {
+            hotbar_c.slots.resize(HOTBAR_SLOTS, None);
+            hotbar_c.slots[index] = slot;
+            true
+        },
+        None => false
+    }
+}
+
+/// The ability a slot uses, if it uses one
+fn slot_ability(ecs_: &ecs::ECS, entity: ecs::Entity, slot: HotbarSlot) -> Option<Ability> {
+    match slot {
+        HotbarSlot::Ability(ability) => Some(ability),
+        HotbarSlot::Spell(index) => ecs_.caster_component.get(entity)
+                                        .and_then(|caster_c| caster_c.spells.get(index))
+                                        .map(|spell| spell.ability),
+        HotbarSlot::Consumable => None
+    }
+}
+
+/// Computes what the HUD shows of a slot
+///
+/// ### Returns
+/// The state of the slot, None if it uses something the entity does not have any more
+pub fn slot_state(ecs_: &ecs::ECS, entity: ecs::Entity, slot: HotbarSlot) -> Option<SlotState> {
+    if slot == HotbarSlot::Consumable {
+        let amount = consumables(ecs_, entity);
+        return Some(SlotState { name_key: "item.Consumable".to_string(), charges: Some(amount), cooldown: (0, 0), usable: amount > 0 });
+    }
+    let ability = slot_ability(ecs_, entity, slot)?;
+    // abilities costing mana can be used as often as the mana lasts
+    let charges = match ability.mana_cost() {
+        0 => None,
+        cost => Some(ecs_.caster_component.get(entity).map_or(0, |caster_c| caster_c.current_mana / cost))
+    };
+    Some(SlotState {
+        name_key: ability.locale_key(),
+        charges,
+        cooldown: (targeting::cooldown(ecs_, entity, ability), ability.cooldown_turns()),
+        usable: targeting::can_use(ecs_, entity, ability)
+    })
+}
+
+/// Uses a slot of the player's hotbar: spells and abilities are aimed (see `targeting`),
+/// consumables are used right away
+///
+/// ### Returns
+/// The action to perform for the slot, None if there is none or the slot is aimed first
+pub fn activate_slot(ecs_: &mut ecs::ECS, index: usize) -> Option<PlayerAction> {
+    let player = ecs_.get_player_entity()?;
+    let slot = ecs_.hotbar_component.get(player)?.slots.get(index).copied().flatten()?;
+    match slot_ability(ecs_, player, slot) {
+        Some(ability) => {
+            if !targeting::start_targeting(ecs_, ability) {
+                debug!("Could not aim {:?} of hotbar slot {}", ability, index + 1);
+            }
+            None
+        },
+        None if slot == HotbarSlot::Consumable => Some(PlayerAction::UseConsumable),
+        None => None
+    }
+}
+
+/// Number of consumables in an entity's inventory
+fn consumables(ecs_: &ecs::ECS, entity: ecs::Entity) -> i32 {
+    ecs_.inventory_component.get(entity).map_or(0, |inventory_c| {
+        inventory_c.items.iter()
+                   .filter(|item| matches!(item.item, ItemType::Consumable))
+                   .map(|item| item.amount)
+                   .sum()
+    })
+}
+
+/// Uses up one consumable of an entity's inventory, restoring some of its health
+///
+/// ### Returns
+/// True if a consumable was used, false if the entity has none
+pub fn use_consumable(ecs_: &mut ecs::ECS, entity: ecs::Entity) -> bool {
+    let used = match ecs_.inventory_component.get_mut(entity) {
+        Some(inventory_c) => match inventory_c.items.iter().position(|item| matches!(item.item, ItemType::Consumable) && item.amount > 0) {
+            Some(index) => {
+                inventory_c.items[index].amount -= 1;
+                if inventory_c.items[index].amount == 0 {
+                    inventory_c.items.remove(index);
+                }
+                true
+            },
+            None => false
+        },
+        None => false
+    };
+    if used {
+        if let Some(health_c) = ecs_.health_component.get_mut(entity) {
+            health_c.current = std::cmp::min(health_c.maximum, health_c.current + CONSUMABLE_HEALING);
+        }
+        if let Some(actor_c) = ecs_.actor_component.get_mut(entity) {
+            actor_c.state = actor::ActorState::DoneActing;
+        }
+        ecs_.events.publish(GameEvent::ItemUsed { entity });
+    }
+    used
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamelogic;
+    use crate::gamestate::class::Class;
+    use crate::gamestate::components::{CasterComponent, ClassComponent};
+    use crate::gamestate::{item::Item, spell::Spell};
+
+    #[test]
+    fn test_slots_show_charges_and_cooldowns() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_test_floor(&mut ecs_);
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        ecs_.caster_component.set(player, CasterComponent {
+            current_mana: 12,
+            maximum_mana: 12,
+            spells: vec![Spell::new(1, Ability::Firebolt)],
+            cooldowns: vec![(Ability::Firebolt, 2)]
+        });
+        ecs_.inventory_component.get_mut(player).unwrap().items.push(Item { item: ItemType::Consumable, amount: 1 });
+        let slots = default_slots(&ecs_, player);
+        assert_eq!(&slots[..3], &[Some(HotbarSlot::Spell(0)), Some(HotbarSlot::Consumable), None]);
+        assert!(bind_slot(&mut ecs_, player, 0, slots[0]));
+        assert!(bind_slot(&mut ecs_, player, 1, slots[1]));
+
+        let firebolt = slot_state(&ecs_, player, HotbarSlot::Spell(0)).unwrap();
+        assert_eq!((firebolt.charges, firebolt.cooldown, firebolt.usable), (Some(2), (2, 3), false));
+        // a cooling down spell is not aimed
+        assert_eq!(activate_slot(&mut ecs_, 0), None);
+        assert!(ecs_.targeting.is_none());
+        gamelogic::pass_turn(&mut ecs_);
+        gamelogic::pass_turn(&mut ecs_);
+        assert!(slot_state(&ecs_, player, HotbarSlot::Spell(0)).unwrap().usable);
+
+        ecs_.health_component.get_mut(player).unwrap().current = 50;
+        let action = activate_slot(&mut ecs_, 1).unwrap();
+        gamelogic::perform_action(&mut ecs_, player, action);
+        assert_eq!(ecs_.health_component.get(player).unwrap().current, 50 + CONSUMABLE_HEALING);
+        assert_eq!(slot_state(&ecs_, player, HotbarSlot::Consumable).unwrap().charges, Some(0));
+    }
+
+    #[test]
+    fn test_class_hotbars_keep_the_consumable() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_test_floor(&mut ecs_);
+        // like a run started with `--class`
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        ecs_.class_component.set(player, ClassComponent { class: Class::Warrior, level: 1, experience: 0 });
+        let slots = default_slots(&ecs_, player);
+        assert!(slots.contains(&Some(HotbarSlot::Consumable)));
+        assert_eq!(slot_state(&ecs_, player, HotbarSlot::Consumable).unwrap().charges, Some(0));
+
+        ecs_.inventory_component.get_mut(player).unwrap().items.push(Item { item: ItemType::Consumable, amount: 2 });
+        assert!(use_consumable(&mut ecs_, player));
+        assert_eq!(slot_state(&ecs_, player, HotbarSlot::Consumable).unwrap().charges, Some(1));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::builder;
+use crate::ecs;
+use crate::event::{bus::GameEvent, Hitbox, HitboxType};
+use crate::gamelogic::{self, death, items, overworld};
+use crate::gamestate::{interaction::Interaction, LocationVec};
+use crate::ut;
+
+/// Tests if an interactable entity has anything left to do, e.g. looted corpses and
+/// emptied chests do not
+fn is_available(ecs_: &ecs::ECS, entity: ecs::Entity) -> bool {
+    match ecs_.interactable_component.get(entity).map(|interactable_c| &interactable_c.interaction) {
+        Some(Interaction::Chest { opened, .. }) => !opened,
+        Some(Interaction::Corpse) => ecs_.corpse_component.get(entity).is_some_and(|corpse_c| !corpse_c.looted),
+        Some(_) => true,
+        None => false
+    }
+}
+
+/// Finds what an entity can interact with: something on the field it faces, else
+/// something on its own field like the stairs it stands on
+pub fn find_interactable(ecs_: &ecs::ECS, entity: ecs::Entity) -> Option<ecs::Entity> {
+    let location_c = ecs_.location_component.get(entity)?;
+    let locations = [location_c.location + LocationVec::from(location_c.direction), location_c.location];
+    locations.iter()
+             .flat_map(|location| ecs_.get_entities_by_location(*location))
+             .find(|&candidate| candidate != entity && is_available(ecs_, candidate))
+}
+
+/// Lets an entity interact with another one, depending on its `InteractableComponent`
+///
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `actor`  - The entity interacting, usually a player
+/// * `target` - The entity interacted with
+///
+/// ### Returns
+/// True if something happened, false if the target can not be interacted with
+pub fn interact(ecs_: &mut ecs::ECS, actor: ecs::Entity, target: ecs::Entity) -> bool {
+    if !is_available(ecs_, target) {
+        return false;
+    }
+    let interaction = match ecs_.interactable_component.get(target) {
+        Some(interactable_c) => interactable_c.interaction.clone(),
+        None => return false
+    };
+    match interaction {
+        Interaction::Door { open } => set_door(ecs_, target, !open),
+        Interaction::Chest { gold, items, .. } => {
+            let left = items::give_loot(ecs_, actor, gold, items);
+            info!("{}", ecs_.locale.format("log.chest", &[("name", &ut::name_or_id(ecs_, actor)), ("gold", &gold)]));
+            let opened = left.is_empty();
+            set_interaction(ecs_, target, Interaction::Chest { gold: 0, items: left, opened });
+        },
+        Interaction::Talk { line } => {
+            info!("{}", ecs_.locale.format("log.talk", &[("name", &ut::name_or_id(ecs_, target)), ("line", &ecs_.locale.text(&line))]));
+        },
+        Interaction::Lever { flag, pulled } => {
+            ecs_.global_state_table.insert(flag.clone(), if pulled { "off" } else { "on" }.to_string());
+            info!("{}", ecs_.locale.format("log.lever", &[("name", &ut::name_or_id(ecs_, actor))]));
+            set_interaction(ecs_, target, Interaction::Lever { flag, pulled: !pulled });
+        },
+        Interaction::Stairs => {
+            // the last floor of a dungeon and towns are left for the overworld
+            if overworld::leads_out(ecs_) {
+                return overworld::leave(ecs_);
+            }
+            if !gamelogic::change_floor(ecs_, builder::dungeon::create_next_floor) {
+                return false;
+            }
+            ecs_.overworld.depth += 1;
+            if let Some(player_c) = ecs_.player_component.get_mut(actor) {
+                player_c.stage_level += 1;
+            }
+            ecs_.events.publish(GameEvent::Descended { entity: actor });
+        },
+        Interaction::Corpse => return death::loot_corpse(ecs_, actor, target)
+    }
+    true
+}
+
+fn set_interaction(ecs_: &mut ecs::ECS, entity: ecs::Entity, interaction: Interaction) {
+    if let Some(interactable_c) = ecs_.interactable_component.get_mut(entity) {
+        interactable_c.interaction = interaction;
+    }
+}
+
+/// Opens or closes a door. Closed doors block like walls
+fn set_door(ecs_: &mut ecs::ECS, door: ecs::Entity, open: bool) {
+    set_interaction(ecs_, door, Interaction::Door { open });
+    if let Some(location_c) = ecs_.location_component.get_mut(door) {
+        location_c.hitbox = if open { None } else { Some(Hitbox::new_small(HitboxType::Wall)) };
+    }
+    if let Some(render_c) = ecs_.render_component.get_mut(door) {
+        render_c.base_sprite = if open { "door_open" } else { "door" }.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamelogic::{PlayerAction, collision::CollisionMap, targeting::SightMap};
+    use crate::gamestate::movement::Direction;
+
+    #[test]
+    fn test_interacting_with_faced_entities() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_test_floor(&mut ecs_);
+        // the door in the opening between the rooms
+        let door = dungeon::create_door(&mut ecs_, 4.0, 7.0);
+        let player = dungeon::create_test_player(&mut ecs_, 4.0, 6.0);
+        let chest = dungeon::create_chest(&mut ecs_, 5.0, 6.0, 30, Vec::new());
+        assert!(!gamelogic::can_enter(&ecs_, &CollisionMap::build(&ecs_), player, LocationVec { x: 4.0, y: 7.0 }));
+        assert!(!SightMap::build(&ecs_).line_of_sight(LocationVec { x: 4.0, y: 6.0 }, LocationVec { x: 4.0, y: 9.0 }));
+
+        // the player faces down, towards the door
+        assert_eq!(find_interactable(&ecs_, player), Some(door));
+        gamelogic::perform_action(&mut ecs_, player, PlayerAction::Interact(door));
+        assert!(gamelogic::can_enter(&ecs_, &CollisionMap::build(&ecs_), player, LocationVec { x: 4.0, y: 7.0 }));
+        assert!(SightMap::build(&ecs_).line_of_sight(LocationVec { x: 4.0, y: 6.0 }, LocationVec { x: 4.0, y: 9.0 }));
+
+        ecs_.location_component.get_mut(player).unwrap().direction = Direction::Right;
+        assert_eq!(find_interactable(&ecs_, player), Some(chest));
+        assert!(interact(&mut ecs_, player, chest));
+        assert_eq!(ecs_.player_component.get(player).unwrap().gold, 30);
+        // an emptied chest is not interacted with again
+        assert_eq!(find_interactable(&ecs_, player), None);
+    }
+}
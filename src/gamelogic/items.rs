@@ -0,0 +1,252 @@
+use crate::ecs;
+use crate::event::bus::GameEvent;
+use crate::gamestate::components::{GroundItemComponent, LocationComponent, NameComponent, RenderComponent};
+use crate::gamestate::{item::{Item, ItemType}, movement::Direction, LocationVec};
+use crate::render::{animation::AnimationController, layer::RenderLayer};
+use crate::ut;
+
+// items are drawn smaller than a field
+const ITEM_SPRITE_SIZE: f64 = 0.5;
+
+/// Places an item onto the ground
+///
+/// ### Arguments
+/// * `ecs_`     - The entity component system to perform on
+/// * `item`     - The item lying there
+/// * `location` - The field it lies on
+///
+/// ### Returns
+/// The entity of the item
+pub fn spawn_ground_item(ecs_: &mut ecs::ECS, item: Item, location: LocationVec) -> ecs::Entity {
+    ecs_.spawn()
+        .with(LocationComponent {
+            location: LocationVec { x: location.x.round(), y: location.y.round() },
+            previous_location: None,
+            direction: Direction::Down,
+            move_intent: None,
+            // items never block
+            hitbox: None
+        })
+        .with(RenderComponent {
+            base_sprite: "item".to_string(),
+            base_sprite_size: ITEM_SPRITE_SIZE,
+            animation: AnimationController::new(),
+            visible: true,
+            render_layer: RenderLayer::Items,
+            tint: None,
+            opacity: 1.0,
+            flash: None
+        })
+        .with(NameComponent { name: item.item.locale_key() })
+        .with(GroundItemComponent { item })
+        .build()
+}
+
+/// Finds the items lying on a field
+pub fn ground_items_at(ecs_: &ecs::ECS, location: LocationVec) -> Vec<ecs::Entity> {
+    let field = LocationVec { x: location.x.round(), y: location.y.round() };
+    ecs_.get_entities_by_location(field)
+        .into_iter()
+        .filter(|entity| ecs_.ground_item_component.get(*entity).is_some())
+        .collect()
+}
+
+/// Moves an item from the ground into the inventory of an entity. Items that stack are
+/// added to a stack of the same kind, other items need a free slot. Gold is only taken by
+/// players, into their gold
+///
+/// ### Arguments
+/// * `ecs_`        - The entity component system to perform on
+/// * `entity`      - The entity picking the item up
+/// * `ground_item` - The item on the ground
+///
+/// ### Returns
+/// True if the item was picked up, false if it is no item or does not fit
+pub fn pick_up(ecs_: &mut ecs::ECS, entity: ecs::Entity, ground_item: ecs::Entity) -> bool {
+    let fits = match (ecs_.inventory_component.get(entity), ecs_.ground_item_component.get(ground_item)) {
+        (_, Some(ground_item_c)) if matches!(ground_item_c.item.item, ItemType::Gold) => ecs_.player_component.get(entity).is_some(),
+        (Some(inventory_c), Some(ground_item_c)) => {
+            let stack = ground_item_c.item.item.stacks()
+                        && inventory_c.items.iter().any(|other| other.item.stacks() && other.item.locale_key() == ground_item_c.item.item.locale_key());
+            stack || (inventory_c.items.len() as i32) < inventory_c.capacity
+        },
+        _ => false
+    };
+    if !fits {
+        return false;
+    }
+    let item = match ecs_.ground_item_component.get(ground_item) {
+        Some(ground_item_c) => ground_item_c.item.clone(),
+        None => return false
+    };
+    info!("{} picked up {} {}", ut::name_or_id(ecs_, entity), item.amount, ecs_.locale.text(&item.item.locale_key()));
+    ecs_.allocator.deallocate(ground_item);
+    if let ItemType::Gold = item.item {
+        if let Some(player_c) = ecs_.player_component.get_mut(entity) {
+            player_c.gold += item.amount;
+        }
+    } else if let Some(inventory_c) = ecs_.inventory_component.get_mut(entity) {
+        let key = item.item.locale_key();
+        match inventory_c.items.iter_mut().find(|other| item.item.stacks() && other.item.locale_key() == key) {
+            Some(stack) => stack.amount += item.amount,
+            None => inventory_c.items.push(item)
+        }
+    }
+    ecs_.events.publish(GameEvent::PickedUp { entity });
+    true
+}
+
+/// Picks up the items on the field of an entity that a rule allows and that fit into its
+/// inventory. Players are told what they picked up by a toast, it is logged as well
+///
+/// ### Returns
+/// The number of items picked up
+fn pick_up_where<F: Fn(&Item) -> bool>(ecs_: &mut ecs::ECS, entity: ecs::Entity, rule: F) -> usize {
+    let location = match ecs_.location_component.get(entity) {
+        Some(location_c) => location_c.location,
+        None => return 0
+    };
+    let mut picked_up = Vec::new();
+    for ground_item in ground_items_at(ecs_, location) {
+        let item = match ecs_.ground_item_component.get(ground_item) {
+            Some(ground_item_c) if rule(&ground_item_c.item) => ground_item_c.item.clone(),
+            _ => continue
+        };
+        if pick_up(ecs_, entity, ground_item) {
+            picked_up.push(item);
+        }
+    }
+    if !picked_up.is_empty() && ecs_.player_component.get(entity).is_some() {
+        let items: Vec<String> = picked_up.iter()
+            .map(|item| match item.amount {
+                1 => ecs_.locale.text(&item.item.locale_key()),
+                amount => format!("{} {}", amount, ecs_.locale.text(&item.item.locale_key()))
+            })
+            .collect();
+        let summary = ecs_.locale.format("items.picked_up", &[("items", &items.join(", "))]);
+        info!("{}", summary);
+        ecs_.toasts.push(summary);
+    }
+    picked_up.len()
+}
+
+/// Picks up all items on the field of an entity that fit into its inventory
+///
+/// ### Returns
+/// The number of items picked up
+pub fn pick_up_all(ecs_: &mut ecs::ECS, entity: ecs::Entity) -> usize {
+    pick_up_where(ecs_, entity, |_| true)
+}
+
+/// Picks up the items on the field of a player that the auto-pickup rules of the settings
+/// allow, see `config::PickupSettings`. Called when the player steps onto a field
+///
+/// ### Returns
+/// The number of items picked up
+pub fn auto_pick_up(ecs_: &mut ecs::ECS, entity: ecs::Entity) -> usize {
+    let rules = ecs_.settings.pickup.clone();
+    pick_up_where(ecs_, entity, |item| rules.allows(item))
+}
+
+/// Hands over loot, e.g. of a corpse or a chest. Gold is only taken by players, items
+/// only by entities with an inventory with enough capacity left
+///
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `looter` - The entity taking the loot
+/// * `gold`   - The gold of the loot
+/// * `items`  - The items of the loot
+///
+/// ### Returns
+/// The items that did not fit into the inventory
+pub fn give_loot(ecs_: &mut ecs::ECS, looter: ecs::Entity, gold: i32, mut items: Vec<Item>) -> Vec<Item> {
+    if let Some(player_c) = ecs_.player_component.get_mut(looter) {
+        player_c.gold += gold;
+    }
+    if let Some(inventory_c) = ecs_.inventory_component.get_mut(looter) {
+        while !items.is_empty() && (inventory_c.items.len() as i32) < inventory_c.capacity {
+            inventory_c.items.push(items.remove(0));
+        }
+    }
+    items
+}
+
+/// Takes an item out of the inventory of an entity and places it onto the entity's field
+///
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `entity` - The entity dropping the item
+/// * `index`  - The index of the item in the inventory
+///
+/// ### Returns
+/// The item on the ground, None if there is no such item or the entity has no location
+pub fn drop_item(ecs_: &mut ecs::ECS, entity: ecs::Entity, index: usize) -> Option<ecs::Entity> {
+    let location = ecs_.location_component.get(entity)?.location;
+    let item = match ecs_.inventory_component.get_mut(entity) {
+        Some(inventory_c) if index < inventory_c.items.len() => inventory_c.items.remove(index),
+        _ => return None
+    };
+    info!("{} dropped {} {}", ut::name_or_id(ecs_, entity), item.amount, ecs_.locale.text(&item.item.locale_key()));
+    let ground_item = spawn_ground_item(ecs_, item, location);
+    ecs_.events.publish(GameEvent::Dropped { entity });
+    Some(ground_item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamelogic::{self, PlayerAction};
+    use crate::gamestate::item::{Equipment, ItemType};
+
+    #[test]
+    fn test_walking_over_items_picks_them_up() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 6, 5);
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        let inventory_c = ecs_.inventory_component.get_mut(player).unwrap();
+        inventory_c.items.push(Item { item: ItemType::Consumable, amount: 2 });
+        inventory_c.capacity = 2;
+        spawn_ground_item(&mut ecs_, Item { item: ItemType::Consumable, amount: 3 }, LocationVec { x: 2.0, y: 1.0 });
+        spawn_ground_item(&mut ecs_, Item { item: ItemType::Equipment(Equipment::Helm), amount: 1 }, LocationVec { x: 2.0, y: 1.0 });
+
+        gamelogic::perform_player_action(&mut ecs_, PlayerAction::Move(Direction::Right));
+        for _ in 0..crate::UPDATES_PER_SECOND {
+            gamelogic::update(&mut ecs_);
+        }
+        // the potions are stacked, the helm takes the last slot
+        let items = &ecs_.inventory_component.get(player).unwrap().items;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].amount, 5);
+        assert!(ground_items_at(&ecs_, LocationVec { x: 2.0, y: 1.0 }).is_empty());
+
+        let dropped = drop_item(&mut ecs_, player, 1).unwrap();
+        assert!(matches!(ecs_.ground_item_component.get(dropped).unwrap().item.item, ItemType::Equipment(Equipment::Helm)));
+        assert_eq!(ground_items_at(&ecs_, LocationVec { x: 2.0, y: 1.0 }), vec![dropped]);
+        assert_eq!(pick_up_all(&mut ecs_, player), 1);
+    }
+
+    #[test]
+    fn test_auto_pickup_follows_rules() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 6, 5);
+        let player = dungeon::create_test_player(&mut ecs_, 2.0, 1.0);
+        let field = LocationVec { x: 2.0, y: 1.0 };
+        spawn_ground_item(&mut ecs_, Item { item: ItemType::Gold, amount: 15 }, field);
+        spawn_ground_item(&mut ecs_, Item { item: ItemType::Junk, amount: 1 }, field);
+        spawn_ground_item(&mut ecs_, Item { item: ItemType::Consumable, amount: 6 }, field);
+        spawn_ground_item(&mut ecs_, Item { item: ItemType::Equipment(Equipment::Helm), amount: 1 }, field);
+
+        // the junk and the heavy stack of potions are left lying
+        assert_eq!(auto_pick_up(&mut ecs_, player), 2);
+        assert_eq!(ecs_.player_component.get(player).unwrap().gold, 15);
+        assert_eq!(ecs_.inventory_component.get(player).unwrap().items.len(), 1);
+        assert_eq!(ground_items_at(&ecs_, field).len(), 2);
+        assert!(!ecs_.toasts.is_empty());
+
+        ecs_.settings.pickup.max_consumable_weight = 3.0;
+        ecs_.settings.pickup.ignore_junk = false;
+        assert_eq!(auto_pick_up(&mut ecs_, player), 2);
+        assert!(ground_items_at(&ecs_, field).is_empty());
+    }
+}
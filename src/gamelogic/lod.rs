@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use crate::ecs;
+use crate::gamestate::LocationVec;
+
+// fields from the nearest player within which entities are fully updated, about
+// the part of the floor on screen
+const NEAR_RADIUS: f64 = 16.0;
+// fields from the nearest player beyond which throttled entities may freeze
+const FAR_RADIUS: f64 = 48.0;
+
+/// The updates of entities that are throttled far away from the players, named by the
+/// component they update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Throttled {
+    // animations and flashes of the `RenderComponent`
+    Render,
+    // daily routines of the `ScheduleComponent`
+    Schedule,
+    // the turns of NPCs acting on their own, e.g. companions
+    Ai,
+    // steps along the `move_intent` of the `LocationComponent` and their rerouting
+    Movement,
+}
+
+/// How a throttled update treats entities far away
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LodRule {
+    // entities between the near and far radius are updated once every that many
+    // updates, 1 to update them always
+    pub reduced_every: u64,
+    // entities beyond the far radius are not updated at all, else as reduced ones
+    pub freeze_far: bool
+}
+
+/// The simulation level of detail: entities far from all players are updated less
+/// often, so large floors do not slow down the game. Floors other than the current one
+/// are not loaded, so they are not updated at all
+#[derive(Debug, Clone)]
+pub struct SimulationLod {
+    pub near_radius: f64,
+    pub far_radius: f64,
+    // throttled updates without a rule are never throttled
+    pub rules: HashMap<Throttled, LodRule>,
+    // game updates counted, reduced entities take turns on them
+    updates: u64
+}
+
+impl Default for SimulationLod {
+    fn default() -> SimulationLod {
+        let mut rules = HashMap::new();
+        rules.insert(Throttled::Render, LodRule { reduced_every: 4, freeze_far: true });
+        // routines catch up with the clock by themselves, so they are never frozen
+        rules.insert(Throttled::Schedule, LodRule { reduced_every: 5, freeze_far: false });
+        // companions left far behind still have to catch up with the players
+        rules.insert(Throttled::Ai, LodRule { reduced_every: 2, freeze_far: false });
+        // steps are finished, or turn-based games would wait for the actors forever
+        rules.insert(Throttled::Movement, LodRule { reduced_every: 2, freeze_far: false });
+        SimulationLod {
+            near_radius: NEAR_RADIUS,
+            far_radius: FAR_RADIUS,
+            rules,
+            updates: 0
+        }
+    }
+}
+
+impl SimulationLod {
+    /// Counts a game update
+    pub fn advance(&mut self) {
+        self.updates = self.updates.wrapping_add(1);
+    }
+}
+
+/// The locations of all players, entities are throttled by their distance to them
+pub fn focus(ecs_: &ecs::ECS) -> Vec<LocationVec> {
+    ecs_.allocator.live_indices()
+        .into_iter()
+        .filter(|entity| ecs_.player_component.get(*entity).is_some())
+        .filter_map(|entity| ecs_.location_component.get(entity).map(|location_c| location_c.location))
+        .collect()
+}
+
+/// Decides if a throttled update updates an entity now
+///
+/// ### Arguments
+/// * `ecs_`      - The entity component system to perform on
+/// * `focus`     - The locations of the players, see `focus`
+/// * `entity`    - The entity to update
+/// * `throttled` - The update asking
+///
+/// ### Returns
+/// The number of updates to catch up on, e.g. the time to advance animations by, 0 if
+/// the entity is skipped this time
+pub fn ticks(ecs_: &ecs::ECS, focus: &[LocationVec], entity: ecs::Entity, throttled: Throttled) -> u64 {
+    let lod = &ecs_.lod;
+    let rule = match lod.rules.get(&throttled) {
+        Some(rule) => rule,
+        None => return 1
+    };
+    // without players nobody is watching, e.g. on a server waiting for them
+    let location = match ecs_.location_component.get(entity) {
+        Some(location_c) if !focus.is_empty() => location_c.location,
+        _ => return 1
+    };
+    let distance = focus.iter()
+                        .map(|player| ecs_.diagonal_rule.distance(player, &location))
+                        .fold(f64::INFINITY, f64::min);
+    if distance <= lod.near_radius {
+        return 1;
+    }
+    if distance > lod.far_radius && rule.freeze_far {
+        return 0;
+    }
+    let every = rule.reduced_every.max(1);
+    // entities on different fields take turns, so they are not all updated at once
+    let stagger = (location.x.round() as i64).wrapping_mul(31).wrapping_add(location.y.round() as i64).rem_euclid(every as i64) as u64;
+    if lod.updates.wrapping_add(stagger).is_multiple_of(every) {
+        every
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamelogic::{effects, party};
+    use crate::gamestate::party::PartyOrder;
+
+    #[test]
+    fn test_far_entities_animate_less_often() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_test_player(&mut ecs_, 0.0, 0.0);
+        let near = dungeon::create_attack_dummy(&mut ecs_, 10.0, 0.0);
+        let reduced = dungeon::create_attack_dummy(&mut ecs_, 30.0, 0.0);
+        let far = dungeon::create_attack_dummy(&mut ecs_, 60.0, 0.0);
+
+        for _ in 0..8 {
+            effects::update_animations(&mut ecs_);
+            ecs_.lod.advance();
+        }
+        let elapsed = |ecs_: &ecs::ECS, entity| ecs_.render_component.get(entity).unwrap().animation.elapsed_ms();
+        // reduced entities skip updates but catch up on the time
+        assert_eq!(elapsed(&ecs_, reduced), elapsed(&ecs_, near));
+        assert!(elapsed(&ecs_, near) > 0);
+        assert_eq!(elapsed(&ecs_, far), 0);
+
+        ecs_.lod.rules.remove(&Throttled::Render);
+        effects::update_animations(&mut ecs_);
+        assert!(elapsed(&ecs_, far) > 0);
+    }
+
+    #[test]
+    fn test_far_companions_skip_turns() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 40, 4);
+        dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        let near = dungeon::create_companion(&mut ecs_, 3.0, 1.0);
+        let reduced = dungeon::create_companion(&mut ecs_, 30.0, 1.0);
+        for companion in [near, reduced].iter() {
+            let x = ecs_.location_component.get(*companion).unwrap().location.x;
+            let companion_c = ecs_.companion_component.get_mut(*companion).unwrap();
+            companion_c.order = PartyOrder::Hold;
+            companion_c.hold = Some(LocationVec { x, y: 2.0 });
+        }
+
+        // both try to step to the field they hold each turn, the far one only every other turn
+        let mut steps = [0, 0];
+        for _ in 0..4 {
+            party::update_companions(&mut ecs_);
+            for (i, companion) in [near, reduced].iter().enumerate() {
+                if ecs_.location_component.get_mut(*companion).unwrap().move_intent.take().is_some() {
+                    steps[i] += 1;
+                }
+            }
+            ecs_.lod.advance();
+        }
+        assert_eq!(steps, [4, 2]);
+    }
+}
@@ -1,16 +1,69 @@
+pub mod cheats;
+pub mod collision;
+pub mod combat;
+pub mod cutscene;
+pub mod death;
+pub mod effects;
+pub mod hotbar;
+pub mod interaction;
+pub mod items;
+pub mod lod;
+pub mod new_game_plus;
+pub mod overworld;
+pub mod party;
+pub mod pathfinding;
+pub mod run;
+pub mod schedule;
+pub mod scheduling;
+pub mod stats;
+pub mod targeting;
+pub mod validation;
+
 use crate::ecs;
-use crate::gamestate::{actor, movement, status::StatusType, LocationVec};
+use crate::builder;
+use crate::event::bus::GameEvent;
+use crate::gamestate::{actor, movement, dungeon, duration::Duration, party::PartyOrder, spell::Ability, status::StatusType, LocationVec};
+use crate::gamestate::mode::{GameMode, Transition, TransitionEffect, TransitionTarget, FloorBuilder};
+use crate::persistence::history;
 use crate::ut;
-use std::ops::Add;
+use std::time::Instant;
 
 extern crate rand;
 use rand::Rng;
 
+// view radius of entities in fields under clear weather
+pub const BASE_VIEW_RADIUS: f64 = 8.0;
+// damage a burning entity takes each turn
+pub const BURN_DAMAGE: i32 = 2;
+// damage a starving entity takes each turn
+const STARVE_DAMAGE: i32 = 1;
+// health and mana regenerated per rested turn
+const REST_REGENERATION: i32 = 1;
+// game updates a transition between modes or floors takes
+const TRANSITION_UPDATES: u64 = 20;
+// time scale while resting, resting turns pass faster
+const REST_TIME_SCALE: f64 = 4.0;
+
 /// Possible actions for the player
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PlayerAction {
     Interact(ecs::Entity),
     Attack,
     Move(movement::Direction),
+    Wait,
+    Rest,
+    // an ability used on a field, aimed in targeting mode (see `targeting`)
+    UseAbility(Ability, LocationVec),
+    // a consumable of the inventory, see `hotbar::use_consumable`
+    UseConsumable,
+    // all items on the player's field
+    PickUp,
+    // an item of the inventory by its index
+    Drop(usize),
+    // only in games started with `--dev`
+    Cheat(cheats::Cheat),
+    // an order to all companions, see `party::give_order`
+    Order(PartyOrder),
 }
 
 /// Executes a player action
@@ -19,89 +72,210 @@ pub enum PlayerAction {
 /// * `ecs_`          - The entity component system to perform on
 /// * `player_action` - The player_action to execute
 /// 
-/// ### Returns
-/// True if the action was performed successfully, else false
-/// 
 pub fn perform_player_action(ecs_: &mut ecs::ECS, player_action: PlayerAction) {
     if let Some(player) = ecs_.get_player_entity() {
-        match player_action {
-            PlayerAction::Move(dir) => {
-                // get player position
-                if !move_entity(ecs_, player, dir) {
-                    debug!("Player tried to move to a location, but was denied!");
-                } else {
-                    // player is starting to move and therefore acting
-                    ecs_.actor_component.get_mut(player).map(|act| act.state = actor::ActorState::Acting);
+        perform_action(ecs_, player, player_action);
+    }
+}
+
+/// Executes a player action for a certain player entity, e.g. one of several players
+/// in a multiplayer game
+/// 
+/// ### Arguments
+/// * `ecs_`          - The entity component system to perform on
+/// * `player`        - The player entity acting
+/// * `player_action` - The player_action to execute
+/// 
+pub fn perform_action(ecs_: &mut ecs::ECS, player: ecs::Entity, player_action: PlayerAction) {
+    match player_action {
+        PlayerAction::Move(dir) => {
+            let mut collision_map = collision::CollisionMap::build(ecs_);
+            let state = match step_or_attack(ecs_, &mut collision_map, player, dir) {
+                // player is starting to move and therefore acting
+                StepResult::Moved => actor::ActorState::Acting,
+                StepResult::Attacked(_) => actor::ActorState::DoneActing,
+                StepResult::Blocked(blocked) => {
+                    debug!("Player tried to move to a location, but was denied: {:?}", blocked);
+                    return;
                 }
+            };
+            if let Some(act) = ecs_.actor_component.get_mut(player) {
+                act.state = state;
             }
-            PlayerAction::Attack => {
-                // find player
-                if let Some(location_c) = ecs_.location_component.get(player) {
-                    let target_location = location_c.location + LocationVec::from(location_c.direction);
-                    let potential_targets = ecs_.get_entities_by_location(target_location);
-                    for target in potential_targets {
-                        if let Some(_) = ecs_.health_component.get(target) {
-                            attack(ecs_, player, target);
-                            ecs_.actor_component.get_mut(player).map(|act| act.state = actor::ActorState::DoneActing);
+        }
+        PlayerAction::Attack => {
+            // find player
+            if let Some(location_c) = ecs_.location_component.get(player) {
+                let target_location = location_c.location + LocationVec::from(location_c.direction);
+                let potential_targets = ecs_.get_entities_by_location(target_location);
+                for target in potential_targets {
+                    if ecs_.health_component.get(target).is_some() && !death::is_dying(ecs_, target) {
+                        attack(ecs_, player, target);
+                        if let Some(act) = ecs_.actor_component.get_mut(player) {
+                            act.state = actor::ActorState::DoneActing;
                         }
                     }
                 }
-                else {
-                    debug!("Player tried to attack, but has no location!");
+            }
+            else {
+                debug!("Player tried to attack, but has no location!");
+            }
+        }
+        PlayerAction::Wait => {
+            if let Some(act) = ecs_.actor_component.get_mut(player) {
+                act.state = actor::ActorState::DoneActing;
+            }
+        }
+        PlayerAction::Rest => {
+            if !start_resting(ecs_, player) {
+                debug!("Player tried to rest, but could not!");
+            }
+        }
+        PlayerAction::UseAbility(ability, location) => {
+            if !targeting::use_ability(ecs_, player, ability, location) {
+                debug!("Player tried to use {:?} at {:?}, but could not!", ability, location);
+            }
+        }
+        PlayerAction::UseConsumable => {
+            if !hotbar::use_consumable(ecs_, player) {
+                debug!("Player tried to use a consumable, but has none!");
+            }
+        }
+        PlayerAction::PickUp => {
+            if items::pick_up_all(ecs_, player) > 0 {
+                if let Some(act) = ecs_.actor_component.get_mut(player) {
+                    act.state = actor::ActorState::DoneActing;
+                }
+            } else {
+                debug!("Player tried to pick something up, but nothing fit!");
+            }
+        }
+        PlayerAction::Drop(index) => {
+            if items::drop_item(ecs_, player, index).is_some() {
+                if let Some(act) = ecs_.actor_component.get_mut(player) {
+                    act.state = actor::ActorState::DoneActing;
+                }
+            } else {
+                debug!("Player tried to drop item {}, but has none!", index);
+            }
+        }
+        PlayerAction::Cheat(cheat) => {
+            // cheats take no time
+            cheats::perform_cheat(ecs_, player, cheat);
+        }
+        PlayerAction::Order(order) => {
+            // orders take no time either
+            if party::give_order(ecs_, player, order) == 0 {
+                debug!("Player gave the order {:?}, but has no companions!", order);
+            }
+        }
+        PlayerAction::Interact(target) => {
+            if interaction::interact(ecs_, player, target) {
+                if let Some(act) = ecs_.actor_component.get_mut(player) {
+                    act.state = actor::ActorState::DoneActing;
                 }
+            } else {
+                debug!("Player tried to interact with {:?}, but nothing happened!", target);
             }
-            _ => { /*TODO Implement other actions*/ }
         }
     }
 }
 
+/// Outcome of an entity trying to step into a direction, see `step_or_attack`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepResult {
+    Moved,
+    // the field was occupied by a hostile creature, which was attacked instead
+    Attacked(ecs::Entity),
+    Blocked(collision::Blocked),
+}
+
+/// Lets an entity step into a direction like `move_entity`. If a creature hostile to the entity
+/// (see `is_hostile`) is in the way, the entity bumps into it and attacks it instead.
+/// Used for the player as well as for NPCs
+/// 
+/// ### Arguments
+/// * `ecs_`          - The entity component system to perform on
+/// * `collision_map` - The fields that can be entered, updated with the step
+/// * `entity`        - The entity to move
+/// * `dir`           - The direction to move into
+/// 
+pub fn step_or_attack(ecs_: &mut ecs::ECS, collision_map: &mut collision::CollisionMap, entity: ecs::Entity, dir: movement::Direction) -> StepResult {
+    match try_move_entity(ecs_, collision_map, entity, dir) {
+        Err(collision::Blocked::Occupied(target)) if is_hostile(ecs_, entity, target) => {
+            if let Some(location_c) = ecs_.location_component.get_mut(entity) {
+                location_c.direction = dir;
+            }
+            attack(ecs_, entity, target);
+            // the target may have died
+            collision_map.update_entity(ecs_, target);
+            StepResult::Attacked(target)
+        },
+        Err(blocked) => StepResult::Blocked(blocked),
+        Ok(()) => StepResult::Moved
+    }
+}
+
 /// Sets a `MoveIntent` for the entity to move into the given direction by one field. To be able to move into a direction, 
-/// there must be no colliding `event::Hitbox` of equal or greater ordering layer than all entities at the target location 
-/// and there must be an entity at all (e.g. a floor tile entity)
+/// the entity must be able to enter the field there (see `can_enter`). Diagonal steps can not cut the corners of
+/// fields the entity can not enter and take as long as `ECS::diagonal_rule` says
 /// 
 /// ### Arguments
-/// * `ecs_`   - The entity component system to perform on
-/// * `entity` - The entity to move
-/// * `dir`    - The direction to move into
+/// * `ecs_`          - The entity component system to perform on
+/// * `collision_map` - The fields that can be entered, updated with the step
+/// * `entity`        - The entity to move
+/// * `dir`           - The direction to move into
 /// 
 /// ### Returns
 /// True if all prerequirements were fullfilled and the `MoveIntent` was set successfully, else false
-pub fn move_entity(ecs_: &mut ecs::ECS, entity: ecs::Entity, dir: movement::Direction) -> bool {
+pub fn move_entity(ecs_: &mut ecs::ECS, collision_map: &mut collision::CollisionMap, entity: ecs::Entity, dir: movement::Direction) -> bool {
+    try_move_entity(ecs_, collision_map, entity, dir).is_ok()
+}
+
+/// Like `move_entity`, tells why the entity could not move
+fn try_move_entity(ecs_: &mut ecs::ECS, collision_map: &mut collision::CollisionMap, entity: ecs::Entity, dir: movement::Direction) -> Result<(), collision::Blocked> {
     // check if move is okay
     if let Some(location_comp) = ecs_.location_component.get(entity) {
-        let location = location_comp.location.add(dir.into());
-
-        // check all entities standing at target location (tx,ty)
-        let target_entities = ecs_.get_entities_by_location(location);
-        // no walking in space (as floor tiles are entities as well)
-        if target_entities.is_empty() {
-            return false;
-        }
-
-        // check hitboxes at target location
-        for target_entity in target_entities {
-            // they will have a location component as we only get entities with such
-            if let Some(target_entity_location_comp) = ecs_.location_component.get(target_entity) {
-                // get the hitboxes if they exist
-                if let Some(target_hitbox) = &target_entity_location_comp.hitbox {
-                    if let Some(source_hitbox) = &location_comp.hitbox {
-                        if target_hitbox.type_ >= source_hitbox.type_ {
-                            return false;
-                        }
-                    }
-                }
-            }
-        }
+        collision_map.check_step(ecs_, entity, location_comp.location, dir)?;
     }
 
     // now get location as mutable to move
+    let speed = ecs_.diagonal_rule.step_speed(dir, movement::DEFAULT_SPEED);
     if let Some(location_comp) = ecs_.location_component.get_mut(entity) {
         // if we move we set the movement component
-        location_comp.move_intent = Some(movement::MoveIntent::Vector(dir.into(), movement::DEFAULT_SPEED));
+        location_comp.move_intent = Some(movement::MoveIntent::Vector(dir.into(), speed));
         // set now direction
         location_comp.direction = dir;
     }
-    true
+    collision_map.update_entity(ecs_, entity);
+    Ok(())
+}
+
+/// Tests if an entity can take a step from a field into a direction, see `move_entity`
+pub fn can_step(ecs_: &ecs::ECS, collision_map: &collision::CollisionMap, entity: ecs::Entity, from: LocationVec, dir: movement::Direction) -> bool {
+    collision_map.check_step(ecs_, entity, from, dir).is_ok()
+}
+
+/// Tests if an entity can stand on a field, see `collision::CollisionMap::check_enter`
+/// 
+/// ### Arguments
+/// * `ecs_`          - The entity component system to perform on
+/// * `collision_map` - The fields that can be entered, built from `ecs_`
+/// * `entity`        - The entity that wants to enter the field
+/// * `location`      - The location of the field
+/// 
+/// ### Returns
+/// True if the entity can enter the field, else false
+pub fn can_enter(ecs_: &ecs::ECS, collision_map: &collision::CollisionMap, entity: ecs::Entity, location: LocationVec) -> bool {
+    collision_map.check_enter(ecs_, entity, location).is_ok()
+}
+
+/// Tests if an entity fights another one. Hostile NPCs fight players and their companions,
+/// NPCs do not fight among themselves
+pub fn is_hostile(ecs_: &ecs::ECS, entity: ecs::Entity, other: ecs::Entity) -> bool {
+    let hostile_npc = |npc| ecs_.npc_behavior_component.get(npc).is_some_and(|npc_c| npc_c.hostile);
+    let is_ally = |ally| ecs_.player_component.get(ally).is_some() || ecs_.companion_component.get(ally).is_some();
+    (is_ally(entity) && hostile_npc(other)) || (hostile_npc(entity) && is_ally(other))
 }
 
 /// Performs damage calculation for an attacking and definding entity and then applies damage.
@@ -117,66 +291,86 @@ pub fn move_entity(ecs_: &mut ecs::ECS, entity: ecs::Entity, dir: movement::Dire
 /// True if the attack was successful and applied at least one damage, else false
 /// 
 pub fn attack(ecs_: &mut ecs::ECS, attacker: ecs::Entity, target: ecs::Entity) -> bool {
-    let mut attacker_atk = 0;
-    let mut attacker_mul = 2.0;
-    let mut target_def = 0;
-    let mut target_mul = 1.0;
-
-    // calculate atk for attacker
-    if let Some(basestats_c) = ecs_.basestats_component.get(attacker) {
-        attacker_atk = basestats_c.attack;
-
-        // check for basestats status modifications
-        if let Some(status_c) = ecs_.status_component.get(attacker) {
-            for status in &status_c.status {
-                match &status.type_ {
-                    StatusType::BaseStatusModifier(modifier) => attacker_atk += modifier.attack,
-                    StatusType::BaseStatusMuliplier(multiplier) => attacker_mul += multiplier.attack,
-                    _ => {}
-                }
-            }
-        }
-        //TODO check for equipment
-
+    let attacker_stats = combat::effective_stats(ecs_, attacker);
+    let target_stats = combat::effective_stats(ecs_, target);
+    if target_stats.invincible {
+        ecs_.events.publish(GameEvent::Blocked { target, source: Some(attacker) });
+        return false;
     }
 
-    // calculate def for target
-    if let Some(basestats_c) = ecs_.basestats_component.get(target) {
-        target_def = basestats_c.defense;
+    // attacks vary by 10%, the same range as previewed by `combat::damage_range`
+    let dmg_percentile = ecs_.rng.gen_range(combat::MIN_DAMAGE_ROLL..combat::MAX_DAMAGE_ROLL);
+    let damage = combat::damage(&attacker_stats, &target_stats, dmg_percentile, combat::damage_taken_factor(ecs_, target));
 
-        // check for basestats status modifications
-        if let Some(status_c) = ecs_.status_component.get(target) {
-            for status in &status_c.status {
-                match &status.type_ {
-                    StatusType::BaseStatusModifier(modifier) => target_def += modifier.defense,
-                    StatusType::BaseStatusMuliplier(multiplier) => target_mul += multiplier.defense,
-                    StatusType::Invincible => { return false; }
-                }
-            }
-        }
-        //TODO check for equipment
-    }
+    info!("{}", ecs_.locale.format("log.damage", &[
+        ("attacker", &ut::name_or_id(ecs_, attacker)),
+        ("damage", &damage),
+        ("target", &ut::name_or_id(ecs_, target))
+    ]));
 
-    // attacks vary by 10%  (90% - 110%)
-    let dmg_percentile = rand::thread_rng().gen_range(90.0..110.0) / 100.0;
-    // apply damage (do at least 1 damage)
-    let damage = std::cmp::max(
-        1, 
-        ((attacker_atk as f32 * attacker_mul  - target_def as f32 * target_mul) * dmg_percentile) as i32
-    );
+    // if no health no attack
+    apply_damage(ecs_, target, Some(attacker), damage)
+}
 
-    info!("{} dealt {} damage to {}", ut::name_or_id(ecs_, attacker), damage, ut::name_or_id(ecs_, target));
+/// Lowers the health of an entity, not below zero. All damage goes through here, so
+/// every hit is published as `GameEvent::Damaged` and every death as `GameEvent::Died`,
+/// whether it was dealt by an attack or e.g. by burning
+///
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `target` - The entity losing health
+/// * `source` - The entity dealing the damage, None for e.g. starvation
+/// * `amount` - The health lost
+///
+/// ### Returns
+/// True if the target has health to lose, else false
+pub fn apply_damage(ecs_: &mut ecs::ECS, target: ecs::Entity, source: Option<ecs::Entity>, amount: i32) -> bool {
+    let died = match ecs_.health_component.get_mut(target) {
+        Some(health_c) => {
+            let was_alive = health_c.current > 0;
+            health_c.current = std::cmp::max(0, health_c.current - amount);
+            was_alive && health_c.current == 0
+        },
+        None => return false
+    };
 
-    if let Some(target_health) = ecs_.health_component.get_mut(target) {
-        target_health.current -= damage;
-        target_health.current = std::cmp::max(0, target_health.current);
-    } else {
-        // if no health no attack
-        return false;
+    ecs_.events.publish(GameEvent::Damaged { target, source, amount });
+    if died {
+        info!("{}", ecs_.locale.format("log.died", &[("name", &ut::name_or_id(ecs_, target))]));
+        ecs_.events.publish(GameEvent::Died { entity: target, killer: source });
     }
     true
 }
 
+/// Lets all systems react to the game events published since the last call
+/// 
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+/// 
+/// ### Returns
+/// The processed events, for systems outside of the ECS like audio
+pub fn process_events(ecs_: &mut ecs::ECS) -> Vec<GameEvent> {
+    let events = ecs_.events.drain();
+    for event in events.iter().cloned() {
+        stats::record_event(ecs_, &event);
+        match event {
+            GameEvent::Damaged { target, source, .. } => effects::on_hit(ecs_, target, source),
+            GameEvent::Blocked { target, source } => effects::on_block(ecs_, target, source),
+            GameEvent::Died { entity, killer } => {
+                effects::on_death(ecs_, entity, killer);
+                death::start_dying(ecs_, entity);
+                run::on_death(ecs_, entity);
+            },
+            // players pick up what they walk over, as far as the auto-pickup rules allow
+            GameEvent::Stepped { entity } if ecs_.player_component.get(entity).is_some() => { items::auto_pick_up(ecs_, entity); },
+            GameEvent::Stepped { .. } | GameEvent::Looted { .. } | GameEvent::PickedUp { .. } | GameEvent::Dropped { .. } |
+            GameEvent::ItemUsed { .. } | GameEvent::UiClick | GameEvent::FloorChanged | GameEvent::Descended { .. } |
+            GameEvent::RunEnded { .. } => {}
+        }
+    }
+    events
+}
+
 
 /// Sets the location for an entity ignoring any movement restrictions (force move)
 /// 
@@ -192,6 +386,8 @@ pub fn force_move(ecs_: &mut ecs::ECS, entity: ecs::Entity, x: f64, y: f64) -> b
     if let Some(location_c) = ecs_.location_component.get_mut(entity) {
         location_c.location.x = x;
         location_c.location.y = y;
+        // no smooth movement for teleports
+        location_c.previous_location = None;
         true
     } else {
         false
@@ -228,34 +424,389 @@ pub fn check_and_perform_end_turn(ecs_: &mut ecs::ECS) -> bool {
     }
 
     if all_done {
+        debug!("All {} actors are done, the turn ends", actors.len());
         for actor_entity in actors {
             if let Some(actor_c) = ecs_.actor_component.get_mut(actor_entity) {
                 actor_c.state = actor::ActorState::WaitingForTurn;
-                actor_c.turn += 1;
-                actor_c.performed_actions = 0;
             }
         }
+        pass_turn(ecs_);
     }
     all_done
 }
 
+/// Counts a turn for all actors and applies everything happening once per turn,
+/// independent of how the turns are scheduled (see `scheduling`)
+/// 
+/// ### Arguments
+/// * `ecs_`:  - The entity component system to perform on
+/// 
+pub fn pass_turn(ecs_: &mut ecs::ECS) {
+    for entity in ecs_.allocator.live_indices() {
+        if let Some(actor_c) = ecs_.actor_component.get_mut(entity) {
+            actor_c.turn += 1;
+            actor_c.performed_actions = 0;
+        }
+    }
+    advance_world_clock(ecs_);
+    apply_weather_effects(ecs_);
+    apply_hunger(ecs_);
+    death::decay_corpses(ecs_);
+    targeting::advance_cooldowns(ecs_);
+    party::update_companions(ecs_);
+    builder::director::trickle_reinforcements(ecs_);
+    history::record_turn(ecs_);
+}
+
+/// Lets every entity with a `HungerComponent` get hungrier by one. Starving
+/// entities take damage instead
+/// 
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+/// 
+pub fn apply_hunger(ecs_: &mut ecs::ECS) {
+    for entity in ecs_.allocator.live_indices() {
+        let mut starving = false;
+        if let Some(hunger_c) = ecs_.hunger_component.get_mut(entity) {
+            if hunger_c.current > 0 {
+                hunger_c.current -= 1;
+            } else {
+                starving = true;
+            }
+        }
+
+        if starving {
+            apply_damage(ecs_, entity, None, STARVE_DAMAGE);
+        }
+    }
+}
+
+/// Advances all status durations counted in game updates by one and removes
+/// expired status
+/// 
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+/// 
+pub fn update_status_durations(ecs_: &mut ecs::ECS) {
+    for entity in ecs_.allocator.live_indices() {
+        if let Some(status_c) = ecs_.status_component.get_mut(entity) {
+            for status in &mut status_c.status {
+                if let Duration::Updates(updates) = &mut status.duration {
+                    *updates -= 1;
+                }
+            }
+            status_c.status.retain(|status| !status.duration.is_expired());
+        }
+    }
+}
+
+/// Advances all status durations counted in movement steps of an entity by one and
+/// removes expired status
+/// 
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `entity` - The entity that made a step
+/// 
+fn advance_step_durations(ecs_: &mut ecs::ECS, entity: ecs::Entity) {
+    if let Some(status_c) = ecs_.status_component.get_mut(entity) {
+        for status in &mut status_c.status {
+            if let Duration::Steps(steps) = &mut status.duration {
+                *steps -= 1;
+            }
+        }
+        status_c.status.retain(|status| !status.duration.is_expired());
+    }
+}
+
+/// Tests if a location is within view radius of another location, measured like steps
+/// are (see `movement::DiagonalRule`)
+fn in_view(ecs_: &ecs::ECS, from: &LocationVec, to: &LocationVec) -> bool {
+    ecs_.diagonal_rule.distance(from, to) <= view_radius(ecs_)
+}
+
+/// Finds all living hostile entities within view radius of the entity
+/// 
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `entity` - The entity looking around
+/// 
+/// ### Returns
+/// The hostile entities in view
+/// 
+pub fn visible_hostiles(ecs_: &ecs::ECS, entity: ecs::Entity) -> Vec<ecs::Entity> {
+    let location = match ecs_.location_component.get(entity) {
+        Some(location_c) => location_c.location,
+        None => return Vec::new()
+    };
+
+    ecs_.allocator.live_indices().into_iter().filter(|&other| {
+        let hostile = ecs_.npc_behavior_component.get(other).is_some_and(|npc_c| npc_c.hostile);
+        let alive = ecs_.health_component.get(other).is_none_or(|health_c| health_c.current > 0);
+        hostile && alive && ecs_.location_component.get(other)
+                                 .is_some_and(|location_c| in_view(ecs_, &location, &location_c.location))
+    }).collect()
+}
+
+/// Tests if there is a living hostile entity within view radius of the entity
+pub fn hostile_in_view(ecs_: &ecs::ECS, entity: ecs::Entity) -> bool {
+    !visible_hostiles(ecs_, entity).is_empty()
+}
+
+/// Marks all dungeon elements within view radius of the player as explored
+/// 
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+/// 
+pub fn update_explored(ecs_: &mut ecs::ECS) {
+    let location = match ecs_.get_player_entity().and_then(|player| ecs_.location_component.get(player)) {
+        Some(location_c) => location_c.location,
+        None => return
+    };
+
+    let mut seen = Vec::new();
+    for entity in ecs_.allocator.live_indices() {
+        if let (Some(dungeon_c), Some(location_c)) = (ecs_.dungeon_component.get(entity), ecs_.location_component.get(entity)) {
+            if in_view(ecs_, &location, &location_c.location) {
+                seen.push((location_c.location, dungeon_c.type_));
+            }
+        }
+    }
+    for (tile_location, element) in seen {
+        ecs_.explored.explore(&tile_location, element);
+    }
+}
+
+/// Tests if the entity is missing health or mana
+fn needs_rest(ecs_: &ecs::ECS, entity: ecs::Entity) -> bool {
+    let missing_health = ecs_.health_component.get(entity).is_some_and(|health_c| health_c.current < health_c.maximum);
+    let missing_mana = ecs_.caster_component.get(entity).is_some_and(|caster_c| caster_c.current_mana < caster_c.maximum_mana);
+    missing_health || missing_mana
+}
+
+/// Tests if the entity is starving
+fn is_starving(ecs_: &ecs::ECS, entity: ecs::Entity) -> bool {
+    ecs_.hunger_component.get(entity).is_some_and(|hunger_c| hunger_c.current <= 0)
+}
+
+/// Lets a player start resting. While resting the player skips turns until
+/// health and mana are full or an hostile entity comes into view
+/// 
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `player` - The player entity to rest
+/// 
+/// ### Returns
+/// True if the player started resting, else false
+/// 
+pub fn start_resting(ecs_: &mut ecs::ECS, player: ecs::Entity) -> bool {
+    if !needs_rest(ecs_, player) || hostile_in_view(ecs_, player) || is_starving(ecs_, player) {
+        return false;
+    }
+    let progress = actor::RestProgress {
+        start_health: ecs_.health_component.get(player).map_or(0, |health_c| health_c.current),
+        start_mana: ecs_.caster_component.get(player).map_or(0, |caster_c| caster_c.current_mana),
+        turns: 0
+    };
+    match ecs_.player_component.get_mut(player) {
+        Some(player_c) => player_c.resting = Some(progress),
+        None => return false
+    }
+    update_rest_time_scale(ecs_);
+    info!("{}", ecs_.locale.format("log.rest_start", &[("name", &ut::name_or_id(ecs_, player))]));
+    true
+}
+
+/// Tests if a player is resting
+pub fn is_resting(ecs_: &ecs::ECS, player: ecs::Entity) -> bool {
+    ecs_.player_component.get(player).is_some_and(|player_c| player_c.resting.is_some())
+}
+
+/// Stops a player from resting
+pub fn stop_resting(ecs_: &mut ecs::ECS, player: ecs::Entity) {
+    if let Some(progress) = ecs_.player_component.get_mut(player).and_then(|player_c| player_c.resting.take()) {
+        update_rest_time_scale(ecs_);
+        info!("{}", ecs_.locale.format("log.rest_stop", &[("turns", &progress.turns)]));
+    }
+}
+
+/// Stops all players from resting, e.g. when a cutscene starts
+pub fn stop_all_resting(ecs_: &mut ecs::ECS) {
+    for player in players(ecs_) {
+        stop_resting(ecs_, player);
+    }
+}
+
+// all player entities, one for every connected player in multiplayer games
+fn players(ecs_: &ecs::ECS) -> Vec<ecs::Entity> {
+    ecs_.allocator.live_indices()
+        .into_iter()
+        .filter(|entity| ecs_.player_component.get(*entity).is_some())
+        .collect()
+}
+
+// turns only pass faster while every player rests, players still awake would be rushed
+fn update_rest_time_scale(ecs_: &mut ecs::ECS) {
+    let players = players(ecs_);
+    let all_resting = !players.is_empty() && players.iter().all(|player| is_resting(ecs_, *player));
+    ecs_.time_scale.base = if all_resting { REST_TIME_SCALE } else { 1.0 };
+}
+
+/// Lets the resting players skip their turns and regenerate, if it is their turn.
+/// Resting is stopped once health and mana are full or a hostile entity is in view.
+/// As resting only skips turns, hunger and status durations advance like during
+/// every other turn
+/// 
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+/// 
+pub fn continue_resting(ecs_: &mut ecs::ECS) {
+    for player in players(ecs_) {
+        if !is_resting(ecs_, player) || !scheduling::can_act(ecs_, player) {
+            continue;
+        }
+
+        if !needs_rest(ecs_, player) || hostile_in_view(ecs_, player) || is_starving(ecs_, player) {
+            stop_resting(ecs_, player);
+            continue;
+        }
+
+        if let Some(health_c) = ecs_.health_component.get_mut(player) {
+            health_c.current = std::cmp::min(health_c.maximum, health_c.current + REST_REGENERATION);
+        }
+        if let Some(caster_c) = ecs_.caster_component.get_mut(player) {
+            caster_c.current_mana = std::cmp::min(caster_c.maximum_mana, caster_c.current_mana + REST_REGENERATION);
+        }
+        if let Some(progress) = ecs_.player_component.get_mut(player).and_then(|player_c| player_c.resting.as_mut()) {
+            progress.turns += 1;
+        }
+        perform_action(ecs_, player, PlayerAction::Wait);
+    }
+}
+
+/// Computes how far a player has come restoring health and mana since it started resting
+/// 
+/// ### Returns
+/// The completion between 0.0 and 1.0, None if the player is not resting
+/// 
+pub fn rest_completion(ecs_: &ecs::ECS, player: ecs::Entity) -> Option<f64> {
+    let progress = ecs_.player_component.get(player)?.resting.clone()?;
+
+    let mut missing_at_start = 0;
+    let mut restored = 0;
+    if let Some(health_c) = ecs_.health_component.get(player) {
+        missing_at_start += health_c.maximum - progress.start_health;
+        restored += health_c.current - progress.start_health;
+    }
+    if let Some(caster_c) = ecs_.caster_component.get(player) {
+        missing_at_start += caster_c.maximum_mana - progress.start_mana;
+        restored += caster_c.current_mana - progress.start_mana;
+    }
+
+    if missing_at_start <= 0 {
+        Some(1.0)
+    } else {
+        Some((restored as f64 / missing_at_start as f64).clamp(0.0, 1.0))
+    }
+}
+
+/// Computes how far entities can see on the current floor, considering the weather
+pub fn view_radius(ecs_: &ecs::ECS) -> f64 {
+    BASE_VIEW_RADIUS * ecs_.weather.view_radius_factor()
+}
+
+/// Applies the turn based effects of the current weather and of fire: Rain douses
+/// all `StatusType::Burning`, otherwise burning entities take damage
+/// 
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+/// 
+pub fn apply_weather_effects(ecs_: &mut ecs::ECS) {
+    let douse = ecs_.weather.douses_fire();
+
+    for entity in ecs_.allocator.live_indices() {
+        let mut burning = false;
+        if let Some(status_c) = ecs_.status_component.get_mut(entity) {
+            if douse {
+                let before = status_c.status.len();
+                status_c.status.retain(|status| !matches!(status.type_, StatusType::Burning));
+                if status_c.status.len() != before {
+                    debug!("The rain doused the fire on {:?}", entity);
+                }
+            } else {
+                burning = status_c.status.iter().any(|status| matches!(status.type_, StatusType::Burning));
+            }
+        }
+
+        if burning {
+            apply_damage(ecs_, entity, None, BURN_DAMAGE);
+        }
+    }
+}
+
+/// Advances the world clock by one turn and applies the effects of the time of day.
+/// Those effects only take place outside of dungeons, e.g. shops close at night
+/// 
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+/// 
+pub fn advance_world_clock(ecs_: &mut ecs::ECS) {
+    ecs_.world_clock.advance();
+    let outside = ecs_.floor_type != dungeon::FloorType::Dungeon;
+    if outside {
+        schedule::update_schedules(ecs_);
+    }
+
+    for entity in ecs_.allocator.live_indices() {
+        let at_work = schedule::is_at_work(ecs_, entity);
+        if let Some(shop_c) = ecs_.shop_component.get_mut(entity) {
+            // shops in dungeons do not care about the time of day, else the shopkeeper
+            // has to be at work as well
+            let open = !outside || (at_work && ecs_.world_clock.is_between(shop_c.opening_hour, shop_c.closing_hour));
+            if open != shop_c.open {
+                shop_c.open = open;
+                debug!("Shop {:?} is now {}", entity, if open { "open" } else { "closed" });
+            }
+        }
+    }
+}
+
 /// Advances all `MovementIntents` by one step and updates their `LocationComponent`
 /// If the moved entity had an `ActingComponent` and was currently acting, it will be
-/// set to done acting
+/// set to done acting. Entities far from the players are throttled (see `lod`)
 /// 
 /// ### Arguments
 /// * `ecs_` - The entity component system to perform on
 /// 
 pub fn update_entity_positions(ecs_: &mut ecs::ECS) {
+    let focus = lod::focus(ecs_);
+    let mut collision_map = collision::CollisionMap::build(ecs_);
     for entity in ecs_.allocator.live_indices() {
+        // entities far from the players move less often, but as far at once
+        let ticks = lod::ticks(ecs_, &focus, entity, lod::Throttled::Movement);
+        if ticks == 0 {
+            if let Some(location_c) = ecs_.location_component.get_mut(entity) {
+                location_c.previous_location = None;
+            }
+            continue;
+        }
+        pathfinding::reroute_if_blocked(ecs_, &mut collision_map, entity);
         if let Some(movement_c) = ecs_.location_component.get_mut(entity) {
             let mut at_goal = false;
+            movement_c.previous_location = None;
             if let Some(movement_intent) = &mut movement_c.move_intent {
-                movement_c.location = movement_intent.move_from(&movement_c.location);
-                at_goal = movement_intent.has_arrived(&movement_c.location);
+                movement_c.previous_location = Some(movement_c.location);
+                for _ in 0..ticks {
+                    movement_c.location = movement_intent.move_from_scaled(&movement_c.location, ecs_.time_scale.scale());
+                    at_goal = movement_intent.has_arrived(&movement_c.location);
+                    if at_goal {
+                        break;
+                    }
+                }
             }
             if at_goal {
                 movement_c.move_intent = None;
+                advance_step_durations(ecs_, entity);
+                ecs_.events.publish(GameEvent::Stepped { entity });
                 // check if the entity had an 
                 if let Some(acting_c) = ecs_.actor_component.get_mut(entity) {
                     acting_c.state = actor::ActorState::DoneActing;
@@ -263,5 +814,153 @@ pub fn update_entity_positions(ecs_: &mut ecs::ECS) {
 
             }
         }
+        // entities walking a path reach new fields before they arrive at its goal
+        if ecs_.location_component.get(entity).is_some_and(|location_c| location_c.previous_location.is_some()) {
+            collision_map.update_entity(ecs_, entity);
+        }
+    }
+}
+/// Tests if the dungeon is currently simulated, which is not the case in paused modes,
+/// during transitions or while the player paused the game
+pub fn is_simulating(ecs_: &ecs::ECS) -> bool {
+    ecs_.game_mode.is_running() && ecs_.transition.is_none() && !ecs_.paused
+}
+
+/// Advances the game by one update of the event loop. Transitions always progress,
+/// movement, turns and everything else counting game updates only while the dungeon
+/// is simulated, as often as the time scale demands
+/// 
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+/// 
+/// ### Returns
+/// The events handled during the update
+/// 
+pub fn update(ecs_: &mut ecs::ECS) -> Vec<GameEvent> {
+    timed(ecs_, "transition", update_transition);
+    if is_simulating(ecs_) {
+        ecs_.playtime += 1.0 / crate::UPDATES_PER_SECOND as f64;
+        ecs_.lod.advance();
+        timed(ecs_, "cutscene", |ecs_| {
+            cutscene::check_boss_entrances(ecs_);
+            cutscene::update_cutscene(ecs_);
+        });
+        // movement is scaled in every update to stay smooth
+        timed(ecs_, "movement", update_entity_positions);
+        for _ in 0..ecs_.time_scale.advance() {
+            timed(ecs_, "turns", |ecs_| {
+                update_status_durations(ecs_);
+                continue_resting(ecs_);
+                effects::update_flashes(ecs_);
+                effects::update_animations(ecs_);
+                death::update_dying(ecs_);
+                // the world waits for a cutscene, only its scripted moves go on
+                if !cutscene::is_playing(ecs_) {
+                    scheduling::update_turns(ecs_);
+                }
+            });
+        }
+        timed(ecs_, "explore", update_explored);
+        run::check_defeat(ecs_);
+        if cfg!(debug_assertions) {
+            timed(ecs_, "validation", validation::validate);
+        }
+    }
+    if ecs_.game_mode == GameMode::Overworld && ecs_.transition.is_none() && !ecs_.paused {
+        timed(ecs_, "travel", overworld::update_travel);
     }
-}
\ No newline at end of file
+    // events are also processed while paused, so interface sounds are not delayed
+    let events = timed(ecs_, "triggers", process_events);
+    ecs_.toasts.update();
+    ecs_.screen_shake.update();
+    ecs_.profiler.end_update();
+    events
+}
+
+/// Runs a system and adds the time it took to the profiler
+fn timed<T, F: FnOnce(&mut ecs::ECS) -> T>(ecs_: &mut ecs::ECS, name: &'static str, system: F) -> T {
+    let start = Instant::now();
+    let result = system(ecs_);
+    ecs_.profiler.record(name, start.elapsed());
+    result
+}
+
+/// Starts a transition, unless one is already in progress
+/// 
+/// ### Returns
+/// True if the transition was started, else false
+/// 
+fn start_transition(ecs_: &mut ecs::ECS, effect: TransitionEffect, target: TransitionTarget) -> bool {
+    if ecs_.transition.is_some() {
+        return false;
+    }
+    ecs_.transition = Some(Transition::new(effect, target, TRANSITION_UPDATES));
+    true
+}
+
+/// Switches to another game mode by fading the screen out and in again
+/// 
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+/// * `mode` - The mode to switch to
+/// 
+/// ### Returns
+/// True if the switch was started, false if another transition is in progress
+/// 
+pub fn switch_mode(ecs_: &mut ecs::ECS, mode: GameMode) -> bool {
+    start_transition(ecs_, TransitionEffect::Fade, TransitionTarget::Mode(mode))
+}
+
+/// Replaces the current floor with a newly built one. The screen is wiped before
+/// the old floor is torn down and uncovered after the new one was built
+/// 
+/// ### Arguments
+/// * `ecs_`    - The entity component system to perform on
+/// * `builder` - Builds the new floor
+/// 
+/// ### Returns
+/// True if the change was started, false if another transition is in progress
+/// 
+pub fn change_floor(ecs_: &mut ecs::ECS, builder: FloorBuilder) -> bool {
+    start_transition(ecs_, TransitionEffect::Wipe, TransitionTarget::Floor(builder))
+}
+
+/// Advances the current transition by one update and applies its target once the
+/// screen is fully covered
+/// 
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+/// 
+pub fn update_transition(ecs_: &mut ecs::ECS) {
+    let mut target = None;
+    let mut finished = false;
+    if let Some(transition) = &mut ecs_.transition {
+        transition.elapsed += 1;
+        if transition.progress() >= 0.5 {
+            target = transition.target.take();
+        }
+        finished = transition.is_finished();
+    }
+
+    match target {
+        Some(TransitionTarget::Mode(mode)) => {
+            debug!("Switching game mode from {:?} to {:?}", ecs_.game_mode, mode);
+            ecs_.game_mode = mode;
+        },
+        Some(TransitionTarget::Floor(build)) => {
+            builder::dungeon::tear_down_level(ecs_);
+            ecs_.explored.clear();
+            ecs_.violations.clear();
+            build(ecs_);
+            // floors are played in the dungeon view, also when entered from the overworld
+            ecs_.game_mode = GameMode::Dungeon;
+            info!("Built a new {:?} floor with {} entities", ecs_.floor_type, ecs_.allocator.live_indices().len());
+            ecs_.events.publish(GameEvent::FloorChanged);
+        },
+        None => {}
+    }
+
+    if finished {
+        ecs_.transition = None;
+    }
+}
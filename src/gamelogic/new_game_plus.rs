@@ -0,0 +1,146 @@
+use crate::builder::dungeon::PLAYER_INVENTORY_CAPACITY;
+use crate::ecs;
+use crate::gamestate::components::InventoryComponent;
+use crate::gamestate::item::{Item, ItemType};
+use crate::gamestate::run::RunOutcome;
+
+// health and stats of creatures in new-game-plus runs, relative to normal runs
+pub const ENEMY_SCALING: f64 = 1.5;
+// pieces of equipment the player may keep for the next run
+pub const KEPT_EQUIPMENT_LIMIT: usize = 2;
+
+/// Starts a new-game-plus run, in which creatures are stronger. Only possible once a
+/// run was won with the profile
+///
+/// ### Returns
+/// True if the run is a new-game-plus run
+pub fn start(ecs_: &mut ecs::ECS) -> bool {
+    ecs_.new_game_plus = ecs_.profile.completed_run;
+    if !ecs_.new_game_plus {
+        warn!("New-game-plus needs a won run first");
+    }
+    ecs_.new_game_plus
+}
+
+/// Scales a value of a creature, e.g. its health, for new-game-plus runs
+pub fn scale(ecs_: &ecs::ECS, value: i32) -> i32 {
+    if ecs_.new_game_plus {
+        (value as f64 * ENEMY_SCALING).round() as i32
+    } else {
+        value
+    }
+}
+
+/// Puts the equipment kept from the last won run into a player's inventory
+///
+/// ### Arguments
+/// * `kept` - the kept equipment, usually that of the profile
+///
+/// ### Returns
+/// The number of pieces handed over
+pub fn hand_over_equipment(ecs_: &mut ecs::ECS, player: ecs::Entity, kept: &[Item]) -> usize {
+    if ecs_.inventory_component.get(player).is_none() {
+        ecs_.inventory_component.set(player, InventoryComponent { items: Vec::new(), capacity: PLAYER_INVENTORY_CAPACITY });
+    }
+    match ecs_.inventory_component.get_mut(player) {
+        Some(inventory_c) => {
+            inventory_c.items.extend(kept.iter().cloned());
+            kept.len()
+        },
+        None => 0
+    }
+}
+
+/// The equipment the player may choose to keep at the end of a won run
+pub fn equipment_choices(ecs_: &mut ecs::ECS) -> Vec<Item> {
+    let player = match ecs_.get_player_entity() {
+        Some(player) => player,
+        None => return Vec::new()
+    };
+    ecs_.inventory_component.get(player)
+        .map(|inventory_c| inventory_c.items.iter()
+                                             .filter(|item| matches!(item.item, ItemType::Equipment(_)))
+                                             .cloned()
+                                             .collect())
+        .unwrap_or_default()
+}
+
+/// Moves the selection on the game over screen of a won run by some choices, wrapping around
+pub fn select_choice(ecs_: &mut ecs::ECS, offset: i32) {
+    let count = equipment_choices(ecs_).len() as i32;
+    if let Some(summary) = ecs_.run_summary.as_mut().filter(|summary| summary.outcome == RunOutcome::Victory) {
+        if count > 0 {
+            summary.selected = (summary.selected as i32 + offset).rem_euclid(count) as usize;
+        }
+    }
+}
+
+/// Keeps the selected equipment for the next new-game-plus run, or no longer keeps it.
+/// The profile is updated at once
+///
+/// ### Returns
+/// True if the choice changed, false if the limit is reached or the run was lost
+pub fn toggle_kept(ecs_: &mut ecs::ECS) -> bool {
+    let choices = equipment_choices(ecs_);
+    let summary = match ecs_.run_summary.as_mut() {
+        Some(summary) if summary.outcome == RunOutcome::Victory && summary.selected < choices.len() => summary,
+        _ => return false
+    };
+    let selected = summary.selected;
+    if let Some(position) = summary.kept.iter().position(|kept| *kept == selected) {
+        summary.kept.remove(position);
+    } else if summary.kept.len() < KEPT_EQUIPMENT_LIMIT {
+        summary.kept.push(selected);
+    } else {
+        return false;
+    }
+    ecs_.profile.kept_equipment = summary.kept.iter().map(|index| choices[*index].clone()).collect();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamelogic::run;
+    use crate::gamestate::class::Class;
+    use crate::gamestate::item::Equipment;
+
+    fn equipment(equipment: Equipment) -> Item {
+        Item { item: ItemType::Equipment(equipment), amount: 1 }
+    }
+
+    #[test]
+    fn test_won_runs_allow_new_game_plus() {
+        let mut ecs_ = ecs::ECS::new();
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        ecs_.inventory_component.set(player, InventoryComponent {
+            items: vec![equipment(Equipment::Helm), Item { item: ItemType::Consumable, amount: 3 }, equipment(Equipment::Boots), equipment(Equipment::Gloves)],
+            capacity: 4
+        });
+        assert!(!start(&mut ecs_));
+
+        // winning unlocks the mage, two of three pieces can be kept
+        run::end_run(&mut ecs_, RunOutcome::Victory);
+        assert!(ecs_.profile.completed_run && ecs_.profile.has_class(Class::Mage));
+        assert_eq!(equipment_choices(&mut ecs_).len(), 3);
+        assert!(toggle_kept(&mut ecs_));
+        select_choice(&mut ecs_, -1);
+        assert!(toggle_kept(&mut ecs_));
+        select_choice(&mut ecs_, -1);
+        assert!(!toggle_kept(&mut ecs_));
+        assert_eq!(ecs_.profile.kept_equipment, vec![equipment(Equipment::Helm), equipment(Equipment::Gloves)]);
+
+        // the next run has stronger creatures and starts with the kept equipment
+        let mut next = ecs::ECS::new();
+        next.profile = ecs_.profile.clone();
+        assert!(start(&mut next));
+        let rat = dungeon::create_rat(&mut next, 2.0, 2.0);
+        assert_eq!(next.health_component.get(rat).unwrap().maximum, 30);
+        assert_eq!(next.basestats_component.get(rat).unwrap().attack, 6);
+        let player = dungeon::create_test_player(&mut next, 1.0, 1.0);
+        let kept = next.profile.kept_equipment.clone();
+        assert_eq!(hand_over_equipment(&mut next, player, &kept), 2);
+        assert_eq!(next.inventory_component.get(player).unwrap().items, ecs_.profile.kept_equipment);
+    }
+}
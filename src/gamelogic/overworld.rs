@@ -0,0 +1,280 @@
+extern crate rand;
+
+use rand::Rng;
+
+use crate::builder;
+use crate::ecs;
+use crate::event::bus::GameEvent;
+use crate::gamelogic::{change_floor, switch_mode};
+use crate::gamestate::mode::{FloorBuilder, GameMode};
+use crate::gamestate::movement::Direction;
+use crate::gamestate::overworld::{self, Destination, OverworldNode, Travel, TravelEvent};
+use crate::gamestate::LocationVec;
+
+// game updates the players need to cross the whole overworld screen
+const TRAVEL_UPDATES: f64 = 240.0;
+// chance of something happening on a road, in percent
+const EVENT_CHANCE: u32 = 40;
+
+fn node(index: usize) -> Option<OverworldNode> {
+    overworld::world().into_iter().nth(index)
+}
+
+/// Tests if the players may travel to a node: any player has the progression flag it requires
+pub fn is_unlocked(ecs_: &ecs::ECS, node: &OverworldNode) -> bool {
+    let flag = match node.requires {
+        Some(flag) => flag,
+        None => return true
+    };
+    ecs_.allocator.live_indices()
+        .into_iter()
+        .filter_map(|entity| ecs_.player_component.get(entity))
+        .any(|player_c| player_c.progression_flags.get(flag).copied().unwrap_or(false))
+}
+
+/// Tests if the stairs of the current floor lead out to the overworld instead of further
+/// down: on the last floor of a dungeon and in towns
+pub fn leads_out(ecs_: &ecs::ECS) -> bool {
+    match node(ecs_.overworld.current).map(|node| node.destination) {
+        Some(Destination::Dungeon { floors }) => ecs_.overworld.depth + 1 >= floors,
+        Some(Destination::Town) => true,
+        None => false
+    }
+}
+
+/// Leaves the current dungeon or town for the overworld. Players leaving the last floor of
+/// a dungeon get the progression flag for finishing it
+///
+/// ### Returns
+/// True if the overworld is opened, false if another transition is in progress
+pub fn leave(ecs_: &mut ecs::ECS) -> bool {
+    if !switch_mode(ecs_, GameMode::Overworld) {
+        return false;
+    }
+    let completes = node(ecs_.overworld.current).and_then(|node| node.completes);
+    if let Some(flag) = completes {
+        for entity in ecs_.allocator.live_indices() {
+            if let Some(player_c) = ecs_.player_component.get_mut(entity) {
+                player_c.progression_flags.insert(flag.to_string(), true);
+            }
+        }
+    }
+    ecs_.overworld.selected = ecs_.overworld.current;
+    ecs_.overworld.last_event = None;
+    true
+}
+
+/// Moves the selection on the overworld screen from the selected node to the one in a
+/// direction. Only the current node and the nodes its roads lead to can be selected
+pub fn select(ecs_: &mut ecs::ECS, direction: Direction) {
+    let nodes = overworld::world();
+    let (current, selected) = (ecs_.overworld.current, ecs_.overworld.selected);
+    let from = match nodes.get(selected) {
+        Some(node) => node.position,
+        None => return
+    };
+    let step = LocationVec::from(direction);
+    // the closest node in about the direction, ahead counts more than aside
+    let score = |index: &usize| {
+        let offset = nodes[*index].position - from;
+        let ahead = offset.x * step.x + offset.y * step.y;
+        let aside = (offset.x * step.y - offset.y * step.x).abs();
+        if ahead > 0.0 { Some(ahead + 2.0 * aside) } else { None }
+    };
+    let next = std::iter::once(current)
+        .chain(nodes[current].roads.iter().copied())
+        .filter(|index| *index != selected)
+        .filter_map(|index| score(&index).map(|score| (index, score)))
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((index, _)) = next {
+        ecs_.overworld.selected = index;
+    }
+}
+
+/// Sets off on the road from the current node to another one, if it is unlocked. Something
+/// may happen on the way, it is rolled now and applied on arrival
+///
+/// ### Returns
+/// True if the players set off
+pub fn travel_to(ecs_: &mut ecs::ECS, to: usize) -> bool {
+    let from = ecs_.overworld.current;
+    let (start, goal) = match (node(from), node(to)) {
+        (Some(start), Some(goal)) => (start, goal),
+        _ => return false
+    };
+    if ecs_.overworld.travel.is_some() || !start.roads.contains(&to) || !is_unlocked(ecs_, &goal) {
+        return false;
+    }
+    let offset = goal.position - start.position;
+    let duration = ((offset.x * offset.x + offset.y * offset.y).sqrt() * TRAVEL_UPDATES).round() as u64;
+    let event = if ecs_.rng.gen_range(0..100) < EVENT_CHANCE {
+        Some(match ecs_.rng.gen_range(0..3) {
+            0 => TravelEvent::Treasure { gold: ecs_.rng.gen_range(10..30) },
+            1 => TravelEvent::Ambush { damage: ecs_.rng.gen_range(5..15) },
+            _ => TravelEvent::Campfire { health: ecs_.rng.gen_range(10..25) }
+        })
+    } else {
+        None
+    };
+    debug!("Travelling from {} to {} in {} updates, {:?} on the way", start.name, goal.name, duration, event);
+    ecs_.overworld.travel = Some(Travel { from, to, elapsed: 0, duration, event });
+    ecs_.overworld.last_event = None;
+    true
+}
+
+/// Enters the dungeon or town of the current node, on its first floor
+///
+/// ### Returns
+/// True if the floor is being built, false if the players are on the road or another
+/// transition is in progress
+pub fn enter(ecs_: &mut ecs::ECS) -> bool {
+    let destination = match node(ecs_.overworld.current) {
+        Some(node) if ecs_.overworld.travel.is_none() => node.destination,
+        _ => return false
+    };
+    let build: FloorBuilder = match destination {
+        Destination::Dungeon { .. } => builder::dungeon::create_next_floor,
+        Destination::Town => builder::dungeon::create_town
+    };
+    if !change_floor(ecs_, build) {
+        return false;
+    }
+    ecs_.overworld.depth = 0;
+    true
+}
+
+/// Travels to the selected node, or enters the current one if it is selected
+///
+/// ### Returns
+/// True if something happened
+pub fn confirm(ecs_: &mut ecs::ECS) -> bool {
+    if ecs_.overworld.selected == ecs_.overworld.current {
+        enter(ecs_)
+    } else {
+        travel_to(ecs_, ecs_.overworld.selected)
+    }
+}
+
+/// Applies what happened on the road to all players
+fn apply_event(ecs_: &mut ecs::ECS, event: TravelEvent) {
+    let players: Vec<ecs::Entity> = ecs_.allocator.live_indices()
+                                        .into_iter()
+                                        .filter(|entity| ecs_.player_component.get(*entity).is_some())
+                                        .collect();
+    for player in players {
+        match event {
+            TravelEvent::Treasure { gold } => if let Some(player_c) = ecs_.player_component.get_mut(player) {
+                player_c.gold += gold;
+            },
+            TravelEvent::Ambush { damage } => if let Some(health_c) = ecs_.health_component.get_mut(player) {
+                health_c.current = (health_c.current - damage).max(1);
+            },
+            TravelEvent::Campfire { health } => if let Some(health_c) = ecs_.health_component.get_mut(player) {
+                health_c.current = (health_c.current + health).min(health_c.maximum);
+            }
+        }
+    }
+}
+
+/// Advances the players on the road by one update. Once they arrive at the node, what
+/// happened on the way is applied and the node is selected
+///
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+///
+pub fn update_travel(ecs_: &mut ecs::ECS) {
+    let arrived = match &mut ecs_.overworld.travel {
+        Some(travel) => {
+            travel.elapsed += 1;
+            travel.elapsed >= travel.duration
+        },
+        None => return
+    };
+    if !arrived {
+        return;
+    }
+    let travel = ecs_.overworld.travel.take().unwrap();
+    ecs_.overworld.current = travel.to;
+    ecs_.overworld.selected = travel.to;
+    ecs_.overworld.depth = 0;
+    if let Some(event) = travel.event {
+        apply_event(ecs_, event);
+        ecs_.events.publish(GameEvent::UiClick);
+    }
+    ecs_.overworld.last_event = travel.event;
+}
+
+/// The position of the players on the overworld screen, between two nodes while travelling
+///
+/// ### Arguments
+/// * `alpha` - Time since the last update, relative to the time between updates
+///
+pub fn party_position(ecs_: &ecs::ECS, alpha: f64) -> LocationVec {
+    let nodes = overworld::world();
+    match &ecs_.overworld.travel {
+        Some(travel) => {
+            let (from, to) = (nodes[travel.from].position, nodes[travel.to].position);
+            let progress = if travel.duration == 0 {
+                1.0
+            } else {
+                ((travel.elapsed as f64 + alpha) / travel.duration as f64).min(1.0)
+            };
+            LocationVec { x: from.x + (to.x - from.x) * progress, y: from.y + (to.y - from.y) * progress }
+        },
+        None => nodes.get(ecs_.overworld.current).map_or(LocationVec { x: 0.0, y: 0.0 }, |node| node.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamelogic;
+    use crate::gamestate::dungeon::FloorType;
+
+    fn finish_transition(ecs_: &mut ecs::ECS) {
+        while ecs_.transition.is_some() {
+            gamelogic::update_transition(ecs_);
+        }
+    }
+
+    #[test]
+    fn test_travel_unlocks_and_enters_nodes() {
+        let mut ecs_ = ecs::ECS::new();
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+
+        // the crypt's stairs lead down until its last floor
+        assert!(!leads_out(&ecs_));
+        ecs_.overworld.depth = 2;
+        assert!(leads_out(&ecs_));
+        let keep = &overworld::world()[2];
+        assert!(!is_unlocked(&ecs_, keep));
+        assert!(leave(&mut ecs_));
+        finish_transition(&mut ecs_);
+        assert_eq!(ecs_.game_mode, GameMode::Overworld);
+        assert!(is_unlocked(&ecs_, keep));
+        assert!(!travel_to(&mut ecs_, 3));
+
+        // the village lies up right of the crypt
+        select(&mut ecs_, Direction::UpRight);
+        assert_eq!(ecs_.overworld.selected, 1);
+        let gold = ecs_.player_component.get(player).unwrap().gold;
+        assert!(confirm(&mut ecs_));
+        ecs_.overworld.travel.as_mut().unwrap().event = Some(TravelEvent::Treasure { gold: 20 });
+        while ecs_.overworld.travel.is_some() {
+            assert!(!confirm(&mut ecs_));
+            update_travel(&mut ecs_);
+        }
+        assert_eq!(ecs_.overworld.current, 1);
+        assert_eq!(ecs_.player_component.get(player).unwrap().gold, gold + 20);
+
+        // entering the village builds the town and shows it
+        assert!(confirm(&mut ecs_));
+        finish_transition(&mut ecs_);
+        assert_eq!((ecs_.game_mode, ecs_.floor_type), (GameMode::Dungeon, FloorType::Town));
+        assert!(leads_out(&ecs_));
+        // it is not cleared by entering it
+        gamelogic::process_events(&mut ecs_);
+        assert_eq!(ecs_.run_stats.floors_cleared, 0);
+    }
+}
@@ -0,0 +1,260 @@
+use crate::ecs;
+use crate::gamelogic::{self, collision::CollisionMap, death, lod::{self, Throttled}, pathfinding, StepResult};
+use crate::gamestate::{movement::Direction, party::PartyOrder, LocationVec};
+
+// companions following a player stay this close to them, in fields
+const FOLLOW_DISTANCE: f64 = 2.0;
+
+fn to_field(location: LocationVec) -> (i64, i64) {
+    (location.x.round() as i64, location.y.round() as i64)
+}
+
+/// Tests if two locations are on neighbouring fields, diagonals included
+fn is_adjacent(from: LocationVec, to: LocationVec) -> bool {
+    let (from, to) = (to_field(from), to_field(to));
+    (from.0 - to.0).abs().max((from.1 - to.1).abs()) == 1
+}
+
+/// The direction of the step from a field towards another one, None on the same field
+fn direction_to(from: LocationVec, to: LocationVec) -> Option<Direction> {
+    let (from, to) = (to_field(from), to_field(to));
+    let step = LocationVec { x: (to.0 - from.0).signum() as f64, y: (to.1 - from.1).signum() as f64 };
+    Direction::ALL.iter().copied().find(|direction| LocationVec::from(*direction) == step)
+}
+
+fn location_of(ecs_: &ecs::ECS, entity: ecs::Entity) -> Option<LocationVec> {
+    ecs_.location_component.get(entity).map(|location_c| location_c.location)
+}
+
+fn is_alive(ecs_: &ecs::ECS, entity: ecs::Entity) -> bool {
+    // removed entities have no components left
+    ecs_.location_component.get(entity).is_some()
+        && ecs_.health_component.get(entity).is_none_or(|health_c| health_c.current > 0)
+        && !death::is_dying(ecs_, entity)
+}
+
+/// All living companions of the players
+pub fn companions(ecs_: &ecs::ECS) -> Vec<ecs::Entity> {
+    ecs_.allocator.live_indices()
+        .into_iter()
+        .filter(|entity| ecs_.companion_component.get(*entity).is_some() && is_alive(ecs_, *entity))
+        .collect()
+}
+
+/// The creature a player fights: the hostile creature it faces, else the closest one in view
+///
+/// ### Returns
+/// The creature, None if the player sees none
+pub fn player_target(ecs_: &ecs::ECS, player: ecs::Entity) -> Option<ecs::Entity> {
+    let location_c = ecs_.location_component.get(player)?;
+    let (location, facing) = (location_c.location, location_c.location + location_c.direction.into());
+    let hostiles = gamelogic::visible_hostiles(ecs_, player);
+    let distance = |entity: &ecs::Entity| location_of(ecs_, *entity).map_or(f64::MAX, |other| ecs_.diagonal_rule.distance(&location, &other));
+    hostiles.iter()
+            .copied()
+            .find(|hostile| location_of(ecs_, *hostile).is_some_and(|other| to_field(other) == to_field(facing)))
+            .or_else(|| hostiles.iter().copied().min_by(|a, b| distance(a).total_cmp(&distance(b))))
+}
+
+/// Gives all companions an order. Holding companions hold the field they are on, focusing
+/// ones attack the creature the player fights (see `player_target`)
+///
+/// ### Arguments
+/// * `ecs_`   - The entity component system to perform on
+/// * `player` - The player giving the order
+/// * `order`  - The order given
+///
+/// ### Returns
+/// The number of companions ordered
+pub fn give_order(ecs_: &mut ecs::ECS, player: ecs::Entity, order: PartyOrder) -> usize {
+    let target = if order == PartyOrder::FocusTarget { player_target(ecs_, player) } else { None };
+    let companions = companions(ecs_);
+    for companion in companions.iter() {
+        let location = location_of(ecs_, *companion);
+        if let Some(companion_c) = ecs_.companion_component.get_mut(*companion) {
+            companion_c.order = order;
+            companion_c.hold = if order == PartyOrder::Hold { location } else { None };
+            companion_c.target = target;
+        }
+    }
+    debug!("{:?} ordered {} companions to {:?}", player, companions.len(), order);
+    companions.len()
+}
+
+/// Lets a companion take one step towards a goal, on a path around obstacles. Occupied goals,
+/// e.g. a player or a creature, are walked up to
+///
+/// ### Arguments
+/// * `fight` - Bump into hostile creatures in the way, attacking them
+///
+/// ### Returns
+/// True if the companion stepped or attacked
+fn step_towards(ecs_: &mut ecs::ECS, collision_map: &mut CollisionMap, companion: ecs::Entity, goal: LocationVec, fight: bool) -> bool {
+    let location = match location_of(ecs_, companion) {
+        Some(location) => location,
+        None => return false
+    };
+    let next = if is_adjacent(location, goal) {
+        Some(goal)
+    } else if collision_map.is_free(goal) {
+        pathfinding::find_path(ecs_, collision_map, companion, location, goal).and_then(|path| path.first().copied())
+    } else {
+        // the shortest way to any free field next to the goal
+        Direction::ALL.iter()
+            .map(|direction| goal + (*direction).into())
+            .filter(|field| collision_map.is_free(*field))
+            .filter_map(|field| pathfinding::find_path(ecs_, collision_map, companion, location, field))
+            .min_by_key(|path| path.len())
+            .and_then(|path| path.first().copied())
+    };
+    let direction = match next.and_then(|next| direction_to(location, next)) {
+        Some(direction) => direction,
+        None => return false
+    };
+    if fight {
+        !matches!(gamelogic::step_or_attack(ecs_, collision_map, companion, direction), StepResult::Blocked(_))
+    } else {
+        gamelogic::move_entity(ecs_, collision_map, companion, direction)
+    }
+}
+
+/// Lets a companion attack a hostile creature next to it
+///
+/// ### Returns
+/// True if it attacked
+fn attack_adjacent(ecs_: &mut ecs::ECS, collision_map: &mut CollisionMap, companion: ecs::Entity) -> bool {
+    let location = match location_of(ecs_, companion) {
+        Some(location) => location,
+        None => return false
+    };
+    let adjacent = gamelogic::visible_hostiles(ecs_, companion)
+        .into_iter()
+        .filter_map(|hostile| location_of(ecs_, hostile))
+        .find(|other| is_adjacent(location, *other));
+    match adjacent.and_then(|other| direction_to(location, other)) {
+        Some(direction) => matches!(gamelogic::step_or_attack(ecs_, collision_map, companion, direction), StepResult::Attacked(_)),
+        None => false
+    }
+}
+
+/// Lets every companion act once on the order it was given. Companions focusing a
+/// creature that died follow the players again. Called once per turn, companions far
+/// away from the players are throttled (see `lod`) and skip turns
+///
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+///
+pub fn update_companions(ecs_: &mut ecs::ECS) {
+    let players = lod::focus(ecs_);
+    let mut collision_map = CollisionMap::build(ecs_);
+    for companion in companions(ecs_) {
+        let location = match ecs_.location_component.get(companion) {
+            // still stepping
+            Some(location_c) if location_c.move_intent.is_some() => continue,
+            Some(location_c) => location_c.location,
+            None => continue
+        };
+        if lod::ticks(ecs_, &players, companion, Throttled::Ai) == 0 {
+            continue;
+        }
+        let (mut order, hold, target) = match ecs_.companion_component.get(companion) {
+            Some(companion_c) => (companion_c.order, companion_c.hold, companion_c.target),
+            None => continue
+        };
+        let target = target.filter(|target| is_alive(ecs_, *target)).and_then(|target| location_of(ecs_, target));
+        if order == PartyOrder::FocusTarget && target.is_none() {
+            order = PartyOrder::Follow;
+            if let Some(companion_c) = ecs_.companion_component.get_mut(companion) {
+                companion_c.order = order;
+                companion_c.target = None;
+            }
+        }
+        let leader = players.iter()
+                            .copied()
+                            .min_by(|a, b| ecs_.diagonal_rule.distance(&location, a).total_cmp(&ecs_.diagonal_rule.distance(&location, b)));
+
+        match order {
+            PartyOrder::Follow => {
+                if !attack_adjacent(ecs_, &mut collision_map, companion) {
+                    if let Some(leader) = leader.filter(|leader| ecs_.diagonal_rule.distance(&location, leader) > FOLLOW_DISTANCE) {
+                        step_towards(ecs_, &mut collision_map, companion, leader, true);
+                    }
+                }
+            },
+            PartyOrder::Hold => {
+                if !attack_adjacent(ecs_, &mut collision_map, companion) {
+                    if let Some(hold) = hold.filter(|hold| to_field(*hold) != to_field(location)) {
+                        step_towards(ecs_, &mut collision_map, companion, hold, true);
+                    }
+                }
+            },
+            PartyOrder::FocusTarget => {
+                if let Some(target) = target {
+                    step_towards(ecs_, &mut collision_map, companion, target, true);
+                }
+            },
+            PartyOrder::Retreat => {
+                if let Some(leader) = leader.filter(|leader| !is_adjacent(location, *leader)) {
+                    step_towards(ecs_, &mut collision_map, companion, leader, false);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+
+    fn turn(ecs_: &mut ecs::ECS) {
+        update_companions(ecs_);
+        for _ in 0..20 {
+            gamelogic::update_entity_positions(ecs_);
+        }
+    }
+
+    #[test]
+    fn test_companions_follow_orders() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 12, 8);
+        let player = dungeon::create_test_player(&mut ecs_, 2.0, 2.0);
+        let companion = dungeon::create_companion(&mut ecs_, 2.0, 3.0);
+        let distance_to_player = |ecs_: &ecs::ECS| ecs_.diagonal_rule.distance(&location_of(ecs_, companion).unwrap(), &location_of(ecs_, player).unwrap());
+
+        // follows the player across the room
+        ecs_.location_component.get_mut(player).unwrap().location = LocationVec { x: 9.0, y: 5.0 };
+        for _ in 0..10 {
+            turn(&mut ecs_);
+        }
+        assert!(distance_to_player(&ecs_) <= FOLLOW_DISTANCE);
+
+        // stays behind while holding
+        assert_eq!(give_order(&mut ecs_, player, PartyOrder::Hold), 1);
+        let held = location_of(&ecs_, companion).unwrap();
+        ecs_.location_component.get_mut(player).unwrap().location = LocationVec { x: 2.0, y: 2.0 };
+        for _ in 0..5 {
+            turn(&mut ecs_);
+        }
+        assert_eq!(location_of(&ecs_, companion), Some(held));
+
+        // attacks the creature the player fights until it dies
+        let rat = dungeon::create_rat(&mut ecs_, 3.0, 5.0);
+        ecs_.health_component.get_mut(rat).unwrap().current = 1000;
+        give_order(&mut ecs_, player, PartyOrder::FocusTarget);
+        assert_eq!(ecs_.companion_component.get(companion).unwrap().target, Some(rat));
+        for _ in 0..10 {
+            turn(&mut ecs_);
+        }
+        let health = ecs_.health_component.get(rat).unwrap().current;
+        assert!(health < 1000);
+
+        // retreats next to the player without fighting
+        give_order(&mut ecs_, player, PartyOrder::Retreat);
+        for _ in 0..10 {
+            turn(&mut ecs_);
+        }
+        assert!(is_adjacent(location_of(&ecs_, companion).unwrap(), location_of(&ecs_, player).unwrap()));
+        assert_eq!(ecs_.health_component.get(rat).unwrap().current, health);
+    }
+}
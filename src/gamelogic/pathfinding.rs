@@ -0,0 +1,178 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::ecs;
+use crate::gamelogic::{self, collision::CollisionMap};
+use crate::gamestate::{movement::{self, Easing, MoveIntent}, LocationVec};
+
+// fields searched at most before a goal is given up as unreachable, keeps searches
+// for far away or walled in goals cheap
+const MAX_SEARCHED_FIELDS: usize = 4096;
+// costs are in hundredths of a field, so they can be compared exactly
+const STEP_COST: i64 = 100;
+
+type Field = (i64, i64);
+
+fn to_field(location: LocationVec) -> Field {
+    (location.x.round() as i64, location.y.round() as i64)
+}
+
+fn to_location(field: Field) -> LocationVec {
+    LocationVec { x: field.0 as f64, y: field.1 as f64 }
+}
+
+/// Finds the shortest way for an entity over fields it can enter, in the eight directions it can
+/// step into (see `gamelogic::can_step`). Diagonal steps cost as much as `ECS::diagonal_rule` says
+///
+/// ### Arguments
+/// * `ecs_`          - The entity component system to search in
+/// * `collision_map` - The fields that can be entered, built from `ecs_`
+/// * `entity`        - The entity that wants to walk
+/// * `start`         - The field to start on
+/// * `goal`          - The field to walk to
+///
+/// ### Returns
+/// The fields to walk through after the start, ending with the goal. None if the goal can not be reached
+pub fn find_path(ecs_: &ecs::ECS, collision_map: &CollisionMap, entity: ecs::Entity, start: LocationVec, goal: LocationVec) -> Option<Vec<LocationVec>> {
+    let start = to_field(start);
+    let goal = to_field(goal);
+    let diagonal_cost = (ecs_.diagonal_rule.diagonal_cost() * STEP_COST as f64).round() as i64;
+    // the cost of the way without obstacles, A* needs it to never be too high
+    let estimate = |field: Field| {
+        let dx = (field.0 - goal.0).abs();
+        let dy = (field.1 - goal.1).abs();
+        dx.min(dy) * diagonal_cost + (dx - dy).abs() * STEP_COST
+    };
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<Field, Field> = HashMap::new();
+    let mut costs: HashMap<Field, i64> = HashMap::new();
+    open.push(Reverse((estimate(start), start)));
+    costs.insert(start, 0);
+
+    while let Some(Reverse((_, field))) = open.pop() {
+        if field == goal {
+            let mut path = vec![to_location(field)];
+            let mut current = field;
+            while let Some(previous) = came_from.get(&current) {
+                if *previous != start {
+                    path.push(to_location(*previous));
+                }
+                current = *previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if costs.len() > MAX_SEARCHED_FIELDS {
+            break;
+        }
+
+        for direction in movement::Direction::ALL.iter() {
+            let step = LocationVec::from(*direction);
+            let next = (field.0 + step.x as i64, field.1 + step.y as i64);
+            let cost = costs[&field] + if direction.is_diagonal() { diagonal_cost } else { STEP_COST };
+            if costs.get(&next).is_some_and(|known_cost| *known_cost <= cost) {
+                continue;
+            }
+            if collision_map.check_step(ecs_, entity, to_location(field), *direction).is_err() {
+                continue;
+            }
+            costs.insert(next, cost);
+            came_from.insert(next, field);
+            open.push(Reverse((cost + estimate(next), next)));
+        }
+    }
+    None
+}
+
+/// Lets an entity walk a path to a goal
+///
+/// ### Arguments
+/// * `easing` - How the entity speeds up and slows down over the whole path
+///
+/// ### Returns
+/// True if a path was found and the entity started walking it, else false
+pub fn walk_to(ecs_: &mut ecs::ECS, entity: ecs::Entity, goal: LocationVec, speed: f64, easing: Easing) -> bool {
+    let start = match ecs_.location_component.get(entity) {
+        Some(location_c) => location_c.location,
+        None => return false
+    };
+    match find_path(ecs_, &CollisionMap::build(ecs_), entity, start, goal) {
+        Some(path) => {
+            if let Some(location_c) = ecs_.location_component.get_mut(entity) {
+                location_c.move_intent = Some(MoveIntent::Path(path, speed).eased(easing, &start));
+            }
+            true
+        },
+        None => false
+    }
+}
+
+/// Finds a new way for an entity walking a `MoveIntent::Path` whose next field got blocked,
+/// e.g. by a creature stepping onto it. Paths are only checked when the entity stands
+/// in the center of a field. If the goal can no longer be reached the entity stops.
+/// The collision map is updated with the new way
+pub fn reroute_if_blocked(ecs_: &mut ecs::ECS, collision_map: &mut CollisionMap, entity: ecs::Entity) {
+    let (location, next_waypoint, goal) = match ecs_.location_component.get(entity) {
+        Some(location_c) => match location_c.move_intent.as_ref().and_then(|move_intent| move_intent.next_waypoint().map(|next| (move_intent, next))) {
+            Some((move_intent, next_waypoint)) => (location_c.location, next_waypoint, move_intent.target_goal(&location_c.location)),
+            None => return
+        },
+        None => return
+    };
+    if location.x.fract() != 0.0 || location.y.fract() != 0.0 || gamelogic::can_enter(ecs_, collision_map, entity, next_waypoint) {
+        return;
+    }
+
+    let path = find_path(ecs_, collision_map, entity, location, goal);
+    debug!("Path of {:?} to {:?} is blocked, rerouted: {}", entity, goal, path.is_some());
+    if let Some(location_c) = ecs_.location_component.get_mut(entity) {
+        match (path, &mut location_c.move_intent) {
+            (Some(path), Some(move_intent)) => set_waypoints(move_intent, path),
+            _ => location_c.move_intent = None
+        }
+    }
+    collision_map.update_entity(ecs_, entity);
+}
+
+/// Replaces the waypoints of a path, keeping its speed and easing
+fn set_waypoints(move_intent: &mut MoveIntent, path: Vec<LocationVec>) {
+    match move_intent {
+        MoveIntent::Path(waypoints, _) => *waypoints = path,
+        MoveIntent::Eased(intent, _, _) => set_waypoints(intent, path),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+
+    #[test]
+    fn test_paths_avoid_and_reroute_around_blockers() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 6, 5);
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 2.0);
+        let goal = LocationVec { x: 4.0, y: 2.0 };
+
+        let collision_map = CollisionMap::build(&ecs_);
+        let path = find_path(&ecs_, &collision_map, player, LocationVec { x: 1.0, y: 2.0 }, goal).unwrap();
+        assert_eq!(path, vec![LocationVec { x: 2.0, y: 2.0 }, LocationVec { x: 3.0, y: 2.0 }, goal]);
+        // diagonal steps are taken when they are shorter
+        let path = find_path(&ecs_, &collision_map, player, LocationVec { x: 1.0, y: 1.0 }, LocationVec { x: 3.0, y: 3.0 }).unwrap();
+        assert_eq!(path, vec![LocationVec { x: 2.0, y: 2.0 }, LocationVec { x: 3.0, y: 3.0 }]);
+
+        assert!(walk_to(&mut ecs_, player, goal, movement::DEFAULT_SPEED, Easing::EaseInOut));
+        // a wall appears in the way, the player walks around it without cutting its corners
+        dungeon::create_wall_tile(&mut ecs_, 2.0, 2.0);
+        let mut collision_map = CollisionMap::build(&ecs_);
+        reroute_if_blocked(&mut ecs_, &mut collision_map, player);
+        let move_intent = ecs_.location_component.get(player).unwrap().move_intent.clone().unwrap();
+        assert!(move_intent.next_waypoint() == Some(LocationVec { x: 1.0, y: 1.0 }) || move_intent.next_waypoint() == Some(LocationVec { x: 1.0, y: 3.0 }));
+        assert_eq!(move_intent.target_goal(&LocationVec { x: 1.0, y: 2.0 }), goal);
+
+        // there is no floor outside of the walls
+        assert!(find_path(&ecs_, &collision_map, player, LocationVec { x: 1.0, y: 2.0 }, LocationVec { x: 1.0, y: -3.0 }).is_none());
+    }
+}
@@ -0,0 +1,105 @@
+use crate::ecs;
+use crate::event::bus::GameEvent;
+use crate::gamelogic::{stats, stop_all_resting, switch_mode};
+use crate::gamestate::mode::GameMode;
+use crate::gamestate::run::{RunOutcome, RunSummary};
+
+/// Wins the game if the entity that died was the final boss
+pub fn on_death(ecs_: &mut ecs::ECS, entity: ecs::Entity) {
+    if ecs_.boss_component.get(entity).is_some_and(|boss_c| boss_c.final_boss) {
+        end_run(ecs_, RunOutcome::Victory);
+    }
+}
+
+/// Ends the run in defeat once all players are out of health, however they lost it
+///
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+///
+pub fn check_defeat(ecs_: &mut ecs::ECS) {
+    let players: Vec<ecs::Entity> = ecs_.allocator.live_indices()
+                                        .into_iter()
+                                        .filter(|entity| ecs_.player_component.get(*entity).is_some())
+                                        .collect();
+    let all_dead = !players.is_empty()
+                   && players.iter().all(|player| ecs_.health_component.get(*player).is_some_and(|health_c| health_c.current <= 0));
+    if all_dead {
+        end_run(ecs_, RunOutcome::Defeat);
+    }
+}
+
+/// Ends the run: the dungeon stops and the game over screen shows its statistics.
+/// What happens to the saves of the run is up to the frontends, see `slots::on_run_ended`
+///
+/// ### Arguments
+/// * `ecs_`    - The entity component system to perform on
+/// * `outcome` - Whether the run was won or lost
+///
+pub fn end_run(ecs_: &mut ecs::ECS, outcome: RunOutcome) {
+    if ecs_.run_summary.is_some() {
+        return;
+    }
+    let summary = summarize(ecs_, outcome);
+    info!("The run ended in {:?} after {} turns", outcome, summary.turns);
+    ecs_.run_summary = Some(summary);
+    stats::record_run_end(ecs_, outcome);
+    stop_all_resting(ecs_);
+    ecs_.targeting = None;
+    ecs_.cutscene = None;
+    ecs_.action_queue.clear();
+    ecs_.events.publish(GameEvent::RunEnded { outcome });
+    // a transition in progress, e.g. to another floor, is replaced
+    ecs_.transition = None;
+    switch_mode(ecs_, GameMode::GameOver);
+}
+
+fn summarize(ecs_: &mut ecs::ECS, outcome: RunOutcome) -> RunSummary {
+    let player = ecs_.get_player_entity();
+    RunSummary {
+        outcome,
+        turns: player.and_then(|player| ecs_.actor_component.get(player)).map_or(0, |actor_c| actor_c.turn),
+        kills: ecs_.run_stats.kills,
+        gold: player.and_then(|player| ecs_.player_component.get(player)).map_or(0, |player_c| player_c.gold),
+        deepest_floor: ecs_.run_stats.deepest_floor,
+        selected: 0,
+        kept: Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamelogic;
+    use crate::gamestate::components::{BossComponent, NpcBehaviorComponent};
+    use crate::gamestate::movement::Direction;
+
+    #[test]
+    fn test_runs_end_in_victory_or_defeat() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_test_floor(&mut ecs_);
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        let boss = dungeon::create_attack_dummy(&mut ecs_, 2.0, 1.0);
+        ecs_.npc_behavior_component.set(boss, NpcBehaviorComponent { hostile: true });
+        ecs_.boss_component.set(boss, BossComponent { final_boss: true, introduced: true });
+        ecs_.health_component.get_mut(boss).unwrap().current = 1;
+
+        ecs_.location_component.get_mut(player).unwrap().direction = Direction::Right;
+        gamelogic::perform_player_action(&mut ecs_, gamelogic::PlayerAction::Attack);
+        gamelogic::update(&mut ecs_);
+        let summary = ecs_.run_summary.clone().unwrap();
+        assert_eq!((summary.outcome, summary.kills), (RunOutcome::Victory, 1));
+        for _ in 0..crate::UPDATES_PER_SECOND {
+            gamelogic::update(&mut ecs_);
+        }
+        assert_eq!(ecs_.game_mode, GameMode::GameOver);
+
+        // a run only ends once, dying after winning changes nothing
+        ecs_.health_component.get_mut(player).unwrap().current = 0;
+        check_defeat(&mut ecs_);
+        assert_eq!(ecs_.run_summary.as_ref().unwrap().outcome, RunOutcome::Victory);
+        ecs_.run_summary = None;
+        check_defeat(&mut ecs_);
+        assert_eq!(ecs_.run_summary.as_ref().unwrap().outcome, RunOutcome::Defeat);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::ecs;
+use crate::gamelogic::{collision::CollisionMap, lod::{self, Throttled}, pathfinding};
+use crate::gamestate::{movement::MoveIntent, schedule::Activity};
+
+/// Lets NPCs follow their daily schedules. When an entry of a schedule begins, the NPC
+/// walks to its goal, on a path around obstacles if one is found. Sleeping NPCs are
+/// hidden once they arrived. Called once per turn outside of dungeons, NPCs far away
+/// from the players are throttled (see `lod`) and catch up later
+///
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+///
+pub fn update_schedules(ecs_: &mut ecs::ECS) {
+    let hour = ecs_.world_clock.hour();
+    let focus = lod::focus(ecs_);
+    let mut collision_map = CollisionMap::build(ecs_);
+    for entity in ecs_.allocator.live_indices() {
+        if ecs_.schedule_component.get(entity).is_none() || lod::ticks(ecs_, &focus, entity, Throttled::Schedule) == 0 {
+            continue;
+        }
+        let (index, entry, current) = match ecs_.schedule_component.get(entity) {
+            Some(schedule_c) => match schedule_c.schedule.active(hour).and_then(|index| schedule_c.schedule.entry(index).map(|entry| (index, entry.clone()))) {
+                Some((index, entry)) => (index, entry, schedule_c.current),
+                None => continue
+            },
+            None => continue
+        };
+        let location = match ecs_.location_component.get(entity) {
+            Some(location_c) => location_c.location,
+            None => continue
+        };
+
+        if current != Some(index) {
+            debug!("{:?} starts to {:?} at {:?}", entity, entry.activity, entry.goal);
+            let move_intent = match pathfinding::find_path(ecs_, &collision_map, entity, location, entry.goal) {
+                Some(path) if !path.is_empty() => Some(MoveIntent::Path(path, entry.speed)),
+                Some(_) => None,
+                // open ground without fields to walk on
+                None => Some(entry.move_intent())
+            };
+            if let Some(location_c) = ecs_.location_component.get_mut(entity) {
+                location_c.move_intent = move_intent;
+            }
+            collision_map.update_entity(ecs_, entity);
+            if let Some(schedule_c) = ecs_.schedule_component.get_mut(entity) {
+                schedule_c.current = Some(index);
+            }
+        }
+
+        let asleep = entry.activity == Activity::Sleep && location == entry.goal;
+        if let Some(render_c) = ecs_.render_component.get_mut(entity) {
+            render_c.visible = !asleep;
+        }
+    }
+}
+
+/// Tests if an NPC is at work, NPCs without a schedule always are
+pub fn is_at_work(ecs_: &ecs::ECS, entity: ecs::Entity) -> bool {
+    let schedule_c = match ecs_.schedule_component.get(entity) {
+        Some(schedule_c) => schedule_c,
+        None => return true
+    };
+    let entry = match schedule_c.current.and_then(|index| schedule_c.schedule.entry(index)) {
+        Some(entry) => entry,
+        None => return false
+    };
+    entry.activity == Activity::Work
+        && ecs_.location_component.get(entity).is_some_and(|location_c| location_c.location == entry.goal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamelogic;
+    use crate::gamestate::{clock::{WorldClock, TURNS_PER_HOUR}, dungeon::FloorType, LocationVec};
+
+    #[test]
+    fn test_shopkeeper_works_by_day_and_sleeps_at_night() {
+        let mut ecs_ = ecs::ECS::new();
+        ecs_.floor_type = FloorType::Town;
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 10, 8);
+        let counter = LocationVec { x: 7.0, y: 2.0 };
+        let home = LocationVec { x: 2.0, y: 5.0 };
+        let shopkeeper = dungeon::create_shopkeeper(&mut ecs_, counter, home);
+        ecs_.world_clock = WorldClock::new(5);
+
+        let walk = |ecs_: &mut ecs::ECS, hours: u64| {
+            for _ in 0..hours * TURNS_PER_HOUR {
+                gamelogic::advance_world_clock(ecs_);
+                for _ in 0..10 {
+                    gamelogic::update_entity_positions(ecs_);
+                }
+            }
+        };
+        walk(&mut ecs_, 1);
+        assert!(!ecs_.render_component.get(shopkeeper).unwrap().visible);
+
+        // at the counter once the shop opens
+        walk(&mut ecs_, 2);
+        assert_eq!(ecs_.location_component.get(shopkeeper).unwrap().location, counter);
+        assert!(is_at_work(&ecs_, shopkeeper));
+        assert!(ecs_.shop_component.get(shopkeeper).unwrap().open);
+
+        walk(&mut ecs_, 13);
+        assert_eq!(ecs_.location_component.get(shopkeeper).unwrap().location, home);
+        assert!(!ecs_.shop_component.get(shopkeeper).unwrap().open);
+        assert!(!ecs_.render_component.get(shopkeeper).unwrap().visible);
+    }
+}
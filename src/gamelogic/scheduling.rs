@@ -0,0 +1,265 @@
+use std::collections::VecDeque;
+
+use crate::ecs;
+use crate::gamelogic::{self, PlayerAction};
+use crate::gamestate::actor::{ActorState, Scheduling};
+use crate::UPDATES_PER_SECOND;
+
+// updates an actor waits after each action in real-time games, shorter for actors
+// with several actions per turn
+const ACTION_COOLDOWN_UPDATES: u64 = UPDATES_PER_SECOND / 3;
+// updates a turn lasts in real-time games, for everything counted in turns like hunger
+const REALTIME_TURN_UPDATES: u64 = UPDATES_PER_SECOND;
+
+// actions the player can queue while it can not act
+const ACTION_QUEUE_SIZE: usize = 2;
+
+/// Actions of the player pressed while it could not act yet, e.g. during a step. They are
+/// performed one after another as soon as the player can act again
+#[derive(Debug, Default)]
+pub struct ActionQueue {
+    actions: VecDeque<PlayerAction>
+}
+
+impl ActionQueue {
+    /// Queues an action, unless the queue is full or the action was just queued, so
+    /// repeated presses of a held key do not pile up steps
+    ///
+    /// ### Returns
+    /// True if the action was queued
+    pub fn push(&mut self, action: PlayerAction) -> bool {
+        if self.actions.len() >= ACTION_QUEUE_SIZE || self.actions.back() == Some(&action) {
+            return false;
+        }
+        self.actions.push_back(action);
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<PlayerAction> {
+        self.actions.pop_front()
+    }
+
+    pub fn clear(&mut self) {
+        self.actions.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+/// Tests if an actor can start an action now. In turn-based games it must be its turn,
+/// in real-time games it must not be acting or waiting for its cooldown. Input handling
+/// only asks this, so it works the same for either way of scheduling
+pub fn can_act(ecs_: &ecs::ECS, entity: ecs::Entity) -> bool {
+    match ecs_.actor_component.get(entity) {
+        Some(actor_c) => match ecs_.scheduling {
+            Scheduling::TurnBased => actor_c.state == ActorState::WaitingForTurn,
+            Scheduling::RealTime => actor_c.state == ActorState::WaitingForTurn && actor_c.cooldown == 0
+        },
+        None => false
+    }
+}
+
+/// Advances the turns by one update as the game's `Scheduling` says. Turn-based games
+/// end the turn once all actors are done (see `gamelogic::check_and_perform_end_turn`),
+/// real-time games let actors act again after a cooldown and pass a turn every
+/// `REALTIME_TURN_UPDATES` updates
+///
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+///
+pub fn update_turns(ecs_: &mut ecs::ECS) {
+    match ecs_.scheduling {
+        Scheduling::TurnBased => {
+            gamelogic::check_and_perform_end_turn(ecs_);
+        },
+        Scheduling::RealTime => {
+            update_cooldowns(ecs_);
+            ecs_.realtime_updates += 1;
+            if ecs_.realtime_updates >= REALTIME_TURN_UPDATES {
+                ecs_.realtime_updates = 0;
+                gamelogic::pass_turn(ecs_);
+            }
+        }
+    }
+    perform_queued_action(ecs_);
+}
+
+/// Lets the player perform the next queued action if it can act
+fn perform_queued_action(ecs_: &mut ecs::ECS) {
+    let player = match ecs_.get_player_entity() {
+        Some(player) if can_act(ecs_, player) => player,
+        _ => return
+    };
+    // resting is interrupted by any input, there is nothing left to do then
+    if gamelogic::is_resting(ecs_, player) {
+        ecs_.action_queue.clear();
+        return;
+    }
+    if let Some(action) = ecs_.action_queue.pop() {
+        gamelogic::perform_action(ecs_, player, action);
+    }
+}
+
+/// Predicts which actors act next, e.g. to show the turn order. Actors with several
+/// actions per turn come up more often, actors done for the turn or cooling down come
+/// up later. Only actors in view of the player are included, hidden enemies stay hidden
+///
+/// ### Arguments
+/// * `ecs_`  - The entity component system to perform on
+/// * `count` - Number of actions to predict
+///
+/// ### Returns
+/// The actor of each of the next actions, actors appear once per action
+pub fn upcoming_turns(ecs_: &ecs::ECS, count: usize) -> Vec<ecs::Entity> {
+    let entities = ecs_.allocator.live_indices();
+    let viewer = entities.iter()
+                         .find(|entity| ecs_.player_component.get(**entity).is_some())
+                         .and_then(|player| ecs_.location_component.get(*player))
+                         .map(|location_c| location_c.location);
+
+    // when each actor acts next and how long it takes until it acts again, in turns
+    // for turn-based games and in updates for real-time games
+    let mut timeline: Vec<(ecs::Entity, f64, f64)> = Vec::new();
+    for entity in entities {
+        let actor_c = match ecs_.actor_component.get(entity) {
+            Some(actor_c) if !gamelogic::death::is_dying(ecs_, entity) => actor_c,
+            _ => continue
+        };
+        let visible = match (viewer, ecs_.location_component.get(entity)) {
+            (Some(viewer), Some(location_c)) => gamelogic::in_view(ecs_, &viewer, &location_c.location),
+            _ => false
+        };
+        if !visible {
+            continue;
+        }
+        let actions = actor_c.max_actions.max(1);
+        let (next, period) = match ecs_.scheduling {
+            Scheduling::TurnBased => {
+                let period = 1.0 / actions as f64;
+                let finished = actor_c.state == ActorState::DoneActing && actor_c.performed_actions >= actions;
+                (if finished { 1.0 } else { actor_c.performed_actions.min(actions) as f64 * period }, period)
+            },
+            Scheduling::RealTime => {
+                let period = (ACTION_COOLDOWN_UPDATES / actions).max(1) as f64;
+                (if actor_c.state == ActorState::WaitingForTurn { actor_c.cooldown as f64 } else { period }, period)
+            }
+        };
+        timeline.push((entity, next, period));
+    }
+
+    let mut upcoming = Vec::new();
+    while upcoming.len() < count && !timeline.is_empty() {
+        // ties go to the actor found first
+        let mut first = 0;
+        for (i, (_, next, _)) in timeline.iter().enumerate() {
+            if *next < timeline[first].1 {
+                first = i;
+            }
+        }
+        let (entity, next, period) = &mut timeline[first];
+        upcoming.push(*entity);
+        *next += *period;
+    }
+    upcoming
+}
+
+/// Starts the cooldown of actors that finished an action and counts down the others
+fn update_cooldowns(ecs_: &mut ecs::ECS) {
+    for entity in ecs_.allocator.live_indices() {
+        if let Some(actor_c) = ecs_.actor_component.get_mut(entity) {
+            match actor_c.state {
+                ActorState::DoneActing => {
+                    actor_c.state = ActorState::WaitingForTurn;
+                    actor_c.performed_actions += 1;
+                    actor_c.cooldown = ACTION_COOLDOWN_UPDATES / actor_c.max_actions.max(1);
+                },
+                ActorState::WaitingForTurn => actor_c.cooldown = actor_c.cooldown.saturating_sub(1),
+                ActorState::Acting => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamelogic::{perform_action, PlayerAction};
+    use crate::gamestate::{components::ActorComponent, movement::Direction, LocationVec};
+
+    #[test]
+    fn test_real_time_actors_wait_for_their_cooldown() {
+        let mut ecs_ = ecs::ECS::new();
+        ecs_.scheduling = Scheduling::RealTime;
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 6, 5);
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+
+        perform_action(&mut ecs_, player, PlayerAction::Wait);
+        assert!(!can_act(&ecs_, player));
+        for _ in 0..ACTION_COOLDOWN_UPDATES {
+            update_turns(&mut ecs_);
+        }
+        assert!(!can_act(&ecs_, player));
+        update_turns(&mut ecs_);
+        assert!(can_act(&ecs_, player));
+
+        // moving is acting until the player arrives
+        perform_action(&mut ecs_, player, PlayerAction::Move(Direction::Right));
+        update_turns(&mut ecs_);
+        assert!(!can_act(&ecs_, player));
+
+        // turns pass with time, without waiting for anybody
+        let turns = ecs_.world_clock.turns;
+        for _ in 0..REALTIME_TURN_UPDATES {
+            update_turns(&mut ecs_);
+        }
+        assert_eq!(ecs_.world_clock.turns, turns + 1);
+    }
+
+    #[test]
+    fn test_queued_actions_are_performed_once_the_player_can_act() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 6, 5);
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+
+        perform_action(&mut ecs_, player, PlayerAction::Move(Direction::Right));
+        assert!(ecs_.action_queue.push(PlayerAction::Move(Direction::Down)));
+        // a held key repeats its press
+        assert!(!ecs_.action_queue.push(PlayerAction::Move(Direction::Down)));
+        assert!(ecs_.action_queue.push(PlayerAction::Wait));
+        assert!(!ecs_.action_queue.push(PlayerAction::Attack));
+
+        // the player is still walking
+        update_turns(&mut ecs_);
+        assert!(!ecs_.action_queue.is_empty());
+        ecs_.actor_component.get_mut(player).unwrap().state = ActorState::DoneActing;
+        update_turns(&mut ecs_);
+        assert_eq!(ecs_.action_queue.pop(), Some(PlayerAction::Wait));
+        assert_eq!(ecs_.location_component.get(player).unwrap().move_intent.as_ref().unwrap().target_goal(&LocationVec { x: 1.0, y: 1.0 }),
+                   LocationVec { x: 1.0, y: 2.0 });
+    }
+
+    #[test]
+    fn test_fast_actors_come_up_more_often() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 6, 5);
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        let fast = dungeon::create_attack_dummy(&mut ecs_, 2.0, 1.0);
+        ecs_.actor_component.set(fast, ActorComponent {
+            state: ActorState::WaitingForTurn,
+            turn: 0,
+            max_actions: 2,
+            performed_actions: 0,
+            cooldown: 0
+        });
+
+        assert_eq!(upcoming_turns(&ecs_, 5), vec![player, fast, fast, player, fast]);
+        // the player has acted this turn
+        let actor_c = ecs_.actor_component.get_mut(player).unwrap();
+        actor_c.state = ActorState::DoneActing;
+        actor_c.performed_actions = 1;
+        assert_eq!(upcoming_turns(&ecs_, 3), vec![fast, fast, player]);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::ecs;
+use crate::event::bus::GameEvent;
+use crate::gamestate::{achievement::Achievement, class::Class, run::{RunOutcome, RunStats}};
+
+/// Applies a change to the statistics of the run and to the lifetime totals of the profile
+fn count<F: Fn(&mut RunStats)>(ecs_: &mut ecs::ECS, change: F) {
+    change(&mut ecs_.run_stats);
+    change(&mut ecs_.profile.lifetime);
+}
+
+fn is_player(ecs_: &ecs::ECS, entity: Option<ecs::Entity>) -> bool {
+    entity.is_some_and(|entity| ecs_.player_component.get(entity).is_some())
+}
+
+/// Counts what a game event means for the statistics, then unlocks the achievements
+/// they earn
+///
+/// ### Arguments
+/// * `ecs_`  - The entity component system to perform on
+/// * `event` - The event that happened
+///
+pub fn record_event(ecs_: &mut ecs::ECS, event: &GameEvent) {
+    match *event {
+        GameEvent::Damaged { target, source, amount } => {
+            let amount = amount.max(0) as u64;
+            if is_player(ecs_, source) {
+                count(ecs_, |stats| stats.damage_dealt += amount);
+            }
+            if is_player(ecs_, Some(target)) {
+                count(ecs_, |stats| stats.damage_taken += amount);
+            }
+        },
+        GameEvent::Died { entity, killer } => {
+            let hostile = ecs_.npc_behavior_component.get(entity).is_some_and(|npc_c| npc_c.hostile);
+            if hostile && is_player(ecs_, killer) {
+                let kind = ecs_.name_component.get(entity).map_or_else(|| "unknown".to_string(), |name_c| name_c.name.clone());
+                count(ecs_, |stats| {
+                    stats.kills += 1;
+                    *stats.kills_by_type.entry(kind.clone()).or_insert(0) += 1;
+                });
+            }
+        },
+        GameEvent::ItemUsed { entity } if is_player(ecs_, Some(entity)) => count(ecs_, |stats| stats.items_used += 1),
+        // floors entered from the overworld are not cleared yet
+        GameEvent::Descended { entity } if is_player(ecs_, Some(entity)) => count(ecs_, |stats| stats.floors_cleared += 1),
+        GameEvent::FloorChanged => {
+            let deepest = ecs_.allocator.live_indices()
+                                        .into_iter()
+                                        .filter_map(|entity| ecs_.player_component.get(entity).map(|player_c| player_c.stage_level))
+                                        .max()
+                                        .unwrap_or(0);
+            count(ecs_, |stats| stats.deepest_floor = stats.deepest_floor.max(deepest));
+        },
+        _ => return
+    }
+    unlock_achievements(ecs_);
+}
+
+/// Counts a run that ended for the profile. Winning a run unlocks new-game-plus and the
+/// mage class, the equipment kept from the last won run is chosen anew
+pub fn record_run_end(ecs_: &mut ecs::ECS, outcome: RunOutcome) {
+    ecs_.profile.runs += 1;
+    if outcome == RunOutcome::Victory {
+        ecs_.profile.victories += 1;
+        ecs_.profile.completed_run = true;
+        ecs_.profile.kept_equipment.clear();
+        if ecs_.profile.unlocked_classes.insert(Class::Mage) {
+            let text = ecs_.locale.format("class.unlocked", &[("name", &ecs_.locale.text(&Class::Mage.locale_key()))]);
+            info!("{}", text);
+            ecs_.toasts.push(text);
+        }
+    }
+    unlock_achievements(ecs_);
+}
+
+/// Unlocks all achievements earned but not unlocked yet, each is announced by a toast
+///
+/// ### Returns
+/// The newly unlocked achievements
+pub fn unlock_achievements(ecs_: &mut ecs::ECS) -> Vec<Achievement> {
+    let earned: Vec<Achievement> = Achievement::ALL.iter()
+        .copied()
+        .filter(|achievement| !ecs_.profile.achievements.contains(achievement))
+        .filter(|achievement| achievement.is_earned(&ecs_.run_stats, &ecs_.profile.lifetime, ecs_.profile.victories))
+        .collect();
+    for achievement in earned.iter() {
+        ecs_.profile.achievements.insert(*achievement);
+        let text = ecs_.locale.format("achievement.unlocked", &[("name", &ecs_.locale.text(&achievement.locale_key()))]);
+        info!("{}", text);
+        ecs_.toasts.push(text);
+    }
+    earned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamelogic;
+    use crate::gamestate::components::NpcBehaviorComponent;
+    use crate::gamestate::movement::Direction;
+
+    #[test]
+    fn test_kills_count_for_run_and_profile() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_test_floor(&mut ecs_);
+        let player = dungeon::create_test_player(&mut ecs_, 2.0, 4.0);
+        // the dummy of the test floor stands right of the player
+        let dummy = ecs_.get_entities_by_location(crate::gamestate::LocationVec { x: 3.0, y: 4.0 })
+                        .into_iter()
+                        .find(|entity| ecs_.health_component.get(*entity).is_some())
+                        .unwrap();
+        ecs_.npc_behavior_component.set(dummy, NpcBehaviorComponent { hostile: true });
+        ecs_.health_component.get_mut(dummy).unwrap().current = 1;
+        ecs_.profile.lifetime.kills = 41;
+
+        ecs_.location_component.get_mut(player).unwrap().direction = Direction::Right;
+        gamelogic::perform_player_action(&mut ecs_, gamelogic::PlayerAction::Attack);
+        gamelogic::update(&mut ecs_);
+
+        assert_eq!(ecs_.run_stats.kills, 1);
+        assert_eq!(ecs_.run_stats.kills_by_type.get("creature.dummy"), Some(&1));
+        assert_eq!(ecs_.run_stats.damage_dealt, ecs_.profile.lifetime.damage_dealt);
+        assert!(ecs_.run_stats.damage_dealt >= 1);
+        assert_eq!(ecs_.profile.lifetime.kills, 42);
+        assert!(ecs_.profile.achievements.contains(&Achievement::FirstBlood));
+        assert!(!ecs_.toasts.is_empty());
+        // achievements are only unlocked once
+        assert!(unlock_achievements(&mut ecs_).is_empty());
+    }
+}
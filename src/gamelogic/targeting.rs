@@ -0,0 +1,287 @@
+use std::collections::HashSet;
+
+use crate::ecs;
+use crate::event::HitboxType;
+use crate::gamelogic::{self, death, effects, PlayerAction};
+use crate::gamestate::components::StatusComponent;
+use crate::gamestate::{actor, dungeon::DungeonElement, duration::Duration, movement::Direction, spell::Ability, status, LocationVec};
+
+// game updates a target hit by a firebolt keeps burning
+const FIREBOLT_BURN_UPDATES: i32 = 3 * crate::UPDATES_PER_SECOND as i32;
+
+type Field = (i64, i64);
+
+fn to_field(location: LocationVec) -> Field {
+    (location.x.round() as i64, location.y.round() as i64)
+}
+
+/// An ability the player is aiming, from pressing its hotkey until the target is confirmed
+/// or targeting is cancelled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Targeting {
+    pub ability: Ability,
+    // the field aimed at, moved with the move keys or the mouse
+    pub cursor: LocationVec
+}
+
+/// The fields blocking the sight, walls and everything blocking like them, e.g. closed
+/// doors. Built once so many lines can be checked
+pub struct SightMap {
+    walls: HashSet<Field>
+}
+
+impl SightMap {
+    pub fn build(ecs_: &ecs::ECS) -> SightMap {
+        let walls = ecs_.allocator.live_indices()
+                        .into_iter()
+                        .filter_map(|entity| ecs_.location_component.get(entity).map(|location_c| (entity, location_c)))
+                        .filter(|(entity, location_c)| {
+                            ecs_.dungeon_component.get(*entity).is_some_and(|dungeon_c| dungeon_c.type_ == DungeonElement::Wall)
+                            || location_c.hitbox.as_ref().is_some_and(|hitbox| hitbox.type_ == HitboxType::Wall)
+                        })
+                        .map(|(_, location_c)| to_field(location_c.location))
+                        .collect();
+        SightMap { walls }
+    }
+
+    /// Tests if nothing blocks the line between the middles of two fields. The fields
+    /// themselves do not block it, so walls can be seen
+    pub fn line_of_sight(&self, from: LocationVec, to: LocationVec) -> bool {
+        let (from, to) = (to_field(from), to_field(to));
+        let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs());
+        (1..steps).all(|step| {
+            let t = step as f64 / steps as f64;
+            let field = LocationVec {
+                x: from.0 as f64 + (to.0 - from.0) as f64 * t,
+                y: from.1 as f64 + (to.1 - from.1) as f64 * t
+            };
+            !self.walls.contains(&to_field(field))
+        })
+    }
+}
+
+/// Tests if a caster can use an ability on a field: it is within the ability's range and
+/// in sight
+///
+/// ### Arguments
+/// * `ecs_`       - The entity component system to perform on
+/// * `sight_map`  - The sight of the current floor, see `SightMap::build`
+/// * `caster`     - The entity using the ability
+/// * `ability`    - The ability used
+/// * `location`   - The field aimed at
+///
+pub fn in_reach(ecs_: &ecs::ECS, sight_map: &SightMap, caster: ecs::Entity, ability: Ability, location: LocationVec) -> bool {
+    match ecs_.location_component.get(caster) {
+        Some(location_c) => ecs_.diagonal_rule.distance(&location_c.location, &location) <= ability.range()
+                            && sight_map.line_of_sight(location_c.location, location),
+        None => false
+    }
+}
+
+/// Finds the creature an ability used on a field hits, a living one hostile to the caster
+pub fn target_at(ecs_: &ecs::ECS, caster: ecs::Entity, location: LocationVec) -> Option<ecs::Entity> {
+    let field = LocationVec { x: location.x.round(), y: location.y.round() };
+    ecs_.get_entities_by_location(field)
+        .into_iter()
+        .find(|target| gamelogic::is_hostile(ecs_, caster, *target)
+                       && ecs_.health_component.get(*target).is_some_and(|health_c| health_c.current > 0)
+                       && !death::is_dying(ecs_, *target))
+}
+
+/// Finds all creatures a caster can use an ability on, e.g. to highlight them
+///
+/// ### Returns
+/// The targets, nearest first
+pub fn valid_targets(ecs_: &ecs::ECS, caster: ecs::Entity, ability: Ability) -> Vec<ecs::Entity> {
+    let from = match ecs_.location_component.get(caster) {
+        Some(location_c) => location_c.location,
+        None => return Vec::new()
+    };
+    let sight_map = SightMap::build(ecs_);
+    let mut targets: Vec<(f64, ecs::Entity)> = gamelogic::visible_hostiles(ecs_, caster)
+        .into_iter()
+        .filter_map(|target| ecs_.location_component.get(target).map(|location_c| (target, location_c.location)))
+        .filter(|(target, location)| !death::is_dying(ecs_, *target) && in_reach(ecs_, &sight_map, caster, ability, *location))
+        .map(|(target, location)| (ecs_.diagonal_rule.distance(&from, &location), target))
+        .collect();
+    targets.sort_by(|a, b| a.0.total_cmp(&b.0));
+    targets.into_iter().map(|(_, target)| target).collect()
+}
+
+/// Turns until an entity can use an ability again, 0 if it can use it now
+pub fn cooldown(ecs_: &ecs::ECS, caster: ecs::Entity, ability: Ability) -> u32 {
+    ecs_.caster_component.get(caster)
+        .and_then(|caster_c| caster_c.cooldowns.iter().find(|(other, _)| *other == ability))
+        .map_or(0, |(_, turns)| *turns)
+}
+
+/// Tests if an entity has the mana an ability needs and it is not cooling down
+pub fn can_use(ecs_: &ecs::ECS, caster: ecs::Entity, ability: Ability) -> bool {
+    if ability.mana_cost() == 0 && ability.cooldown_turns() == 0 {
+        return true;
+    }
+    ecs_.caster_component.get(caster).is_some_and(|caster_c| caster_c.current_mana >= ability.mana_cost())
+        && cooldown(ecs_, caster, ability) == 0
+}
+
+/// Counts down the cooldowns of all abilities by a turn
+///
+/// ### Arguments
+/// * `ecs_` - The entity component system to perform on
+///
+pub fn advance_cooldowns(ecs_: &mut ecs::ECS) {
+    for entity in ecs_.allocator.live_indices() {
+        if let Some(caster_c) = ecs_.caster_component.get_mut(entity) {
+            for (_, turns) in caster_c.cooldowns.iter_mut() {
+                *turns = turns.saturating_sub(1);
+            }
+            caster_c.cooldowns.retain(|(_, turns)| *turns > 0);
+        }
+    }
+}
+
+/// Starts aiming an ability of the player. The cursor starts on the nearest valid target,
+/// on the player itself if there is none
+///
+/// ### Returns
+/// True if targeting started, false if the player can not use the ability
+pub fn start_targeting(ecs_: &mut ecs::ECS, ability: Ability) -> bool {
+    let player = match ecs_.get_player_entity() {
+        Some(player) => player,
+        None => return false
+    };
+    if !can_use(ecs_, player, ability) {
+        debug!("Not enough mana for {:?} or it is cooling down", ability);
+        return false;
+    }
+    let cursor = valid_targets(ecs_, player, ability)
+        .first()
+        .or(Some(&player))
+        .and_then(|entity| ecs_.location_component.get(*entity))
+        .map(|location_c| LocationVec { x: location_c.location.x.round(), y: location_c.location.y.round() });
+    match cursor {
+        Some(cursor) => {
+            ecs_.targeting = Some(Targeting { ability, cursor });
+            true
+        },
+        None => false
+    }
+}
+
+/// Moves the targeting cursor by one field
+pub fn move_cursor(ecs_: &mut ecs::ECS, dir: Direction) {
+    if let Some(targeting) = &mut ecs_.targeting {
+        targeting.cursor += dir.into();
+    }
+}
+
+/// Puts the targeting cursor on the field of a location, e.g. the one under the mouse
+pub fn set_cursor(ecs_: &mut ecs::ECS, location: LocationVec) {
+    if let Some(targeting) = &mut ecs_.targeting {
+        targeting.cursor = LocationVec { x: location.x.round(), y: location.y.round() };
+    }
+}
+
+pub fn cancel_targeting(ecs_: &mut ecs::ECS) {
+    ecs_.targeting = None;
+}
+
+/// Ends targeting on the field under the cursor, if the player can use the ability there
+///
+/// ### Returns
+/// The action using the ability, None if the cursor is on no valid target and targeting goes on
+pub fn confirm_targeting(ecs_: &mut ecs::ECS) -> Option<PlayerAction> {
+    let targeting = ecs_.targeting?;
+    let player = ecs_.get_player_entity()?;
+    let sight_map = SightMap::build(ecs_);
+    if target_at(ecs_, player, targeting.cursor).is_none() || !in_reach(ecs_, &sight_map, player, targeting.ability, targeting.cursor) {
+        debug!("No valid target for {:?} at {:?}", targeting.ability, targeting.cursor);
+        return None;
+    }
+    ecs_.targeting = None;
+    Some(PlayerAction::UseAbility(targeting.ability, targeting.cursor))
+}
+
+/// Uses an ability on a field. The range and sight are checked again, as the target may
+/// have moved since it was aimed at
+///
+/// ### Arguments
+/// * `ecs_`     - The entity component system to perform on
+/// * `caster`   - The entity using the ability
+/// * `ability`  - The ability used
+/// * `location` - The field aimed at
+///
+/// ### Returns
+/// True if the ability was used, else false
+pub fn use_ability(ecs_: &mut ecs::ECS, caster: ecs::Entity, ability: Ability, location: LocationVec) -> bool {
+    let target = match target_at(ecs_, caster, location) {
+        Some(target) if in_reach(ecs_, &SightMap::build(ecs_), caster, ability, location) => target,
+        _ => return false
+    };
+    if !can_use(ecs_, caster, ability) {
+        return false;
+    }
+    if let Some(caster_c) = ecs_.caster_component.get_mut(caster) {
+        caster_c.current_mana -= ability.mana_cost();
+        if ability.cooldown_turns() > 0 {
+            caster_c.cooldowns.push((ability, ability.cooldown_turns()));
+        }
+    }
+
+    gamelogic::attack(ecs_, caster, target);
+    if ability == Ability::Firebolt {
+        effects::on_spell_impact(ecs_, location);
+        if ecs_.status_component.get(target).is_none() {
+            ecs_.status_component.set(target, StatusComponent { status: Vec::new() });
+        }
+        if let Some(status_c) = ecs_.status_component.get_mut(target) {
+            status_c.status.push(status::Status { type_: status::StatusType::Burning, duration: Duration::Updates(FIREBOLT_BURN_UPDATES) });
+        }
+    }
+    if let Some(actor_c) = ecs_.actor_component.get_mut(caster) {
+        actor_c.state = actor::ActorState::DoneActing;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamestate::components::{CasterComponent, NpcBehaviorComponent};
+
+    #[test]
+    fn test_targets_need_range_and_sight() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_test_floor(&mut ecs_);
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 5.0);
+        ecs_.caster_component.set(player, CasterComponent { current_mana: 5, maximum_mana: 5, spells: Vec::new(), cooldowns: Vec::new() });
+        // in range, in range behind the walls between the rooms, out of range
+        let near = dungeon::create_attack_dummy(&mut ecs_, 4.0, 3.0);
+        let hidden = dungeon::create_attack_dummy(&mut ecs_, 1.0, 9.0);
+        let far = dungeon::create_attack_dummy(&mut ecs_, 7.0, 5.0);
+        for dummy in [near, hidden, far].iter() {
+            ecs_.npc_behavior_component.set(*dummy, NpcBehaviorComponent { hostile: true });
+        }
+        // the dummy of the test floor is no enemy
+        assert_eq!(valid_targets(&ecs_, player, Ability::Firebolt), vec![near]);
+
+        assert!(start_targeting(&mut ecs_, Ability::Firebolt));
+        assert_eq!(ecs_.targeting.unwrap().cursor, LocationVec { x: 4.0, y: 3.0 });
+        move_cursor(&mut ecs_, Direction::Left);
+        assert_eq!(confirm_targeting(&mut ecs_), None);
+        move_cursor(&mut ecs_, Direction::Right);
+        let action = confirm_targeting(&mut ecs_);
+        assert_eq!(action, Some(PlayerAction::UseAbility(Ability::Firebolt, LocationVec { x: 4.0, y: 3.0 })));
+        assert!(ecs_.targeting.is_none());
+
+        gamelogic::perform_action(&mut ecs_, player, action.unwrap());
+        let health_c = ecs_.health_component.get(near).unwrap();
+        assert!(health_c.current < health_c.maximum);
+        assert_eq!(ecs_.caster_component.get(player).unwrap().current_mana, 0);
+        // without mana there is no second firebolt
+        assert!(!start_targeting(&mut ecs_, Ability::Firebolt));
+        assert!(ecs_.status_component.get(near).unwrap().status.iter().any(|status| matches!(status.type_, status::StatusType::Burning)));
+        assert_eq!(ecs_.actor_component.get(player).unwrap().state, actor::ActorState::DoneActing);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::ecs;
+use crate::gamelogic::death;
+use crate::gamestate::{actor, item::Equipment};
+
+/// Invariants of the ECS an entity can violate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind {
+    // a component without the one it needs, see `ecs::missing_pairing`
+    MissingPairing,
+    DyingButMoving,
+    DyingButActing,
+    ExcessHealth,
+    MisplacedEquipment
+}
+
+/// An entity in a state the game logic never produces on purpose
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub entity: ecs::Entity,
+    pub kind: ViolationKind,
+    pub description: String
+}
+
+/// Violations of the invariants of the ECS already logged. Each kind is only reported
+/// once per entity and floor, also if the details change, e.g. the health of an entity
+#[derive(Debug, Default)]
+pub struct Violations {
+    // only a few at a time, so a list is enough
+    reported: Vec<(ecs::Entity, ViolationKind)>
+}
+
+impl Violations {
+    /// Forgets the reported violations, e.g. once the entities of a floor are gone
+    pub fn clear(&mut self) {
+        self.reported.clear();
+    }
+}
+
+/// Finds entities in states the game logic never produces on purpose, e.g. a
+/// rendered entity without a location. Such states point to misuse of the ECS
+///
+/// ### Arguments
+/// * `ecs_` - The entity component system to scan
+///
+/// ### Returns
+/// Each violation found
+pub fn find_violations(ecs_: &ecs::ECS) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for entity in ecs_.allocator.live_indices() {
+        let mut violation = |kind: ViolationKind, description: String| {
+            violations.push(Violation { entity, kind, description });
+        };
+
+        if let Some((component, needed)) = ecs::missing_pairing(ecs_, entity) {
+            violation(ViolationKind::MissingPairing, format!("{:?} has a {} but no {}", entity, component, needed));
+        }
+
+        if death::is_dying(ecs_, entity) {
+            if ecs_.location_component.get(entity).is_some_and(|location_c| location_c.move_intent.is_some()) {
+                violation(ViolationKind::DyingButMoving, format!("{:?} is dying but still moving", entity));
+            }
+            if ecs_.actor_component.get(entity).is_some_and(|actor_c| actor_c.state == actor::ActorState::Acting) {
+                violation(ViolationKind::DyingButActing, format!("{:?} is dying but still acting", entity));
+            }
+        }
+
+        if let Some(health_c) = ecs_.health_component.get(entity) {
+            if health_c.current > health_c.maximum {
+                violation(ViolationKind::ExcessHealth, format!("{:?} has {} of {} health", entity, health_c.current, health_c.maximum));
+            }
+        }
+
+        if let Some(humanoid_c) = ecs_.humanoid_component.get(entity) {
+            let slots = [
+                ("main hand", &humanoid_c.main_hand),
+                ("off hand", &humanoid_c.off_hand),
+                ("head", &humanoid_c.head),
+                ("body", &humanoid_c.body),
+                ("hand", &humanoid_c.hand),
+                ("leg", &humanoid_c.leg),
+                ("feet", &humanoid_c.feet),
+            ];
+            for (slot, equipment) in slots.iter() {
+                if let Some(equipment) = equipment {
+                    if !fits(slot, equipment) {
+                        violation(ViolationKind::MisplacedEquipment, format!("{:?} wears {:?} on the {} slot", entity, equipment, slot));
+                    }
+                }
+            }
+        }
+    }
+    violations
+}
+
+/// Tests if equipment can be worn on a slot of a humanoid
+fn fits(slot: &str, equipment: &Equipment) -> bool {
+    match equipment {
+        Equipment::OneHandWeapon => slot == "main hand" || slot == "off hand",
+        Equipment::TwoHandWeapon => slot == "main hand",
+        Equipment::Helm => slot == "head",
+        Equipment::ChestArmor => slot == "body",
+        Equipment::Gloves => slot == "hand",
+        Equipment::Pants => slot == "leg",
+        Equipment::Boots => slot == "feet",
+    }
+}
+
+/// Logs violations of the invariants of the ECS not reported before. Runs each game
+/// update in debug builds only, as scanning all entities is too slow for releases
+///
+/// ### Arguments
+/// * `ecs_` - The entity component system to validate
+///
+pub fn validate(ecs_: &mut ecs::ECS) {
+    for violation in find_violations(ecs_) {
+        let key = (violation.entity, violation.kind);
+        if !ecs_.violations.reported.contains(&key) {
+            ecs_.violations.reported.push(key);
+            error!("Invariant violated: {}", violation.description);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamestate::components::HumanoidComponent;
+
+    #[test]
+    fn test_inconsistent_states_are_found() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_test_floor(&mut ecs_);
+        let player = dungeon::create_test_player(&mut ecs_, 2.0, 4.0);
+        assert!(find_violations(&ecs_).is_empty());
+
+        ecs_.health_component.get_mut(player).unwrap().current = 150;
+        ecs_.humanoid_component.set(player, HumanoidComponent {
+            off_hand: None,
+            main_hand: Some(Equipment::OneHandWeapon),
+            head: Some(Equipment::Boots),
+            body: None,
+            hand: None,
+            leg: None,
+            feet: None,
+        });
+        let violations = find_violations(&ecs_);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[1].kind, ViolationKind::MisplacedEquipment);
+        assert!(violations[1].description.contains("Boots"));
+
+        // a changed description is still the same violation
+        validate(&mut ecs_);
+        ecs_.health_component.get_mut(player).unwrap().current = 160;
+        validate(&mut ecs_);
+        assert_eq!(ecs_.violations.reported.len(), 2);
+        ecs_.violations.clear();
+        validate(&mut ecs_);
+        assert_eq!(ecs_.violations.reported.len(), 2);
+    }
+}
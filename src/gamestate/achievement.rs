@@ -0,0 +1,56 @@
+extern crate serde;
+
+use serde::{Serialize, Deserialize};
+
+use crate::gamestate::run::RunStats;
+
+/// Milestones of the player, unlocked once for the profile and never lost
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Achievement {
+    // the first kill
+    FirstBlood,
+    // 100 kills over all runs
+    Slayer,
+    // 10 items used over all runs
+    Alchemist,
+    // floor 5 reached in a run
+    Delver,
+    // a floor cleared without taking any damage in the run
+    Untouched,
+    // the final boss defeated
+    Champion,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 6] = [
+        Achievement::FirstBlood,
+        Achievement::Slayer,
+        Achievement::Alchemist,
+        Achievement::Delver,
+        Achievement::Untouched,
+        Achievement::Champion,
+    ];
+
+    /// Key of the name in the locale
+    pub fn locale_key(&self) -> String {
+        format!("achievement.{:?}", self)
+    }
+
+    /// Tests if the statistics earn the achievement
+    ///
+    /// ### Arguments
+    /// * `run`       - The statistics of the current run
+    /// * `lifetime`  - The statistics of all runs, including the current one
+    /// * `victories` - Runs won, including the current one
+    ///
+    pub fn is_earned(&self, run: &RunStats, lifetime: &RunStats, victories: u32) -> bool {
+        match self {
+            Achievement::FirstBlood => lifetime.kills >= 1,
+            Achievement::Slayer => lifetime.kills >= 100,
+            Achievement::Alchemist => lifetime.items_used >= 10,
+            Achievement::Delver => run.deepest_floor >= 5,
+            Achievement::Untouched => run.floors_cleared >= 1 && run.damage_taken == 0,
+            Achievement::Champion => victories >= 1,
+        }
+    }
+}
@@ -8,4 +8,31 @@ pub enum ActorState {
     WaitingForTurn,
     Acting,
     DoneActing
-}
\ No newline at end of file
+}
+
+/// How the actors of a game take their actions, chosen per game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Scheduling {
+    // all actors act once, then the next turn starts
+    #[default]
+    TurnBased,
+    // actors act whenever their cooldown after the last action is over, turns pass with time
+    RealTime,
+}
+
+impl Scheduling {
+    pub fn next(&self) -> Scheduling {
+        match self {
+            Scheduling::TurnBased => Scheduling::RealTime,
+            Scheduling::RealTime => Scheduling::TurnBased,
+        }
+    }
+}
+
+/// Progress of the player resting until healed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestProgress {
+    pub start_health: i32,
+    pub start_mana: i32,
+    pub turns: u64
+}
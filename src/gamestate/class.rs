@@ -2,7 +2,33 @@ extern crate serde;
 
 use serde::{Serialize, Deserialize};
 
-#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+use crate::gamestate::spell::Ability;
+
+#[derive(Eq, PartialEq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Class {
-    Warrior
-}
\ No newline at end of file
+    Warrior,
+    // unlocked in the profile by winning a run
+    Mage,
+}
+
+impl Class {
+    pub const ALL: [Class; 2] = [Class::Warrior, Class::Mage];
+
+    /// Abilities every member of the class can use
+    pub fn abilities(&self) -> &'static [Ability] {
+        match self {
+            Class::Warrior => &[Ability::Shoot],
+            Class::Mage => &[Ability::Firebolt],
+        }
+    }
+
+    /// Key of the class' name in the locale
+    pub fn locale_key(&self) -> String {
+        format!("class.{:?}", self)
+    }
+
+    /// Reads a class from its name, ignoring case, e.g. `mage`
+    pub fn parse(name: &str) -> Option<Class> {
+        Class::ALL.iter().copied().find(|class| format!("{:?}", class).eq_ignore_ascii_case(name))
+    }
+}
@@ -0,0 +1,124 @@
+extern crate serde;
+
+use serde::{Serialize, Deserialize};
+
+// number of turns one ingame hour lasts
+pub const TURNS_PER_HOUR: u64 = 25;
+pub const HOURS_PER_DAY: u64 = 24;
+// hour of the day a new game starts at
+pub const START_HOUR: u64 = 8;
+// opacity of the ambient tint at midnight
+const MAX_NIGHT_ALPHA: f32 = 0.6;
+
+/// Coarse parts of a day, used for schedules and spawn tables
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum DayTime {
+    Dawn,   // 5:00 - 8:00
+    Day,    // 8:00 - 18:00
+    Dusk,   // 18:00 - 21:00
+    Night   // 21:00 - 5:00
+}
+
+/// The world clock, advanced once per game turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldClock {
+    pub turns: u64
+}
+
+impl WorldClock {
+    pub fn new(start_hour: u64) -> WorldClock {
+        WorldClock {
+            turns: start_hour * TURNS_PER_HOUR
+        }
+    }
+
+    /// Advances the clock by one turn
+    pub fn advance(&mut self) {
+        self.turns += 1;
+    }
+
+    /// The number of full days passed since the clock started
+    pub fn day(&self) -> u64 {
+        self.turns / (TURNS_PER_HOUR * HOURS_PER_DAY)
+    }
+
+    /// The current time of the day in hours (0.0 - 24.0), including fractions of the hour
+    pub fn hour(&self) -> f64 {
+        (self.turns % (TURNS_PER_HOUR * HOURS_PER_DAY)) as f64 / TURNS_PER_HOUR as f64
+    }
+
+    pub fn day_time(&self) -> DayTime {
+        let hour = self.hour();
+        if !(5.0..21.0).contains(&hour) {
+            DayTime::Night
+        } else if hour < 8.0 {
+            DayTime::Dawn
+        } else if hour < 18.0 {
+            DayTime::Day
+        } else {
+            DayTime::Dusk
+        }
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.day_time() == DayTime::Night
+    }
+
+    /// Tests if the current hour lies within the given span of hours. Spans may
+    /// wrap around midnight, e.g. `from` 20.0 `to` 6.0
+    pub fn is_between(&self, from: f64, to: f64) -> bool {
+        let hour = self.hour();
+        if from <= to {
+            hour >= from && hour < to
+        } else {
+            hour >= from || hour < to
+        }
+    }
+
+    /// Computes the color the screen is tinted with at the current time of day.
+    /// The tint is fully transparent at noon and darkest at midnight, with a warm
+    /// shift during dawn and dusk
+    pub fn ambient_tint(&self) -> [f32; 4] {
+        // 1.0 at midnight, 0.0 at noon
+        let darkness = (((self.hour() / HOURS_PER_DAY as f64) * 2.0 * std::f64::consts::PI).cos() + 1.0) / 2.0;
+        // keep the day bright for most of the time
+        let alpha = ((darkness as f32 - 0.3).max(0.0) / 0.7) * MAX_NIGHT_ALPHA;
+
+        match self.day_time() {
+            DayTime::Dawn | DayTime::Dusk => [0.35, 0.15, 0.1, alpha],
+            _ => [0.02, 0.02, 0.15, alpha]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WorldClock, DayTime, TURNS_PER_HOUR};
+
+    #[test]
+    fn day_time_cycle() {
+        let mut clock = WorldClock::new(0);
+        assert_eq!(clock.day_time(), DayTime::Night);
+        for _ in 0..(6 * TURNS_PER_HOUR) {
+            clock.advance();
+        }
+        assert_eq!(clock.day_time(), DayTime::Dawn);
+        assert_eq!(WorldClock::new(12).day_time(), DayTime::Day);
+        assert_eq!(WorldClock::new(19).day_time(), DayTime::Dusk);
+        assert_eq!(WorldClock::new(24).day(), 1);
+    }
+
+    #[test]
+    fn between_wraps_midnight() {
+        assert!(WorldClock::new(23).is_between(20.0, 6.0));
+        assert!(WorldClock::new(2).is_between(20.0, 6.0));
+        assert!(!WorldClock::new(12).is_between(20.0, 6.0));
+        assert!(WorldClock::new(12).is_between(8.0, 18.0));
+    }
+
+    #[test]
+    fn tint_darkest_at_midnight() {
+        assert_eq!(WorldClock::new(12).ambient_tint()[3], 0.0);
+        assert!(WorldClock::new(0).ambient_tint()[3] > WorldClock::new(20).ambient_tint()[3]);
+    }
+}
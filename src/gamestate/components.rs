@@ -4,12 +4,14 @@ use serde::{Serialize, Deserialize};
 
 use std::collections::HashMap;
 
-use crate::gamestate::{actor, status, item, class, spell, movement, dungeon, LocationVec};
+use crate::ecs;
+use crate::gamestate::{actor, status, item, class, spell, hotbar, interaction, movement, dungeon, party, schedule, LocationVec};
 
 use crate::event;
 
 use crate::render::{
     animation,
+    layer,
     sprite
 };
 
@@ -37,6 +39,16 @@ pub struct ActorComponent {
     pub turn: u64,
     pub max_actions: u64,
     pub performed_actions : u64,
+    // updates until the actor can act again in real-time games
+    #[serde(default)]
+    pub cooldown: u64,
+}
+
+/// Enables an Entity to get hungry and starve
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HungerComponent {
+    pub current: i32,
+    pub maximum: i32
 }
 
 /// Enables an Entity to recieve status effects
@@ -57,11 +69,31 @@ pub struct InventoryComponent {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LocationComponent {
     pub location: LocationVec,
+    // location before the last game update, only set while moving
+    pub previous_location: Option<LocationVec>,
     pub direction: movement::Direction,
     pub move_intent: Option<movement::MoveIntent>,
     pub hitbox: Option<event::Hitbox>
 }
 
+impl LocationComponent {
+    /// Location between the previous and the current one, used to render movement
+    /// smoothly between two game updates
+    ///
+    /// ### Arguments
+    /// * `alpha` - Fraction of the time until the next update that has passed (0.0 - 1.0)
+    ///
+    pub fn interpolated(&self, alpha: f64) -> LocationVec {
+        match self.previous_location {
+            Some(previous) => LocationVec {
+                x: previous.x + (self.location.x - previous.x) * alpha,
+                y: previous.y + (self.location.y - previous.y) * alpha
+            },
+            None => self.location
+        }
+    }
+}
+
 /// Enables an entity to have a name
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NameComponent {
@@ -81,7 +113,17 @@ pub struct ClassComponent {
 pub struct CasterComponent {
     pub current_mana: i32,
     pub maximum_mana: i32,
-    pub spells: Vec<spell::Spell>
+    pub spells: Vec<spell::Spell>,
+    // abilities used recently and the turns until they can be used again
+    #[serde(default)]
+    pub cooldowns: Vec<(spell::Ability, u32)>
+}
+
+/// Enables an entity to use spells, abilities and consumables with the number keys
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HotbarComponent {
+    // one per number key, see `hotbar::HOTBAR_SLOTS`
+    pub slots: Vec<Option<hotbar::HotbarSlot>>
 }
 
 /// Enables an entity to equip items like a human
@@ -101,6 +143,42 @@ pub struct HumanoidComponent {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NpcBehaviorComponent {
     // todo behavior for monsters etc
+    pub hostile: bool
+}
+
+/// Lets an entity fight alongside the players, following the orders of the party
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompanionComponent {
+    pub order: party::PartyOrder,
+    // the field it holds, while ordered to
+    #[serde(default)]
+    pub hold: Option<LocationVec>,
+    // the creature it attacks, while ordered to. Not saved, it falls back to following
+    #[serde(skip)]
+    pub target: Option<ecs::Entity>
+}
+
+/// Marks an entity as the boss of a floor
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BossComponent {
+    // defeating it wins the game
+    #[serde(default)]
+    pub final_boss: bool,
+    // its entrance was played, see `gamelogic::cutscene::check_boss_entrances`
+    #[serde(default)]
+    pub introduced: bool
+}
+
+/// Classifies an entity as an item lying on the ground, which can be picked up
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroundItemComponent {
+    pub item: item::Item
+}
+
+/// Enables the player to interact with an entity by facing it, e.g. a door or a chest
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InteractableComponent {
+    pub interaction: interaction::Interaction
 }
 
 /// Enables an entity to drop items
@@ -110,6 +188,36 @@ pub struct ItemDropComponent {
     pub item: Option<item::ItemId>
 }
 
+/// Marks an entity as currently dying, it is replaced by a corpse once
+/// its death animation has finished
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DyingComponent {
+    // in game updates
+    pub remaining: u32,
+    pub duration: u32
+}
+
+impl DyingComponent {
+    /// Progress of the death animation between 0.0 and 1.0
+    pub fn progress(&self) -> f64 {
+        if self.duration == 0 {
+            1.0
+        } else {
+            1.0 - self.remaining as f64 / self.duration as f64
+        }
+    }
+}
+
+/// Classifies an entity as the remains of a dead entity, which can be looted
+/// and decays after some turns
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CorpseComponent {
+    pub decay_turns: u32,
+    pub gold: i32,
+    pub items: Vec<item::Item>,
+    pub looted: bool
+}
+
 /// Classifies an entity as specific parts of the dungeon environment
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DungeonComponent {
@@ -124,7 +232,10 @@ pub struct PlayerComponent {
     pub gold: i32,
 
     // used for tracking progress of player
-    pub progression_flags: HashMap<String, bool>
+    pub progression_flags: HashMap<String, bool>,
+    // set while the player rests until healed
+    #[serde(default)]
+    pub resting: Option<actor::RestProgress>
 }
 
 /// Enables an entity to have a sprite and be rendered
@@ -132,9 +243,56 @@ pub struct PlayerComponent {
 pub struct RenderComponent {
     pub base_sprite: sprite::SpriteId, // change to ressource entity?
     pub base_sprite_size: f64,
-    pub animation: Option<animation::AnimationState>,
+    pub animation: animation::AnimationController,
     pub visible: bool,
-    pub render_layer: i32
+    pub render_layer: layer::RenderLayer,
+    // multiplied with the sprites colors, None to keep them
+    pub tint: Option<[f32; 3]>,
+    // 0.0 (invisible) to 1.0 (opaque)
+    pub opacity: f32,
+    pub flash: Option<animation::HitFlash>
+}
+
+impl RenderComponent {
+    /// The color the sprite is drawn with, None if it is drawn unchanged
+    pub fn color(&self) -> Option<[f32; 4]> {
+        if self.tint.is_none() && self.opacity >= 1.0 {
+            return None;
+        }
+        let [r, g, b] = self.tint.unwrap_or([1.0, 1.0, 1.0]);
+        Some([r, g, b, self.opacity.clamp(0.0, 1.0)])
+    }
+}
+
+/// Enables an entity to sell and buy items during its opening hours
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShopComponent {
+    pub open: bool,
+    pub opening_hour: f64,
+    pub closing_hour: f64
+}
+
+/// Lets an NPC follow a daily schedule, e.g. a shopkeeper standing at the counter by
+/// day and going home at night
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduleComponent {
+    pub schedule: schedule::Schedule,
+    // index of the entry followed, None until the first update outside a dungeon
+    #[serde(default)]
+    pub current: Option<usize>
+}
+
+/// Marks an entity as target of a quest, shown on the map
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuestTargetComponent {
+    pub quest: String
+}
+
+/// Identifies an entity across the network and in incremental saves. Assigned when
+/// the entity is first synchronized, see `persistence::delta::sync_id`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncIdComponent {
+    pub id: u64
 }
 
 /// Enables an entity to trigger events when certain triggers are met
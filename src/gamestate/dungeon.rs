@@ -8,5 +8,14 @@ pub enum DungeonElement {
     Floor,
     Wall,
     Connector,          // teleporter between dungeon levels
+    Water,              // ground creatures can not walk on
     Event,
-}
\ No newline at end of file
+}
+
+/// Kind of map the current floor is, determines which overworld mechanics apply
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum FloorType {
+    Dungeon,
+    Overworld,
+    Town,
+}
@@ -7,4 +7,15 @@ pub enum Duration {
     Infinite,       // Says until removed otherwise
     Steps(i32),     // Movement steps of the entity
     Updates(i32)    // Game updates
-}
\ No newline at end of file
+}
+
+impl Duration {
+    /// True if the duration has run out
+    pub fn is_expired(&self) -> bool {
+        match self {
+            Duration::Infinite => false,
+            Duration::Steps(steps) => *steps <= 0,
+            Duration::Updates(updates) => *updates <= 0
+        }
+    }
+}
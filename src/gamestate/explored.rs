@@ -0,0 +1,61 @@
+extern crate serde;
+
+use serde::{Serialize, Deserialize};
+
+use std::collections::HashMap;
+
+use crate::gamestate::{dungeon::DungeonElement, LocationVec};
+
+/// Grid coordinates of a field
+pub type TileCoord = (i64, i64);
+
+/// Remembers which fields of the current floor the player has seen and what
+/// dungeon element was there
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExploredMap {
+    pub tiles: HashMap<TileCoord, DungeonElement>
+}
+
+impl ExploredMap {
+    pub fn new() -> ExploredMap {
+        ExploredMap {
+            tiles: HashMap::new()
+        }
+    }
+
+    /// Marks a field as explored. Walls and connectors take precedence over
+    /// floors at the same field, as they are the more interesting information
+    pub fn explore(&mut self, location: &LocationVec, element: DungeonElement) {
+        let coord = tile_coord(location);
+        let keep_old = match self.tiles.get(&coord) {
+            Some(DungeonElement::Floor) | None => false,
+            Some(_) => element == DungeonElement::Floor
+        };
+        if !keep_old {
+            self.tiles.insert(coord, element);
+        }
+    }
+
+    pub fn is_explored(&self, location: &LocationVec) -> bool {
+        self.tiles.contains_key(&tile_coord(location))
+    }
+
+    /// Forgets everything, e.g. when entering a new floor
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    /// The smallest and largest explored coordinates, None if nothing was explored
+    pub fn bounds(&self) -> Option<(TileCoord, TileCoord)> {
+        let mut tiles = self.tiles.keys();
+        let first = *tiles.next()?;
+        Some(tiles.fold((first, first), |(min, max), &(x, y)| {
+            ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+        }))
+    }
+}
+
+/// Rounds a location to the grid coordinates of the field it is on
+pub fn tile_coord(location: &LocationVec) -> TileCoord {
+    (location.x.round() as i64, location.y.round() as i64)
+}
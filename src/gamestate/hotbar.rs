@@ -0,0 +1,19 @@
+extern crate serde;
+
+use serde::{Serialize, Deserialize};
+
+use crate::gamestate::spell::Ability;
+
+// slots of a hotbar, used with the number keys 1 to 9 and 0
+pub const HOTBAR_SLOTS: usize = 10;
+
+/// What a slot of the hotbar uses, see `HotbarComponent`
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum HotbarSlot {
+    // index of a spell of the `CasterComponent`
+    Spell(usize),
+    // an ability of the class
+    Ability(Ability),
+    // the first consumable of the inventory
+    Consumable,
+}
@@ -0,0 +1,22 @@
+extern crate serde;
+
+use serde::{Serialize, Deserialize};
+
+use crate::gamestate::item::Item;
+
+/// What happens when an entity is interacted with, see `InteractableComponent`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Interaction {
+    // opens and closes, closed doors block the way and the sight
+    Door { open: bool },
+    // holds loot until it is taken
+    Chest { gold: i32, items: Vec<Item>, opened: bool },
+    // a line said by an NPC, a key of the locale
+    Talk { line: String },
+    // switches a flag of the global state table on and off
+    Lever { flag: String, pulled: bool },
+    // leads down to the next floor
+    Stairs,
+    // the loot of a `CorpseComponent`
+    Corpse,
+}
@@ -4,20 +4,60 @@ use serde::{Serialize, Deserialize};
 
 pub type ItemId = i32;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Item {
     pub item: ItemType,
     pub amount: i32
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Item {
+    /// Weight of all items of the stack
+    pub fn weight(&self) -> f64 {
+        self.item.weight() * self.amount as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ItemType {
     Equipment(Equipment),
     Consumable,
-    KeyItem
+    KeyItem,
+    // coins, added to the gold of the player instead of the inventory
+    Gold,
+    // worth nothing, auto-pickup can ignore it
+    Junk
+}
+
+impl ItemType {
+    /// Key of the item's name in the locale, equipment is named by its kind
+    pub fn locale_key(&self) -> String {
+        match self {
+            ItemType::Equipment(equipment) => format!("item.{:?}", equipment),
+            ItemType::Consumable => "item.Consumable".to_string(),
+            ItemType::KeyItem => "item.KeyItem".to_string(),
+            ItemType::Gold => "item.Gold".to_string(),
+            ItemType::Junk => "item.Junk".to_string()
+        }
+    }
+
+    /// True for items of which many are carried in one slot of the inventory
+    pub fn stacks(&self) -> bool {
+        matches!(self, ItemType::Consumable | ItemType::Gold)
+    }
+
+    /// Weight of one item of the type
+    pub fn weight(&self) -> f64 {
+        match self {
+            ItemType::Equipment(_) => 3.0,
+            ItemType::Consumable => 0.5,
+            ItemType::KeyItem => 0.1,
+            ItemType::Gold => 0.0,
+            ItemType::Junk => 1.0
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Equipment {
     OneHandWeapon,
     TwoHandWeapon,
@@ -1,3 +1,4 @@
+pub mod achievement;
 pub mod actor;
 pub mod components;
 pub mod duration;
@@ -5,8 +6,21 @@ pub mod status;
 pub mod item;
 pub mod class;
 pub mod spell;
+pub mod hotbar;
+pub mod interaction;
 pub mod movement;
 pub mod dungeon;
+pub mod clock;
+pub mod weather;
+pub mod explored;
+pub mod mode;
+pub mod overworld;
+pub mod party;
+pub mod profile;
+pub mod rng;
+pub mod run;
+pub mod schedule;
+pub mod time;
 
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
@@ -65,10 +79,14 @@ impl SubAssign for LocationVec {
 impl From<Direction> for LocationVec {
     fn from(dir: Direction) -> Self {
         match dir {
-            Direction::Up    => Self {x: 0.0, y: -1.0},
-            Direction::Left  => Self {x: -1.0, y: 0.0},
-            Direction::Down  => Self {x: 0.0, y: 1.0},
-            Direction::Right => Self {x: 1.0, y: 0.0},
+            Direction::Up        => Self {x: 0.0, y: -1.0},
+            Direction::Left      => Self {x: -1.0, y: 0.0},
+            Direction::Down      => Self {x: 0.0, y: 1.0},
+            Direction::Right     => Self {x: 1.0, y: 0.0},
+            Direction::UpLeft    => Self {x: -1.0, y: -1.0},
+            Direction::UpRight   => Self {x: 1.0, y: -1.0},
+            Direction::DownLeft  => Self {x: -1.0, y: 1.0},
+            Direction::DownRight => Self {x: 1.0, y: 1.0},
         }
     }
 }
@@ -0,0 +1,80 @@
+extern crate serde;
+
+use serde::{Serialize, Deserialize};
+
+use crate::ecs::ECS;
+
+/// The mode the game is in, determines which systems are updated and what is rendered
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum GameMode {
+    Dungeon,
+    Map,        // full screen map, the dungeon is paused
+    Settings,   // settings menu, the dungeon is paused
+    LoadMenu,   // list of saved games, the dungeon is paused
+    GameOver,   // the run ended in victory or defeat, see `ECS::run_summary`
+    Overworld,  // travel between dungeons and towns, the floor left is paused
+}
+
+impl GameMode {
+    /// True if the dungeon is simulated in this mode
+    pub fn is_running(&self) -> bool {
+        *self == GameMode::Dungeon
+    }
+}
+
+/// Visual style of a transition
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum TransitionEffect {
+    Fade,
+    Wipe,
+}
+
+/// Builds a new floor into the ECS after the old one was torn down
+pub type FloorBuilder = fn(&mut ECS);
+
+/// The change a transition performs at its midpoint, while the screen is fully covered
+pub enum TransitionTarget {
+    Mode(GameMode),
+    Floor(FloorBuilder),
+}
+
+/// A transition between two game modes or floors. The first half covers the screen,
+/// then the target is applied and the second half uncovers the screen again
+pub struct Transition {
+    pub effect: TransitionEffect,
+    // taken once it was applied
+    pub target: Option<TransitionTarget>,
+    // in game updates
+    pub elapsed: u64,
+    pub duration: u64,
+}
+
+impl Transition {
+    pub fn new(effect: TransitionEffect, target: TransitionTarget, duration: u64) -> Transition {
+        Transition {
+            effect,
+            target: Some(target),
+            elapsed: 0,
+            duration
+        }
+    }
+
+    /// Progress of the whole transition between 0.0 and 1.0
+    pub fn progress(&self) -> f64 {
+        if self.duration == 0 {
+            1.0
+        } else {
+            (self.elapsed as f64 / self.duration as f64).min(1.0)
+        }
+    }
+
+    /// How much of the screen is covered, rising from 0.0 to 1.0 at the midpoint and
+    /// falling back to 0.0 at the end
+    pub fn coverage(&self) -> f64 {
+        1.0 - (2.0 * self.progress() - 1.0).abs()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
@@ -9,23 +9,181 @@ use crate::UPDATES_PER_SECOND;
 pub const DEFAULT_SPEED: f64 = 5f64;
 // number of decimal digits for rounding
 const PRECISION: f64 = 0.0000001;
+// eased movement never gets slower than this fraction of its speed, so it does not
+// stand still at the start or end
+const MIN_EASED_SPEED: f64 = 0.2;
 
 #[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Direction {
     Up,
     Left,
     Down,
-    Right
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::Left,
+        Direction::Down,
+        Direction::Right,
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+    ];
+
+    pub fn is_diagonal(&self) -> bool {
+        matches!(self, Direction::UpLeft | Direction::UpRight | Direction::DownLeft | Direction::DownRight)
+    }
+
+    /// The diagonal between two directions, e.g. up and left held at the same time
+    ///
+    /// ### Returns
+    /// The diagonal, None if the directions are not a vertical and a horizontal one
+    pub fn combine(&self, other: Direction) -> Option<Direction> {
+        match (self, other) {
+            (Direction::Up, Direction::Left) | (Direction::Left, Direction::Up) => Some(Direction::UpLeft),
+            (Direction::Up, Direction::Right) | (Direction::Right, Direction::Up) => Some(Direction::UpRight),
+            (Direction::Down, Direction::Left) | (Direction::Left, Direction::Down) => Some(Direction::DownLeft),
+            (Direction::Down, Direction::Right) | (Direction::Right, Direction::Down) => Some(Direction::DownRight),
+            _ => None
+        }
+    }
+
+    /// The vertical and horizontal direction a diagonal is made of, None for others
+    pub fn split(&self) -> Option<(Direction, Direction)> {
+        match self {
+            Direction::UpLeft => Some((Direction::Up, Direction::Left)),
+            Direction::UpRight => Some((Direction::Up, Direction::Right)),
+            Direction::DownLeft => Some((Direction::Down, Direction::Left)),
+            Direction::DownRight => Some((Direction::Down, Direction::Right)),
+            _ => None
+        }
+    }
+
+    /// Clockwise rotation in degrees of a sprite facing the direction, sprites face down
+    pub fn rotation(&self) -> f64 {
+        match self {
+            Direction::Up        => 180.0,
+            Direction::Left      => 90.0,
+            Direction::Down      => 0.0,
+            Direction::Right     => -90.0,
+            Direction::UpLeft    => 135.0,
+            Direction::UpRight   => -135.0,
+            Direction::DownLeft  => 45.0,
+            Direction::DownRight => -45.0,
+        }
+    }
+}
+
+/// How far a diagonal step is, for the time it takes, path costs and view distances
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum DiagonalRule {
+    // as far as it really is, √2 fields
+    #[default]
+    Euclidean,
+    // as far as any other step, one field
+    Uniform,
+}
+
+impl DiagonalRule {
+    /// Length of a diagonal step in fields
+    pub fn diagonal_cost(&self) -> f64 {
+        match self {
+            DiagonalRule::Euclidean => std::f64::consts::SQRT_2,
+            DiagonalRule::Uniform => 1.0,
+        }
+    }
+
+    /// Distance between two locations when walking it
+    pub fn distance(&self, from: &LocationVec, to: &LocationVec) -> f64 {
+        let dx = (to.x - from.x).abs();
+        let dy = (to.y - from.y).abs();
+        match self {
+            DiagonalRule::Euclidean => (dx * dx + dy * dy).sqrt(),
+            DiagonalRule::Uniform => dx.max(dy),
+        }
+    }
+
+    /// Speed of a step into a direction, so a diagonal step takes as long as its length.
+    /// Diagonal steps move along both axes, see `MoveIntent::move_from`
+    pub fn step_speed(&self, direction: Direction, speed: f64) -> f64 {
+        if direction.is_diagonal() {
+            speed * 2.0 / self.diagonal_cost()
+        } else {
+            speed
+        }
+    }
+
+    pub fn next(&self) -> DiagonalRule {
+        match self {
+            DiagonalRule::Euclidean => DiagonalRule::Uniform,
+            DiagonalRule::Uniform => DiagonalRule::Euclidean,
+        }
+    }
+}
+
+/// How the speed of a movement changes between its start and its goal
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Easing {
+    Constant,
+    // speeds up from the start
+    EaseIn,
+    // slows down towards the goal
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Factor for the speed of a movement, chosen so the movement takes about as long as
+    /// with constant speed
+    ///
+    /// ### Arguments
+    /// * `progress` - Fraction of the distance already moved (0.0 - 1.0)
+    ///
+    pub fn speed_factor(&self, progress: f64) -> f64 {
+        let progress = progress.clamp(0.0, 1.0);
+        // quadratic curves, the speed of t² at the time it reaches `progress` is 2√progress
+        let factor = match self {
+            Easing::Constant => 1.0,
+            Easing::EaseIn => 2.0 * progress.sqrt(),
+            Easing::EaseOut => 2.0 * (1.0 - progress).sqrt(),
+            Easing::EaseInOut if progress < 0.5 => 2.0 * (2.0 * progress).sqrt(),
+            Easing::EaseInOut => 2.0 * (2.0 * (1.0 - progress)).sqrt(),
+        };
+        factor.max(MIN_EASED_SPEED)
+    }
 }
 
 /// Describes the intent of an entity to move to a certain location
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum MoveIntent {
     Vector(LocationVec, f64),
-    Position(LocationVec, f64)
+    Position(LocationVec, f64),
+    // waypoints not reached yet, walked through in order, e.g. a path found by
+    // `gamelogic::pathfinding::find_path`
+    Path(Vec<LocationVec>, f64),
+    // another intent with its speed changed by an easing curve, over the whole distance
+    // it had to move when it started
+    Eased(Box<MoveIntent>, Easing, f64)
 }
 
 impl MoveIntent {
+    /// Lets the intent change its speed by an easing curve
+    ///
+    /// ### Arguments
+    /// * `easing`   - The curve
+    /// * `location` - The location the movement starts at
+    ///
+    pub fn eased(self, easing: Easing, location: &LocationVec) -> MoveIntent {
+        let distance = self.remaining_distance(location);
+        MoveIntent::Eased(Box::new(self), easing, distance)
+    }
 
     // move towards goal and give new Location vec, will change if Vector MoveInten
     // param location: current location
@@ -48,6 +206,19 @@ impl MoveIntent {
     /// The new location after moving a step
     /// 
     pub fn move_from(&mut self, location: &LocationVec) -> LocationVec {
+        self.move_from_scaled(location, 1.0)
+    }
+
+    /// Like `move_from`, but with the step scaled by a time scale, e.g. to move in slow motion
+    /// 
+    /// ### Arguments
+    /// `location`   - The current location from which the `MoveIntent` shall make its move
+    /// `time_scale` - Factor for the step size, 1.0 is normal speed
+    /// 
+    /// ### Returns
+    /// The new location after moving a step
+    /// 
+    pub fn move_from_scaled(&mut self, location: &LocationVec, time_scale: f64) -> LocationVec {
         match self {
             MoveIntent::Position(target_location, speed) => {
                 // map this to MoveIntent::Vector
@@ -56,10 +227,31 @@ impl MoveIntent {
                     y: target_location.y - location.y
                 };
                 let mut dummy_move_intent = MoveIntent::Vector(distance_vector, *speed);
-                dummy_move_intent.move_from(location)
+                dummy_move_intent.move_from_scaled(location, time_scale)
+            },
+            MoveIntent::Path(waypoints, speed) => {
+                // waypoints the entity was moved onto are skipped
+                while waypoints.first() == Some(location) {
+                    waypoints.remove(0);
+                }
+                let next_waypoint = match waypoints.first() {
+                    Some(waypoint) => *waypoint,
+                    None => return *location
+                };
+                // walk the segment to the next waypoint as a MoveIntent::Position
+                let mut segment_intent = MoveIntent::Position(next_waypoint, *speed);
+                let new_location = segment_intent.move_from_scaled(location, time_scale);
+                if segment_intent.has_arrived(&new_location) {
+                    waypoints.remove(0);
+                }
+                new_location
+            },
+            MoveIntent::Eased(intent, easing, distance) => {
+                let progress = if *distance > 0.0 { 1.0 - intent.remaining_distance(location) / *distance } else { 1.0 };
+                intent.move_from_scaled(location, time_scale * easing.speed_factor(progress))
             },
             MoveIntent::Vector(movement_vec, speed) => {
-                let step = *speed / UPDATES_PER_SECOND as f64;
+                let step = *speed * time_scale / UPDATES_PER_SECOND as f64;
 
                 // get the direction of axis we will move towards
                 let x_direction = if movement_vec.x == 0.0 { 0.0 } else {movement_vec.x / movement_vec.x.abs()};
@@ -91,19 +283,15 @@ impl MoveIntent {
                 }, *speed);
 
 
-                // check if truncating is required
-                // (required to fight unprecision of f64s)
+                // check if rounding to the field is required
+                // (required to fight unprecision of f64s, from both sides of the field)
                 let mut new_x = location.x + x_step * x_direction;
-                if new_x.abs() - new_x.trunc().abs() < PRECISION && new_x > new_x.trunc() {
-                    new_x = new_x.round();
-                } else if new_x.abs() - (new_x + PRECISION).trunc().abs() < PRECISION{
+                if (new_x - new_x.round()).abs() < PRECISION {
                     new_x = new_x.round();
                 }
 
                 let mut new_y = location.y + y_step * y_direction;
-                if new_y.abs() - new_y.trunc().abs() < PRECISION && new_y > new_y.trunc() {
-                    new_y = new_y.round();
-                } else if new_y.abs() - (new_y + PRECISION).trunc().abs() < PRECISION{
+                if (new_y - new_y.round()).abs() < PRECISION {
                     new_y = new_y.round();
                 }
 
@@ -131,7 +319,40 @@ impl MoveIntent {
             },
             MoveIntent::Vector(movement_vec, _) => {
                 movement_vec.x == 0.0 && movement_vec.y == 0.0
-            }
+            },
+            MoveIntent::Path(waypoints, _) => {
+                waypoints.iter().all(|waypoint| waypoint == current_location)
+            },
+            MoveIntent::Eased(intent, _, _) => intent.has_arrived(current_location)
+        }
+    }
+
+    /// Distance left to move, along the axes as entities move in steps along them
+    pub fn remaining_distance(&self, current_location: &LocationVec) -> f64 {
+        let axis_distance = |from: &LocationVec, to: &LocationVec| (to.x - from.x).abs() + (to.y - from.y).abs();
+        match self {
+            MoveIntent::Position(target_location, _) => axis_distance(current_location, target_location),
+            MoveIntent::Vector(movement_vec, _) => movement_vec.x.abs() + movement_vec.y.abs(),
+            MoveIntent::Path(waypoints, _) => {
+                let mut distance = 0.0;
+                let mut from = current_location;
+                for waypoint in waypoints.iter() {
+                    distance += axis_distance(from, waypoint);
+                    from = waypoint;
+                }
+                distance
+            },
+            MoveIntent::Eased(intent, _, _) => intent.remaining_distance(current_location)
+        }
+    }
+
+    /// The waypoint a `MoveIntent::Path` is currently walking to, None for other intents
+    /// or once the path is walked
+    pub fn next_waypoint(&self) -> Option<LocationVec> {
+        match self {
+            MoveIntent::Path(waypoints, _) => waypoints.first().copied(),
+            MoveIntent::Eased(intent, _, _) => intent.next_waypoint(),
+            _ => None
         }
     }
 
@@ -149,14 +370,16 @@ impl MoveIntent {
             MoveIntent::Vector(movement_vec, _) => LocationVec {
                 x: current_location.x + movement_vec.x,
                 y: current_location.y + movement_vec.y
-            }
+            },
+            MoveIntent::Path(waypoints, _) => *waypoints.last().unwrap_or(current_location),
+            MoveIntent::Eased(intent, _, _) => intent.target_goal(current_location)
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{LocationVec, UPDATES_PER_SECOND, MoveIntent};
+    use super::{DiagonalRule, Direction, Easing, LocationVec, UPDATES_PER_SECOND, MoveIntent};
     const ZERO_VEC: LocationVec = LocationVec {x: 0.0, y: 0.0};
 
     #[test]
@@ -178,10 +401,61 @@ mod tests {
         let mut location = ZERO_VEC;
         for _ in 0..UPDATES_PER_SECOND {
             location = intent.move_from(&location);
-            print!("\n{:?}\n", &location);
-            print!("{:?}\n\n", &intent);
+            trace!("{:?} {:?}", &location, &intent);
+        }
+        assert!(intent.has_arrived(&location));
+    }
+
+    #[test]
+    fn move_path() {
+        let waypoints = vec![LocationVec{x:1.0, y:0.0}, LocationVec{x:1.0, y:1.0}, LocationVec{x:2.0, y:1.0}];
+        let mut intent = MoveIntent::Path(waypoints, 3.0);
+        let mut location = ZERO_VEC;
+        assert_eq!(intent.target_goal(&location), LocationVec{x:2.0, y:1.0});
+        // one field per segment at one field per 10 updates, no diagonal shortcuts
+        for _ in 0..(UPDATES_PER_SECOND / 3) {
+            location = intent.move_from(&location);
+            assert_eq!(location.y, 0.0);
+        }
+        assert_eq!(intent.next_waypoint(), Some(LocationVec{x:1.0, y:1.0}));
+        for _ in 0..(2 * UPDATES_PER_SECOND / 3) {
+            location = intent.move_from(&location);
+        }
+        assert!(intent.has_arrived(&location));
+        assert_eq!(location, LocationVec{x:2.0, y:1.0});
+    }
+
+    #[test]
+    fn move_eased() {
+        let goal = LocationVec{x:3.0, y:0.0};
+        let mut intent = MoveIntent::Position(goal, 3.0).eased(Easing::EaseInOut, &ZERO_VEC);
+        let mut location = ZERO_VEC;
+        let mut steps = Vec::new();
+        while !intent.has_arrived(&location) && steps.len() < 100 {
+            let new_location = intent.move_from(&location);
+            steps.push(new_location.x - location.x);
+            location = new_location;
+        }
+        // lands exactly on the goal, after speeding up and slowing down again
+        assert_eq!(location, goal);
+        let fastest = steps.iter().cloned().fold(0.0, f64::max);
+        assert!(steps[0] < fastest && steps[steps.len() - 2] < fastest);
+        // about as long as moving with constant speed
+        assert!((steps.len() as i64 - UPDATES_PER_SECOND as i64).abs() <= UPDATES_PER_SECOND as i64 / 5);
+    }
+
+    #[test]
+    fn move_diagonal() {
+        // a uniform diagonal step takes as long as any other step
+        let speed = DiagonalRule::Uniform.step_speed(Direction::UpRight, 1.0);
+        let mut intent = MoveIntent::Vector(LocationVec::from(Direction::UpRight), speed);
+        let mut location = ZERO_VEC;
+        for _ in 0..UPDATES_PER_SECOND {
+            location = intent.move_from(&location);
         }
-        print!("{:?}", &location);
         assert!(intent.has_arrived(&location));
+        assert_eq!(location, LocationVec{x:1.0, y:-1.0});
+        assert_eq!(Direction::Left.combine(Direction::Down), Some(Direction::DownLeft));
+        assert_eq!(Direction::Left.combine(Direction::Right), None);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,132 @@
+extern crate serde;
+
+use serde::{Serialize, Deserialize};
+
+use crate::gamestate::LocationVec;
+
+/// What a node of the overworld leads to
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Destination {
+    // the stairs of its last floor lead back to the overworld
+    Dungeon { floors: i32 },
+    // the road out leads back to the overworld at once
+    Town,
+}
+
+/// A place on the overworld the players can travel to
+#[derive(Debug, Clone)]
+pub struct OverworldNode {
+    // key of its name in the locale
+    pub name: &'static str,
+    // on the overworld screen, from 0.0 to 1.0 on both axes
+    pub position: LocationVec,
+    pub destination: Destination,
+    // progression flag a player needs to travel there, None if it is always open
+    pub requires: Option<&'static str>,
+    // progression flag the players get for reaching the end of its dungeon
+    pub completes: Option<&'static str>,
+    // indices of the nodes roads lead to, roads go both ways
+    pub roads: Vec<usize>
+}
+
+/// The nodes of the overworld. The players start in the first one, further dungeons are
+/// opened by finishing the ones before
+pub fn world() -> Vec<OverworldNode> {
+    vec![
+        OverworldNode {
+            name: "overworld.crypt",
+            position: LocationVec { x: 0.15, y: 0.7 },
+            destination: Destination::Dungeon { floors: 3 },
+            requires: None,
+            completes: Some("crypt_cleared"),
+            roads: vec![1, 2]
+        },
+        OverworldNode {
+            name: "overworld.village",
+            position: LocationVec { x: 0.4, y: 0.35 },
+            destination: Destination::Town,
+            requires: None,
+            completes: None,
+            roads: vec![0, 2]
+        },
+        OverworldNode {
+            name: "overworld.keep",
+            position: LocationVec { x: 0.65, y: 0.75 },
+            destination: Destination::Dungeon { floors: 4 },
+            requires: Some("crypt_cleared"),
+            completes: Some("keep_cleared"),
+            roads: vec![0, 1, 3]
+        },
+        OverworldNode {
+            name: "overworld.harbor",
+            position: LocationVec { x: 0.85, y: 0.25 },
+            destination: Destination::Town,
+            requires: Some("keep_cleared"),
+            completes: None,
+            roads: vec![2]
+        },
+    ]
+}
+
+/// Something happening to the players on the road, applied once they arrive
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum TravelEvent {
+    // gold found for every player
+    Treasure { gold: i32 },
+    // damage every player takes, it does not kill
+    Ambush { damage: i32 },
+    // health every player regains
+    Campfire { health: i32 },
+}
+
+impl TravelEvent {
+    /// Key of the event's message in the locale
+    pub fn locale_key(&self) -> &'static str {
+        match self {
+            TravelEvent::Treasure { .. } => "overworld.event.treasure",
+            TravelEvent::Ambush { .. } => "overworld.event.ambush",
+            TravelEvent::Campfire { .. } => "overworld.event.campfire"
+        }
+    }
+}
+
+/// The players walking a road between two nodes
+#[derive(Debug, Clone)]
+pub struct Travel {
+    pub from: usize,
+    pub to: usize,
+    // in game updates
+    pub elapsed: u64,
+    pub duration: u64,
+    // rolled when setting off
+    pub event: Option<TravelEvent>
+}
+
+impl Travel {
+    /// Progress of the travel between 0.0 and 1.0
+    pub fn progress(&self) -> f64 {
+        if self.duration == 0 {
+            1.0
+        } else {
+            (self.elapsed as f64 / self.duration as f64).min(1.0)
+        }
+    }
+}
+
+/// Where the players are on the overworld, see `gamelogic::overworld`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Overworld {
+    // the node the players are at, or were at last while in a dungeon or town
+    pub current: usize,
+    // floors descended in the dungeon of the current node
+    pub depth: i32,
+    // the node chosen on the overworld screen
+    #[serde(skip)]
+    pub selected: usize,
+    #[serde(skip)]
+    pub travel: Option<Travel>,
+    // what happened on the last road, shown until the players set off again
+    #[serde(skip)]
+    pub last_event: Option<TravelEvent>
+}
@@ -0,0 +1,23 @@
+extern crate serde;
+
+use serde::{Serialize, Deserialize};
+
+/// An order the players give all their companions, see `gamelogic::party`
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum PartyOrder {
+    // stay close to the nearest player, fighting creatures next to them
+    Follow,
+    // stay on the field they were on when ordered, fighting creatures next to them
+    Hold,
+    // attack the creature the player fights
+    FocusTarget,
+    // return to the nearest player without fighting
+    Retreat,
+}
+
+impl PartyOrder {
+    /// Key of the order's name in the locale
+    pub fn locale_key(&self) -> String {
+        format!("party.{:?}", self)
+    }
+}
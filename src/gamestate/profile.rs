@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+// weight of the newest measurement in the averages, smooths out single slow updates
+const SMOOTHING: f64 = 0.1;
+
+/// Average time spent in a system per game update, in milliseconds
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemTiming {
+    pub name: &'static str,
+    pub average_ms: f64,
+    // time spent since the current update started
+    current: Duration
+}
+
+/// Measures how long the systems of the game loop take, shown in the profiler
+/// overlay. Systems running several times in an update, e.g. while fast forwarding,
+/// are counted once with their total time
+#[derive(Debug, Clone)]
+pub struct FrameProfiler {
+    pub visible: bool,
+    // in the order the systems were first measured, which is the order they run in
+    systems: Vec<SystemTiming>,
+    frame_ms: f64
+}
+
+impl FrameProfiler {
+    pub fn new() -> FrameProfiler {
+        FrameProfiler {
+            visible: false,
+            systems: Vec::new(),
+            frame_ms: 0.0
+        }
+    }
+
+    /// Adds time spent in a system to the current update
+    pub fn record(&mut self, name: &'static str, duration: Duration) {
+        match self.systems.iter_mut().find(|timing| timing.name == name) {
+            Some(timing) => timing.current += duration,
+            None => self.systems.push(SystemTiming { name, average_ms: 0.0, current: duration })
+        }
+    }
+
+    /// Ends the current update, its times go into the averages
+    pub fn end_update(&mut self) {
+        for timing in self.systems.iter_mut() {
+            timing.average_ms += (timing.current.as_secs_f64() * 1000.0 - timing.average_ms) * SMOOTHING;
+            timing.current = Duration::from_secs(0);
+        }
+    }
+
+    /// Records how long rendering a frame took
+    pub fn record_frame(&mut self, duration: Duration) {
+        self.frame_ms += (duration.as_secs_f64() * 1000.0 - self.frame_ms) * SMOOTHING;
+    }
+
+    pub fn systems(&self) -> &[SystemTiming] {
+        &self.systems
+    }
+
+    /// Average time of a whole update
+    pub fn update_ms(&self) -> f64 {
+        self.systems.iter().map(|timing| timing.average_ms).sum()
+    }
+
+    /// Average time of rendering a frame
+    pub fn frame_ms(&self) -> f64 {
+        self.frame_ms
+    }
+}
+
+impl Default for FrameProfiler {
+    fn default() -> FrameProfiler {
+        FrameProfiler::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_systems_count_once_per_update() {
+        let mut profiler = FrameProfiler::new();
+        for _ in 0..100 {
+            profiler.record("movement", Duration::from_millis(2));
+            profiler.record("turns", Duration::from_millis(1));
+            profiler.record("turns", Duration::from_millis(1));
+            profiler.end_update();
+        }
+        let names: Vec<&str> = profiler.systems().iter().map(|timing| timing.name).collect();
+        assert_eq!(names, vec!["movement", "turns"]);
+        assert!((profiler.systems()[1].average_ms - 2.0).abs() < 0.01);
+        assert!((profiler.update_ms() - 4.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,136 @@
+extern crate rand;
+extern crate serde;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::RngCore;
+use serde::{Serialize, Deserialize};
+
+const MULTIPLIER: u64 = 6364136223846793005;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The random number generator of a game (PCG32). All randomness affecting the game
+/// has to go through it, so a game can be repeated from its seed. Its state is
+/// serializable, so it continues the same sequence after loading a save.
+/// Use it with the methods of `rand::Rng`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameRng {
+    seed: u64,
+    state: u64,
+    increment: u64
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> GameRng {
+        GameRng::with_stream(seed, 0)
+    }
+
+    fn with_stream(seed: u64, stream: u64) -> GameRng {
+        let mut rng = GameRng {
+            seed,
+            state: 0,
+            // must be odd
+            increment: (stream << 1) | 1
+        };
+        rng.step();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.step();
+        rng
+    }
+
+    /// The seed the generator was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Creates an independent generator for one purpose, e.g. generating a certain floor.
+    /// It only depends on the seed and the name, not on how much randomness was used
+    /// before, so e.g. floors stay the same no matter what happened on earlier floors
+    ///
+    /// ### Arguments
+    /// * `name` - The purpose of the generator, like `"floor_3"`
+    ///
+    pub fn derive(&self, name: &str) -> GameRng {
+        // FNV-1a
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in name.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        GameRng::with_stream(self.seed, hash)
+    }
+
+    fn step(&mut self) {
+        self.state = self.state.wrapping_mul(MULTIPLIER).wrapping_add(self.increment);
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.step();
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rotation = (old >> 59) as u32;
+        xorshifted.rotate_right(rotation)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        ((self.next_u32() as u64) << 32) | self.next_u32() as u64
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Seed of the daily challenge, the same for everyone on the same (UTC) day
+pub fn daily_seed() -> u64 {
+    let days = SystemTime::now().duration_since(UNIX_EPOCH)
+                                .map(|duration| duration.as_secs() / SECONDS_PER_DAY)
+                                .unwrap_or(0);
+    GameRng::new(days).next_u64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = GameRng::new(1234);
+        let mut b = GameRng::new(1234);
+        let mut c = GameRng::new(1235);
+        let sequence_a: Vec<u32> = (0..20).map(|_| a.gen_range(0..1000)).collect();
+        let sequence_b: Vec<u32> = (0..20).map(|_| b.gen_range(0..1000)).collect();
+        let sequence_c: Vec<u32> = (0..20).map(|_| c.gen_range(0..1000)).collect();
+        assert_eq!(sequence_a, sequence_b);
+        assert_ne!(sequence_a, sequence_c);
+    }
+
+    #[test]
+    fn test_serialized_rng_continues_sequence() {
+        let mut rng = GameRng::new(99);
+        rng.next_u32();
+        let saved = serde_yaml::to_string(&rng).unwrap();
+        let mut loaded: GameRng = serde_yaml::from_str(&saved).unwrap();
+        assert_eq!(rng.next_u64(), loaded.next_u64());
+    }
+
+    #[test]
+    fn test_derive_is_independent_of_usage() {
+        let mut rng = GameRng::new(7);
+        let before = rng.derive("floor_1");
+        rng.next_u64();
+        assert_eq!(rng.derive("floor_1"), before);
+        assert_ne!(rng.derive("floor_2"), before);
+    }
+}
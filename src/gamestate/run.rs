@@ -0,0 +1,45 @@
+extern crate serde;
+
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Deserialize};
+
+/// How a run ended
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum RunOutcome {
+    // the final boss was defeated
+    Victory,
+    // all players died
+    Defeat,
+}
+
+/// Statistics counted during a run, saved with it. The lifetime totals of the player
+/// profile are counted the same way, see `gamelogic::stats`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunStats {
+    // hostile creatures killed by players
+    pub kills: u32,
+    // the same kills by the name key of the creature, e.g. `creature.dummy`
+    pub kills_by_type: BTreeMap<String, u32>,
+    pub damage_dealt: u64,
+    pub damage_taken: u64,
+    pub items_used: u32,
+    // floors left through their stairs
+    pub floors_cleared: u32,
+    pub deepest_floor: i32,
+}
+
+/// What the game over screen shows of a run that ended
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunSummary {
+    pub outcome: RunOutcome,
+    pub turns: u64,
+    pub kills: u32,
+    pub gold: i32,
+    pub deepest_floor: i32,
+    // after a victory: the equipment choice selected on the screen and the ones kept
+    // for the next new-game-plus run, see `gamelogic::new_game_plus`
+    pub selected: usize,
+    pub kept: Vec<usize>,
+}
@@ -0,0 +1,84 @@
+extern crate serde;
+
+use serde::{Serialize, Deserialize};
+
+use crate::gamestate::{movement::MoveIntent, LocationVec};
+
+/// What an NPC does during a part of its day
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Activity {
+    // e.g. a shopkeeper standing at the counter, shops are only open then
+    Work,
+    Leisure,
+    // hidden once the NPC arrived, e.g. in its home
+    Sleep,
+}
+
+/// A part of a daily schedule, from an hour of the day on the NPC walks to a goal and
+/// does an activity there until the next entry begins
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleEntry {
+    pub hour: f64,
+    pub goal: LocationVec,
+    pub speed: f64,
+    pub activity: Activity
+}
+
+impl ScheduleEntry {
+    /// The intent walking to the goal
+    pub fn move_intent(&self) -> MoveIntent {
+        MoveIntent::Position(self.goal, self.speed)
+    }
+}
+
+/// The daily routine of an NPC, driven by the world clock
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Schedule {
+    // sorted by their hour
+    entries: Vec<ScheduleEntry>
+}
+
+impl Schedule {
+    pub fn new(mut entries: Vec<ScheduleEntry>) -> Schedule {
+        entries.sort_by(|a, b| a.hour.partial_cmp(&b.hour).unwrap_or(std::cmp::Ordering::Equal));
+        Schedule { entries }
+    }
+
+    /// Finds the entry active at an hour of the day. Before the first entry of a day
+    /// the last one of the day before is still active
+    ///
+    /// ### Returns
+    /// The index of the entry, None for an empty schedule
+    pub fn active(&self, hour: f64) -> Option<usize> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        Some(self.entries.iter()
+                 .rposition(|entry| entry.hour <= hour)
+                 .unwrap_or(self.entries.len() - 1))
+    }
+
+    pub fn entry(&self, index: usize) -> Option<&ScheduleEntry> {
+        self.entries.get(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(hour: f64, activity: Activity) -> ScheduleEntry {
+        ScheduleEntry { hour, goal: LocationVec { x: 0.0, y: 0.0 }, speed: 1.0, activity }
+    }
+
+    #[test]
+    fn test_active_entry_wraps_around_midnight() {
+        let schedule = Schedule::new(vec![entry(20.0, Activity::Sleep), entry(7.0, Activity::Work)]);
+        let activity = |hour| schedule.entry(schedule.active(hour).unwrap()).unwrap().activity;
+        assert_eq!(activity(3.0), Activity::Sleep);
+        assert_eq!(activity(7.0), Activity::Work);
+        assert_eq!(activity(19.5), Activity::Work);
+        assert_eq!(activity(23.0), Activity::Sleep);
+        assert_eq!(Schedule::default().active(12.0), None);
+    }
+}
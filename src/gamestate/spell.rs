@@ -4,5 +4,53 @@ use serde::{Serialize, Deserialize};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Spell {
-    id: i32
-}
\ No newline at end of file
+    id: i32,
+    // what casting the spell does
+    pub ability: Ability
+}
+
+impl Spell {
+    pub fn new(id: i32, ability: Ability) -> Spell {
+        Spell { id, ability }
+    }
+}
+
+/// Abilities used on a target at a distance, aimed in targeting mode (see `gamelogic::targeting`)
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Ability {
+    // a ranged attack with the attack of the user
+    Shoot,
+    // a spell hitting the target and setting it on fire
+    Firebolt,
+}
+
+impl Ability {
+    /// Fields the target may be away at most, measured like steps are
+    pub fn range(&self) -> f64 {
+        match self {
+            Ability::Shoot => 6.0,
+            Ability::Firebolt => 4.0,
+        }
+    }
+
+    /// Mana used up, abilities without cost or cooldown need no `CasterComponent`
+    pub fn mana_cost(&self) -> i32 {
+        match self {
+            Ability::Shoot => 0,
+            Ability::Firebolt => 5,
+        }
+    }
+
+    /// Turns until the ability can be used again, abilities with a cooldown need a `CasterComponent`
+    pub fn cooldown_turns(&self) -> u32 {
+        match self {
+            Ability::Shoot => 0,
+            Ability::Firebolt => 3,
+        }
+    }
+
+    /// Key of the ability's name in the locale
+    pub fn locale_key(&self) -> String {
+        format!("ability.{:?}", self)
+    }
+}
@@ -14,7 +14,8 @@ pub struct Status {
 pub enum StatusType {
     BaseStatusModifier(BaseStatusModifier),
     BaseStatusMuliplier(BaseStatusMuliplier),
-    Invincible
+    Invincible,
+    Burning     // takes fire damage each turn
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -0,0 +1,93 @@
+/// Scales how fast the game runs compared to real time, e.g. for slow motion effects
+/// or fast forwarding while resting. A scale of 1.0 runs one game update per update
+/// of the event loop
+#[derive(Debug, Clone)]
+pub struct TimeScale {
+    // scale used while no effect is active
+    pub base: f64,
+    // temporary scale and the number of event loop updates it stays active
+    effect: Option<(f64, u32)>,
+    // fraction of a game update carried over to the next event loop update
+    accumulator: f64
+}
+
+impl TimeScale {
+    pub fn new() -> TimeScale {
+        TimeScale {
+            base: 1.0,
+            effect: None,
+            accumulator: 0.0
+        }
+    }
+
+    /// The current scale
+    pub fn scale(&self) -> f64 {
+        match self.effect {
+            Some((scale, _)) => scale,
+            None => self.base
+        }
+    }
+
+    /// Overrides the scale for a while, replacing any active effect
+    ///
+    /// ### Arguments
+    /// * `scale`   - The scale during the effect
+    /// * `updates` - Number of event loop updates the effect lasts
+    ///
+    pub fn set_effect(&mut self, scale: f64, updates: u32) {
+        self.effect = Some((scale.max(0.0), updates));
+    }
+
+    /// Advances by one event loop update
+    ///
+    /// ### Returns
+    /// The number of game updates to run for this event loop update, fractions of
+    /// updates are carried over
+    pub fn advance(&mut self) -> u32 {
+        self.accumulator += self.scale();
+        let steps = self.accumulator.floor();
+        self.accumulator -= steps;
+
+        if let Some((_, remaining)) = &mut self.effect {
+            *remaining = remaining.saturating_sub(1);
+            if *remaining == 0 {
+                self.effect = None;
+            }
+        }
+        steps as u32
+    }
+}
+
+impl Default for TimeScale {
+    fn default() -> TimeScale {
+        TimeScale::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normal_speed_runs_one_step() {
+        let mut time_scale = TimeScale::new();
+        assert_eq!((0..10).map(|_| time_scale.advance()).sum::<u32>(), 10);
+    }
+
+    #[test]
+    fn test_slow_motion_carries_fractions() {
+        let mut time_scale = TimeScale::new();
+        time_scale.set_effect(0.25, 8);
+        let steps: Vec<u32> = (0..8).map(|_| time_scale.advance()).collect();
+        assert_eq!(steps, vec![0, 0, 0, 1, 0, 0, 0, 1]);
+        // effect is over
+        assert_eq!(time_scale.scale(), 1.0);
+    }
+
+    #[test]
+    fn test_fast_forward() {
+        let mut time_scale = TimeScale::new();
+        time_scale.base = 4.0;
+        assert_eq!(time_scale.advance(), 4);
+    }
+}
@@ -0,0 +1,28 @@
+extern crate serde;
+
+use serde::{Serialize, Deserialize};
+
+/// Weather of a floor
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Weather {
+    Clear,
+    Rain,   // douses fire
+    Snow,
+    Fog     // reduces the view radius
+}
+
+impl Weather {
+    /// Factor the view radius of entities is multiplied with under this weather
+    pub fn view_radius_factor(&self) -> f64 {
+        match self {
+            Weather::Fog  => 0.5,
+            Weather::Snow => 0.8,
+            _ => 1.0
+        }
+    }
+
+    /// True if fire related status can not persist under this weather
+    pub fn douses_fire(&self) -> bool {
+        *self == Weather::Rain
+    }
+}
@@ -0,0 +1,123 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::InputAction;
+use crate::ecs::ECS;
+use crate::gamelogic;
+use crate::input;
+use crate::logging;
+use crate::render::RenderConfig;
+use crate::replay::{RecordedInput, ReplayPlayer, ReplayRecorder};
+use crate::UPDATES_PER_SECOND;
+
+/// How long and how fast a headless game runs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeadlessOptions {
+    // number of game updates to run, None runs until the replay ends or forever
+    pub ticks: Option<u64>,
+    // updates at UPDATES_PER_SECOND like a windowed game instead of as fast as possible
+    pub realtime: bool,
+    // the player waits whenever it is its turn and no replay controls it, otherwise
+    // the game stands still until an input arrives
+    pub player_waits: bool
+}
+
+/// Runs the game loop without a window, textures or audio, e.g. for tests or balance
+/// simulations. The player is controlled by a replay, without one it can wait whenever
+/// it is its turn, so the rest of the dungeon keeps acting
+///
+/// ### Arguments
+/// * `ecs_`            - The game to run
+/// * `replay_player`   - Replay whose inputs are used for the player
+/// * `replay_recorder` - Records all actions performed, including the waiting
+/// * `options`         - How long and how fast to run
+///
+/// ### Returns
+/// The number of game updates that were run
+pub fn run(ecs_: &mut ECS, mut replay_player: Option<ReplayPlayer>, mut replay_recorder: Option<ReplayRecorder>, options: HeadlessOptions) -> u64 {
+    // the view is not shown, but actions like toggling the minimap still change it
+    let mut render_conf = RenderConfig {
+        scale: ecs_.settings.display.scale,
+        window_xs: ecs_.settings.display.window_width,
+        window_ys: ecs_.settings.display.window_height,
+        focused_entity: ecs_.get_player_entity(),
+        focused_point: None,
+        show_minimap: true
+    };
+    let replay_given = replay_player.is_some();
+    let update_duration = Duration::from_secs_f64(1.0 / UPDATES_PER_SECOND as f64);
+
+    let mut tick: u64 = 0;
+    while options.ticks.is_none_or(|ticks| tick < ticks) {
+        let start = Instant::now();
+        let _span = logging::span("update", tick, update_duration);
+
+        let mut inputs = Vec::new();
+        if let Some(replay) = &mut replay_player {
+            inputs = replay.inputs_until(tick);
+            if replay.is_finished() {
+                info!("Replay finished after {} updates", tick);
+                replay_player = None;
+            }
+        } else if replay_given && options.ticks.is_none() {
+            break;
+        } else if options.player_waits && is_player_waiting(ecs_) {
+            inputs.push(RecordedInput::new(tick, InputAction::Wait));
+        }
+        for recorded in inputs {
+            recorded.restore_cursor(ecs_);
+            if let Some(recorder) = &mut replay_recorder {
+                recorder.record(&recorded, ecs_);
+            }
+            input::handle_action(recorded.action, ecs_, &mut render_conf);
+        }
+
+        let time_scale = ecs_.time_scale.scale();
+        gamelogic::update(ecs_);
+        // particles are never shown, but still spawned by effects
+        if gamelogic::is_simulating(ecs_) {
+            ecs_.particles.update(time_scale / UPDATES_PER_SECOND as f64);
+        }
+        tick += 1;
+        // nothing happens any more once the run ended
+        if ecs_.run_summary.is_some() {
+            info!("The run ended after {} updates", tick);
+            break;
+        }
+
+        if options.realtime {
+            if let Some(remaining) = update_duration.checked_sub(start.elapsed()) {
+                thread::sleep(remaining);
+            }
+        }
+    }
+    info!("Simulated {} updates, {:.1} seconds of playtime", tick, ecs_.playtime);
+    tick
+}
+
+fn is_player_waiting(ecs_: &mut ECS) -> bool {
+    match ecs_.get_player_entity() {
+        Some(player) => gamelogic::scheduling::can_act(ecs_, player),
+        None => false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder;
+
+    #[test]
+    fn test_headless_run_advances_game() {
+        let mut ecs_ = ECS::new();
+        builder::dungeon::create_test_floor(&mut ecs_);
+        builder::dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        let turns_before = ecs_.world_clock.turns;
+
+        let options = HeadlessOptions { ticks: Some(UPDATES_PER_SECOND * 2), realtime: false, player_waits: true };
+        assert_eq!(run(&mut ecs_, None, None, options), UPDATES_PER_SECOND * 2);
+        assert!((ecs_.playtime - 2.0).abs() < 1e-6);
+        // the player waited, so turns passed
+        assert!(ecs_.world_clock.turns > turns_before);
+    }
+}
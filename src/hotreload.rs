@@ -0,0 +1,109 @@
+extern crate notify;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::error::{Error, Result};
+
+pub const ASSETS_DIR: &str = "./assets";
+// changes are reported once a file was left alone for this long, so a file being
+// saved is not read half written
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// An asset file that changed, by the kind of asset it is and its name within the
+/// directory of that kind
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetChange {
+    // a file in `assets/textures/`
+    Texture(String),
+    // a file in `assets/sounds/`, music lies in the `music/` subdirectory
+    Sound(String),
+    // the language of a file in `assets/locale/`
+    Locale(String),
+}
+
+/// Watches the assets directory for changed files, so they can be loaded again while
+/// the game runs
+pub struct AssetWatcher {
+    // stops watching when dropped
+    _watcher: RecommendedWatcher,
+    events: Receiver<DebouncedEvent>,
+    root: PathBuf
+}
+
+impl AssetWatcher {
+    pub fn new(dir: &Path) -> Result<AssetWatcher> {
+        let root = fs::canonicalize(dir)?;
+        let (sender, events) = channel();
+        let mut watcher = notify::watcher(sender, DEBOUNCE).map_err(|err| watch_error(&root, err))?;
+        watcher.watch(&root, RecursiveMode::Recursive).map_err(|err| watch_error(&root, err))?;
+        info!("Watching {:?} for changed assets", root);
+        Ok(AssetWatcher {
+            _watcher: watcher,
+            events,
+            root
+        })
+    }
+
+    /// The assets that changed since the last call, each one once
+    pub fn changes(&self) -> Vec<AssetChange> {
+        let mut changes = Vec::new();
+        for event in self.events.try_iter() {
+            let path = match event {
+                DebouncedEvent::Create(path) | DebouncedEvent::Write(path) | DebouncedEvent::Rename(_, path) => path,
+                DebouncedEvent::Error(err, path) => {
+                    warn!("Error while watching {:?}: {}", path.as_ref().unwrap_or(&self.root), err);
+                    continue;
+                },
+                _ => continue
+            };
+            if let Some(change) = path.strip_prefix(&self.root).ok().and_then(classify) {
+                if !changes.contains(&change) {
+                    debug!("Asset changed: {:?}", change);
+                    changes.push(change);
+                }
+            }
+        }
+        changes
+    }
+}
+
+fn watch_error(root: &Path, err: notify::Error) -> Error {
+    Error::Asset { path: root.to_path_buf(), reason: err.to_string() }
+}
+
+/// The asset a file is, from its path relative to the assets directory
+fn classify(path: &Path) -> Option<AssetChange> {
+    let mut components = path.iter().filter_map(|component| component.to_str());
+    let kind = components.next()?;
+    let name = components.collect::<Vec<&str>>().join("/");
+    if name.is_empty() {
+        return None;
+    }
+    match kind {
+        "textures" => Some(AssetChange::Texture(name)),
+        "sounds" => Some(AssetChange::Sound(name)),
+        "locale" => Path::new(&name).file_stem()
+                                    .and_then(|stem| stem.to_str())
+                                    .map(|language| AssetChange::Locale(language.to_string())),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_asset_paths() {
+        assert_eq!(classify(Path::new("textures/player.png")), Some(AssetChange::Texture("player.png".to_string())));
+        assert_eq!(classify(Path::new("sounds/music/boss.wav")), Some(AssetChange::Sound("music/boss.wav".to_string())));
+        assert_eq!(classify(Path::new("locale/en.yaml")), Some(AssetChange::Locale("en".to_string())));
+        assert_eq!(classify(Path::new("fonts/DejaVuSansMono.ttf")), None);
+        assert_eq!(classify(Path::new("textures")), None);
+    }
+}
@@ -0,0 +1,259 @@
+use piston::Button;
+use piston::Key;
+
+use crate::config::{self, InputAction, SettingsEntry};
+use crate::ecs::ECS;
+use crate::event::bus::GameEvent;
+use crate::gamelogic::{cheats::Cheat, new_game_plus, scheduling, switch_mode};
+use crate::gamestate::{mode::GameMode, movement::Direction};
+use crate::persistence::slots::{self, SaveSlot};
+
+/// Maps a pressed button to the action bound to it. While the settings menu or the
+/// cheat console is open the button is handled by them instead
+///
+/// ### Returns
+/// The bound action, None if there is none or the button was used by the menu
+pub fn button_action(press_args: &Button, ecs_: &mut ECS) -> Option<InputAction> {
+   // no input while the screen changes
+   if ecs_.transition.is_some() {
+       return None;
+   }
+   if ecs_.cheat_console.open {
+       return cheat_console_controls(press_args, ecs_);
+   }
+   // the menus read keys directly, so they can not be locked out by bindings
+   match ecs_.game_mode {
+       GameMode::Settings => {
+           settings_controls(press_args, ecs_);
+           return None;
+       },
+       GameMode::LoadMenu => {
+           load_menu_controls(press_args, ecs_);
+           return None;
+       },
+       GameMode::GameOver => {
+           game_over_controls(press_args, ecs_);
+           return None;
+       },
+       _ => {}
+   }
+
+   match press_args {
+       Button::Keyboard(key) => ecs_.settings.action_for(*key),
+       _ => None
+   }
+}
+
+/// Combines move keys held at the same time into diagonal steps, e.g. holding left
+/// and pressing up steps up left. Only works where key releases are reported
+#[derive(Debug, Default)]
+pub struct MoveChord {
+    // vertical and horizontal move actions held, in the order they were pressed
+    held: Vec<Direction>,
+    // event loop updates since the last move key was pressed
+    held_updates: u64
+}
+
+impl MoveChord {
+    /// Takes the action of a pressed key
+    ///
+    /// ### Returns
+    /// The diagonal move if the action moves vertically or horizontally while the
+    /// other one is held, else the action itself
+    pub fn press(&mut self, action: InputAction) -> InputAction {
+        let direction = match action.move_direction() {
+            Some(direction) if !direction.is_diagonal() => direction,
+            _ => return action
+        };
+        self.held.retain(|held| *held != direction);
+        let diagonal = self.held.iter().rev().find_map(|held| held.combine(direction));
+        self.held.push(direction);
+        self.held_updates = 0;
+        diagonal.map_or(action, InputAction::move_to)
+    }
+
+    /// Takes the action of a released key
+    pub fn release(&mut self, action: InputAction) {
+        if let Some(direction) = action.move_direction() {
+            self.held.retain(|held| *held != direction);
+        }
+    }
+
+    /// The move to repeat while move keys are held, so the player keeps walking. Only
+    /// once the player can act again with nothing queued and the keys are held longer than
+    /// the move repeat delay of the settings, so a short press is one step. Called once
+    /// per event loop update
+    pub fn repeat(&mut self, ecs_: &mut ECS) -> Option<InputAction> {
+        let last = *self.held.last()?;
+        self.held_updates += 1;
+        if self.held_updates <= ecs_.settings.accessibility.move_repeat_updates() {
+            return None;
+        }
+        // while aiming, the keys move the cursor one field per press
+        if ecs_.game_mode != GameMode::Dungeon || ecs_.transition.is_some() || !ecs_.action_queue.is_empty() || ecs_.targeting.is_some() {
+            return None;
+        }
+        if !ecs_.get_player_entity().is_some_and(|player| scheduling::can_act(ecs_, player)) {
+            return None;
+        }
+        let direction = self.held.iter().rev().skip(1).find_map(|held| held.combine(last)).unwrap_or(last);
+        Some(InputAction::move_to(direction))
+    }
+}
+
+/// Controls of the cheat console: letters are typed, backspace deletes the last one and
+/// enter performs the typed cheat like its function key would. The console key closes it
+fn cheat_console_controls(button: &Button, ecs_: &mut ECS) -> Option<InputAction> {
+    let key = match button {
+        Button::Keyboard(key) => *key,
+        _ => return None
+    };
+    let console = &mut ecs_.cheat_console;
+    match key {
+        Key::Return => {
+            let command = std::mem::take(&mut console.input);
+            console.open = false;
+            match Cheat::parse(&command) {
+                Some(cheat) => return Some(cheat_action(cheat)),
+                None => warn!("There is no cheat {:?}", command)
+            }
+        },
+        Key::Backspace => {
+            console.input.pop();
+        },
+        Key::Backquote => {
+            console.open = false;
+            console.input.clear();
+        },
+        _ => {
+            if let Some(letter) = char::from_u32(u32::from(key)).filter(|letter| letter.is_ascii_lowercase()) {
+                console.input.push(letter);
+            }
+        }
+    }
+    None
+}
+
+// the action of a cheat's function key, so typed cheats are performed the same way
+fn cheat_action(cheat: Cheat) -> InputAction {
+    match cheat {
+        Cheat::GodMode => InputAction::CheatGodMode,
+        Cheat::RevealMap => InputAction::CheatRevealMap,
+        Cheat::TeleportToStairs => InputAction::CheatTeleportToStairs,
+        Cheat::GrantExperience => InputAction::CheatGrantExperience,
+        Cheat::GrantGold => InputAction::CheatGrantGold,
+        Cheat::GrantItems => InputAction::CheatGrantItems,
+        Cheat::SpawnWave => InputAction::CheatSpawnWave,
+    }
+}
+
+/// Controls of the settings menu: up and down select an entry, left and right change
+/// it, enter toggles it or starts rebinding a key. Leaving the menu saves the settings
+fn settings_controls(button: &Button, ecs_: &mut ECS) {
+    let key = match button {
+        Button::Keyboard(key) => *key,
+        _ => return
+    };
+    let entries = config::settings_entries();
+    let selected = entries[ecs_.settings_menu.selected.min(entries.len() - 1)];
+
+    if ecs_.settings_menu.rebinding {
+        if let SettingsEntry::Binding(action) = selected {
+            ecs_.settings.bind(action, key);
+        }
+        ecs_.settings_menu.rebinding = false;
+        ecs_.events.publish(GameEvent::UiClick);
+        return;
+    }
+
+    match key {
+        Key::Up => {
+            ecs_.settings_menu.selected = (ecs_.settings_menu.selected + entries.len() - 1) % entries.len();
+        },
+        Key::Down => {
+            ecs_.settings_menu.selected = (ecs_.settings_menu.selected + 1) % entries.len();
+        },
+        Key::Left => ecs_.settings.adjust(selected, -1),
+        Key::Right => ecs_.settings.adjust(selected, 1),
+        Key::Return => match selected {
+            SettingsEntry::Binding(_) => ecs_.settings_menu.rebinding = true,
+            _ => ecs_.settings.adjust(selected, 1)
+        },
+        Key::Backspace => {
+            if let Err(err) = ecs_.settings.save() {
+                warn!("Could not save settings to {:?}: {}", config::settings_path(), err);
+            }
+            switch_mode(ecs_, GameMode::Dungeon);
+        },
+        _ => return
+    }
+    ecs_.events.publish(GameEvent::UiClick);
+}
+
+/// Controls of the load menu: up and down select a slot, enter loads it and S saves
+/// the current game into it. The autosave slot can not be saved to
+fn load_menu_controls(button: &Button, ecs_: &mut ECS) {
+    let key = match button {
+        Button::Keyboard(key) => *key,
+        _ => return
+    };
+    let slot_count = ecs_.load_menu.slots.len().max(1);
+
+    match key {
+        Key::Up => {
+            ecs_.load_menu.selected = (ecs_.load_menu.selected + slot_count - 1) % slot_count;
+        },
+        Key::Down => {
+            ecs_.load_menu.selected = (ecs_.load_menu.selected + 1) % slot_count;
+        },
+        Key::Return => {
+            let slot = match ecs_.load_menu.selected_slot() {
+                Some(info) if info.metadata.is_some() => info.slot.clone(),
+                _ => return
+            };
+            match slots::load(ecs_, &slot) {
+                Ok(()) => { switch_mode(ecs_, GameMode::Dungeon); },
+                Err(err) => warn!("Could not load {:?}: {}", slot, err)
+            }
+        },
+        Key::S => {
+            let slot = match ecs_.load_menu.selected_slot() {
+                Some(info) if info.slot != SaveSlot::Auto => info.slot.clone(),
+                _ => return
+            };
+            let format = ecs_.settings.save_format;
+            if let Err(err) = slots::save(ecs_, &slot, format) {
+                warn!("Could not save to {:?}: {}", slot.path(format), err);
+            }
+            // show the new metadata
+            let selected = ecs_.load_menu.selected;
+            ecs_.load_menu = slots::LoadMenu::open();
+            ecs_.load_menu.selected = selected;
+        },
+        Key::Backspace => {
+            // after a run ended there is no dungeon to go back to
+            let mode = if ecs_.run_summary.is_some() { GameMode::GameOver } else { GameMode::Dungeon };
+            switch_mode(ecs_, mode);
+        },
+        _ => return
+    }
+    ecs_.events.publish(GameEvent::UiClick);
+}
+
+/// Controls of the game over screen: enter opens the load menu, e.g. to go back to the
+/// autosave. After a victory up and down select equipment and space keeps it for the
+/// next new-game-plus run. Quitting works like everywhere else
+fn game_over_controls(button: &Button, ecs_: &mut ECS) {
+    match button {
+        Button::Keyboard(Key::Return) => {
+            ecs_.load_menu = slots::LoadMenu::open();
+            if switch_mode(ecs_, GameMode::LoadMenu) {
+                ecs_.events.publish(GameEvent::UiClick);
+            }
+        },
+        Button::Keyboard(Key::Up) => new_game_plus::select_choice(ecs_, -1),
+        Button::Keyboard(Key::Down) => new_game_plus::select_choice(ecs_, 1),
+        Button::Keyboard(Key::Space) if new_game_plus::toggle_kept(ecs_) => ecs_.events.publish(GameEvent::UiClick),
+        _ => {}
+    }
+}
@@ -0,0 +1,280 @@
+// reading the keyboard of the game window or the terminal
+#[cfg(any(feature = "window", feature = "terminal"))]
+pub mod keys;
+
+use crate::config::{self, InputAction};
+use crate::ecs::{ECS, Entity};
+use crate::event::bus::GameEvent;
+use crate::gamelogic::*;
+use crate::persistence::{history, slots};
+use crate::render::RenderConfig;
+use crate::gamestate::mode::GameMode;
+
+/// Performs an input action of the player. Game actions pressed while the player can not
+/// act yet are queued and performed once it can (see `scheduling::ActionQueue`)
+pub fn handle_action(action: InputAction, ecs_: &mut ECS, conf: &mut RenderConfig) {
+   if ecs_.transition.is_some() {
+       return;
+   }
+   if view_controls(action, conf) {
+       ecs_.events.publish(GameEvent::UiClick);
+       return;
+   }
+   if action == InputAction::ToggleProfiler {
+       ecs_.profiler.visible = !ecs_.profiler.visible;
+       return;
+   }
+   if action == InputAction::ToggleCheatConsole {
+       if ecs_.dev_mode {
+           ecs_.cheat_console.open = !ecs_.cheat_console.open;
+       } else {
+           debug!("The cheat console is only available in games started with --dev");
+       }
+       return;
+   }
+   if action == InputAction::Pause {
+       ecs_.paused = !ecs_.paused;
+       ecs_.events.publish(GameEvent::UiClick);
+       return;
+   }
+   if action == InputAction::ToggleInspector {
+       ecs_.inspector.visible = !ecs_.inspector.visible;
+       ecs_.inspector.selected = None;
+       return;
+   }
+   if action == InputAction::UndoTurn {
+       // only to step through bugs, turns are not remembered outside of debug mode
+       if ecs_.debug_mode && !history::undo_turn(ecs_) {
+           debug!("There is no turn to undo");
+       }
+       return;
+   }
+   if ecs_.paused {
+       return;
+   }
+   if ecs_.game_mode == GameMode::Map {
+       map_controls(action, ecs_);
+       return;
+   }
+   if ecs_.game_mode == GameMode::Overworld {
+       overworld_controls(action, ecs_);
+       return;
+   }
+   // the game over screen reads keys directly, see `keys::button_action`
+   if ecs_.game_mode == GameMode::GameOver {
+       return;
+   }
+   // any input interrupts resting
+   if let Some(player) = ecs_.get_player_entity().filter(|player| is_resting(ecs_, *player)) {
+       stop_resting(ecs_, player);
+       return;
+   }
+   // the player has no control during cutscenes, but can still open menus
+   if cutscene::is_playing(ecs_) {
+       if !menu_controls(action, ecs_) {
+           cutscene_controls(action, ecs_);
+       }
+       return;
+   }
+   if is_aiming_action(action, ecs_) {
+       if let Some(player_action) = aiming_controls(action, ecs_) {
+           perform_or_queue(ecs_, player_action);
+       }
+       return;
+   }
+   if menu_controls(action, ecs_) {
+       return;
+   }
+   if let Some(player) = ecs_.get_player_entity() {
+       if ecs_.actor_component.get(player).is_some() {
+           if scheduling::can_act(ecs_, player) {
+               dungeon_actor_controls(action, ecs_);
+           } else {
+               dungeon_passive_controls(action, ecs_);
+           }
+       }
+   }
+}
+
+/// Controls changing what is shown, independent of the game state
+///
+/// ### Returns
+/// True if the action was handled, else false
+///
+fn view_controls(action: InputAction, conf: &mut RenderConfig) -> bool {
+    match action {
+        InputAction::ToggleMinimap => {
+            conf.show_minimap = !conf.show_minimap;
+            true
+        },
+        _ => false
+    }
+}
+
+fn map_controls(action: InputAction, ecs_: &mut ECS) {
+    if action == InputAction::ToggleMap && switch_mode(ecs_, GameMode::Dungeon) {
+        ecs_.events.publish(GameEvent::UiClick);
+    }
+}
+
+/// Controls of the overworld: moves select a node, confirming travels there or enters the
+/// node the players are at
+fn overworld_controls(action: InputAction, ecs_: &mut ECS) {
+    if let Some(direction) = action.move_direction() {
+        overworld::select(ecs_, direction);
+        ecs_.events.publish(GameEvent::UiClick);
+        return;
+    }
+    if matches!(action, InputAction::ConfirmTarget | InputAction::Interact) && overworld::confirm(ecs_) {
+        ecs_.events.publish(GameEvent::UiClick);
+    }
+}
+
+/// Controls opening other screens from the dungeon
+///
+/// ### Returns
+/// True if the action was handled, else false
+///
+fn menu_controls(action: InputAction, ecs_: &mut ECS) -> bool {
+    let mode = match action {
+        InputAction::ToggleMap => GameMode::Map,
+        InputAction::Settings => {
+            ecs_.settings_menu = config::SettingsMenu::default();
+            GameMode::Settings
+        },
+        InputAction::LoadMenu => {
+            ecs_.load_menu = slots::LoadMenu::open();
+            GameMode::LoadMenu
+        },
+        _ => return false
+    };
+    if switch_mode(ecs_, mode) {
+        ecs_.events.publish(GameEvent::UiClick);
+    }
+    true
+}
+
+/// Controls during a cutscene: confirming closes its dialogue, cancelling skips it
+fn cutscene_controls(action: InputAction, ecs_: &mut ECS) {
+    match action {
+        InputAction::ConfirmTarget | InputAction::Interact => cutscene::confirm_dialogue(ecs_),
+        InputAction::CancelTarget => cutscene::skip(ecs_),
+        _ => {}
+    }
+}
+
+/// Tests if an action aims an ability or uses the hotbar, see `aiming_controls`
+pub fn is_aiming_action(action: InputAction, ecs_: &ECS) -> bool {
+    ecs_.targeting.is_some() || action.ability().is_some() || action.hotbar_slot().is_some()
+}
+
+/// Controls aiming abilities and using the hotbar. They only resolve the action of the
+/// player, so in multiplayer games the client aims and the server performs the action
+///
+/// ### Returns
+/// The action to perform, None while aiming goes on or if there is nothing to do
+pub fn aiming_controls(action: InputAction, ecs_: &mut ECS) -> Option<PlayerAction> {
+    if ecs_.targeting.is_some() {
+        return targeting_controls(action, ecs_);
+    }
+    if let Some(ability) = action.ability() {
+        if !targeting::start_targeting(ecs_, ability) {
+            debug!("Could not start aiming {:?}", ability);
+        }
+        return None;
+    }
+    action.hotbar_slot().and_then(|index| hotbar::activate_slot(ecs_, index))
+}
+
+/// Controls while aiming an ability: moves steer the cursor, confirming uses the ability
+/// on the target under it once the player can act, other keys are ignored
+fn targeting_controls(action: InputAction, ecs_: &mut ECS) -> Option<PlayerAction> {
+    if let Some(direction) = action.move_direction() {
+        targeting::move_cursor(ecs_, direction);
+        return None;
+    }
+    match action {
+        InputAction::ConfirmTarget | InputAction::Attack => targeting::confirm_targeting(ecs_),
+        InputAction::CancelTarget => {
+            targeting::cancel_targeting(ecs_);
+            None
+        },
+        _ => match action.ability() {
+            // pressing the hotkey again stops aiming, another one aims that ability instead
+            Some(ability) if ecs_.targeting.is_some_and(|targeting| targeting.ability == ability) => {
+                targeting::cancel_targeting(ecs_);
+                None
+            },
+            Some(ability) => {
+                targeting::start_targeting(ecs_, ability);
+                None
+            },
+            None => action.hotbar_slot().and_then(|index| {
+                // another slot is used instead
+                targeting::cancel_targeting(ecs_);
+                hotbar::activate_slot(ecs_, index)
+            })
+        }
+    }
+}
+
+/// Performs an action of the player if it can act, else queues it
+fn perform_or_queue(ecs_: &mut ECS, player_action: PlayerAction) {
+    if let Some(player) = ecs_.get_player_entity() {
+        if scheduling::can_act(ecs_, player) {
+            perform_action(ecs_, player, player_action);
+        } else {
+            ecs_.action_queue.push(player_action);
+        }
+    }
+}
+
+fn dungeon_actor_controls(action: InputAction, ecs_: &mut ECS) {
+    if let Some(player) = ecs_.get_player_entity() {
+        if let Some(player_action) = player_action_for(action, ecs_, player) {
+            perform_action(ecs_, player, player_action);
+        }
+    }
+}
+
+/// Maps an input action to the action a player entity performs for it
+///
+/// ### Returns
+/// The player action, None if the input is no game action or there is nothing to do,
+/// e.g. no corpse to loot
+pub fn player_action_for(action: InputAction, ecs_: &ECS, player: Entity) -> Option<PlayerAction> {
+    if let Some(direction) = action.move_direction() {
+        return Some(PlayerAction::Move(direction));
+    }
+    if let Some(order) = action.party_order() {
+        return Some(PlayerAction::Order(order));
+    }
+    match action {
+        InputAction::Attack => Some(PlayerAction::Attack),
+        InputAction::Wait => Some(PlayerAction::Wait),
+        InputAction::Rest => Some(PlayerAction::Rest),
+        InputAction::Interact => interaction::find_interactable(ecs_, player).map(PlayerAction::Interact),
+        InputAction::PickUp => Some(PlayerAction::PickUp),
+        // without an inventory screen, the last item of the inventory is dropped
+        InputAction::Drop => ecs_.inventory_component.get(player)
+                                 .and_then(|inventory_c| inventory_c.items.len().checked_sub(1))
+                                 .map(PlayerAction::Drop),
+        InputAction::CheatGodMode => Some(PlayerAction::Cheat(cheats::Cheat::GodMode)),
+        InputAction::CheatRevealMap => Some(PlayerAction::Cheat(cheats::Cheat::RevealMap)),
+        InputAction::CheatTeleportToStairs => Some(PlayerAction::Cheat(cheats::Cheat::TeleportToStairs)),
+        InputAction::CheatGrantExperience => Some(PlayerAction::Cheat(cheats::Cheat::GrantExperience)),
+        InputAction::CheatGrantGold => Some(PlayerAction::Cheat(cheats::Cheat::GrantGold)),
+        InputAction::CheatGrantItems => Some(PlayerAction::Cheat(cheats::Cheat::GrantItems)),
+        InputAction::CheatSpawnWave => Some(PlayerAction::Cheat(cheats::Cheat::SpawnWave)),
+        _ => None
+    }
+}
+
+/// Queues the action of the player while it is still acting
+fn dungeon_passive_controls(action: InputAction, ecs_: &mut ECS) {
+    if let Some(player) = ecs_.get_player_entity() {
+        if let Some(player_action) = player_action_for(action, ecs_, player) {
+            ecs_.action_queue.push(player_action);
+        }
+    }
+}
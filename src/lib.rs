@@ -0,0 +1,40 @@
+pub mod assets;
+pub mod gamestate;
+pub mod gamelogic;
+pub mod event;
+pub mod render;
+pub mod builder;
+pub mod ecs;
+pub mod error;
+pub mod input;
+pub mod ut;
+pub mod locale;
+pub mod logging;
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod config;
+pub mod replay;
+pub mod persistence;
+pub mod net;
+pub mod headless;
+#[cfg(feature = "hot-reload")]
+pub mod hotreload;
+#[cfg(feature = "window")]
+pub mod window;
+#[cfg(feature = "terminal")]
+pub mod terminal;
+
+extern crate piston;
+#[cfg(feature = "window")]
+extern crate graphics;
+#[cfg(feature = "window")]
+extern crate glutin_window;
+#[cfg(feature = "window")]
+extern crate opengl_graphics;
+extern crate rand;
+
+#[macro_use]
+extern crate log;
+
+// game ticks per second
+pub const UPDATES_PER_SECOND: u64 = 30;
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+extern crate serde_yaml;
+
+// english texts are built in, so there is always a text to fall back to
+const FALLBACK: &str = include_str!("../assets/locale/en.yaml");
+
+/// Translations of all player facing texts, looked up by key
+pub struct Locale {
+    pub language: String,
+    texts: HashMap<String, String>,
+    fallback: HashMap<String, String>
+}
+
+impl Locale {
+    /// Loads the texts of a language from `assets/locale/<language>.yaml`. Keys missing
+    /// in the file (or the whole file) fall back to the built in english texts
+    pub fn load(language: &str) -> Locale {
+        let path = Path::new("./assets/locale/").join(format!("{}.yaml", language));
+        let texts = match fs::read_to_string(&path) {
+            Ok(content) => parse(&content).unwrap_or_else(|err| {
+                warn!("Could not parse locale {:?}: {}", path, err);
+                HashMap::new()
+            }),
+            Err(err) => {
+                warn!("Could not read locale {:?}: {}", path, err);
+                HashMap::new()
+            }
+        };
+
+        Locale {
+            language: language.to_string(),
+            texts,
+            fallback: parse(FALLBACK).expect("Built in locale is broken")
+        }
+    }
+
+    /// Looks up the text for a key. Unknown keys are returned as they are, which
+    /// also allows plain texts to be passed through
+    pub fn text(&self, key: &str) -> String {
+        self.texts.get(key)
+                  .or_else(|| self.fallback.get(key))
+                  .cloned()
+                  .unwrap_or_else(|| key.to_string())
+    }
+
+    /// Looks up the text for a key and fills in its parameters
+    ///
+    /// ### Arguments
+    /// * `key`    - The key of the text
+    /// * `params` - Pairs of parameter name and value, replacing `{name}` in the text
+    ///
+    pub fn format(&self, key: &str, params: &[(&str, &dyn ToString)]) -> String {
+        let mut text = self.text(key);
+        for (name, value) in params {
+            text = text.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        text
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Locale {
+        let fallback = parse(FALLBACK).expect("Built in locale is broken");
+        Locale {
+            language: "en".to_string(),
+            texts: fallback.clone(),
+            fallback
+        }
+    }
+}
+
+fn parse(content: &str) -> Result<HashMap<String, String>, serde_yaml::Error> {
+    // an empty file is no map at all
+    if content.trim().is_empty() {
+        return Ok(HashMap::new());
+    }
+    serde_yaml::from_str(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Locale;
+
+    #[test]
+    fn format_parameters() {
+        let locale = Locale::default();
+        assert_eq!(locale.format("log.died", &[("name", &"Dummy")]), "Dummy died");
+        assert_eq!(locale.format("hud.health", &[("current", &5), ("maximum", &10)]), "HP 5/10");
+    }
+
+    #[test]
+    fn unknown_key_passes_through() {
+        assert_eq!(Locale::default().text("Some plain text"), "Some plain text");
+    }
+}
@@ -0,0 +1,268 @@
+extern crate dirs;
+extern crate log;
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::config::LogSettings;
+
+// a log file growing beyond this is moved aside when the game starts
+const MAX_LOG_FILE_SIZE: u64 = 8 * 1024 * 1024;
+
+struct LoggerState {
+    start: Instant,
+    level: LevelFilter,
+    // sorted by length, the first module a target lies in decides its level
+    modules: Vec<(String, LevelFilter)>,
+    console: bool,
+    file: Option<File>
+}
+
+/// Writes log lines to stderr and the log file, prefixed with the time since the
+/// start and the span they were written in
+struct GameLogger {
+    state: Mutex<Option<LoggerState>>
+}
+
+static LOGGER: GameLogger = GameLogger { state: Mutex::new(None) };
+
+thread_local! {
+    // innermost span of the current thread
+    static CURRENT_SPAN: Cell<Option<(&'static str, u64)>> = const { Cell::new(None) };
+    static LOGGED_ONCE: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+impl LoggerState {
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.modules.iter()
+            .find(|(module, _)| is_in_module(target, module))
+            .map_or(self.level, |(_, level)| *level)
+    }
+}
+
+impl Log for GameLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match &*self.state.lock().unwrap() {
+            Some(state) => metadata.level() <= state.level_for(metadata.target()),
+            None => false
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        let mut guard = self.state.lock().unwrap();
+        let state = match &mut *guard {
+            Some(state) if record.level() <= state.level_for(record.target()) => state,
+            _ => return
+        };
+
+        let span = CURRENT_SPAN.with(|span| span.get())
+                               .map(|(name, number)| format!(" [{} {}]", name, number))
+                               .unwrap_or_default();
+        let line = format!("{:>10.3} {:<5} [{}]{} {}\n",
+                           state.start.elapsed().as_secs_f64(), record.level(), record.target(), span, record.args());
+        if state.console {
+            let _ = io::stderr().write_all(line.as_bytes());
+        }
+        if let Some(file) = &mut state.file {
+            // a failing log file can not be reported anywhere, so it is closed
+            if file.write_all(line.as_bytes()).is_err() {
+                state.file = None;
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(Some(file)) = self.state.lock().unwrap().as_mut().map(|state| state.file.as_mut()) {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Installs the logger with the default settings. Called once at startup, before
+/// anything is logged
+pub fn init() {
+    *LOGGER.state.lock().unwrap() = Some(LoggerState {
+        start: Instant::now(),
+        level: LevelFilter::Info,
+        modules: Vec::new(),
+        console: true,
+        file: None
+    });
+    log::set_max_level(LevelFilter::Info);
+    if log::set_logger(&LOGGER).is_err() {
+        eprintln!("A logger was already installed");
+    }
+}
+
+/// Applies log levels and opens or closes the log file
+///
+/// ### Arguments
+/// * `settings` - Levels and whether to write to the log file
+///
+pub fn configure(settings: &LogSettings) {
+    let file = if settings.to_file {
+        match open_log_file() {
+            Ok(file) => Some(file),
+            Err(err) => {
+                warn!("Could not open log file {:?}: {}", log_path(), err);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut modules: Vec<(String, LevelFilter)> = settings.modules.iter()
+        .map(|(module, level)| (module.clone(), *level))
+        .collect();
+    modules.sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+    // nothing above the most verbose level is ever passed to the logger
+    let max_level = modules.iter().map(|(_, level)| *level).fold(settings.level, Ord::max);
+
+    if let Some(state) = &mut *LOGGER.state.lock().unwrap() {
+        state.level = settings.level;
+        state.modules = modules;
+        state.file = file;
+    }
+    log::set_max_level(max_level);
+}
+
+/// Turns writing to stderr on or off, e.g. while the terminal shows the game. The log
+/// file is still written
+///
+/// ### Returns
+/// True if log lines were written to stderr before
+pub fn set_console(console: bool) -> bool {
+    match &mut *LOGGER.state.lock().unwrap() {
+        Some(state) => std::mem::replace(&mut state.console, console),
+        None => false
+    }
+}
+
+/// Location of the log file, `<data dir>/rustac/logs/rustac.log`
+pub fn log_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rustac")
+        .join("logs")
+        .join("rustac.log")
+}
+
+/// Opens the log file for appending. A file that grew too large is kept as
+/// `rustac.old.log` and a new one is started
+fn open_log_file() -> io::Result<File> {
+    let path = log_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    if fs::metadata(&path).is_ok_and(|metadata| metadata.len() > MAX_LOG_FILE_SIZE) {
+        fs::rename(&path, path.with_extension("old.log"))?;
+    }
+    OpenOptions::new().create(true).append(true).open(&path)
+}
+
+fn is_in_module(target: &str, module: &str) -> bool {
+    target == module || (target.starts_with(module) && target[module.len()..].starts_with("::"))
+}
+
+/// Parses a level filter like `RUST_LOG`, e.g. `info,rustac::net=debug`, into the
+/// log settings. Entries that can not be parsed are skipped with a warning
+///
+/// ### Arguments
+/// * `settings` - The settings to change
+/// * `filter`   - Comma separated levels, optionally prefixed with `<module>=`
+///
+pub fn apply_filter(settings: &mut LogSettings, filter: &str) {
+    for entry in filter.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (module, level) = match entry.find('=') {
+            Some(i) => (Some(&entry[..i]), &entry[i + 1..]),
+            None => (None, entry)
+        };
+        match (module, level.parse::<LevelFilter>()) {
+            (Some(module), Ok(level)) => { settings.modules.insert(module.to_string(), level); },
+            (None, Ok(level)) => settings.level = level,
+            (_, Err(_)) => warn!("Unknown log level {:?}", entry)
+        }
+    }
+}
+
+/// True the first time it is called with a key, so problems that are found again
+/// every frame, like missing textures, are only logged once
+pub fn first_time(key: &str) -> bool {
+    LOGGED_ONCE.with(|logged| logged.borrow_mut().insert(key.to_string()))
+}
+
+/// A numbered part of the game loop, like an update or a frame. Everything logged
+/// while it is alive is marked with it, and it logs a warning if it takes longer than
+/// its budget, so slow updates can be related to what happened in them
+pub struct Span {
+    name: &'static str,
+    number: u64,
+    budget: Duration,
+    start: Instant,
+    outer: Option<(&'static str, u64)>
+}
+
+/// Starts a span, which ends when the returned value is dropped
+///
+/// ### Arguments
+/// * `name`   - What the span measures, e.g. `update`
+/// * `number` - Which one of them it is, e.g. the tick
+/// * `budget` - Time the span may take without being reported
+///
+pub fn span(name: &'static str, number: u64, budget: Duration) -> Span {
+    let outer = CURRENT_SPAN.with(|span| span.replace(Some((name, number))));
+    Span {
+        name,
+        number,
+        budget,
+        start: Instant::now(),
+        outer
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        if elapsed > self.budget {
+            warn!("Slow {}: took {:.1} ms of {:.1} ms", self.name, elapsed.as_secs_f64() * 1000.0, self.budget.as_secs_f64() * 1000.0);
+        } else {
+            trace!("{} {} took {:.2} ms", self.name, self.number, elapsed.as_secs_f64() * 1000.0);
+        }
+        CURRENT_SPAN.with(|span| span.set(self.outer));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_levels() {
+        let mut settings = LogSettings::default();
+        apply_filter(&mut settings, "warn, rustac::net=debug,rustac::net::client=off,rustac::gamelogic=loud");
+        assert_eq!(settings.level, LevelFilter::Warn);
+        assert_eq!(settings.modules.len(), 2);
+
+        let mut modules: Vec<_> = settings.modules.into_iter().collect();
+        modules.sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+        let state = LoggerState {
+            start: Instant::now(),
+            level: settings.level,
+            modules,
+            console: false,
+            file: None
+        };
+        assert_eq!(state.level_for("rustac::net::server"), LevelFilter::Debug);
+        assert_eq!(state.level_for("rustac::net::client"), LevelFilter::Off);
+        assert_eq!(state.level_for("rustac::network"), LevelFilter::Warn);
+        assert_eq!(state.level_for("rustac::render"), LevelFilter::Warn);
+    }
+}
@@ -1,160 +1,204 @@
-mod gamestate;
-mod gamelogic;
-mod event;
-mod render;
-mod builder;
-mod ecs;
-mod input;
-mod ut;
-
-extern crate piston;
-extern crate graphics;
-extern crate glutin_window;
-extern crate opengl_graphics;
+extern crate rand;
+extern crate rustac;
 
 #[macro_use]
 extern crate log;
-extern crate simple_logger;
-
-use piston::window::WindowSettings;
-use glutin_window::GlutinWindow as Window;
-use opengl_graphics::{GlGraphics, OpenGL};
-use piston::event_loop::*;
-use piston::input::*;
 
-use render::{render_game, sprite, RenderConfig};
-use gamestate::components;
-use event::{Hitbox, HitboxType};
-use crate::gamestate::LocationVec;
+use std::path::{Path, PathBuf};
 
-// game ticks per second
-const UPDATES_PER_SECOND: u64 = 30;
+use rustac::{builder, config, ecs, gamelogic, gamestate, headless, locale, logging, net, replay};
+use rustac::persistence::{history, profile};
+use rustac::persistence::slots::{self, SaveSlot};
+use rustac::render::animation;
+#[cfg(feature = "terminal")]
+use rustac::terminal;
+#[cfg(feature = "window")]
+use rustac::window;
 
 fn main() {
-    // initialize logger
-    simple_logger::init().unwrap();
+    // initialize logger, the levels of the settings are applied once they are loaded
+    logging::init();
 
     // setup of main data structures
     let mut ecs_ = ecs::ECS::new();
-    let mut render_conf = RenderConfig {
-        scale: 100.0,
-        window_xs: 1000,
-        window_ys: 1000,
-        focused_entity: None
+    ecs_.locale = locale::Locale::load("en");
+    ecs_.animations = animation::AnimationLibrary::load(Path::new(animation::SHEETS_DIR));
+    ecs_.settings = config::Settings::load();
+    ecs_.profile = profile::PlayerProfile::load();
+
+    // `--seed <number>` or `--daily` choose the seed of the game, otherwise it is random.
+    // `--record <file>` records the inputs of this session, `--replay <file>` plays them back
+    // with the game options they were recorded with instead of the local ones.
+    // `--load <slot>` continues a saved game, `--save <slot>` saves into a slot when quitting.
+    // `--server <address>` hosts a multiplayer game without a window,
+    // `--connect <address>` joins one, optionally with `--name <name>`.
+    // `--headless` runs the game without a window, which is the default in builds
+    // without the `window` feature, for `--ticks <n>` updates or until the replay ends.
+    // `--realtime` runs it at the normal speed instead of as fast as possible.
+    // `--terminal` plays in the terminal instead of a window (`terminal` feature)
+    // `--dev` allows cheats with function keys, `--cheat <names>` performs some at the start,
+    // e.g. `--cheat god,stairs` (see `gamelogic::cheats::Cheat::command`).
+    // `--debug` remembers the last turns, so they can be undone with the UndoTurn key.
+    // `--new-game-plus` starts a run with stronger creatures and the equipment kept from the
+    // last won run, `--class <name>` starts it with a class unlocked in the profile, e.g. `mage`.
+    // `--log <filter>` overrides the log levels of the settings for this session,
+    // e.g. `--log debug,rustac::net=trace`
+    let args: Vec<String> = std::env::args().collect();
+    let arg_value = |name: &str| args.iter()
+                                     .position(|arg| arg == name)
+                                     .and_then(|i| args.get(i + 1));
+    let mut log_settings = ecs_.settings.log.clone();
+    if let Some(filter) = arg_value("--log") {
+        logging::apply_filter(&mut log_settings, filter);
+    }
+    logging::configure(&log_settings);
+    let replay_player = match arg_value("--replay").map(|path| (path, replay::Replay::load(&PathBuf::from(path)))) {
+        Some((_, Ok(replay))) => Some(replay::ReplayPlayer::new(replay)),
+        Some((path, Err(err))) => {
+            error!("Could not load replay {:?}: {}", path, err);
+            return;
+        },
+        None => None
     };
+    let seed = match &replay_player {
+        Some(player) => player.seed(),
+        None if args.iter().any(|arg| arg == "--daily") => gamestate::rng::daily_seed(),
+        None => arg_value("--seed").and_then(|seed| seed.parse().ok())
+                                   .unwrap_or_else(rand::random)
+    };
+    let options = match &replay_player {
+        Some(player) => player.options().clone(),
+        None => {
+            let new_game_plus = args.iter().any(|arg| arg == "--new-game-plus") && gamelogic::new_game_plus::start(&mut ecs_);
+            let class = match arg_value("--class").map(|name| (name, gamestate::class::Class::parse(name))) {
+                Some((_, Some(class))) if ecs_.profile.has_class(class) => Some(class),
+                Some((name, _)) => {
+                    warn!("The class {:?} is not unlocked", name);
+                    None
+                },
+                None => None
+            };
+            replay::GameOptions {
+                difficulty: ecs_.settings.difficulty,
+                diagonal_rule: ecs_.settings.diagonal_rule,
+                scheduling: ecs_.settings.scheduling,
+                permadeath: ecs_.settings.permadeath,
+                dev_mode: args.iter().any(|arg| arg == "--dev"),
+                new_game_plus: if new_game_plus { Some(ecs_.profile.kept_equipment.clone()) } else { None },
+                class
+            }
+        }
+    };
+    let replay_recorder = arg_value("--record").map(|path| replay::ReplayRecorder::new(seed, options.clone(), PathBuf::from(path)));
+    info!("Using seed {}", seed);
+    ecs_.rng = gamestate::rng::GameRng::new(seed);
+
+    ecs_.difficulty = options.difficulty;
+    ecs_.diagonal_rule = options.diagonal_rule;
+    ecs_.scheduling = options.scheduling;
+    ecs_.permadeath = options.permadeath;
+    ecs_.debug_mode = args.iter().any(|arg| arg == "--debug");
+    ecs_.dev_mode = options.dev_mode;
+    ecs_.new_game_plus = options.new_game_plus.is_some();
 
-    info!("Creating window with {:?}", render_conf);
-    
-    // setup of opengl window
-    let opengl  = OpenGL::V3_2;
-	let mut window: Window = WindowSettings::new(
-            "rustac",
-            [render_conf.window_xs, render_conf.window_ys]
-        )
-        .graphics_api(opengl)
-        .exit_on_esc(true)
-        .build()
-        .unwrap();
-
-
-	let ref mut gl  = GlGraphics::new(opengl);
-    let sprite_textures = sprite::setup_sprite_textures();
-	
     // BEGIN test code
-
-    use crate::builder::dungeon;
-
-    dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 10, 8);
-    dungeon::create_empty_room(&mut ecs_, 0.0, 8.0, 8, 10);
-    dungeon::delete_dungeon_entities(&mut ecs_, 
-                                     gamestate::dungeon::DungeonElement::Wall,
-                                     LocationVec { x: 4.0, y: 7.0 });
-    dungeon::delete_dungeon_entities(&mut ecs_, 
-                                     gamestate::dungeon::DungeonElement::Wall,
-                                     LocationVec { x: 4.0, y: 8.0 });
-    dungeon::create_attack_dummy(&mut ecs_, 3.0, 4.0);
-
-    
-    // player
-    let player = create_test_dummy_player(&mut ecs_);
-
-    render_conf.focused_entity = Some(player);
-
+    builder::dungeon::create_test_floor(&mut ecs_);
     // END test code
 
-	//Main loop
-
-    let mut events = Events::new(EventSettings::new());
-    events.set_ups(UPDATES_PER_SECOND);
-
-    let mut button_buffer: Option<Button> = None;
-
-    while let Some(e) = events.next(&mut window) {
-
-        if e.press_args().is_some() || button_buffer.is_some() {
-            debug!("{:?}", &button_buffer);
-            if let Some(p) = e.press_args() {
-                button_buffer = input::handle_input(&p, &mut ecs_);
-            } else {
-                button_buffer = input::handle_input(&button_buffer.unwrap(), &mut ecs_);
+    // the server spawns a player for every client that joins
+    if let Some(address) = arg_value("--server") {
+        match net::server::Server::bind(address) {
+            Ok(server) => server.run(ecs_),
+            Err(err) => error!("Could not start server on {}: {}", address, err)
+        }
+        return;
+    }
+    let player = builder::dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+    if let Some(kept) = &options.new_game_plus {
+        gamelogic::new_game_plus::hand_over_equipment(&mut ecs_, player, kept);
+    }
+    if let Some(class) = options.class {
+        ecs_.class_component.set(player, gamestate::components::ClassComponent { class, level: 1, experience: 0 });
+        let slots = gamelogic::hotbar::default_slots(&ecs_, player);
+        ecs_.hotbar_component.set(player, gamestate::components::HotbarComponent { slots });
+    }
+    builder::dungeon::create_companion(&mut ecs_, 2.0, 1.0);
+    builder::director::populate_floor(&mut ecs_);
+
+    if let Some(slot) = arg_value("--load") {
+        let slot = SaveSlot::parse(slot);
+        if let Err(err) = slots::load(&mut ecs_, &slot) {
+            error!("Could not load {:?}: {}", slot, err);
+        }
+    }
+    if let Some(commands) = arg_value("--cheat") {
+        for command in commands.split(',') {
+            match gamelogic::cheats::Cheat::parse(command) {
+                Some(cheat) => gamelogic::perform_player_action(&mut ecs_, gamelogic::PlayerAction::Cheat(cheat)),
+                None => warn!("There is no cheat {:?}", command)
             }
         }
-
-        if let Some(_) = e.update_args() {
-            gamelogic::update_entity_positions(&mut ecs_);
+    }
+    // the world as it is before the first turn
+    history::record_turn(&mut ecs_);
+    let quit_slot = arg_value("--save").map(|slot| SaveSlot::parse(slot));
+
+    let in_terminal = cfg!(feature = "terminal") && args.iter().any(|arg| arg == "--terminal");
+    let headless = args.iter().any(|arg| arg == "--headless") || (!cfg!(feature = "window") && !in_terminal);
+
+    // new games begin with the intro. Replays start without it, so they play the same
+    // in a window and headless
+    let replaying = replay_player.is_some() || replay_recorder.is_some();
+    if !headless && !replaying && arg_value("--load").is_none() && arg_value("--connect").is_none() {
+        if let Some(player) = ecs_.get_player_entity() {
+            let intro = gamelogic::cutscene::intro(&ecs_, player);
+            gamelogic::cutscene::start(&mut ecs_, intro);
         }
-
-        if let Some(r) = e.render_args() {
-            render_game(gl, &r, &mut ecs_, &sprite_textures, &render_conf);
+    }
+
+    if headless {
+        let options = headless::HeadlessOptions {
+            ticks: arg_value("--ticks").and_then(|ticks| ticks.parse().ok()),
+            realtime: args.iter().any(|arg| arg == "--realtime"),
+            player_waits: true
+        };
+        headless::run(&mut ecs_, replay_player, replay_recorder, options);
+    } else if in_terminal {
+        #[cfg(feature = "terminal")]
+        terminal::run(&mut ecs_, replay_player, replay_recorder);
+    } else {
+        #[cfg(feature = "window")]
+        {
+            let net_client = match arg_value("--connect") {
+                Some(address) => {
+                    let name = arg_value("--name").cloned().unwrap_or_else(|| ecs_.locale.text("creature.player"));
+                    match net::client::Client::connect(address, &name) {
+                        Ok(client) => Some(client),
+                        Err(err) => {
+                            error!("Could not connect to server {}: {}", address, err);
+                            return;
+                        }
+                    }
+                },
+                None => None
+            };
+            window::run(&mut ecs_, replay_player, replay_recorder, net_client);
         }
+    }
 
-        gamelogic::check_and_perform_end_turn(&mut ecs_);
-	}
+    if let Some(slot) = quit_slot {
+        let format = ecs_.settings.save_format;
+        if let Err(err) = slots::save(&mut ecs_, &slot, format) {
+            error!("Could not save to {:?}: {}", slot.path(format), err);
+        }
+    }
+    // only the window changes settings, simulations do not count for the profile
+    if !headless {
+        if let Err(err) = ecs_.settings.save() {
+            warn!("Could not save settings to {:?}: {}", config::settings_path(), err);
+        }
+        if let Err(err) = ecs_.profile.save() {
+            warn!("Could not save the profile to {:?}: {}", profile::profile_path(), err);
+        }
+    }
 }
-    
-
-fn create_test_dummy_player(ecs_: &mut ecs::ECS) -> ecs::Entity {
-
-    let player = ecs_.allocator.allocate();
-
-    ecs_.actor_component.set(player, components::ActorComponent {
-        state: gamestate::actor::ActorState::WaitingForTurn,
-        turn: 0,
-        max_actions: 1,
-        performed_actions: 0
-    });
-
-    ecs_.location_component.set(player, components::LocationComponent {
-        location: LocationVec {x: 1.0, y: 1.0},
-        direction: gamestate::movement::Direction::Down,
-        move_intent: None,
-        hitbox: Some(Hitbox::new_small(HitboxType::Creature))
-    });
-
-    ecs_.render_component.set(player, components::RenderComponent {
-        base_sprite: "player".to_string(),
-        base_sprite_size: 1.0,
-        animation: None,
-        visible: true,
-        render_layer: 2
-    });
-
-    ecs_.player_component.set(player, components::PlayerComponent {
-        stage_level: 0,
-        gold: 0,
-        progression_flags: std::collections::HashMap::new()
-    });
-
-    ecs_.basestats_component.set(player, components::BaseStatsComponent {
-        attack: 10,
-        defense: 10,
-        magic: 10,
-        resistence: 10
-    });
-
-    ecs_.name_component.set(player, components::NameComponent {
-        name: "Player".to_string()
-    });
-
-    player
-}
\ No newline at end of file
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::TcpStream;
+
+use crate::ecs::{ECS, Entity};
+use crate::net::{ClientMessage, Connection, RemoteAction, ServerMessage, WorldUpdate};
+use crate::persistence::delta::{self, SyncId};
+
+/// Client of a multiplayer game. The game is not simulated locally, the client sends
+/// the player's actions to the server and mirrors the world the server sends back
+/// into the local ECS, where it is rendered as usual
+pub struct Client {
+    connection: Connection<ServerMessage>,
+    // the client's own player, known once the server welcomed the client
+    player: Option<SyncId>,
+    // local entities mirroring the server's ones
+    entities: HashMap<SyncId, Entity>
+}
+
+impl Client {
+    /// Connects to a server and joins its game
+    ///
+    /// ### Arguments
+    /// * `address` - Address of the server, e.g. `"127.0.0.1:7777"`
+    /// * `name`    - Name of the player's character
+    ///
+    pub fn connect(address: &str, name: &str) -> io::Result<Client> {
+        let mut connection = Connection::new(TcpStream::connect(address)?)?;
+        connection.send(&ClientMessage::Join { name: name.to_string() })?;
+        info!("Connected to {}", address);
+        Ok(Client {
+            connection,
+            player: None,
+            entities: HashMap::new()
+        })
+    }
+
+    /// Sends an action of the player to the server, which performs it once it is the
+    /// player's turn
+    pub fn send_action(&mut self, action: RemoteAction) {
+        if let Err(err) = self.connection.send(&ClientMessage::Action(action)) {
+            warn!("Could not send {:?} to the server: {}", action, err);
+        }
+    }
+
+    /// Applies everything the server sent since the last call to the local ECS
+    ///
+    /// ### Returns
+    /// False once the connection to the server is lost
+    pub fn update(&mut self, ecs_: &mut ECS) -> bool {
+        for message in self.connection.receive() {
+            match message {
+                ServerMessage::Welcome { player } => {
+                    // the local world is replaced by the server's
+                    for entity in ecs_.allocator.live_indices() {
+                        ecs_.allocator.deallocate(entity);
+                    }
+                    ecs_.index_cache.clear();
+                    self.entities.clear();
+                    self.player = Some(player);
+                },
+                ServerMessage::Update(update) => self.apply_update(ecs_, update)
+            }
+        }
+        self.connection.is_connected()
+    }
+
+    /// Tells the server the player leaves the game
+    pub fn leave(&mut self) {
+        let _ = self.connection.send(&ClientMessage::Leave);
+        self.connection.close();
+    }
+
+    fn apply_update(&mut self, ecs_: &mut ECS, update: WorldUpdate) {
+        ecs_.world_clock = update.shared.world_clock;
+        ecs_.floor_type = update.shared.floor_type;
+        ecs_.weather = update.shared.weather;
+        delta::apply_delta(ecs_, update.delta, &mut self.entities);
+
+        // the own player is the one the local game treats as player, e.g. for the hud
+        if let Some(player) = self.player.and_then(|id| self.entities.get(&id)) {
+            ecs_.index_cache.insert("Player".to_string(), *player);
+        }
+    }
+}
@@ -0,0 +1,227 @@
+extern crate bincode;
+extern crate serde;
+
+pub mod client;
+pub mod server;
+
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+
+use crate::config::InputAction;
+use crate::ecs::{ECS, Entity};
+use crate::gamelogic::PlayerAction;
+use crate::gamestate::{clock, dungeon, spell::Ability, weather, LocationVec};
+use crate::input;
+use crate::persistence::delta::{SyncId, WorldDelta, WorldDeltaRef};
+
+// larger messages are refused, protects against broken or malicious peers
+const MAX_MESSAGE_SIZE: u32 = 64 * 1024 * 1024;
+
+/// Messages sent from a client to the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ClientMessage {
+    // first message of a client, the server spawns a player for it
+    Join { name: String },
+    Action(RemoteAction),
+    Leave,
+}
+
+/// An action a client sends for its player. Most are input actions the server resolves,
+/// aiming and the hotbar are local to the client, which sends what they resolved to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RemoteAction {
+    Input(InputAction),
+    // an ability aimed on the client, see `targeting::confirm_targeting`
+    UseAbility(Ability, LocationVec),
+    // the consumable slot of the client's hotbar
+    UseConsumable,
+}
+
+impl RemoteAction {
+    /// The remote action of a player action resolved by the client, None for player
+    /// actions the server resolves from input actions
+    pub fn of(player_action: PlayerAction) -> Option<RemoteAction> {
+        match player_action {
+            PlayerAction::UseAbility(ability, location) => Some(RemoteAction::UseAbility(ability, location)),
+            PlayerAction::UseConsumable => Some(RemoteAction::UseConsumable),
+            _ => None
+        }
+    }
+
+    /// The action a client's player performs on the server
+    ///
+    /// ### Returns
+    /// The player action, None if there is nothing to do, see `input::player_action_for`
+    pub fn player_action(&self, ecs_: &ECS, player: Entity) -> Option<PlayerAction> {
+        match *self {
+            RemoteAction::Input(action) => input::player_action_for(action, ecs_, player),
+            RemoteAction::UseAbility(ability, location) => Some(PlayerAction::UseAbility(ability, location)),
+            RemoteAction::UseConsumable => Some(PlayerAction::UseConsumable)
+        }
+    }
+}
+
+/// Global state of the world every client needs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedState {
+    pub world_clock: clock::WorldClock,
+    pub floor_type: dungeon::FloorType,
+    pub weather: weather::Weather,
+}
+
+/// Changes of the world around a client's player after a game update
+#[derive(Debug, Deserialize)]
+pub struct WorldUpdate {
+    pub shared: SharedState,
+    // changes of the entities near the player since the last update, entities that got
+    // out of reach or are gone are removed
+    pub delta: WorldDelta
+}
+
+/// Borrowed counterpart of `WorldUpdate` built by the server
+#[derive(Serialize)]
+pub struct WorldUpdateRef<'a> {
+    pub shared: SharedState,
+    pub delta: WorldDeltaRef<'a>
+}
+
+/// Messages sent from the server to a client
+#[derive(Debug, Deserialize)]
+pub enum ServerMessage {
+    // answer to `ClientMessage::Join` with the client's player
+    Welcome { player: SyncId },
+    Update(WorldUpdate),
+}
+
+/// Borrowed counterpart of `ServerMessage`, serializes to the same format
+#[derive(Serialize)]
+pub enum ServerMessageRef<'a> {
+    Welcome { player: SyncId },
+    Update(WorldUpdateRef<'a>),
+}
+
+/// A connection to another peer. Messages are received by a background thread, so
+/// receiving never blocks the game loop. Sending blocks until the message is written
+pub struct Connection<In> {
+    stream: TcpStream,
+    incoming: Receiver<In>,
+    connected: bool
+}
+
+impl<In: DeserializeOwned + Send + 'static> Connection<In> {
+    pub fn new(stream: TcpStream) -> io::Result<Connection<In>> {
+        stream.set_nonblocking(false)?;
+        stream.set_nodelay(true)?;
+        let mut reader = stream.try_clone()?;
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            loop {
+                let message = read_message(&mut reader);
+                match message {
+                    Ok(message) => if sender.send(message).is_err() {
+                        break;
+                    },
+                    Err(err) => {
+                        debug!("Connection closed: {}", err);
+                        break;
+                    }
+                }
+            }
+        });
+        Ok(Connection {
+            stream,
+            incoming,
+            connected: true
+        })
+    }
+
+    /// Sends a message, the connection is considered closed if that fails
+    pub fn send<T: Serialize>(&mut self, message: &T) -> io::Result<()> {
+        let result = write_message(&mut self.stream, message);
+        if result.is_err() {
+            self.connected = false;
+        }
+        result
+    }
+
+    /// Takes all messages received since the last call
+    pub fn receive(&mut self) -> Vec<In> {
+        let mut messages = Vec::new();
+        loop {
+            match self.incoming.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.connected = false;
+                    break;
+                }
+            }
+        }
+        messages
+    }
+
+    /// Closes the connection, which also ends the receiving thread
+    pub fn close(&mut self) {
+        if self.connected {
+            self.connected = false;
+            let _ = self.stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+}
+
+/// Writes a message as its length followed by its bincode encoding
+fn write_message<T: Serialize, W: Write>(writer: &mut W, message: &T) -> io::Result<()> {
+    let data = bincode::serialize(message).map_err(io::Error::other)?;
+    writer.write_all(&(data.len() as u32).to_le_bytes())?;
+    writer.write_all(&data)?;
+    writer.flush()
+}
+
+fn read_message<T: DeserializeOwned, R: Read>(reader: &mut R) -> io::Result<T> {
+    let mut length = [0; 4];
+    reader.read_exact(&mut length)?;
+    let length = u32::from_le_bytes(length);
+    if length > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("message of {} bytes is too large", length)));
+    }
+    let mut data = vec![0; length as usize];
+    reader.read_exact(&mut data)?;
+    bincode::deserialize(&data).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_framing() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &ClientMessage::Join { name: "Alice".to_string() }).unwrap();
+        write_message(&mut buffer, &ClientMessage::Action(RemoteAction::Input(InputAction::Attack))).unwrap();
+        let firebolt = RemoteAction::UseAbility(Ability::Firebolt, LocationVec { x: 4.0, y: 3.0 });
+        write_message(&mut buffer, &ClientMessage::Action(firebolt)).unwrap();
+
+        let mut reader = &buffer[..];
+        match read_message(&mut reader).unwrap() {
+            ClientMessage::Join { name } => assert_eq!(name, "Alice"),
+            other => panic!("unexpected message {:?}", other)
+        }
+        match read_message(&mut reader).unwrap() {
+            ClientMessage::Action(action) => assert_eq!(action, RemoteAction::Input(InputAction::Attack)),
+            other => panic!("unexpected message {:?}", other)
+        }
+        match read_message(&mut reader).unwrap() {
+            ClientMessage::Action(action) => assert_eq!(action, firebolt),
+            other => panic!("unexpected message {:?}", other)
+        }
+        assert!(read_message::<ClientMessage, _>(&mut reader).is_err());
+    }
+}
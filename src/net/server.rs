@@ -0,0 +1,264 @@
+use std::io;
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::builder;
+use crate::ecs::{ECS, Entity};
+use crate::gamelogic;
+use crate::gamestate::components;
+use crate::logging;
+use crate::net::{ClientMessage, Connection, RemoteAction, ServerMessageRef, SharedState, WorldUpdateRef};
+use crate::persistence::delta::{self, ChangeTracker};
+
+// distance in fields up to which entities are sent to a client
+const NET_VIEW_DISTANCE: f64 = 24.0;
+// the first player spawns here, later ones next to it
+const SPAWN_X: f64 = 1.0;
+const SPAWN_Y: f64 = 1.0;
+
+struct RemoteClient {
+    connection: Connection<ClientMessage>,
+    // spawned once the client joined
+    player: Option<Entity>,
+    // action which could not be performed yet because the player is still acting
+    buffered_action: Option<RemoteAction>,
+    // what the client knows about the world, new clients get everything
+    tracker: ChangeTracker
+}
+
+/// Authoritative server of a multiplayer game. It runs the game loop without a window,
+/// performs the actions clients send for their players and sends every client the
+/// changes of the world around its player after each update
+pub struct Server {
+    listener: TcpListener,
+    clients: Vec<RemoteClient>,
+    // number of updates so far
+    tick: u64
+}
+
+impl Server {
+    pub fn bind(address: &str) -> io::Result<Server> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        info!("Server listening on {}", listener.local_addr()?);
+        Ok(Server {
+            listener,
+            clients: Vec::new(),
+            tick: 0
+        })
+    }
+
+    /// Runs the game loop at `UPDATES_PER_SECOND` until the process is ended
+    pub fn run(mut self, mut ecs_: ECS) {
+        let update_duration = Duration::from_secs_f64(1.0 / crate::UPDATES_PER_SECOND as f64);
+        loop {
+            let start = Instant::now();
+            self.update(&mut ecs_);
+            if let Some(remaining) = update_duration.checked_sub(start.elapsed()) {
+                thread::sleep(remaining);
+            }
+        }
+    }
+
+    /// Accepts new clients, performs the actions they sent, advances the game by one
+    /// update and sends the new state to all clients
+    pub fn update(&mut self, ecs_: &mut ECS) {
+        let _span = logging::span("update", self.tick, Duration::from_secs_f64(1.0 / crate::UPDATES_PER_SECOND as f64));
+        self.accept_clients();
+        self.handle_messages(ecs_);
+        gamelogic::update(ecs_);
+        self.send_updates(ecs_);
+        self.tick += 1;
+    }
+
+    fn accept_clients(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, address)) => match Connection::new(stream) {
+                    Ok(connection) => {
+                        info!("Client connected from {}", address);
+                        self.clients.push(RemoteClient {
+                            connection,
+                            player: None,
+                            buffered_action: None,
+                            tracker: ChangeTracker::new()
+                        });
+                    },
+                    Err(err) => warn!("Could not set up connection to {}: {}", address, err)
+                },
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    warn!("Could not accept client: {}", err);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn handle_messages(&mut self, ecs_: &mut ECS) {
+        for i in 0..self.clients.len() {
+            for message in self.clients[i].connection.receive() {
+                match message {
+                    ClientMessage::Join { name } => self.join(ecs_, i, name),
+                    ClientMessage::Action(action) => {
+                        // any input interrupts resting, like in a local game
+                        match self.clients[i].player.filter(|player| gamelogic::is_resting(ecs_, *player)) {
+                            Some(player) => gamelogic::stop_resting(ecs_, player),
+                            None => self.clients[i].buffered_action = Some(action)
+                        }
+                    },
+                    ClientMessage::Leave => self.clients[i].connection.close()
+                }
+            }
+
+            let client = &mut self.clients[i];
+            if let Some(player) = client.player {
+                let waiting = gamelogic::scheduling::can_act(ecs_, player);
+                if waiting {
+                    if let Some(action) = client.buffered_action.take() {
+                        if let Some(player_action) = action.player_action(ecs_, player) {
+                            gamelogic::perform_action(ecs_, player, player_action);
+                        }
+                    }
+                }
+            }
+        }
+
+        // players of clients that left are removed from the game
+        for client in self.clients.iter().filter(|client| !client.connection.is_connected()) {
+            if let Some(player) = client.player {
+                info!("{} left the game", crate::ut::name_or_id(ecs_, player));
+                ecs_.allocator.deallocate(player);
+            }
+        }
+        self.clients.retain(|client| client.connection.is_connected());
+    }
+
+    /// Spawns the player of a client and tells the client which one it is
+    fn join(&mut self, ecs_: &mut ECS, client: usize, name: String) {
+        if self.clients[client].player.is_some() {
+            return;
+        }
+        let player_count = self.clients.iter().filter(|client| client.player.is_some()).count();
+        let player = builder::dungeon::create_test_player(ecs_, SPAWN_X + player_count as f64, SPAWN_Y);
+        info!("{} joined the game", name);
+        ecs_.name_component.set(player, components::NameComponent { name });
+
+        let sync_id = delta::sync_id(ecs_, player);
+        let client = &mut self.clients[client];
+        client.player = Some(player);
+        if let Err(err) = client.connection.send(&ServerMessageRef::Welcome { player: sync_id }) {
+            warn!("Could not welcome client: {}", err);
+        }
+    }
+
+    fn send_updates(&mut self, ecs_: &mut ECS) {
+        let shared = SharedState {
+            world_clock: ecs_.world_clock.clone(),
+            floor_type: ecs_.floor_type,
+            weather: ecs_.weather
+        };
+
+        for i in 0..self.clients.len() {
+            let center = match self.clients[i].player.and_then(|player| ecs_.location_component.get(player)) {
+                Some(location_c) => location_c.location,
+                None => continue
+            };
+            // entities without a location are always sent
+            let nearby: Vec<Entity> = ecs_.allocator.live_indices()
+                .into_iter()
+                .filter(|entity| ecs_.location_component.get(*entity).is_none_or(|location_c| {
+                    let offset = location_c.location - center;
+                    offset.x.abs() <= NET_VIEW_DISTANCE && offset.y.abs() <= NET_VIEW_DISTANCE
+                }))
+                .collect();
+            for entity in nearby.iter() {
+                delta::sync_id(ecs_, *entity);
+            }
+
+            let client = &mut self.clients[i];
+            let update = WorldUpdateRef {
+                shared: shared.clone(),
+                delta: client.tracker.delta(ecs_, &nearby)
+            };
+            if let Err(err) = client.connection.send(&ServerMessageRef::Update(update)) {
+                warn!("Could not send update to client: {}", err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::InputAction;
+    use crate::gamestate::{components::CasterComponent, spell::Ability, LocationVec};
+    use crate::net::client::Client;
+
+    fn step(server: &mut Server, server_ecs: &mut ECS, client: &mut Client, client_ecs: &mut ECS) {
+        server.update(server_ecs);
+        thread::sleep(Duration::from_millis(2));
+        assert!(client.update(client_ecs));
+    }
+
+    fn join(server: &mut Server, server_ecs: &mut ECS, client: &mut Client, client_ecs: &mut ECS) {
+        for _ in 0..200 {
+            step(server, server_ecs, client, client_ecs);
+            if client_ecs.get_player_entity().is_some() {
+                return;
+            }
+        }
+        panic!("the client got no player");
+    }
+
+    #[test]
+    fn test_client_joins_and_moves() {
+        let mut server = Server::bind("127.0.0.1:0").unwrap();
+        let address = server.listener.local_addr().unwrap().to_string();
+        let mut server_ecs = ECS::new();
+        builder::dungeon::create_test_floor(&mut server_ecs);
+        let mut client = Client::connect(&address, "Alice").unwrap();
+        let mut client_ecs = ECS::new();
+
+        join(&mut server, &mut server_ecs, &mut client, &mut client_ecs);
+        let player = client_ecs.get_player_entity().unwrap();
+        assert_eq!(crate::ut::name_or_id(&client_ecs, player), "Alice");
+        assert_eq!(client_ecs.location_component.get(player).unwrap().location.x, SPAWN_X);
+
+        client.send_action(RemoteAction::Input(InputAction::MoveRight));
+        for _ in 0..200 {
+            step(&mut server, &mut server_ecs, &mut client, &mut client_ecs);
+            let player = client_ecs.get_player_entity().unwrap();
+            if client_ecs.location_component.get(player).unwrap().location.x == SPAWN_X + 1.0 {
+                return;
+            }
+        }
+        panic!("the player did not move");
+    }
+
+    #[test]
+    fn test_server_uses_abilities_aimed_by_clients() {
+        let mut server = Server::bind("127.0.0.1:0").unwrap();
+        let address = server.listener.local_addr().unwrap().to_string();
+        let mut server_ecs = ECS::new();
+        builder::dungeon::create_test_floor(&mut server_ecs);
+        let rat = builder::dungeon::create_rat(&mut server_ecs, SPAWN_X + 2.0, SPAWN_Y + 2.0);
+        let mut client = Client::connect(&address, "Alice").unwrap();
+        let mut client_ecs = ECS::new();
+
+        join(&mut server, &mut server_ecs, &mut client, &mut client_ecs);
+        let player = server.clients[0].player.unwrap();
+        server_ecs.caster_component.set(player, CasterComponent { current_mana: 5, maximum_mana: 5, spells: Vec::new(), cooldowns: Vec::new() });
+        client.send_action(RemoteAction::UseAbility(Ability::Firebolt, LocationVec { x: SPAWN_X + 2.0, y: SPAWN_Y + 2.0 }));
+        for _ in 0..200 {
+            step(&mut server, &mut server_ecs, &mut client, &mut client_ecs);
+            if server_ecs.caster_component.get(player).unwrap().current_mana == 0 {
+                let health_c = server_ecs.health_component.get(rat).unwrap();
+                assert!(health_c.current < health_c.maximum);
+                return;
+            }
+        }
+        panic!("the firebolt was not cast");
+    }
+}
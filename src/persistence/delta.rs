@@ -0,0 +1,192 @@
+extern crate serde;
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Serialize, Deserialize};
+
+use crate::ecs::{ECS, Entity};
+use crate::gamestate::components::SyncIdComponent;
+use crate::persistence::{self, EntitySnapshot, EntitySnapshotRef};
+
+/// Id of an entity that stays the same across processes and saves, unlike `Entity`
+pub type SyncId = u64;
+
+/// The sync id of an entity, assigning one if it has none yet
+pub fn sync_id(ecs_: &mut ECS, entity: Entity) -> SyncId {
+    if let Some(sync_id_c) = ecs_.sync_id_component.get(entity) {
+        return sync_id_c.id;
+    }
+    let id = ecs_.next_sync_id;
+    ecs_.next_sync_id += 1;
+    ecs_.sync_id_component.set(entity, SyncIdComponent { id });
+    id
+}
+
+/// Changes of one entity since the last delta
+#[derive(Debug, Deserialize)]
+pub struct EntityDelta {
+    pub id: SyncId,
+    // the entity is new to the receiver or lost a component, so it is recreated from
+    // the full snapshot instead of updating the existing one
+    pub replace: bool,
+    pub components: EntitySnapshot
+}
+
+/// Borrowed counterpart of `EntityDelta`, serializes to the same format
+#[derive(Serialize)]
+pub struct EntityDeltaRef<'a> {
+    id: SyncId,
+    replace: bool,
+    components: EntitySnapshotRef<'a>
+}
+
+/// Changes of a set of entities since the last delta
+#[derive(Debug, Default, Deserialize)]
+pub struct WorldDelta {
+    pub changed: Vec<EntityDelta>,
+    // entities that were part of the last delta but no longer are
+    pub removed: Vec<SyncId>
+}
+
+/// Borrowed counterpart of `WorldDelta`, serializes to the same format
+#[derive(Serialize)]
+pub struct WorldDeltaRef<'a> {
+    changed: Vec<EntityDeltaRef<'a>>,
+    removed: Vec<SyncId>
+}
+
+/// Remembers which state of the entities a receiver, e.g. a client or the autosave,
+/// already knows, so only the components that changed since are sent to it.
+/// A new tracker knows nothing, so its first delta contains full snapshots
+#[derive(Debug, Default)]
+pub struct ChangeTracker {
+    // component hashes of every entity known to the receiver
+    hashes: HashMap<SyncId, Vec<Option<u64>>>
+}
+
+impl ChangeTracker {
+    pub fn new() -> ChangeTracker {
+        ChangeTracker::default()
+    }
+
+    /// Captures the changes of the given entities since the last delta, the receiver
+    /// is assumed to apply them. Entities known from the last delta that are not given
+    /// are reported as removed
+    ///
+    /// ### Arguments
+    /// * `ecs_`     - The entity component system to capture from
+    /// * `entities` - All entities the receiver should know, see `sync_id`. Entities
+    ///   without a sync id are skipped
+    ///
+    pub fn delta<'a>(&mut self, ecs_: &'a ECS, entities: &[Entity]) -> WorldDeltaRef<'a> {
+        let mut changed = Vec::new();
+        let mut present = HashSet::new();
+        for entity in entities {
+            let id = match ecs_.sync_id_component.get(*entity) {
+                Some(sync_id_c) => sync_id_c.id,
+                None => continue
+            };
+            present.insert(id);
+            let current = persistence::component_hashes(ecs_, *entity);
+            let delta = match self.hashes.get(&id) {
+                Some(previous) if *previous == current => continue,
+                // components can not be removed from entities, only recreated without them
+                Some(previous) if !previous.iter().zip(&current).any(|(before, now)| before.is_some() && now.is_none()) => EntityDeltaRef {
+                    id,
+                    replace: false,
+                    components: persistence::capture_changes(ecs_, *entity, previous, &current)
+                },
+                _ => EntityDeltaRef {
+                    id,
+                    replace: true,
+                    components: persistence::capture_entity(ecs_, *entity)
+                }
+            };
+            changed.push(delta);
+            self.hashes.insert(id, current);
+        }
+
+        let mut removed: Vec<SyncId> = self.hashes.keys().filter(|id| !present.contains(id)).copied().collect();
+        removed.sort_unstable();
+        for id in removed.iter() {
+            self.hashes.remove(id);
+        }
+        WorldDeltaRef { changed, removed }
+    }
+}
+
+/// Applies a delta to the entities known to the receiver
+///
+/// ### Arguments
+/// * `ecs_`     - The entity component system to apply the delta to
+/// * `delta`    - The changes to apply
+/// * `entities` - The local entity of every sync id, updated for new and removed entities
+///
+pub fn apply_delta(ecs_: &mut ECS, delta: WorldDelta, entities: &mut HashMap<SyncId, Entity>) {
+    for entity_delta in delta.changed {
+        let existing = entities.get(&entity_delta.id).copied().filter(|entity| ecs_.allocator.is_live(*entity));
+        match existing {
+            Some(entity) if !entity_delta.replace => persistence::update_entity(ecs_, entity, entity_delta.components),
+            _ => {
+                if let Some(entity) = existing {
+                    ecs_.allocator.deallocate(entity);
+                }
+                entities.insert(entity_delta.id, persistence::restore_entity(ecs_, entity_delta.components));
+            }
+        }
+    }
+    for id in delta.removed {
+        if let Some(entity) = entities.remove(&id) {
+            ecs_.allocator.deallocate(entity);
+        }
+    }
+}
+
+/// The local entity of every sync id in the ECS, e.g. after loading a save
+pub fn sync_entities(ecs_: &ECS) -> HashMap<SyncId, Entity> {
+    ecs_.allocator.live_indices()
+                  .into_iter()
+                  .filter_map(|entity| ecs_.sync_id_component.get(entity).map(|sync_id_c| (sync_id_c.id, entity)))
+                  .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::components::{HealthComponent, NameComponent};
+
+    fn send(tracker: &mut ChangeTracker, ecs_: &ECS, receiver: &mut ECS, entities: &mut HashMap<SyncId, Entity>) -> usize {
+        let data = bincode::serialize(&tracker.delta(ecs_, &ecs_.allocator.live_indices())).unwrap();
+        apply_delta(receiver, bincode::deserialize(&data).unwrap(), entities);
+        data.len()
+    }
+
+    #[test]
+    fn test_delta_only_contains_changes() {
+        let mut ecs_ = ECS::new();
+        let entity = ecs_.allocator.allocate();
+        ecs_.health_component.set(entity, HealthComponent { maximum: 20, current: 20 });
+        ecs_.name_component.set(entity, NameComponent { name: "A rather long name to notice".to_string() });
+        let id = sync_id(&mut ecs_, entity);
+
+        let mut tracker = ChangeTracker::new();
+        let mut receiver = ECS::new();
+        let mut entities = HashMap::new();
+        let full_size = send(&mut tracker, &ecs_, &mut receiver, &mut entities);
+        let copy = entities[&id];
+        assert_eq!(receiver.name_component.get(copy).unwrap().name, "A rather long name to notice");
+
+        let unchanged = tracker.delta(&ecs_, &ecs_.allocator.live_indices());
+        assert!(unchanged.changed.is_empty() && unchanged.removed.is_empty());
+
+        ecs_.health_component.get_mut(entity).unwrap().current = 5;
+        assert!(send(&mut tracker, &ecs_, &mut receiver, &mut entities) < full_size);
+        assert_eq!(receiver.health_component.get(copy).unwrap().current, 5);
+        assert!(receiver.name_component.get(copy).is_some());
+
+        ecs_.allocator.deallocate(entity);
+        send(&mut tracker, &ecs_, &mut receiver, &mut entities);
+        assert!(!receiver.allocator.is_live(copy));
+        assert!(entities.is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+extern crate bincode;
+extern crate flate2;
+extern crate serde;
+extern crate serde_yaml;
+
+use std::io::{Read, Write};
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+
+use crate::error::Result;
+
+/// Encoding of save files
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum SaveFormat {
+    // readable and editable, for debugging
+    Yaml,
+    // gzip compressed bincode, much smaller and faster to load for large worlds
+    #[default]
+    Binary,
+}
+
+impl SaveFormat {
+    pub const ALL: [SaveFormat; 2] = [SaveFormat::Binary, SaveFormat::Yaml];
+
+    /// File extension of saves in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SaveFormat::Yaml => "yaml",
+            SaveFormat::Binary => "sav"
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<SaveFormat> {
+        SaveFormat::ALL.iter().copied().find(|format| format.extension() == extension)
+    }
+
+    /// Serializes a value in this format
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>> {
+        match self {
+            SaveFormat::Yaml => Ok(serde_yaml::to_vec(value)?),
+            SaveFormat::Binary => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                bincode::serialize_into(&mut encoder, value)?;
+                encoder.flush()?;
+                Ok(encoder.finish()?)
+            }
+        }
+    }
+
+    /// Deserializes a value in this format. Binary data is only read as far as the
+    /// value reaches, so a prefix of the saved data can be read without the rest
+    pub fn decode<T: DeserializeOwned, R: Read>(&self, reader: R) -> Result<T> {
+        match self {
+            SaveFormat::Yaml => Ok(serde_yaml::from_reader(reader)?),
+            SaveFormat::Binary => Ok(bincode::deserialize_from(GzDecoder::new(reader))?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_formats_roundtrip() {
+        let mut tiles: HashMap<(i64, i64), String> = HashMap::new();
+        tiles.insert((-3, 4), "wall".to_string());
+        tiles.insert((0, 0), "floor".to_string());
+        for format in SaveFormat::ALL.iter() {
+            let data = format.encode(&tiles).unwrap();
+            let decoded: HashMap<(i64, i64), String> = format.decode(&data[..]).unwrap();
+            assert_eq!(decoded, tiles);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+
+use crate::ecs::ECS;
+use crate::persistence::{self, format::SaveFormat};
+
+// turns that can be undone at most
+const HISTORY_TURNS: usize = 20;
+
+/// The states of the world at the start of the last turns, kept in debug mode so
+/// turns can be undone to step through bugs of the gamelogic again
+#[derive(Debug, Default)]
+pub struct TurnHistory {
+    // turn of the world clock and the world at its start, oldest first
+    snapshots: VecDeque<(u64, Vec<u8>)>
+}
+
+impl TurnHistory {
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+/// Remembers the world as it is at the start of the current turn, forgetting the
+/// oldest turn if there are too many. Does nothing outside of debug mode
+///
+/// ### Arguments
+/// * `ecs_` - The entity component system to remember
+///
+pub fn record_turn(ecs_: &mut ECS) {
+    if !ecs_.debug_mode {
+        return;
+    }
+    let turn = ecs_.world_clock.turns;
+    match SaveFormat::Binary.encode(&persistence::capture(ecs_)) {
+        Ok(data) => {
+            let history = &mut ecs_.turn_history;
+            // a turn recorded again, e.g. after undoing it, replaces the old state
+            history.snapshots.retain(|(recorded, _)| *recorded < turn);
+            if history.snapshots.len() >= HISTORY_TURNS {
+                history.snapshots.pop_front();
+            }
+            history.snapshots.push_back((turn, data));
+        },
+        Err(err) => warn!("Could not remember turn {}: {}", turn, err)
+    }
+}
+
+/// Reverts the world to the start of the previous turn. Everything done in the current
+/// turn is undone as well
+///
+/// ### Arguments
+/// * `ecs_` - The entity component system to revert
+///
+/// ### Returns
+/// True if the world was reverted, false if no earlier turn is remembered
+pub fn undo_turn(ecs_: &mut ECS) -> bool {
+    let turn = ecs_.world_clock.turns;
+    let (recorded, data) = match ecs_.turn_history.snapshots.iter().rev().find(|(recorded, _)| *recorded < turn) {
+        Some((recorded, data)) => (*recorded, data),
+        None => return false
+    };
+    match SaveFormat::Binary.decode(&data[..]) {
+        Ok(snapshot) => {
+            persistence::restore(ecs_, snapshot);
+            // the reverted turn is kept, so it can be undone again until an older one is reached
+            ecs_.turn_history.snapshots.retain(|(other, _)| *other <= recorded);
+            info!("Reverted the world to turn {}", recorded);
+            true
+        },
+        Err(err) => {
+            warn!("Could not revert to turn {}: {}", recorded, err);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamelogic::{self, PlayerAction};
+    use crate::gamestate::{movement::Direction, LocationVec};
+
+    #[test]
+    fn test_undo_reverts_turns() {
+        let mut ecs_ = ECS::new();
+        ecs_.debug_mode = true;
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 6, 5);
+        dungeon::create_test_player(&mut ecs_, 1.0, 1.0);
+        let start_turn = ecs_.world_clock.turns;
+        record_turn(&mut ecs_);
+        assert!(!undo_turn(&mut ecs_));
+
+        // two steps right, every step is a turn
+        for _ in 0..2 {
+            gamelogic::perform_player_action(&mut ecs_, PlayerAction::Move(Direction::Right));
+            for _ in 0..crate::UPDATES_PER_SECOND {
+                gamelogic::update(&mut ecs_);
+            }
+        }
+        let player_location = |ecs_: &mut ECS| {
+            let player = ecs_.get_player_entity().unwrap();
+            ecs_.location_component.get(player).unwrap().location
+        };
+        assert_eq!(ecs_.world_clock.turns, start_turn + 2);
+        assert_eq!(ecs_.turn_history.len(), 3);
+        assert_eq!(player_location(&mut ecs_), LocationVec { x: 3.0, y: 1.0 });
+
+        assert!(undo_turn(&mut ecs_));
+        assert_eq!(ecs_.world_clock.turns, start_turn + 1);
+        assert_eq!(player_location(&mut ecs_), LocationVec { x: 2.0, y: 1.0 });
+        assert!(undo_turn(&mut ecs_));
+        assert_eq!(player_location(&mut ecs_), LocationVec { x: 1.0, y: 1.0 });
+        assert!(!undo_turn(&mut ecs_));
+    }
+}
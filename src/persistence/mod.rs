@@ -0,0 +1,336 @@
+extern crate bincode;
+extern crate rand;
+extern crate serde;
+
+pub mod delta;
+pub mod format;
+pub mod history;
+pub mod profile;
+pub mod slots;
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::io;
+
+use serde::{Serialize, Deserialize};
+
+use crate::builder::director;
+use crate::config::Difficulty;
+use crate::ecs::{ECS, Entity};
+use crate::gamestate::components::*;
+use crate::gamestate::{clock, dungeon, explored, overworld, rng, run, time, weather};
+use crate::gamestate::actor::Scheduling;
+use crate::gamestate::movement::DiagonalRule;
+
+/// Declares the components that are saved and synchronized over the network. Generates
+/// the owned snapshot of an entity used for loading, a borrowed one used for saving
+/// which serializes to the same format, the functions converting between them and
+/// the ECS, the change detection used by `delta` and the listing of the inspector
+macro_rules! saved_components {
+    ($($field:ident: $type_:ty),* $(,)?) => {
+        /// All saved components of one entity
+        #[derive(Debug, Deserialize)]
+        pub struct EntitySnapshot {
+            $(
+                #[serde(default)]
+                pub $field: Option<$type_>,
+            )*
+        }
+
+        /// Borrowed counterpart of EntitySnapshot, so saving does not need to copy the world
+        #[derive(Serialize)]
+        pub struct EntitySnapshotRef<'a> {
+            $( $field: Option<&'a $type_>, )*
+        }
+
+        pub fn capture_entity(ecs_: &ECS, entity: Entity) -> EntitySnapshotRef<'_> {
+            EntitySnapshotRef {
+                $( $field: ecs_.$field.get(entity), )*
+            }
+        }
+
+        /// Captures only the components whose hash differs between two states of an entity
+        ///
+        /// ### Arguments
+        /// * `ecs_`     - The entity component system to capture from
+        /// * `entity`   - The entity to capture
+        /// * `previous` - Component hashes of the state known to the receiver
+        /// * `current`  - Current component hashes, see `component_hashes`
+        ///
+        pub fn capture_changes<'a>(ecs_: &'a ECS, entity: Entity, previous: &[Option<u64>], current: &[Option<u64>]) -> EntitySnapshotRef<'a> {
+            let mut i = 0;
+            $(
+                let $field = if current[i] != previous[i] { ecs_.$field.get(entity) } else { None };
+                i += 1;
+            )*
+            debug_assert_eq!(i, current.len());
+            EntitySnapshotRef { $( $field, )* }
+        }
+
+        /// Hashes of all saved components of an entity in declaration order, None for
+        /// components the entity does not have
+        pub fn component_hashes(ecs_: &ECS, entity: Entity) -> Vec<Option<u64>> {
+            vec![ $( ecs_.$field.get(entity).map(hash_component), )* ]
+        }
+
+        /// Names and current values of all components of an entity, e.g. for the entity
+        /// inspector. Components declared here are listed without further changes
+        pub fn inspect_entity(ecs_: &ECS, entity: Entity) -> Vec<(&'static str, String)> {
+            let mut components = Vec::new();
+            $(
+                if let Some(component) = ecs_.$field.get(entity) {
+                    components.push((stringify!($field), format!("{:?}", component)));
+                }
+            )*
+            components
+        }
+
+        /// Sets all components of a snapshot on an existing entity, components missing
+        /// in the snapshot are left as they are
+        pub fn update_entity(ecs_: &mut ECS, entity: Entity, snapshot: EntitySnapshot) {
+            $(
+                if let Some(component) = snapshot.$field {
+                    ecs_.$field.set(entity, component);
+                }
+            )*
+        }
+
+        /// Creates a new entity from a snapshot
+        pub fn restore_entity(ecs_: &mut ECS, snapshot: EntitySnapshot) -> Entity {
+            let entity = ecs_.allocator.allocate();
+            update_entity(ecs_, entity, snapshot);
+            entity
+        }
+    };
+}
+
+saved_components! {
+    actor_component: ActorComponent,
+    basestats_component: BaseStatsComponent,
+    boss_component: BossComponent,
+    caster_component: CasterComponent,
+    class_component: ClassComponent,
+    companion_component: CompanionComponent,
+    corpse_component: CorpseComponent,
+    dungeon_component: DungeonComponent,
+    dying_component: DyingComponent,
+    health_component: HealthComponent,
+    ground_item_component: GroundItemComponent,
+    hotbar_component: HotbarComponent,
+    humanoid_component: HumanoidComponent,
+    hunger_component: HungerComponent,
+    interactable_component: InteractableComponent,
+    inventory_component: InventoryComponent,
+    item_drop_component: ItemDropComponent,
+    location_component: LocationComponent,
+    name_component: NameComponent,
+    npc_behavior_component: NpcBehaviorComponent,
+    player_component: PlayerComponent,
+    quest_target_component: QuestTargetComponent,
+    render_component: RenderComponent,
+    schedule_component: ScheduleComponent,
+    shop_component: ShopComponent,
+    status_component: StatusComponent,
+    sync_id_component: SyncIdComponent,
+}
+
+/// The global state of the ECS that is saved besides the entities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedResources {
+    pub rng: rng::GameRng,
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub permadeath: bool,
+    #[serde(default)]
+    pub diagonal_rule: DiagonalRule,
+    #[serde(default)]
+    pub scheduling: Scheduling,
+    #[serde(default)]
+    pub realtime_updates: u64,
+    pub world_clock: clock::WorldClock,
+    pub floor_type: dungeon::FloorType,
+    pub weather: weather::Weather,
+    pub explored: explored::ExploredMap,
+    pub global_state_table: HashMap<String, String>,
+    pub playtime: f64,
+    // saves without one belong to a run of their own
+    #[serde(default = "rand::random")]
+    pub run_id: u64,
+    #[serde(default)]
+    pub next_sync_id: delta::SyncId,
+    #[serde(default)]
+    pub run_stats: run::RunStats,
+    #[serde(default)]
+    pub spawn_director: director::SpawnDirector,
+    #[serde(default)]
+    pub overworld: overworld::Overworld,
+    #[serde(default)]
+    pub new_game_plus: bool,
+}
+
+/// A saved game, as it is read from a save file
+#[derive(Debug, Deserialize)]
+pub struct WorldSnapshot {
+    pub resources: SavedResources,
+    pub entities: Vec<EntitySnapshot>
+}
+
+/// A game to be saved, borrowing the entities from the ECS.
+/// Serializes to the format `WorldSnapshot` is read from
+#[derive(Serialize)]
+pub struct WorldSnapshotRef<'a> {
+    resources: SavedResources,
+    entities: Vec<EntitySnapshotRef<'a>>
+}
+
+/// Captures the state of the game, which can be serialized into a save
+///
+/// ### Arguments
+/// * `ecs_` - The entity component system to capture
+///
+pub fn capture(ecs_: &ECS) -> WorldSnapshotRef<'_> {
+    WorldSnapshotRef {
+        resources: capture_resources(ecs_),
+        entities: ecs_.allocator.live_indices()
+                                .into_iter()
+                                .map(|entity| capture_entity(ecs_, entity))
+                                .collect()
+    }
+}
+
+/// Copies the saved global state of the ECS
+pub fn capture_resources(ecs_: &ECS) -> SavedResources {
+    SavedResources {
+        rng: ecs_.rng.clone(),
+        difficulty: ecs_.difficulty,
+        permadeath: ecs_.permadeath,
+        diagonal_rule: ecs_.diagonal_rule,
+        scheduling: ecs_.scheduling,
+        realtime_updates: ecs_.realtime_updates,
+        world_clock: ecs_.world_clock.clone(),
+        floor_type: ecs_.floor_type,
+        weather: ecs_.weather,
+        explored: ecs_.explored.clone(),
+        global_state_table: ecs_.global_state_table.clone(),
+        playtime: ecs_.playtime,
+        run_id: ecs_.run_id,
+        next_sync_id: ecs_.next_sync_id,
+        run_stats: ecs_.run_stats.clone(),
+        spawn_director: ecs_.spawn_director.clone(),
+        overworld: ecs_.overworld.clone(),
+        new_game_plus: ecs_.new_game_plus
+    }
+}
+
+/// Replaces the saved global state of the ECS, the entities are left as they are
+pub fn restore_resources(ecs_: &mut ECS, resources: SavedResources) {
+    ecs_.rng = resources.rng;
+    ecs_.difficulty = resources.difficulty;
+    ecs_.permadeath = resources.permadeath;
+    ecs_.diagonal_rule = resources.diagonal_rule;
+    ecs_.scheduling = resources.scheduling;
+    ecs_.realtime_updates = resources.realtime_updates;
+    ecs_.world_clock = resources.world_clock;
+    ecs_.floor_type = resources.floor_type;
+    ecs_.weather = resources.weather;
+    ecs_.explored = resources.explored;
+    ecs_.global_state_table = resources.global_state_table;
+    ecs_.playtime = resources.playtime;
+    ecs_.run_id = resources.run_id;
+    ecs_.next_sync_id = resources.next_sync_id;
+    ecs_.run_stats = resources.run_stats;
+    ecs_.spawn_director = resources.spawn_director;
+    ecs_.overworld = resources.overworld;
+    ecs_.new_game_plus = resources.new_game_plus;
+}
+
+/// Replaces the state of the game with a saved one. All entities are removed and
+/// recreated, so entity indices from before are no longer valid. Temporary state
+/// like resting, pausing or transitions is reset, as is the end of a run
+///
+/// ### Arguments
+/// * `ecs_`     - The entity component system to restore into
+/// * `snapshot` - The saved game
+///
+pub fn restore(ecs_: &mut ECS, snapshot: WorldSnapshot) {
+    for entity in ecs_.allocator.live_indices() {
+        ecs_.allocator.deallocate(entity);
+    }
+    ecs_.index_cache.clear();
+
+    restore_resources(ecs_, snapshot.resources);
+
+    ecs_.transition = None;
+    ecs_.paused = false;
+    ecs_.action_queue.clear();
+    ecs_.targeting = None;
+    ecs_.cutscene = None;
+    ecs_.run_summary = None;
+    ecs_.time_scale = time::TimeScale::new();
+    // the next autosave can not build on one of another game
+    ecs_.autosave_tracker = None;
+
+    for entity in snapshot.entities {
+        restore_entity(ecs_, entity);
+    }
+}
+
+/// Hashes the serialized form of a component, so changes are detected without the
+/// components having to implement `Hash` or track their changes
+fn hash_component<T: Serialize>(component: &T) -> u64 {
+    struct HashWriter(DefaultHasher);
+    impl io::Write for HashWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.write(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut writer = HashWriter(DefaultHasher::new());
+    // writing to the hasher can not fail, neither can serializing the components
+    let _ = bincode::serialize_into(&mut writer, component);
+    writer.0.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::{LocationVec, dungeon::DungeonElement, movement::Direction};
+    use crate::persistence::format::SaveFormat;
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let mut ecs_ = ECS::new();
+        let entity = ecs_.allocator.allocate();
+        ecs_.health_component.set(entity, HealthComponent { maximum: 20, current: 7 });
+        ecs_.location_component.set(entity, LocationComponent {
+            location: LocationVec { x: 2.0, y: -1.0 },
+            previous_location: None,
+            direction: Direction::Left,
+            move_intent: None,
+            hitbox: None
+        });
+        ecs_.explored.explore(&LocationVec { x: -3.0, y: 4.0 }, DungeonElement::Wall);
+        ecs_.playtime = 12.5;
+
+        for format in SaveFormat::ALL.iter() {
+            let data = format.encode(&capture(&ecs_)).unwrap();
+            let mut loaded = ECS::new();
+            restore(&mut loaded, format.decode(&data[..]).unwrap());
+
+            let entities = loaded.allocator.live_indices();
+            assert_eq!(entities.len(), 1);
+            assert_eq!(loaded.health_component.get(entities[0]).unwrap().current, 7);
+            assert_eq!(loaded.location_component.get(entities[0]).unwrap().location, LocationVec { x: 2.0, y: -1.0 });
+            assert!(loaded.name_component.get(entities[0]).is_none());
+            assert!(loaded.explored.is_explored(&LocationVec { x: -3.0, y: 4.0 }));
+            assert_eq!(loaded.playtime, 12.5);
+            // the loaded game continues the run
+            assert_eq!(loaded.run_id, ecs_.run_id);
+        }
+    }
+}
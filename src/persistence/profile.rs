@@ -0,0 +1,93 @@
+extern crate dirs;
+extern crate serde;
+extern crate serde_yaml;
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Serialize, Deserialize};
+
+use crate::error::Result;
+use crate::gamestate::{achievement::Achievement, class::Class, item::Item, run::RunStats};
+
+/// What the player achieved over all runs, stored in a yaml file next to the saves
+/// but independent of them, so it survives deleted saves and permadeath
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerProfile {
+    // totals of the statistics of all runs
+    pub lifetime: RunStats,
+    pub runs: u32,
+    pub victories: u32,
+    pub achievements: BTreeSet<Achievement>,
+    // classes unlocked besides the warrior every player starts with
+    pub unlocked_classes: BTreeSet<Class>,
+    // a run was won, which allows new-game-plus runs
+    pub completed_run: bool,
+    // equipment chosen at the end of the last won run, handed to the player
+    // in the next new-game-plus run
+    pub kept_equipment: Vec<Item>,
+}
+
+impl PlayerProfile {
+    /// Loads the profile file, starting a new profile if there is none or it can not
+    /// be read
+    pub fn load() -> PlayerProfile {
+        let path = profile_path();
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_yaml::from_str(&content).unwrap_or_else(|err| {
+                warn!("Could not parse profile {:?}: {}", path, err);
+                PlayerProfile::default()
+            }),
+            Err(err) => {
+                info!("No profile loaded from {:?}: {}", path, err);
+                PlayerProfile::default()
+            }
+        }
+    }
+
+    /// Tests if new runs may be started with a class
+    pub fn has_class(&self, class: Class) -> bool {
+        class == Class::Warrior || self.unlocked_classes.contains(&class)
+    }
+
+    /// Writes the profile file, creating its directory if needed
+    pub fn save(&self) -> Result<()> {
+        let path = profile_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_yaml::to_string(self)?;
+        Ok(fs::write(&path, content)?)
+    }
+}
+
+/// Location of the profile file, `<data dir>/rustac/profile.yaml`
+pub fn profile_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rustac")
+        .join("profile.yaml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::item::{Equipment, ItemType};
+
+    #[test]
+    fn test_profile_roundtrip() {
+        let mut profile: PlayerProfile = serde_yaml::from_str("runs: 3\n").unwrap();
+        assert_eq!((profile.runs, profile.lifetime.kills), (3, 0));
+        profile.lifetime.kills_by_type.insert("creature.dummy".to_string(), 2);
+        profile.achievements.insert(Achievement::FirstBlood);
+        assert!(profile.has_class(Class::Warrior) && !profile.has_class(Class::Mage));
+        profile.unlocked_classes.insert(Class::Mage);
+        profile.completed_run = true;
+        profile.kept_equipment.push(Item { item: ItemType::Equipment(Equipment::Helm), amount: 1 });
+
+        let loaded: PlayerProfile = serde_yaml::from_str(&serde_yaml::to_string(&profile).unwrap()).unwrap();
+        assert_eq!(loaded, profile);
+    }
+}
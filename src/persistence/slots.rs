@@ -0,0 +1,466 @@
+extern crate bincode;
+extern crate dirs;
+extern crate serde;
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
+
+use crate::ecs::ECS;
+use crate::error::{Error, Result};
+use crate::persistence::{self, SavedResources, WorldSnapshot, WorldSnapshotRef, format::SaveFormat};
+use crate::persistence::delta::{self, ChangeTracker, WorldDelta, WorldDeltaRef};
+use crate::gamestate::run::RunOutcome;
+use crate::ut;
+
+// numbered slots offered in the load menu
+pub const SLOT_COUNT: u32 = 5;
+const AUTOSAVE_NAME: &str = "autosave";
+const SLOT_PREFIX: &str = "slot_";
+const JOURNAL_EXTENSION: &str = "journal";
+
+/// A place a game can be saved to
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum SaveSlot {
+    // written on floor changes and when quitting
+    Auto,
+    Numbered(u32),
+    Named(String),
+}
+
+impl SaveSlot {
+    /// Parses a slot from the command line: `auto`, a number or a name
+    pub fn parse(value: &str) -> SaveSlot {
+        match value {
+            AUTOSAVE_NAME | "auto" => SaveSlot::Auto,
+            _ => match value.parse() {
+                Ok(number) => SaveSlot::Numbered(number),
+                Err(_) => SaveSlot::Named(value.to_string())
+            }
+        }
+    }
+
+    /// Name of the slot's file without extension
+    fn file_stem(&self) -> String {
+        match self {
+            SaveSlot::Auto => AUTOSAVE_NAME.to_string(),
+            SaveSlot::Numbered(number) => format!("{}{}", SLOT_PREFIX, number),
+            // names end up in file names, so only harmless characters are kept
+            SaveSlot::Named(name) => name.chars()
+                                         .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+                                         .collect()
+        }
+    }
+
+    fn from_file_stem(stem: &str) -> SaveSlot {
+        if stem == AUTOSAVE_NAME {
+            return SaveSlot::Auto;
+        }
+        match stem.strip_prefix(SLOT_PREFIX).and_then(|number| number.parse().ok()) {
+            Some(number) => SaveSlot::Numbered(number),
+            None => SaveSlot::Named(stem.to_string())
+        }
+    }
+
+    /// Path of the slot's file in a format
+    pub fn path(&self, format: SaveFormat) -> PathBuf {
+        saves_dir().join(self.file_stem()).with_extension(format.extension())
+    }
+
+    /// The file the slot is saved in and its format, None if the slot is empty
+    pub fn existing_file(&self) -> Option<(PathBuf, SaveFormat)> {
+        SaveFormat::ALL.iter()
+                       .map(|format| (self.path(*format), *format))
+                       .find(|(path, _)| path.exists())
+    }
+
+    /// Text shown for the slot in the load menu
+    pub fn describe(&self, ecs_: &ECS) -> String {
+        match self {
+            SaveSlot::Auto => ecs_.locale.text("saves.autosave"),
+            SaveSlot::Numbered(number) => ecs_.locale.format("saves.slot", &[("number", number)]),
+            SaveSlot::Named(name) => name.clone()
+        }
+    }
+}
+
+/// Information about a save shown in the load menu without loading the whole game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveMetadata {
+    pub character_name: String,
+    pub floor: i32,
+    // in seconds
+    pub playtime: f64,
+    // unix time in seconds
+    pub saved_at: u64,
+    // seed the game was started with
+    pub seed: u64,
+    // the run the save belongs to, runs started with the same seed differ in it
+    #[serde(default)]
+    pub run_id: u64
+}
+
+impl SaveMetadata {
+    pub fn of(ecs_: &mut ECS) -> SaveMetadata {
+        let player = ecs_.get_player_entity();
+        SaveMetadata {
+            character_name: player.map_or(String::new(), |player| ut::name_or_id(ecs_, player)),
+            floor: player.and_then(|player| ecs_.player_component.get(player))
+                         .map_or(0, |player_c| player_c.stage_level),
+            playtime: ecs_.playtime,
+            saved_at: SystemTime::now().duration_since(UNIX_EPOCH)
+                                       .map_or(0, |duration| duration.as_secs()),
+            seed: ecs_.rng.seed(),
+            run_id: ecs_.run_id
+        }
+    }
+
+    /// Playtime as hours, minutes and seconds
+    pub fn playtime_text(&self) -> String {
+        let seconds = self.playtime as u64;
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    }
+}
+
+#[derive(Serialize)]
+struct SaveFileRef<'a> {
+    metadata: SaveMetadata,
+    world: WorldSnapshotRef<'a>
+}
+
+#[derive(Deserialize)]
+struct SaveFile {
+    metadata: SaveMetadata,
+    world: WorldSnapshot
+}
+
+// read when listing saves, the rest of the file is skipped
+#[derive(Deserialize)]
+struct SaveHeader {
+    metadata: SaveMetadata
+}
+
+// changes since the previous autosave, appended to the journal next to the last
+// full autosave
+#[derive(Serialize)]
+struct JournalEntryRef<'a> {
+    metadata: SaveMetadata,
+    resources: SavedResources,
+    delta: WorldDeltaRef<'a>
+}
+
+#[derive(Deserialize)]
+struct JournalEntry {
+    metadata: SaveMetadata,
+    resources: SavedResources,
+    delta: WorldDelta
+}
+
+/// Directory the saves are stored in
+pub fn saves_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rustac")
+        .join("saves")
+}
+
+fn journal_path() -> PathBuf {
+    saves_dir().join(AUTOSAVE_NAME).with_extension(JOURNAL_EXTENSION)
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(())
+    }
+}
+
+/// Saves the game into a slot, replacing what was saved there before in any format
+///
+/// ### Arguments
+/// * `ecs_`   - The entity component system to save
+/// * `slot`   - The slot to save to
+/// * `format` - Encoding of the save file
+///
+pub fn save(ecs_: &mut ECS, slot: &SaveSlot, format: SaveFormat) -> Result<()> {
+    let entities = ecs_.allocator.live_indices();
+    if *slot == SaveSlot::Auto {
+        // the journal refers to the entities by their sync ids
+        for entity in entities.iter() {
+            delta::sync_id(ecs_, *entity);
+        }
+        // the journal holds changes to the replaced save. It goes first, as its entries
+        // would be applied to the new save if the game stopped while writing it
+        remove_if_exists(&journal_path())?;
+    }
+    let save_file = SaveFileRef {
+        metadata: SaveMetadata::of(ecs_),
+        world: persistence::capture(ecs_)
+    };
+    let path = slot.path(format);
+    write_atomic(&path, &format.encode(&save_file)?)?;
+    // a save in another format would be ambiguous when loading
+    for other in SaveFormat::ALL.iter().filter(|other| **other != format) {
+        remove_if_exists(&slot.path(*other))?;
+    }
+    if *slot == SaveSlot::Auto {
+        let mut tracker = ChangeTracker::new();
+        // the save contains everything, which the tracker learns by taking a delta
+        tracker.delta(ecs_, &entities);
+        ecs_.autosave_tracker = Some(tracker);
+    }
+    info!("Saved game to {:?}", path);
+    Ok(())
+}
+
+/// Replaces the game with the one saved in a slot, in whatever format it was saved
+///
+/// ### Arguments
+/// * `ecs_` - The entity component system to load into
+/// * `slot` - The slot to load from
+///
+pub fn load(ecs_: &mut ECS, slot: &SaveSlot) -> Result<()> {
+    let (path, format) = slot.existing_file()
+                             .ok_or_else(|| Error::EmptySlot(slot.clone()))?;
+    let save_file: SaveFile = format.decode(io::BufReader::new(fs::File::open(&path)?))?;
+    persistence::restore(ecs_, save_file.world);
+    // the turns of the previous game can not be undone into this one
+    ecs_.turn_history.clear();
+    let mut metadata = save_file.metadata;
+    if *slot == SaveSlot::Auto {
+        let mut entities = delta::sync_entities(ecs_);
+        for entry in read_journal() {
+            persistence::restore_resources(ecs_, entry.resources);
+            delta::apply_delta(ecs_, entry.delta, &mut entities);
+            metadata = entry.metadata;
+        }
+    }
+    info!("Loaded {} on floor {} from {:?}", metadata.character_name, metadata.floor, path);
+    Ok(())
+}
+
+/// Saves into the autosave slot, failing only with a warning as the game goes on
+/// anyway. The first autosave of a game is a full save in the format chosen in the
+/// settings, later ones only append what changed since to the autosave's journal
+pub fn autosave(ecs_: &mut ECS) {
+    // a run that ended can not be continued, the autosave stays as it was before
+    if ecs_.run_summary.is_some() {
+        return;
+    }
+    let result = match ecs_.autosave_tracker.take() {
+        Some(tracker) if SaveSlot::Auto.existing_file().is_some() => append_journal(ecs_, tracker),
+        _ => save(ecs_, &SaveSlot::Auto, ecs_.settings.save_format)
+    };
+    if let Err(err) = result {
+        warn!("Could not autosave to {:?}: {}", saves_dir(), err);
+    }
+}
+
+/// Deletes what is saved in a slot, in any format
+///
+/// ### Arguments
+/// * `slot` - The slot to empty
+///
+pub fn delete(slot: &SaveSlot) -> Result<()> {
+    for format in SaveFormat::ALL.iter() {
+        remove_if_exists(&slot.path(*format))?;
+    }
+    if *slot == SaveSlot::Auto {
+        remove_if_exists(&journal_path())?;
+    }
+    info!("Deleted the save in {:?}", slot);
+    Ok(())
+}
+
+/// Deletes or keeps the saves of a run that just ended. With permadeath a lost run
+/// can not be continued, so every save of it is deleted, the autosave as well as the
+/// slots saved by hand. Otherwise, and after a won run, the saves are kept
+pub fn on_run_ended(ecs_: &mut ECS) {
+    ecs_.autosave_tracker = None;
+    let lost = ecs_.run_summary.as_ref().is_some_and(|summary| summary.outcome == RunOutcome::Defeat);
+    if !ecs_.permadeath || !lost {
+        return;
+    }
+    // runs started with `--daily` or `--seed` share their seed, so saves are matched by run
+    let run_id = ecs_.run_id;
+    for info in list_slots() {
+        if info.metadata.as_ref().is_some_and(|metadata| metadata.run_id == run_id) {
+            if let Err(err) = delete(&info.slot) {
+                warn!("Could not delete the save in {:?} of the lost run: {}", info.slot, err);
+            }
+        }
+    }
+}
+
+/// Replaces the autosave and its journal with a full save, so loading it does not
+/// need to replay the journal
+pub fn compact_autosave(ecs_: &mut ECS) {
+    ecs_.autosave_tracker = None;
+    autosave(ecs_);
+}
+
+/// Appends the changes since the last autosave to the journal. If that fails, the
+/// tracker is dropped, so the next autosave is a full one again
+fn append_journal(ecs_: &mut ECS, mut tracker: ChangeTracker) -> Result<()> {
+    let entities = ecs_.allocator.live_indices();
+    for entity in entities.iter() {
+        delta::sync_id(ecs_, *entity);
+    }
+    let metadata = SaveMetadata::of(ecs_);
+    let entry = JournalEntryRef {
+        metadata,
+        resources: persistence::capture_resources(ecs_),
+        delta: tracker.delta(ecs_, &entities)
+    };
+    let data = bincode::serialize(&entry)?;
+
+    let mut journal = fs::OpenOptions::new().create(true).append(true).open(journal_path())?;
+    journal.write_all(&data)?;
+    journal.sync_all()?;
+    debug!("Appended {} bytes to the autosave journal", data.len());
+    ecs_.autosave_tracker = Some(tracker);
+    Ok(())
+}
+
+/// Reads all complete entries of the autosave journal. An entry cut off because the
+/// game crashed while writing it ends the journal
+fn read_journal() -> Vec<JournalEntry> {
+    let mut entries = Vec::new();
+    let mut reader = match fs::File::open(journal_path()) {
+        Ok(file) => io::BufReader::new(file),
+        Err(_) => return entries
+    };
+    while reader.fill_buf().is_ok_and(|buffer| !buffer.is_empty()) {
+        match bincode::deserialize_from(&mut reader) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => {
+                warn!("Ignoring the rest of the autosave journal: {}", err);
+                break;
+            }
+        }
+    }
+    entries
+}
+
+/// Writes a file so that it either has the old or the new content, even if the game
+/// crashes while writing: the content is written to a temporary file first, which
+/// then replaces the target
+fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temp_path = path.with_extension("tmp");
+    let mut file = fs::File::create(&temp_path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)
+}
+
+/// A slot of the load menu, with the metadata of its save if there is one
+#[derive(Debug, Clone)]
+pub struct SlotInfo {
+    pub slot: SaveSlot,
+    pub metadata: Option<SaveMetadata>
+}
+
+/// Lists the autosave, all numbered slots and all other saves found in the saves
+/// directory, e.g. named ones. Unreadable saves are listed as empty
+pub fn list_slots() -> Vec<SlotInfo> {
+    let mut slots = vec![SaveSlot::Auto];
+    slots.extend((1..=SLOT_COUNT).map(SaveSlot::Numbered));
+
+    let mut others: Vec<SaveSlot> = fs::read_dir(saves_dir())
+        .map(|entries| entries.filter_map(|entry| entry.ok())
+                              .map(|entry| entry.path())
+                              .filter(|path| path.extension()
+                                                 .and_then(|extension| extension.to_str())
+                                                 .and_then(SaveFormat::from_extension)
+                                                 .is_some())
+                              .filter_map(|path| path.file_stem().and_then(|stem| stem.to_str()).map(SaveSlot::from_file_stem))
+                              .filter(|slot| !slots.contains(slot))
+                              .collect())
+        .unwrap_or_default();
+    others.sort_by_key(|slot| slot.file_stem());
+    others.dedup();
+    slots.extend(others);
+
+    slots.into_iter().map(|slot| {
+        let mut metadata = slot.existing_file().and_then(|(path, format)| read_metadata(&path, format));
+        // the autosave is as recent as the last entry of its journal
+        if slot == SaveSlot::Auto && metadata.is_some() {
+            metadata = read_journal().pop().map(|entry| entry.metadata).or(metadata);
+        }
+        SlotInfo { slot, metadata }
+    }).collect()
+}
+
+fn read_metadata(path: &Path, format: SaveFormat) -> Option<SaveMetadata> {
+    let file = fs::File::open(path).ok()?;
+    match format.decode::<SaveHeader, _>(io::BufReader::new(file)) {
+        Ok(header) => Some(header.metadata),
+        Err(err) => {
+            warn!("Could not read save {:?}: {}", path, err);
+            None
+        }
+    }
+}
+
+/// State of the load menu
+#[derive(Debug, Clone, Default)]
+pub struct LoadMenu {
+    pub selected: usize,
+    // read when the menu is opened
+    pub slots: Vec<SlotInfo>
+}
+
+impl LoadMenu {
+    pub fn open() -> LoadMenu {
+        LoadMenu {
+            selected: 0,
+            slots: list_slots()
+        }
+    }
+
+    pub fn selected_slot(&self) -> Option<&SlotInfo> {
+        self.slots.get(self.selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_file_names() {
+        for slot in [SaveSlot::Auto, SaveSlot::Numbered(3), SaveSlot::Named("my_run".to_string())] {
+            assert_eq!(SaveSlot::from_file_stem(&slot.file_stem()), slot);
+        }
+        assert_eq!(SaveSlot::Named("../evil/run".to_string()).file_stem(), "evilrun");
+        assert_eq!(SaveSlot::parse("2"), SaveSlot::Numbered(2));
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let path = std::env::temp_dir().join("rustac_test_write_atomic.yaml");
+        write_atomic(&path, b"old").unwrap();
+        write_atomic(&path, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert!(!path.with_extension("tmp").exists());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_empty_slot_keeps_game() {
+        let mut ecs_ = ECS::new();
+        let entity = ecs_.allocator.allocate();
+        let slot = SaveSlot::Named("rustac_test_never_saved".to_string());
+        match load(&mut ecs_, &slot) {
+            Err(Error::EmptySlot(empty)) => assert_eq!(empty, slot),
+            other => panic!("expected an empty slot, got {:?}", other)
+        }
+        assert!(ecs_.allocator.is_live(entity));
+    }
+}
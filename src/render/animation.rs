@@ -1,13 +1,536 @@
 extern crate serde;
+extern crate serde_yaml;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 
 use serde::{Serialize, Deserialize};
 
-pub type AnimationId = i32;
+use crate::gamestate::LocationVec;
+use crate::render::{aseprite, sprite::SpriteId};
+
+// sprite sheets lie next to their textures, one `<sprite>.json` per sprite
+pub const SHEETS_DIR: &str = "./assets/textures";
+// optional rules replacing `AnimationRules::default`
+const RULES_FILE: &str = "animations.yaml";
+
+/// One frame of a clip
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipFrame {
+    // index into the frames of the sheet
+    pub index: usize,
+    pub duration_ms: u32
+}
+
+/// A sequence of frames of a sprite sheet, e.g. a walk cycle
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationClip {
+    // in the order they are played
+    pub frames: Vec<ClipFrame>
+}
+
+impl AnimationClip {
+    /// Time it takes to play all frames once
+    pub fn duration_ms(&self) -> u32 {
+        self.frames.iter().map(|frame| frame.duration_ms).sum()
+    }
+
+    /// The frame shown after playing for a while
+    ///
+    /// ### Arguments
+    /// * `elapsed_ms` - Time since the clip started
+    /// * `looping`    - Whether the clip starts over at the end or stays on its last frame
+    ///
+    pub fn frame_at(&self, elapsed_ms: u32, looping: bool) -> Option<&ClipFrame> {
+        let duration = self.duration_ms();
+        if duration == 0 {
+            return self.frames.last();
+        }
+        let mut time = if looping { elapsed_ms % duration } else { elapsed_ms };
+        for frame in self.frames.iter() {
+            if time < frame.duration_ms {
+                return Some(frame);
+            }
+            time -= frame.duration_ms;
+        }
+        self.frames.last()
+    }
+}
+
+/// An image holding several frames of a sprite and the clips playing them
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteSheet {
+    // file name of the image within `assets/textures/`
+    pub image: String,
+    // x, y, width and height of each frame within the image, in pixels
+    pub frames: Vec<[u32; 4]>,
+    pub clips: HashMap<String, AnimationClip>
+}
+
+/// The sprite sheets of all sprites that are animated
+#[derive(Debug, Default)]
+pub struct AnimationLibrary {
+    sheets: HashMap<SpriteId, SpriteSheet>,
+    // names of the variants of each clip with any, the clip itself first, found once
+    // a sheet is loaded, see `clip_for`
+    variants: HashMap<SpriteId, HashMap<String, Vec<String>>>,
+    pub rules: AnimationRules
+}
+
+impl AnimationLibrary {
+    /// Loads every sheet in a directory, sheets that can not be loaded are left out with
+    /// a warning and their sprites are not animated
+    pub fn load(dir: &Path) -> AnimationLibrary {
+        let mut library = AnimationLibrary::default();
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                warn!("Could not read sprite sheets in {:?}: {}", dir, err);
+                return library;
+            }
+        };
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            if path.extension().is_some_and(|extension| extension == "json") {
+                library.load_sheet(&path);
+            }
+        }
+        library.load_rules(dir);
+        info!("Loaded {} sprite sheets", library.sheets.len());
+        library
+    }
+
+    /// Loads a changed sheet again, the sheet is kept if the file is broken
+    ///
+    /// ### Arguments
+    /// * `file` - Name of the changed file within `assets/textures/`
+    ///
+    pub fn reload(&mut self, file: &str) {
+        if file == RULES_FILE {
+            self.load_rules(Path::new(SHEETS_DIR));
+        } else if file.ends_with(".json") {
+            self.load_sheet(&Path::new(SHEETS_DIR).join(file));
+        }
+    }
+
+    pub fn sheets(&self) -> impl Iterator<Item = (&SpriteId, &SpriteSheet)> {
+        self.sheets.iter()
+    }
+
+    pub fn sheet(&self, sprite: &str) -> Option<&SpriteSheet> {
+        self.sheets.get(sprite)
+    }
+
+    pub fn clip(&self, sprite: &str, clip: &str) -> Option<&AnimationClip> {
+        self.sheet(sprite).and_then(|sheet| sheet.clips.get(clip))
+    }
+
+    /// The clip a sprite plays for a kind of animation. Sprites without a clip for the
+    /// kind play their idle clip, or the "default" clip of sheets without tags.
+    /// Sheets may hold variants of a clip, tagged with a number after the first one,
+    /// e.g. `idle`, `idle_2` and `idle_3` for grass swaying in different ways
+    ///
+    /// ### Arguments
+    /// * `sprite`  - The sprite playing the clip
+    /// * `kind`    - The kind of animation played
+    /// * `variant` - Picks one of the variants, any number is fine
+    ///
+    pub fn clip_for(&self, sprite: &str, kind: AnimationKind, variant: u32) -> Option<&AnimationClip> {
+        let sheet = self.sheet(sprite)?;
+        let variants = self.variants.get(sprite);
+        let names = [kind.clip_name(), AnimationKind::Idle.clip_name(), "default"];
+        names.iter().find_map(|name| match variants.and_then(|variants| variants.get(*name)) {
+            Some(variant_names) => sheet.clips.get(&variant_names[variant as usize % variant_names.len()]),
+            None => sheet.clips.get(*name)
+        })
+    }
+
+    /// The rectangle of the sheet image showing a clip after some time
+    ///
+    /// ### Arguments
+    /// * `sprite`     - The sprite playing the clip
+    /// * `kind`       - The kind of animation played
+    /// * `variant`    - Picks one of the variants of the clip, see `clip_for`
+    /// * `elapsed_ms` - Time the clip has been played for
+    ///
+    /// ### Returns
+    /// x, y, width and height within the image in pixels, None if the sprite is not animated
+    pub fn frame_rect(&self, sprite: &str, kind: AnimationKind, variant: u32, elapsed_ms: u32) -> Option<[u32; 4]> {
+        let looping = self.rules.hold(kind) == Hold::Loop;
+        let frame = self.clip_for(sprite, kind, variant)?.frame_at(elapsed_ms, looping)?;
+        self.sheet(sprite)?.frames.get(frame.index).copied()
+    }
+
+    pub fn insert(&mut self, sprite: &str, sheet: SpriteSheet) {
+        self.variants.insert(sprite.to_string(), clip_variants(&sheet));
+        self.sheets.insert(sprite.to_string(), sheet);
+    }
+
+    fn load_rules(&mut self, dir: &Path) {
+        let path = dir.join(RULES_FILE);
+        if !path.exists() {
+            return;
+        }
+        match fs::read_to_string(&path).map_err(|err| err.to_string())
+                                        .and_then(|yaml| serde_yaml::from_str(&yaml).map_err(|err| err.to_string())) {
+            Ok(rules) => self.rules = rules,
+            Err(err) => warn!("Could not load animation rules {:?}: {}", path, err)
+        }
+    }
+
+    /// Loads a sheet for the sprite named like its file
+    fn load_sheet(&mut self, path: &Path) {
+        let sprite = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(sprite) => sprite.to_string(),
+            None => return
+        };
+        match aseprite::load_sheet(path) {
+            Ok(sheet) => {
+                debug!("Sprite {} has {} frames and the clips {:?}", sprite, sheet.frames.len(), sheet.clips.keys());
+                self.insert(&sprite, sheet);
+            },
+            Err(err) => warn!("Sprite {} is not animated: {}", sprite, err)
+        }
+    }
+}
+
+/// Names of the variants of the clips of a sheet, only clips with variants are listed
+fn clip_variants(sheet: &SpriteSheet) -> HashMap<String, Vec<String>> {
+    sheet.clips.keys()
+               .map(|name| {
+                   let mut names = vec![name.clone()];
+                   names.extend((2..).map(|number| format!("{}_{}", name, number))
+                                     .take_while(|variant| sheet.clips.contains_key(variant)));
+                   (name.clone(), names)
+               })
+               .filter(|(_, names)| names.len() > 1)
+               .collect()
+}
+
+/// What an entity is shown doing, each kind is played with the clip of the same name.
+/// Kinds are ordered by priority, higher ones interrupt lower ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum AnimationKind {
+    Idle,
+    Walk,
+    Attack,
+    Hurt,
+    Die,
+}
+
+impl AnimationKind {
+    pub fn clip_name(self) -> &'static str {
+        match self {
+            AnimationKind::Idle   => "idle",
+            AnimationKind::Walk   => "walk",
+            AnimationKind::Attack => "attack",
+            AnimationKind::Hurt   => "hurt",
+            AnimationKind::Die    => "die",
+        }
+    }
+}
+
+/// How long a clip is played
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Hold {
+    // repeats until another clip is wanted
+    Loop,
+    // plays to the end once, only clips of a higher priority interrupt it
+    Once,
+    // plays once and stays on its last frame for good
+    Last,
+}
+
+/// Time two clips are blended into each other when switching between them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransitionRule {
+    // None for any clip
+    pub from: Option<AnimationKind>,
+    pub to: AnimationKind,
+    pub blend_ms: u32
+}
+
+/// Decides how clips are played and switched, the same for all entities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnimationRules {
+    // kinds not listed loop
+    pub holds: HashMap<AnimationKind, Hold>,
+    // the first matching rule is used
+    pub transitions: Vec<TransitionRule>,
+    // blend time of switches no rule matches
+    pub blend_ms: u32
+}
+
+impl Default for AnimationRules {
+    fn default() -> AnimationRules {
+        let mut holds = HashMap::new();
+        holds.insert(AnimationKind::Attack, Hold::Once);
+        holds.insert(AnimationKind::Hurt, Hold::Once);
+        holds.insert(AnimationKind::Die, Hold::Last);
+        AnimationRules {
+            holds,
+            transitions: vec![
+                // reactions to hits show at once
+                TransitionRule { from: None, to: AnimationKind::Hurt, blend_ms: 0 },
+                TransitionRule { from: None, to: AnimationKind::Die, blend_ms: 0 },
+                TransitionRule { from: Some(AnimationKind::Walk), to: AnimationKind::Idle, blend_ms: 150 },
+            ],
+            blend_ms: 80
+        }
+    }
+}
+
+impl AnimationRules {
+    pub fn hold(&self, kind: AnimationKind) -> Hold {
+        self.holds.get(&kind).copied().unwrap_or(Hold::Loop)
+    }
+
+    pub fn blend_ms(&self, from: AnimationKind, to: AnimationKind) -> u32 {
+        self.transitions.iter()
+                        .find(|rule| rule.to == to && rule.from.is_none_or(|rule_from| rule_from == from))
+                        .map_or(self.blend_ms, |rule| rule.blend_ms)
+    }
+}
+
+/// Picks the clip an entity plays from what it is doing and keeps track of the time it
+/// is played for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnimationController {
+    current: AnimationKind,
+    // time since the current clip started
+    elapsed_ms: u32,
+    // one shot clip asked for since the last update, e.g. after taking damage
+    triggered: Option<AnimationKind>,
+    // the clip played before and the time it was played for, faded out while blending
+    previous: Option<(AnimationKind, u32)>,
+    blend_ms: u32,
+    blend_remaining_ms: u32,
+    // the variant of the clips played, see `AnimationLibrary::clip_for`
+    #[serde(default)]
+    variant: u32,
+    // time idle clips are ahead, so identical entities do not animate in lockstep
+    #[serde(default)]
+    phase_ms: u32
+}
+
+impl AnimationController {
+    pub fn new() -> AnimationController {
+        AnimationController {
+            current: AnimationKind::Idle,
+            elapsed_ms: 0,
+            triggered: None,
+            previous: None,
+            blend_ms: 0,
+            blend_remaining_ms: 0,
+            variant: 0,
+            phase_ms: 0
+        }
+    }
+
+    /// A controller playing a variant of the clips with the idle clip started at some
+    /// point, both picked by a seed. Used for decorations like grass or torches, seeded
+    /// by their location they look the same after loading and for every client
+    pub fn varied(seed: u64) -> AnimationController {
+        // splitmix64, so neighbouring seeds look unrelated
+        let mut mixed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        mixed ^= mixed >> 31;
+        AnimationController {
+            variant: (mixed >> 32) as u32,
+            phase_ms: (mixed & 0xFFFF) as u32,
+            ..AnimationController::new()
+        }
+    }
+
+    pub fn current(&self) -> AnimationKind {
+        self.current
+    }
+
+    pub fn variant(&self) -> u32 {
+        self.variant
+    }
+
+    /// Time the current clip has been played for, idle clips are shifted by the phase
+    pub fn elapsed_ms(&self) -> u32 {
+        if self.current == AnimationKind::Idle {
+            self.elapsed_ms.saturating_add(self.phase_ms)
+        } else {
+            self.elapsed_ms
+        }
+    }
+
+    /// Asks for a one shot clip like attack or hurt, played from the next update on.
+    /// Of several triggers in an update the one of the highest priority is played
+    pub fn trigger(&mut self, kind: AnimationKind) {
+        if self.triggered.is_none_or(|triggered| kind > triggered) {
+            self.triggered = Some(kind);
+        }
+    }
+
+    /// Advances the current clip and switches to another one if the entity's state or a
+    /// trigger asks for it and the hold rule of the current clip allows it
+    ///
+    /// ### Arguments
+    /// * `state`    - The clip the state of the entity asks for, e.g. walk while it moves
+    /// * `rules`    - How clips are held and blended
+    /// * `duration` - The duration of the entity's clip of a kind, None if it has none
+    /// * `dt_ms`    - Time since the last update
+    ///
+    pub fn advance(&mut self, state: AnimationKind, rules: &AnimationRules, duration: &dyn Fn(AnimationKind) -> Option<u32>, dt_ms: u32) {
+        self.elapsed_ms = self.elapsed_ms.saturating_add(dt_ms);
+        if let Some((_, elapsed)) = &mut self.previous {
+            *elapsed = elapsed.saturating_add(dt_ms);
+        }
+        self.blend_remaining_ms = self.blend_remaining_ms.saturating_sub(dt_ms);
+        if self.blend_remaining_ms == 0 {
+            self.previous = None;
+        }
+
+        let locked = match rules.hold(self.current) {
+            Hold::Loop => false,
+            // sprites without the clip finish it at once
+            Hold::Once => duration(self.current).is_some_and(|duration| self.elapsed_ms < duration),
+            Hold::Last => true,
+        };
+        let triggered = self.triggered.take();
+        let next = match triggered {
+            Some(triggered) if triggered >= state => triggered,
+            _ => state
+        };
+        let restart = triggered == Some(self.current) && rules.hold(self.current) != Hold::Last;
+        if restart || (next != self.current && (!locked || next > self.current)) {
+            self.switch(next, rules);
+        }
+    }
+
+    /// The clip faded out and its weight (1.0 when the switch happened, down to 0.0),
+    /// None if no clips are blended
+    pub fn blend(&self) -> Option<(AnimationKind, u32, f32)> {
+        match self.previous {
+            Some((kind, elapsed)) if self.blend_ms > 0 => Some((kind, elapsed, self.blend_remaining_ms as f32 / self.blend_ms as f32)),
+            _ => None
+        }
+    }
+
+    fn switch(&mut self, next: AnimationKind, rules: &AnimationRules) {
+        self.blend_ms = rules.blend_ms(self.current, next);
+        self.blend_remaining_ms = self.blend_ms;
+        self.previous = if self.blend_ms > 0 { Some((self.current, self.elapsed_ms())) } else { None };
+        self.current = next;
+        self.elapsed_ms = 0;
+    }
+}
+
+impl Default for AnimationController {
+    fn default() -> AnimationController {
+        AnimationController::new()
+    }
+}
+
+/// Short lived modifier flashing a sprite in a color and knocking it away from
+/// the source of the flash, e.g. when an entity takes damage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HitFlash {
+    pub color: [f32; 3],
+    // offset (in fields) at the start of the flash, shrinking to zero
+    pub knock: LocationVec,
+    // in game updates
+    pub remaining: u32,
+    pub duration: u32
+}
+
+impl HitFlash {
+    pub fn new(color: [f32; 3], knock: LocationVec, duration: u32) -> HitFlash {
+        HitFlash {
+            color,
+            knock,
+            remaining: duration,
+            duration
+        }
+    }
+
+    /// Strength of the flash, 1.0 when it starts fading to 0.0
+    pub fn intensity(&self) -> f32 {
+        if self.duration == 0 {
+            0.0
+        } else {
+            self.remaining as f32 / self.duration as f32
+        }
+    }
+
+    /// Current offset of the sprite
+    pub fn offset(&self) -> LocationVec {
+        let intensity = self.intensity() as f64;
+        LocationVec {
+            x: self.knock.x * intensity,
+            y: self.knock.y * intensity
+        }
+    }
+
+    /// Color added on top of the sprite
+    pub fn overlay_color(&self) -> [f32; 4] {
+        let intensity = self.intensity();
+        [self.color[0] * intensity, self.color[1] * intensity, self.color[2] * intensity, 1.0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every clip lasts 100 ms
+    fn duration(_kind: AnimationKind) -> Option<u32> {
+        Some(100)
+    }
+
+    #[test]
+    fn test_one_shot_clips_hold_until_finished() {
+        let rules = AnimationRules::default();
+        let mut controller = AnimationController::new();
+
+        controller.advance(AnimationKind::Walk, &rules, &duration, 30);
+        assert_eq!(controller.current(), AnimationKind::Walk);
+
+        controller.trigger(AnimationKind::Attack);
+        controller.advance(AnimationKind::Walk, &rules, &duration, 30);
+        assert_eq!(controller.current(), AnimationKind::Attack);
+        // walking does not interrupt the attack, it is blended in once the attack is over
+        controller.advance(AnimationKind::Walk, &rules, &duration, 60);
+        assert_eq!(controller.current(), AnimationKind::Attack);
+        controller.advance(AnimationKind::Idle, &rules, &duration, 60);
+        assert_eq!(controller.current(), AnimationKind::Idle);
+        assert_eq!(controller.blend().map(|(kind, _, weight)| (kind, weight)), Some((AnimationKind::Attack, 1.0)));
+
+        // dying interrupts everything and is never left
+        controller.trigger(AnimationKind::Hurt);
+        controller.advance(AnimationKind::Die, &rules, &duration, 30);
+        assert_eq!(controller.current(), AnimationKind::Die);
+        assert!(controller.blend().is_none());
+        controller.trigger(AnimationKind::Hurt);
+        controller.advance(AnimationKind::Idle, &rules, &duration, 500);
+        assert_eq!(controller.current(), AnimationKind::Die);
+    }
+
+    #[test]
+    fn test_varied_controllers_pick_clip_variants() {
+        let clip = |duration_ms| AnimationClip { frames: vec![ClipFrame { index: 0, duration_ms }] };
+        let mut clips = HashMap::new();
+        clips.insert("idle".to_string(), clip(100));
+        clips.insert("idle_2".to_string(), clip(200));
+        clips.insert("idle_3".to_string(), clip(300));
+        let mut library = AnimationLibrary::default();
+        library.insert("grass_tile", SpriteSheet { image: "grass_tile.png".to_string(), frames: vec![[0, 0, 16, 16]], clips });
+
+        let durations: Vec<u32> = (0..3).map(|variant| library.clip_for("grass_tile", AnimationKind::Walk, variant).unwrap().duration_ms()).collect();
+        assert_eq!(durations, vec![100, 200, 300]);
+        assert_eq!(library.clip_for("grass_tile", AnimationKind::Idle, 4).unwrap().duration_ms(), 200);
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AnimationState {
-    animation: AnimationId,
-    current_frame: i32,
-    is_playing: bool,
-    is_loop: bool
-}
\ No newline at end of file
+        // neighbouring tiles differ, the same tile is the same every time
+        let controllers: Vec<AnimationController> = (0..8).map(AnimationController::varied).collect();
+        assert!(controllers.iter().any(|controller| controller.elapsed_ms() != controllers[0].elapsed_ms()));
+        assert!(controllers.iter().any(|controller| controller.variant() % 3 != controllers[0].variant() % 3));
+        assert_eq!(AnimationController::varied(5).variant(), controllers[5].variant());
+    }
+}
@@ -0,0 +1,163 @@
+extern crate serde_json;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+use crate::render::animation::{AnimationClip, ClipFrame, SpriteSheet};
+
+// the parts of a sheet exported by Aseprite with "Export Sprite Sheet" > "JSON Data"
+// that are needed, everything else is ignored
+
+#[derive(Debug, Deserialize)]
+struct AsepriteFile {
+    frames: AsepriteFrames,
+    meta: AsepriteMeta
+}
+
+// the export either lists the frames as an array or as a map by their file names
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AsepriteFrames {
+    Array(Vec<AsepriteFrame>),
+    Hash(BTreeMap<String, AsepriteFrame>),
+}
+
+#[derive(Debug, Deserialize)]
+struct AsepriteFrame {
+    frame: AsepriteRect,
+    // in milliseconds
+    duration: u32
+}
+
+#[derive(Debug, Deserialize)]
+struct AsepriteRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AsepriteMeta {
+    image: String,
+    #[serde(default)]
+    frame_tags: Vec<AsepriteTag>
+}
+
+#[derive(Debug, Deserialize)]
+struct AsepriteTag {
+    name: String,
+    from: usize,
+    to: usize,
+    // "forward", "reverse" or "pingpong"
+    #[serde(default)]
+    direction: String
+}
+
+/// Loads a sprite sheet exported by Aseprite
+///
+/// ### Arguments
+/// * `path` - The JSON file of the sheet, the image it names lies next to it
+///
+pub fn load_sheet(path: &Path) -> Result<SpriteSheet> {
+    let json = fs::read_to_string(path)?;
+    parse_sheet(&json).map_err(|err| Error::Asset { path: path.to_path_buf(), reason: err.to_string() })
+}
+
+/// Builds a sprite sheet from the JSON Aseprite exports. Every tag becomes a clip of the
+/// same name, a sheet without tags gets a single "default" clip of all frames
+pub fn parse_sheet(json: &str) -> serde_json::Result<SpriteSheet> {
+    let file: AsepriteFile = serde_json::from_str(json)?;
+    let frames = match file.frames {
+        AsepriteFrames::Array(frames) => frames,
+        AsepriteFrames::Hash(frames) => {
+            // names like "player 10.aseprite" have to be ordered by their number
+            let mut frames: Vec<(String, AsepriteFrame)> = frames.into_iter().collect();
+            frames.sort_by_key(|(name, _)| frame_number(name));
+            frames.into_iter().map(|(_, frame)| frame).collect()
+        }
+    };
+
+    let mut clips = HashMap::new();
+    if file.meta.frame_tags.is_empty() && !frames.is_empty() {
+        clips.insert("default".to_string(), clip(&frames, 0, frames.len() - 1, "forward"));
+    }
+    for tag in file.meta.frame_tags.iter() {
+        if tag.from > tag.to || tag.to >= frames.len() {
+            warn!("Tag {} covers frames {} to {} of {}, it is left out", tag.name, tag.from, tag.to, frames.len());
+            continue;
+        }
+        clips.insert(tag.name.clone(), clip(&frames, tag.from, tag.to, &tag.direction));
+    }
+
+    Ok(SpriteSheet {
+        image: file.meta.image,
+        frames: frames.iter().map(|frame| [frame.frame.x, frame.frame.y, frame.frame.w, frame.frame.h]).collect(),
+        clips
+    })
+}
+
+/// The frames from `from` to `to` in the order the direction plays them
+fn clip(frames: &[AsepriteFrame], from: usize, to: usize, direction: &str) -> AnimationClip {
+    let mut order: Vec<usize> = (from..=to).collect();
+    match direction {
+        "reverse" => order.reverse(),
+        // back again without playing the first and last frame twice
+        "pingpong" => order.extend((from + 1..to).rev()),
+        _ => {}
+    }
+    AnimationClip {
+        frames: order.into_iter().map(|index| ClipFrame { index, duration_ms: frames[index].duration }).collect()
+    }
+}
+
+/// The last number in a frame's name
+fn frame_number(name: &str) -> u64 {
+    let digits: String = name.chars()
+                             .rev()
+                             .skip_while(|c| !c.is_ascii_digit())
+                             .take_while(|c| c.is_ascii_digit())
+                             .collect();
+    digits.chars().rev().collect::<String>().parse().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHEET: &str = r#"{
+        "frames": {
+            "player 10.aseprite": { "frame": { "x": 160, "y": 0, "w": 16, "h": 16 }, "duration": 50 },
+            "player 0.aseprite": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "duration": 100 },
+            "player 1.aseprite": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "duration": 100 },
+            "player 2.aseprite": { "frame": { "x": 32, "y": 0, "w": 16, "h": 16 }, "duration": 200 }
+        },
+        "meta": {
+            "image": "player.png",
+            "size": { "w": 176, "h": 16 },
+            "frameTags": [
+                { "name": "walk", "from": 0, "to": 2, "direction": "pingpong" },
+                { "name": "hurt", "from": 3, "to": 3, "direction": "forward" },
+                { "name": "broken", "from": 2, "to": 7, "direction": "forward" }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_aseprite_sheet() {
+        let sheet = parse_sheet(SHEET).unwrap();
+        assert_eq!(sheet.image, "player.png");
+        // frame 10 comes after frame 2
+        assert_eq!(sheet.frames[3], [160, 0, 16, 16]);
+
+        let walk: Vec<(usize, u32)> = sheet.clips["walk"].frames.iter().map(|frame| (frame.index, frame.duration_ms)).collect();
+        assert_eq!(walk, vec![(0, 100), (1, 100), (2, 200), (1, 100)]);
+        assert_eq!(sheet.clips["hurt"].frames.len(), 1);
+        assert!(!sheet.clips.contains_key("broken"));
+    }
+}
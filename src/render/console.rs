@@ -0,0 +1,28 @@
+use crate::ecs;
+use crate::gamelogic::cheats::Cheat;
+use crate::render::renderer::Renderer;
+
+const TEXT_SIZE: u32 = 14;
+const LINE_HEIGHT: f64 = 18.0;
+const MARGIN: f64 = 6.0;
+const TEXT_COLOR: [f32; 4] = [1.0, 0.85, 0.3, 1.0];
+const HINT_COLOR: [f32; 4] = [0.7, 0.7, 0.7, 1.0];
+const PANEL_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.8];
+
+/// Renders the cheat console at the bottom of the screen while it is open: the typed
+/// command and the commands there are
+pub fn render_cheat_console(renderer: &mut dyn Renderer, ecs_: &ecs::ECS) {
+    if !ecs_.cheat_console.open {
+        return;
+    }
+    let commands: Vec<&str> = Cheat::ALL.iter().map(|cheat| cheat.command()).collect();
+    let prompt = ecs_.locale.format("console.prompt", &[("input", &ecs_.cheat_console.input)]);
+    let hint = ecs_.locale.format("console.hint", &[("commands", &commands.join(", "))]);
+
+    renderer.set_camera(None);
+    let [width, height] = renderer.size();
+    let y = height - 2.0 * LINE_HEIGHT - 2.0 * MARGIN;
+    renderer.draw_rect(PANEL_COLOR, [0.0, y, width, 2.0 * LINE_HEIGHT + 2.0 * MARGIN]);
+    renderer.draw_text(&prompt, MARGIN, y + MARGIN, TEXT_SIZE, TEXT_COLOR);
+    renderer.draw_text(&hint, MARGIN, y + MARGIN + LINE_HEIGHT, TEXT_SIZE, HINT_COLOR);
+}
@@ -0,0 +1,39 @@
+use crate::ecs;
+use crate::render::renderer::Renderer;
+
+// height of the black bars above and below the scene
+const LETTERBOX_HEIGHT: f64 = 48.0;
+const LETTERBOX_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+const BOX_HEIGHT: f64 = 80.0;
+const MARGIN: f64 = 8.0;
+const TEXT_SIZE: u32 = 14;
+const LINE_HEIGHT: f64 = 20.0;
+const BOX_COLOR: [f32; 4] = [0.1, 0.1, 0.15, 0.9];
+const SPEAKER_COLOR: [f32; 4] = [1.0, 0.85, 0.3, 1.0];
+const TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const HINT_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+
+/// Renders the cutscene playing: the scene is framed by black bars, its dialogue is shown
+/// in a box at the bottom with the name of the speaker
+pub fn render_cutscene(renderer: &mut dyn Renderer, ecs_: &ecs::ECS) {
+    let cutscene = match &ecs_.cutscene {
+        Some(cutscene) => cutscene,
+        None => return
+    };
+    let [width, height] = renderer.size();
+    renderer.set_camera(None);
+    renderer.draw_rect(LETTERBOX_COLOR, [0.0, 0.0, width, LETTERBOX_HEIGHT]);
+    renderer.draw_rect(LETTERBOX_COLOR, [0.0, height - LETTERBOX_HEIGHT, width, LETTERBOX_HEIGHT]);
+
+    if let Some((speaker, text)) = &cutscene.dialogue {
+        let y = height - BOX_HEIGHT - MARGIN;
+        renderer.draw_rect(BOX_COLOR, [MARGIN, y, width - 2.0 * MARGIN, BOX_HEIGHT]);
+        let name = speaker.and_then(|speaker| ecs_.name_component.get(speaker))
+                          .map(|name_c| ecs_.locale.text(&name_c.name));
+        if let Some(name) = name {
+            renderer.draw_text(&name, 2.0 * MARGIN, y + MARGIN, TEXT_SIZE, SPEAKER_COLOR);
+        }
+        renderer.draw_text(&ecs_.locale.text(text), 2.0 * MARGIN, y + MARGIN + LINE_HEIGHT, TEXT_SIZE, TEXT_COLOR);
+        renderer.draw_text(&ecs_.locale.text("cutscene.hint"), 2.0 * MARGIN, y + BOX_HEIGHT - MARGIN - TEXT_SIZE as f64, TEXT_SIZE, HINT_COLOR);
+    }
+}
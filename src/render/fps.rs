@@ -0,0 +1,90 @@
+extern crate piston;
+extern crate graphics;
+extern crate opengl_graphics;
+
+use std::time::{Duration, Instant};
+
+use piston::input::RenderArgs;
+use opengl_graphics::GlGraphics;
+
+use crate::locale::Locale;
+use crate::render::{RenderConfig, text::{TextRenderer, TextStyle}};
+
+const TEXT_SIZE: u32 = 12;
+const TEXT_COLOR: [f32; 4] = [1.0, 1.0, 0.3, 1.0];
+const MARGIN: f64 = 6.0;
+
+/// Counts how often something happens per second, the rate is updated once per second
+struct RateCounter {
+    window_start: Instant,
+    count: u32,
+    rate: u32
+}
+
+impl RateCounter {
+    fn new() -> RateCounter {
+        RateCounter {
+            window_start: Instant::now(),
+            count: 0,
+            rate: 0
+        }
+    }
+
+    fn tick(&mut self) {
+        self.count += 1;
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.rate = (self.count as f64 / elapsed.as_secs_f64()).round() as u32;
+            self.count = 0;
+            self.window_start = Instant::now();
+        }
+    }
+}
+
+/// Measures rendered frames and game updates per second
+pub struct FpsCounter {
+    frames: RateCounter,
+    updates: RateCounter
+}
+
+impl FpsCounter {
+    pub fn new() -> FpsCounter {
+        FpsCounter {
+            frames: RateCounter::new(),
+            updates: RateCounter::new()
+        }
+    }
+
+    /// Should be called once per rendered frame
+    pub fn frame(&mut self) {
+        self.frames.tick();
+    }
+
+    /// Should be called once per game update
+    pub fn update(&mut self) {
+        self.updates.tick();
+    }
+
+    pub fn fps(&self) -> u32 {
+        self.frames.rate
+    }
+
+    pub fn ups(&self) -> u32 {
+        self.updates.rate
+    }
+
+    /// Renders the counter into the bottom left corner of the window
+    pub fn render(&self, gl: &mut GlGraphics, args: &RenderArgs, conf: &RenderConfig, locale: &Locale, text: &mut TextRenderer) {
+        let label = locale.format("hud.fps", &[("fps", &self.fps()), ("ups", &self.ups())]);
+        let y = conf.window_ys as f64 - MARGIN - TEXT_SIZE as f64;
+        gl.draw(args.viewport(), |c, gl| {
+            text.draw_text(&label, MARGIN, y, TextStyle { size: TEXT_SIZE, color: TEXT_COLOR }, c.transform, gl);
+        });
+    }
+}
+
+impl Default for FpsCounter {
+    fn default() -> FpsCounter {
+        FpsCounter::new()
+    }
+}
@@ -0,0 +1,57 @@
+use crate::ecs;
+use crate::gamelogic::new_game_plus;
+use crate::gamestate::run::RunOutcome;
+use crate::render::{RenderConfig, renderer::Renderer};
+
+const MARGIN: f64 = 40.0;
+const LINE_HEIGHT: f64 = 24.0;
+const TITLE_SIZE: u32 = 32;
+const TEXT_SIZE: u32 = 16;
+
+const VICTORY_COLOR: [f32; 4] = [1.0, 0.85, 0.3, 1.0];
+const DEFEAT_COLOR: [f32; 4] = [0.85, 0.2, 0.2, 1.0];
+const TEXT_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 1.0];
+const SELECTED_COLOR: [f32; 4] = [1.0, 0.9, 0.3, 1.0];
+
+/// Renders the end of a run with its statistics, replacing the dungeon view while
+/// `GameMode::GameOver` is active. After a victory the equipment that can be kept for
+/// new-game-plus is listed
+pub fn render_game_over(renderer: &mut dyn Renderer, ecs_: &mut ecs::ECS, conf: &RenderConfig) {
+    let summary = match &ecs_.run_summary {
+        Some(summary) => summary.clone(),
+        None => return
+    };
+    let (title, title_color) = match summary.outcome {
+        RunOutcome::Victory => (ecs_.locale.text("gameover.victory"), VICTORY_COLOR),
+        RunOutcome::Defeat => (ecs_.locale.text("gameover.defeat"), DEFEAT_COLOR)
+    };
+    let lines = [
+        ecs_.locale.format("gameover.turns", &[("turns", &summary.turns)]),
+        ecs_.locale.format("gameover.kills", &[("kills", &summary.kills)]),
+        ecs_.locale.format("gameover.gold", &[("gold", &summary.gold)]),
+        ecs_.locale.format("gameover.floor", &[("floor", &summary.deepest_floor)])
+    ];
+    // with permadeath there is no autosave left to go back to
+    let hint = ecs_.locale.text(if ecs_.permadeath { "gameover.hint_permadeath" } else { "gameover.hint" });
+
+    renderer.set_camera(None);
+    renderer.clear([0.05, 0.05, 0.08, 1.0]);
+    renderer.draw_text(&title, MARGIN, MARGIN, TITLE_SIZE, title_color);
+    for (row, line) in lines.iter().enumerate() {
+        let y = 2.0 * MARGIN + TITLE_SIZE as f64 + row as f64 * LINE_HEIGHT;
+        renderer.draw_text(line, MARGIN, y, TEXT_SIZE, TEXT_COLOR);
+    }
+    if summary.outcome == RunOutcome::Victory {
+        let choices = new_game_plus::equipment_choices(ecs_);
+        let top = 2.0 * MARGIN + TITLE_SIZE as f64 + (lines.len() + 1) as f64 * LINE_HEIGHT;
+        let heading = ecs_.locale.format("gameover.keep", &[("kept", &summary.kept.len()), ("limit", &new_game_plus::KEPT_EQUIPMENT_LIMIT)]);
+        renderer.draw_text(&heading, MARGIN, top, TEXT_SIZE, TEXT_COLOR);
+        for (row, item) in choices.iter().enumerate() {
+            let marker = if summary.kept.contains(&row) { "[x]" } else { "[ ]" };
+            let line = format!("{} {}", marker, ecs_.locale.text(&item.item.locale_key()));
+            let color = if row == summary.selected { SELECTED_COLOR } else { TEXT_COLOR };
+            renderer.draw_text(&line, MARGIN, top + (row + 1) as f64 * LINE_HEIGHT, TEXT_SIZE, color);
+        }
+    }
+    renderer.draw_text(&hint, MARGIN, conf.window_ys as f64 - MARGIN, TEXT_SIZE, TEXT_COLOR);
+}
@@ -0,0 +1,95 @@
+use piston::input::RenderArgs;
+use opengl_graphics::GlGraphics;
+use graphics::{Image, Transformed, draw_state::{self, DrawState}, rectangle};
+
+use crate::render::renderer::{Blend, Camera, Color, Renderer};
+use crate::render::{sprite::SpriteTextures, text::{TextRenderer, TextStyle}};
+
+/// Draws with OpenGL into the game window
+pub struct GlRenderer<'a> {
+    gl: &'a mut GlGraphics,
+    args: RenderArgs,
+    textures: &'a mut SpriteTextures,
+    text: &'a mut TextRenderer,
+    camera: Option<Camera>
+}
+
+impl<'a> GlRenderer<'a> {
+    pub fn new(gl: &'a mut GlGraphics, args: &RenderArgs, textures: &'a mut SpriteTextures, text: &'a mut TextRenderer) -> GlRenderer<'a> {
+        GlRenderer {
+            gl,
+            args: *args,
+            textures,
+            text,
+            camera: None
+        }
+    }
+
+    fn to_screen(&self, rect: [f64; 4]) -> [f64; 4] {
+        match &self.camera {
+            Some(camera) => camera.to_screen(rect),
+            None => rect
+        }
+    }
+
+    /// Draws the texture of a sprite, or a part of it
+    fn draw_image(&mut self, sprite: &str, region: Option<[f64; 4]>, rect: [f64; 4], rotation: f64, color: Option<Color>, blend: Blend) -> bool {
+        let rect = self.to_screen(rect);
+        let draw_state = match blend {
+            Blend::Alpha => DrawState::default(),
+            Blend::Add => DrawState::default().blend(draw_state::Blend::Add)
+        };
+        let texture = match self.textures.get_by_name(sprite) {
+            Some(texture) => texture,
+            None => return false
+        };
+        let image = Image::new().rect(rect).maybe_color(color).maybe_src_rect(region);
+        self.gl.draw(self.args.viewport(), |c, gl| {
+            let center_x = rect[0] + rect[2] / 2.0;
+            let center_y = rect[1] + rect[3] / 2.0;
+            let transform = c.transform.trans(center_x, center_y)
+                                       .rot_deg(rotation)
+                                       .trans(-center_x, -center_y);
+            image.draw(texture, &draw_state, transform, gl);
+        });
+        true
+    }
+}
+
+impl Renderer for GlRenderer<'_> {
+    fn size(&self) -> [f64; 2] {
+        self.args.window_size
+    }
+
+    fn clear(&mut self, color: Color) {
+        graphics::clear(color, self.gl);
+    }
+
+    fn set_camera(&mut self, camera: Option<Camera>) {
+        self.camera = camera;
+    }
+
+    fn draw_sprite(&mut self, sprite: &str, rect: [f64; 4], rotation: f64, color: Option<Color>, blend: Blend) -> bool {
+        self.draw_image(sprite, None, rect, rotation, color, blend)
+    }
+
+    fn draw_sprite_region(&mut self, sprite: &str, region: [u32; 4], rect: [f64; 4], rotation: f64, color: Option<Color>, blend: Blend) -> bool {
+        let [x, y, width, height] = region;
+        self.draw_image(sprite, Some([x as f64, y as f64, width as f64, height as f64]), rect, rotation, color, blend)
+    }
+
+    fn draw_rect(&mut self, color: Color, rect: [f64; 4]) {
+        let rect = self.to_screen(rect);
+        self.gl.draw(self.args.viewport(), |c, gl| {
+            rectangle(color, rect, c.transform, gl);
+        });
+    }
+
+    fn draw_text(&mut self, text: &str, x: f64, y: f64, size: u32, color: Color) {
+        let position = self.to_screen([x, y, 0.0, 0.0]);
+        let text_renderer = &mut self.text;
+        self.gl.draw(self.args.viewport(), |c, gl| {
+            text_renderer.draw_text(text, position[0], position[1], TextStyle { size, color }, c.transform, gl);
+        });
+    }
+}
@@ -0,0 +1,162 @@
+use crate::ecs;
+use crate::gamelogic;
+use crate::render::palette::PaletteColor;
+use crate::render::renderer::{Blend, Renderer};
+
+const BAR_WIDTH: f64 = 200.0;
+const BAR_HEIGHT: f64 = 12.0;
+const BAR_MARGIN: f64 = 6.0;
+
+const PARTY_HEALTH_COLOR: [f32; 4] = [0.5, 0.15, 0.15, 1.0];
+const BAR_BACKGROUND: [f32; 4] = [0.1, 0.1, 0.1, 0.8];
+const LABEL_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const LABEL_SIZE: u32 = 11;
+
+const SLOT_SIZE: f64 = 48.0;
+const SLOT_MARGIN: f64 = 4.0;
+const SLOT_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 0.8];
+const UNUSABLE_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+const COOLDOWN_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const CHARGES_COLOR: [f32; 4] = [1.0, 0.9, 0.2, 1.0];
+// characters of a slot's name that fit into it
+const SLOT_NAME_CHARS: usize = 6;
+
+// actions shown in the turn order strip
+const TURN_ORDER_LENGTH: usize = 8;
+const PORTRAIT_SIZE: f64 = 32.0;
+const PLAYER_PORTRAIT_COLOR: [f32; 4] = [0.2, 0.4, 0.8, 0.8];
+const HOSTILE_PORTRAIT_COLOR: [f32; 4] = [0.6, 0.15, 0.15, 0.8];
+
+/// Renders the heads-up display for the player: health, mana, hunger, the
+/// progress of resting and the orders of the companions
+pub fn render_hud(renderer: &mut dyn Renderer, ecs_: &mut ecs::ECS) {
+    let player = match ecs_.get_player_entity() {
+        Some(player) => player,
+        None => return
+    };
+    // cutscenes frame the scene with their own bars
+    if gamelogic::cutscene::is_playing(ecs_) {
+        return;
+    }
+
+    // collect bars as (fill ratio, color, label), colored by the palette of the settings
+    let palette = ecs_.settings.accessibility.palette;
+    let mut bars: Vec<(f64, [f32; 4], String)> = Vec::new();
+    if let Some(health_c) = ecs_.health_component.get(player) {
+        bars.push((ratio(health_c.current, health_c.maximum), palette.color(PaletteColor::Health),
+                   ecs_.locale.format("hud.health", &[("current", &health_c.current), ("maximum", &health_c.maximum)])));
+    }
+    if let Some(caster_c) = ecs_.caster_component.get(player) {
+        bars.push((ratio(caster_c.current_mana, caster_c.maximum_mana), palette.color(PaletteColor::Mana),
+                   ecs_.locale.format("hud.mana", &[("current", &caster_c.current_mana), ("maximum", &caster_c.maximum_mana)])));
+    }
+    if let Some(hunger_c) = ecs_.hunger_component.get(player) {
+        bars.push((ratio(hunger_c.current, hunger_c.maximum), palette.color(PaletteColor::Hunger), ecs_.locale.text("hud.food")));
+    }
+    if let Some(completion) = gamelogic::rest_completion(ecs_, player) {
+        let percent = (completion * 100.0) as i32;
+        bars.push((completion, palette.color(PaletteColor::Rest), ecs_.locale.format("hud.resting", &[("percent", &percent)])));
+    }
+
+    renderer.set_camera(None);
+    for (i, (fill, color, label)) in bars.iter().enumerate() {
+        let x = BAR_MARGIN;
+        let y = BAR_MARGIN + i as f64 * (BAR_HEIGHT + BAR_MARGIN);
+        renderer.draw_rect(BAR_BACKGROUND, [x, y, BAR_WIDTH, BAR_HEIGHT]);
+        renderer.draw_rect(*color, [x, y, BAR_WIDTH * fill, BAR_HEIGHT]);
+        renderer.draw_text(label, x + 4.0, y - 1.0, LABEL_SIZE, LABEL_COLOR);
+    }
+
+    render_party(renderer, ecs_, BAR_MARGIN + bars.len() as f64 * (BAR_HEIGHT + BAR_MARGIN));
+    render_hotbar(renderer, ecs_, player);
+    render_turn_order(renderer, ecs_);
+}
+
+/// Renders the companions below the bars, each with its health and the order it follows
+fn render_party(renderer: &mut dyn Renderer, ecs_: &ecs::ECS, top: f64) {
+    for (i, companion) in gamelogic::party::companions(ecs_).into_iter().enumerate() {
+        let (order, name, health) = match (ecs_.companion_component.get(companion), ecs_.name_component.get(companion)) {
+            (Some(companion_c), Some(name_c)) => (companion_c.order, &name_c.name, ecs_.health_component.get(companion)),
+            _ => continue
+        };
+        let y = top + i as f64 * (BAR_HEIGHT + BAR_MARGIN);
+        renderer.draw_rect(BAR_BACKGROUND, [BAR_MARGIN, y, BAR_WIDTH, BAR_HEIGHT]);
+        if let Some(health_c) = health {
+            renderer.draw_rect(PARTY_HEALTH_COLOR, [BAR_MARGIN, y, BAR_WIDTH * ratio(health_c.current, health_c.maximum), BAR_HEIGHT]);
+        }
+        let label = ecs_.locale.format("hud.companion", &[("name", &ecs_.locale.text(name)), ("order", &ecs_.locale.text(&order.locale_key()))]);
+        renderer.draw_text(&label, BAR_MARGIN + 4.0, y - 1.0, LABEL_SIZE, LABEL_COLOR);
+    }
+}
+
+/// Renders the sprites of the actors acting next in a strip at the top of the screen,
+/// the next one on the left. It is only shown while the player is not alone
+fn render_turn_order(renderer: &mut dyn Renderer, ecs_: &ecs::ECS) {
+    let upcoming = gamelogic::scheduling::upcoming_turns(ecs_, TURN_ORDER_LENGTH);
+    if upcoming.iter().all(|entity| *entity == upcoming[0]) {
+        return;
+    }
+    let width = upcoming.len() as f64 * (PORTRAIT_SIZE + SLOT_MARGIN) - SLOT_MARGIN;
+    let left = (renderer.size()[0] - width) / 2.0;
+    renderer.draw_text(&ecs_.locale.text("hud.turn_order"), left, BAR_MARGIN, LABEL_SIZE, LABEL_COLOR);
+    let y = BAR_MARGIN + LABEL_SIZE as f64 + 2.0;
+    for (i, entity) in upcoming.iter().enumerate() {
+        let x = left + i as f64 * (PORTRAIT_SIZE + SLOT_MARGIN);
+        let background = if ecs_.player_component.get(*entity).is_some() {
+            PLAYER_PORTRAIT_COLOR
+        } else if ecs_.npc_behavior_component.get(*entity).is_some_and(|npc_c| npc_c.hostile) {
+            HOSTILE_PORTRAIT_COLOR
+        } else {
+            BAR_BACKGROUND
+        };
+        renderer.draw_rect(background, [x, y, PORTRAIT_SIZE, PORTRAIT_SIZE]);
+        let sprite = ecs_.render_component.get(*entity).map_or("default", |render_c| render_c.base_sprite.as_str());
+        let rect = [x + 2.0, y + 2.0, PORTRAIT_SIZE - 4.0, PORTRAIT_SIZE - 4.0];
+        if !renderer.draw_sprite(sprite, rect, 0.0, None, Blend::Alpha) {
+            renderer.draw_sprite("default", rect, 0.0, None, Blend::Alpha);
+        }
+    }
+}
+
+/// Renders the hotbar of the player at the bottom of the screen. Slots cooling down are
+/// covered in proportion to the turns left, charges are shown in their corner
+fn render_hotbar(renderer: &mut dyn Renderer, ecs_: &ecs::ECS, player: ecs::Entity) {
+    let slots = match ecs_.hotbar_component.get(player) {
+        Some(hotbar_c) => &hotbar_c.slots,
+        None => return
+    };
+    let size = renderer.size();
+    let width = slots.len() as f64 * (SLOT_SIZE + SLOT_MARGIN) - SLOT_MARGIN;
+    let y = size[1] - SLOT_SIZE - BAR_MARGIN;
+    for (i, slot) in slots.iter().enumerate() {
+        let x = (size[0] - width) / 2.0 + i as f64 * (SLOT_SIZE + SLOT_MARGIN);
+        renderer.draw_rect(BAR_BACKGROUND, [x, y, SLOT_SIZE, SLOT_SIZE]);
+        // the number key of the slot, 0 for the tenth
+        renderer.draw_text(&((i + 1) % 10).to_string(), x + 3.0, y + 1.0, LABEL_SIZE, LABEL_COLOR);
+
+        let state = match slot.and_then(|slot| gamelogic::hotbar::slot_state(ecs_, player, slot)) {
+            Some(state) => state,
+            None => continue
+        };
+        renderer.draw_rect(SLOT_COLOR, [x + 2.0, y + 14.0, SLOT_SIZE - 4.0, SLOT_SIZE - 16.0]);
+        let name: String = ecs_.locale.text(&state.name_key).chars().take(SLOT_NAME_CHARS).collect();
+        renderer.draw_text(&name, x + 3.0, y + 18.0, LABEL_SIZE, if state.usable { LABEL_COLOR } else { UNUSABLE_COLOR });
+        if let Some(charges) = state.charges {
+            renderer.draw_text(&charges.to_string(), x + SLOT_SIZE - 14.0, y + SLOT_SIZE - 14.0, LABEL_SIZE, CHARGES_COLOR);
+        }
+        let (remaining, total) = state.cooldown;
+        if remaining > 0 && total > 0 {
+            let covered = SLOT_SIZE * (remaining as f64 / total as f64).min(1.0);
+            renderer.draw_rect(COOLDOWN_COLOR, [x, y + SLOT_SIZE - covered, SLOT_SIZE, covered]);
+            renderer.draw_text(&remaining.to_string(), x + SLOT_SIZE / 2.0 - 3.0, y + SLOT_SIZE / 2.0 - 6.0, LABEL_SIZE, LABEL_COLOR);
+        }
+    }
+}
+
+fn ratio(current: i32, maximum: i32) -> f64 {
+    if maximum <= 0 {
+        0.0
+    } else {
+        (current as f64 / maximum as f64).clamp(0.0, 1.0)
+    }
+}
@@ -0,0 +1,93 @@
+use crate::ecs;
+use crate::gamestate::LocationVec;
+use crate::persistence;
+use crate::render::{game_camera, renderer::Renderer, RenderConfig};
+
+const TEXT_SIZE: u32 = 12;
+const LINE_HEIGHT: f64 = 16.0;
+const MARGIN: f64 = 6.0;
+const PANEL_WIDTH: f64 = 420.0;
+// characters of a component's value per line, longer values are wrapped
+const LINE_CHARS: usize = 60;
+const TEXT_COLOR: [f32; 4] = [0.6, 0.8, 1.0, 1.0];
+const NAME_COLOR: [f32; 4] = [1.0, 1.0, 0.6, 1.0];
+const PANEL_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.7];
+const SELECTION_COLOR: [f32; 4] = [0.3, 0.6, 1.0, 0.35];
+
+/// State of the entity inspector, a debug overlay listing all components of an entity
+#[derive(Debug, Default)]
+pub struct Inspector {
+    pub visible: bool,
+    // the clicked entity, the focused one if none was clicked
+    pub selected: Option<ecs::Entity>
+}
+
+/// Selects the entity drawn on top at a location for the inspector. Clicking a field
+/// without any entity selects none, so the focused one is shown again
+pub fn select_at(ecs_: &mut ecs::ECS, location: LocationVec) {
+    let field = LocationVec { x: location.x.round(), y: location.y.round() };
+    let selected = ecs_.get_entities_by_location(field)
+                       .into_iter()
+                       .max_by_key(|entity| ecs_.render_component.get(*entity).map(|render_c| render_c.render_layer));
+    debug!("Inspecting {:?} at {:?}", selected, field);
+    ecs_.inspector.selected = selected;
+}
+
+/// Renders the components of the inspected entity into the bottom left corner and marks
+/// its field, if the inspector is shown
+///
+/// ### Arguments
+/// * `alpha` - Time since the last update, relative to the time between updates
+///
+pub fn render_inspector(renderer: &mut dyn Renderer, ecs_: &ecs::ECS, conf: &RenderConfig, alpha: f64) {
+    if !ecs_.inspector.visible {
+        return;
+    }
+    let entity = match ecs_.inspector.selected.filter(|entity| ecs_.allocator.is_live(*entity)).or(conf.focused_entity) {
+        Some(entity) => entity,
+        None => return
+    };
+
+    if let Some(location_c) = ecs_.location_component.get(entity) {
+        let location = location_c.interpolated(alpha);
+        renderer.set_camera(Some(game_camera(ecs_, conf, alpha)));
+        renderer.draw_rect(SELECTION_COLOR, [location.x - 0.5, location.y - 0.5, 1.0, 1.0]);
+    }
+
+    let mut lines = vec![(format!("{:?}", entity), NAME_COLOR)];
+    for (name, value) in persistence::inspect_entity(ecs_, entity) {
+        lines.push((name.to_string(), NAME_COLOR));
+        let chars: Vec<char> = value.chars().collect();
+        lines.extend(chars.chunks(LINE_CHARS).map(|chunk| (format!("  {}", chunk.iter().collect::<String>()), TEXT_COLOR)));
+    }
+
+    renderer.set_camera(None);
+    let height = lines.len() as f64 * LINE_HEIGHT + MARGIN;
+    let y = (renderer.size()[1] - height - MARGIN).max(MARGIN);
+    renderer.draw_rect(PANEL_COLOR, [MARGIN, y, PANEL_WIDTH, height]);
+    for (i, (line, color)) in lines.iter().enumerate() {
+        renderer.draw_text(line, 2.0 * MARGIN, y + i as f64 * LINE_HEIGHT, TEXT_SIZE, *color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+
+    #[test]
+    fn test_clicked_entity_is_inspected() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 6, 5);
+        let player = dungeon::create_test_player(&mut ecs_, 2.0, 2.0);
+
+        // the player is drawn above the floor below it
+        select_at(&mut ecs_, LocationVec { x: 2.3, y: 1.6 });
+        assert_eq!(ecs_.inspector.selected, Some(player));
+        let components = persistence::inspect_entity(&ecs_, player);
+        assert!(components.iter().any(|(name, value)| *name == "health_component" && value.contains("current: 100")));
+
+        select_at(&mut ecs_, LocationVec { x: 9.0, y: 9.0 });
+        assert_eq!(ecs_.inspector.selected, None);
+    }
+}
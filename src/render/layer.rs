@@ -0,0 +1,69 @@
+extern crate serde;
+
+use serde::{Serialize, Deserialize};
+
+/// The layers entities are drawn in, from the bottom to the top. Layers are saved as
+/// numbers, which older saves used for their layers as well
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+#[serde(from = "i32", into = "i32")]
+pub enum RenderLayer {
+    // floors, walls and water
+    Floor,
+    // items, corpses and furniture like doors and chests
+    Items,
+    // creatures, drawn in front of each other from the top of the screen down
+    Actors,
+    Effects,
+    // markers drawn over the scene, e.g. of targets
+    Ui,
+}
+
+impl RenderLayer {
+    /// Tests if the entities of the layer are sorted by their y coordinate, so the
+    /// lower ones on screen are drawn in front of the ones above them
+    pub fn is_y_sorted(self) -> bool {
+        self == RenderLayer::Actors
+    }
+}
+
+impl From<i32> for RenderLayer {
+    fn from(number: i32) -> RenderLayer {
+        match number {
+            i32::MIN..=0 => RenderLayer::Floor,
+            1 => RenderLayer::Items,
+            2 => RenderLayer::Actors,
+            3 => RenderLayer::Effects,
+            _ => RenderLayer::Ui
+        }
+    }
+}
+
+impl From<RenderLayer> for i32 {
+    fn from(layer: RenderLayer) -> i32 {
+        layer as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::ecs;
+    use crate::render;
+
+    #[test]
+    fn test_lower_actors_are_drawn_in_front() {
+        let mut ecs_ = ecs::ECS::new();
+        dungeon::create_empty_room(&mut ecs_, 0.0, 0.0, 6, 6);
+        let lower = dungeon::create_attack_dummy(&mut ecs_, 2.0, 3.0);
+        let upper = dungeon::create_test_player(&mut ecs_, 2.0, 2.0);
+
+        let order = render::render_order(&ecs_, 1.0);
+        assert_eq!(order.keys().copied().collect::<Vec<RenderLayer>>(), vec![RenderLayer::Floor, RenderLayer::Actors]);
+        assert_eq!(order[&RenderLayer::Actors], vec![upper, lower]);
+
+        // layers of older saves were numbers
+        assert_eq!(serde_yaml::from_str::<RenderLayer>("2").unwrap(), RenderLayer::Actors);
+        assert_eq!(serde_yaml::to_string(&RenderLayer::Items).unwrap().trim_start_matches("---").trim(), "1");
+    }
+}
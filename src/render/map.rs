@@ -0,0 +1,87 @@
+extern crate piston;
+extern crate graphics;
+extern crate opengl_graphics;
+
+use piston::input::RenderArgs;
+use opengl_graphics::GlGraphics;
+use graphics::{clear, rectangle, ellipse};
+
+use crate::ecs;
+use crate::gamestate::{dungeon::DungeonElement, explored::tile_coord};
+use crate::render::{RenderConfig, minimap::element_color, palette::PaletteColor};
+
+// space kept free around the map in pixels
+const MARGIN: f64 = 20.0;
+
+const SHOP_COLOR: [f32; 4] = [0.2, 0.5, 1.0, 1.0];
+const QUEST_COLOR: [f32; 4] = [0.9, 0.2, 0.9, 1.0];
+
+/// Renders the whole explored floor scaled to fit the window, with markers for
+/// stairs, shops and quest targets. Replaces the dungeon view while `GameMode::Map` is active
+pub fn render_map(gl: &mut GlGraphics, args: &RenderArgs, ecs_: &mut ecs::ECS, conf: &RenderConfig) {
+    let player_location = ecs_.get_player_entity()
+                              .and_then(|player| ecs_.location_component.get(player))
+                              .map(|location_c| tile_coord(&location_c.location));
+    let player_color = ecs_.settings.accessibility.palette.color(PaletteColor::Player);
+
+    let explored = &ecs_.explored;
+    let ((min_x, min_y), (max_x, max_y)) = match explored.bounds() {
+        Some(bounds) => bounds,
+        None => {
+            gl.draw(args.viewport(), |_c, gl| clear([0.0, 0.0, 0.0, 1.0], gl));
+            return;
+        }
+    };
+
+    // fit the explored area into the window keeping fields square
+    let fields_x = (max_x - min_x + 1) as f64;
+    let fields_y = (max_y - min_y + 1) as f64;
+    let tile_size = ((conf.window_xs as f64 - 2.0 * MARGIN) / fields_x)
+                        .min((conf.window_ys as f64 - 2.0 * MARGIN) / fields_y);
+    let origin_x = (conf.window_xs as f64 - fields_x * tile_size) / 2.0;
+    let origin_y = (conf.window_ys as f64 - fields_y * tile_size) / 2.0;
+    let to_screen = |(x, y): (i64, i64)| -> [f64; 4] {
+        [origin_x + (x - min_x) as f64 * tile_size, origin_y + (y - min_y) as f64 * tile_size, tile_size, tile_size]
+    };
+
+    // markers are only shown for explored fields
+    let mut markers: Vec<((i64, i64), [f32; 4])> = Vec::new();
+    for entity in ecs_.allocator.live_indices() {
+        let color = if ecs_.shop_component.get(entity).is_some() {
+            SHOP_COLOR
+        } else if ecs_.quest_target_component.get(entity).is_some() {
+            QUEST_COLOR
+        } else {
+            continue;
+        };
+        if let Some(location_c) = ecs_.location_component.get(entity) {
+            if explored.is_explored(&location_c.location) {
+                markers.push((tile_coord(&location_c.location), color));
+            }
+        }
+    }
+
+    gl.draw(args.viewport(), |c, gl| {
+        clear([0.0, 0.0, 0.0, 1.0], gl);
+
+        for (coord, element) in &explored.tiles {
+            if let Some(color) = element_color(*element) {
+                rectangle(color, to_screen(*coord), c.transform, gl);
+            }
+        }
+
+        // markers are drawn larger than a field so they stay visible on big floors
+        let grow = |rect: [f64; 4]| [rect[0] - tile_size, rect[1] - tile_size, rect[2] + 2.0 * tile_size, rect[3] + 2.0 * tile_size];
+        for (coord, element) in &explored.tiles {
+            if let (DungeonElement::Connector, Some(color)) = (element, element_color(*element)) {
+                rectangle(color, grow(to_screen(*coord)), c.transform, gl);
+            }
+        }
+        for (coord, color) in &markers {
+            ellipse(*color, grow(to_screen(*coord)), c.transform, gl);
+        }
+        if let Some(coord) = player_location {
+            ellipse(player_color, grow(to_screen(coord)), c.transform, gl);
+        }
+    });
+}
@@ -0,0 +1,90 @@
+extern crate piston;
+extern crate graphics;
+extern crate opengl_graphics;
+
+use piston::input::RenderArgs;
+use opengl_graphics::GlGraphics;
+use graphics::rectangle;
+
+use crate::ecs;
+use crate::gamelogic;
+use crate::gamestate::{dungeon::DungeonElement, explored::tile_coord};
+use crate::render::{RenderConfig, palette::PaletteColor};
+
+// size of one field on the minimap in pixels
+const TILE_SIZE: f64 = 4.0;
+// number of fields shown in each direction around the player
+const RADIUS: i64 = 25;
+const MARGIN: f64 = 6.0;
+
+const BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const FLOOR_COLOR: [f32; 4] = [0.35, 0.35, 0.35, 1.0];
+const WALL_COLOR: [f32; 4] = [0.75, 0.75, 0.75, 1.0];
+const WATER_COLOR: [f32; 4] = [0.15, 0.3, 0.7, 1.0];
+const STAIRS_COLOR: [f32; 4] = [0.9, 0.8, 0.1, 1.0];
+
+/// Color a dungeon element is shown with on maps
+pub fn element_color(element: DungeonElement) -> Option<[f32; 4]> {
+    match element {
+        DungeonElement::Floor => Some(FLOOR_COLOR),
+        DungeonElement::Wall => Some(WALL_COLOR),
+        DungeonElement::Connector => Some(STAIRS_COLOR),
+        DungeonElement::Water => Some(WATER_COLOR),
+        DungeonElement::Event => None
+    }
+}
+
+/// Renders a small map of the explored fields around the player in the top right
+/// corner of the window, together with the stairs and all hostile entities the player
+/// currently sees
+pub fn render_minimap(gl: &mut GlGraphics, args: &RenderArgs, ecs_: &mut ecs::ECS, conf: &RenderConfig) {
+    if !conf.show_minimap {
+        return;
+    }
+    let player = match ecs_.get_player_entity() {
+        Some(player) => player,
+        None => return
+    };
+    let center = match ecs_.location_component.get(player) {
+        Some(location_c) => tile_coord(&location_c.location),
+        None => return
+    };
+
+    let map_size = (2 * RADIUS + 1) as f64 * TILE_SIZE;
+    let origin_x = conf.window_xs as f64 - map_size - MARGIN;
+    let origin_y = MARGIN;
+    let to_screen = |(x, y): (i64, i64)| -> Option<[f64; 4]> {
+        let dx = x - center.0 + RADIUS;
+        let dy = y - center.1 + RADIUS;
+        if dx < 0 || dy < 0 || dx > 2 * RADIUS || dy > 2 * RADIUS {
+            return None;
+        }
+        Some([origin_x + dx as f64 * TILE_SIZE, origin_y + dy as f64 * TILE_SIZE, TILE_SIZE, TILE_SIZE])
+    };
+
+    let enemies: Vec<(i64, i64)> = gamelogic::visible_hostiles(ecs_, player).into_iter()
+        .filter_map(|enemy| ecs_.location_component.get(enemy))
+        .map(|location_c| tile_coord(&location_c.location))
+        .collect();
+    let explored = &ecs_.explored;
+    let palette = ecs_.settings.accessibility.palette;
+    let (player_color, enemy_color) = (palette.color(PaletteColor::Player), palette.color(PaletteColor::Hostile));
+
+    gl.draw(args.viewport(), |c, gl| {
+        rectangle(BACKGROUND_COLOR, [origin_x, origin_y, map_size, map_size], c.transform, gl);
+
+        for (coord, element) in &explored.tiles {
+            if let (Some(rect), Some(color)) = (to_screen(*coord), element_color(*element)) {
+                rectangle(color, rect, c.transform, gl);
+            }
+        }
+        for enemy in &enemies {
+            if let Some(rect) = to_screen(*enemy) {
+                rectangle(enemy_color, rect, c.transform, gl);
+            }
+        }
+        if let Some(rect) = to_screen(center) {
+            rectangle(player_color, rect, c.transform, gl);
+        }
+    });
+}
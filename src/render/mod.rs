@@ -1,19 +1,50 @@
+// the state of animations, particles and sprites is part of the game and the scene and
+// menus are built for any `renderer::Renderer`. Screens only drawn in the window and its
+// OpenGL backend need the `window` feature
 pub mod animation;
+pub mod aseprite;
+pub mod console;
+pub mod cutscene;
+pub mod gameover;
+pub mod hud;
+pub mod inspector;
+pub mod layer;
+pub mod overworld;
+pub mod palette;
+pub mod particle;
+pub mod preview;
+pub mod profiler;
+pub mod renderer;
+pub mod saves;
+pub mod settings;
+pub mod shake;
 pub mod sprite;
+pub mod targeting;
+pub mod toast;
+pub mod transition;
+#[cfg(feature = "window")]
+pub mod fps;
+#[cfg(feature = "window")]
+pub mod gl;
+#[cfg(feature = "window")]
+pub mod map;
+#[cfg(feature = "window")]
+pub mod minimap;
+#[cfg(feature = "terminal")]
+pub mod terminal;
+#[cfg(feature = "window")]
+pub mod text;
+#[cfg(feature = "window")]
+pub mod weather;
 
 use std::collections::BTreeMap;
 
-extern crate piston;
-extern crate graphics;
-extern crate opengl_graphics;
-
-use piston::input::RenderArgs;
-use opengl_graphics:: {GlGraphics};
-use graphics::{Image, clear, draw_state::DrawState, Transformed};
-use graphics::rectangle::square;
-
 use crate::ecs;
-use crate::gamestate::movement::Direction;
+use crate::gamelogic::cutscene::CameraFocus;
+use crate::logging;
+use crate::gamestate::{components::RenderComponent, dungeon::FloorType, LocationVec};
+use layer::RenderLayer;
+use renderer::{Blend, Camera, Renderer};
 
 #[derive(Debug)]
 pub struct RenderConfig {
@@ -22,91 +53,268 @@ pub struct RenderConfig {
     pub window_xs: u32,
     pub window_ys: u32,
     // basicly central camera
-    pub focused_entity: Option<ecs::Entity>
+    pub focused_entity: Option<ecs::Entity>,
+    // a free camera point used instead of the focused entity, e.g. during a cutscene
+    pub focused_point: Option<LocationVec>,
+    pub show_minimap: bool
 }
 
-pub fn render_game(gl: &mut GlGraphics, args: &RenderArgs, ecs_: &mut ecs::ECS, tex: &sprite::SpriteTextures, conf: &RenderConfig) {
-    //TODO dont ignore render layers
-
-    // clear screen
-    clear([0.0, 0.0, 0.0, 1.0], gl);
-
-    // middle of screen in ingame grid
-    let half_window_x = (conf.window_xs as f64 / 2.0) / conf.scale;
-    let half_window_y = (conf.window_ys as f64 / 2.0) / conf.scale;
-
-    // offset for focused entity (camera) (default middle of screen)
-    let mut focused_entity_position_x = half_window_x;
-    let mut focused_entity_position_y = half_window_y;
+impl RenderConfig {
+    /// Takes over a new window size, everything rendered afterwards is laid out for it
+    ///
+    /// ### Arguments
+    /// * `window_size` - Width and height of the window in points, as given by resize events
+    ///
+    pub fn resize(&mut self, window_size: [f64; 2]) {
+        self.window_xs = window_size[0].max(1.0) as u32;
+        self.window_ys = window_size[1].max(1.0) as u32;
+    }
 
-    if let Some(focused_entity) = conf.focused_entity {
-        if let Some(location_c) = ecs_.location_component.get(focused_entity) {
-            focused_entity_position_x = location_c.location.x;
-            focused_entity_position_y = location_c.location.y;
+    /// Points the camera at what the cutscene playing shows, otherwise at the player.
+    /// Loading a game recreates all entities, so the player is also refocused once the
+    /// focused entity is gone
+    pub fn update_focus(&mut self, ecs_: &mut ecs::ECS) {
+        match ecs_.cutscene.as_ref().and_then(|cutscene| cutscene.camera) {
+            Some(CameraFocus::Entity(entity)) => {
+                self.focused_entity = Some(entity);
+                self.focused_point = None;
+            },
+            Some(CameraFocus::Point(point)) => self.focused_point = Some(point),
+            None => {
+                self.focused_point = None;
+                let on_player = self.focused_entity.is_some_and(|entity| ecs_.allocator.is_live(entity)
+                                                                           && ecs_.player_component.get(entity).is_some());
+                if !on_player {
+                    self.focused_entity = ecs_.get_player_entity();
+                }
+            }
         }
     }
+}
 
-    let x_offset = half_window_x - focused_entity_position_x;
-    let y_offset = half_window_y - focused_entity_position_y;
-
-    // create render order
-    let mut render_levels: BTreeMap<i32, Vec<ecs::Entity>> = BTreeMap::new();
+/// Groups all visible entities by their render layer, lower layers are drawn first.
+/// Layers sorted by y list the entities from the top of the screen down
+///
+/// ### Arguments
+/// * `alpha` - Time since the last update, moving entities are sorted by where they are drawn
+///
+pub fn render_order(ecs_: &ecs::ECS, alpha: f64) -> BTreeMap<RenderLayer, Vec<ecs::Entity>> {
+    let mut render_levels: BTreeMap<RenderLayer, Vec<ecs::Entity>> = BTreeMap::new();
 
     for entity in ecs_.allocator.live_indices() {
         if let Some(render_c) = ecs_.render_component.get(entity) {
             if !render_c.visible {
                 continue;
             }
+            render_levels.entry(render_c.render_layer).or_default().push(entity);
+        }
+    }
 
-            if !render_levels.contains_key(&render_c.render_layer) {
-                render_levels.insert(render_c.render_layer, Vec::new());
-            }
-
-            render_levels.get_mut(&render_c.render_layer).map(|vector| vector.push(entity));
+    let drawn_y = |entity: &ecs::Entity| ecs_.location_component.get(*entity).map_or(0.0, |location_c| location_c.interpolated(alpha).y);
+    for (layer, entities) in render_levels.iter_mut() {
+        if layer.is_y_sorted() {
+            // stable, so entities in the same row keep their order
+            entities.sort_by(|a, b| drawn_y(a).partial_cmp(&drawn_y(b)).unwrap_or(std::cmp::Ordering::Equal));
         }
     }
+    render_levels
+}
+
+/// Renders the dungeon around the focused entity
+///
+/// ### Arguments
+/// * `renderer` - The backend to draw with
+/// * `alpha`    - Fraction of the time between two game updates that has passed since the
+///   last one, moving entities are drawn between their previous and current location
+///
+pub fn render_game(renderer: &mut dyn Renderer, ecs_: &mut ecs::ECS, conf: &RenderConfig, alpha: f64) {
+    // clear screen
+    renderer.set_camera(None);
+    renderer.clear([0.0, 0.0, 0.0, 1.0]);
+
+    // middle of screen in ingame grid
+    let half_window_x = (conf.window_xs as f64 / 2.0) / conf.scale;
+    let half_window_y = (conf.window_ys as f64 / 2.0) / conf.scale;
+
+    let camera = game_camera(ecs_, conf, alpha);
+    let x_offset = camera.offset.x;
+    let y_offset = camera.offset.y;
+    renderer.set_camera(Some(camera));
 
     // render entities in render order
-    for (_render_level, entities) in render_levels {
+    for (_render_level, entities) in render_order(ecs_, alpha) {
         for entity in entities {
-            let render_c = ecs_.render_component.get(entity)
-                                                .expect("No render component, even though it must have one");
+            let render_c = match ecs::require(&ecs_.render_component, entity) {
+                Ok(render_c) => render_c,
+                Err(err) => {
+                    warn!("Could not render: {}", err);
+                    continue;
+                }
+            };
             if let Some(location_c) = ecs_.location_component.get(entity) {
-                let location = location_c.location;
+                let mut location = location_c.interpolated(alpha);
+                if let Some(flash) = &render_c.flash {
+                    location += flash.offset();
+                }
                 // we need a location to render the entity
-                // check if entity is within cameras vision
+                // check if entity is within cameras vision (in ingame units)
                 // (+1 to render one row and column more to have no tiles appearing from nowhere)
-                if location.x + x_offset + 1.0 < 0.0 || location.x + x_offset >= conf.window_xs as f64 {
+                if location.x + x_offset + 1.0 < 0.0 || location.x + x_offset - 1.0 >= 2.0 * half_window_x {
                     continue;
                 }
-                if location.y + y_offset + 1.0 < 0.0 || location.y + y_offset >= conf.window_ys as f64 {
+                if location.y + y_offset + 1.0 < 0.0 || location.y + y_offset - 1.0 >= 2.0 * half_window_y {
                     continue;
                 }
 
-                // check if texture actually exists
-                if let Some(texture) = tex.get(&render_c.base_sprite) {
-                    // we got a location so we will do some math
-                    let x = (location.x + x_offset) * conf.scale - conf.scale / 2.0;
-                    let y = (location.y + y_offset) * conf.scale - conf.scale / 2.0;
-                    let size = conf.scale * render_c.base_sprite_size;
-                    let image = Image::new().rect(square(x, y, size));
-                    gl.draw(args.viewport(), |c, gl| {
-                        let rotation = match location_c.direction {
-                            Direction::Up    => 180.0,
-                            Direction::Left  => 90.0,
-                            Direction::Down  => 0.0,
-                            Direction::Right => -90.0,
-                        };
-                        let new_c = c.trans(x + conf.scale / 2.0, y + conf.scale /2.0)
-                                              .rot_deg(rotation)
-                                              .trans(-x - conf.scale / 2.0, -y - conf.scale / 2.0);
-                        image.draw(texture, &DrawState::default(), new_c.transform, gl);
-                    });
+                let mut size = render_c.base_sprite_size;
+                let mut color = render_c.color();
+
+                // dying entities fade out and shrink towards their center
+                if let Some(dying_c) = ecs_.dying_component.get(entity) {
+                    let progress = dying_c.progress();
+                    let [r, g, b, a] = color.unwrap_or([1.0, 1.0, 1.0, 1.0]);
+                    color = Some([r, g, b, a * (1.0 - progress as f32)]);
+                    size *= 1.0 - 0.5 * progress;
+                }
+                // the location is the center of the entity's field
+                let shrink = (render_c.base_sprite_size - size) / 2.0;
+                let rect = [location.x - 0.5 + shrink, location.y - 0.5 + shrink, size, size];
+                let rotation = location_c.direction.rotation();
+
+                if draw_entity_sprite(renderer, &ecs_.animations, render_c, rect, rotation, color, Blend::Alpha) {
+                    if let Some(flash) = &render_c.flash {
+                        draw_entity_sprite(renderer, &ecs_.animations, render_c, rect, rotation, Some(flash.overlay_color()), Blend::Add);
+                    }
                 } else {
-                    print!("Texture not found for {:?}", render_c.base_sprite);
+                    if logging::first_time(&render_c.base_sprite) {
+                        warn!("Texture not found for {:?}", render_c.base_sprite);
+                    }
+                    renderer.draw_sprite("default", rect, rotation, color, Blend::Alpha);
                 }
             }
         }
     }
 
-}
\ No newline at end of file
+    // particles are drawn on top of all entities
+    ecs_.particles.render(renderer);
+
+    // tint the whole screen depending on the time of day (only outside of dungeons)
+    renderer.set_camera(None);
+    if ecs_.floor_type != FloorType::Dungeon {
+        let tint = ecs_.world_clock.ambient_tint();
+        if tint[3] > 0.0 {
+            let [width, height] = renderer.size();
+            renderer.draw_rect(tint, [0.0, 0.0, width, height]);
+        }
+    }
+}
+
+/// The camera of the dungeon, which keeps the focused entity in the middle of the screen
+///
+/// ### Arguments
+/// * `alpha` - Time since the last update, relative to the time between updates
+///
+pub fn game_camera(ecs_: &ecs::ECS, conf: &RenderConfig, alpha: f64) -> Camera {
+    // middle of screen in ingame grid
+    let half_window_x = (conf.window_xs as f64 / 2.0) / conf.scale;
+    let half_window_y = (conf.window_ys as f64 / 2.0) / conf.scale;
+
+    // offset for focused entity (camera) (default middle of screen)
+    let mut focused_entity_position_x = half_window_x;
+    let mut focused_entity_position_y = half_window_y;
+
+    if let Some(point) = conf.focused_point {
+        focused_entity_position_x = point.x;
+        focused_entity_position_y = point.y;
+    } else if let Some(focused_entity) = conf.focused_entity {
+        if let Some(location_c) = ecs_.location_component.get(focused_entity) {
+            let location = location_c.interpolated(alpha);
+            focused_entity_position_x = location.x;
+            focused_entity_position_y = location.y;
+        }
+    }
+
+    // cutscenes move the camera themselves
+    let shake = if conf.focused_point.is_none() { ecs_.screen_shake.offset(alpha) } else { LocationVec { x: 0.0, y: 0.0 } };
+
+    Camera {
+        offset: LocationVec { x: half_window_x - focused_entity_position_x + shake.x, y: half_window_y - focused_entity_position_y + shake.y },
+        scale: conf.scale
+    }
+}
+
+/// Draws the sprite of an entity, animated sprites with the current frame of their clip.
+/// While the entity switches between clips, the previous one fades out on top
+///
+/// ### Returns
+/// False if the renderer does not know the sprite
+fn draw_entity_sprite(renderer: &mut dyn Renderer, animations: &animation::AnimationLibrary, render_c: &RenderComponent,
+                      rect: [f64; 4], rotation: f64, color: Option<renderer::Color>, blend: Blend) -> bool {
+    let sprite = &render_c.base_sprite;
+    let controller = &render_c.animation;
+    let region = match animations.frame_rect(sprite, controller.current(), controller.variant(), controller.elapsed_ms()) {
+        Some(region) => region,
+        None => return renderer.draw_sprite(sprite, rect, rotation, color, blend)
+    };
+    if !renderer.draw_sprite_region(sprite, region, rect, rotation, color, blend) {
+        return false;
+    }
+    if let Some((kind, elapsed_ms, weight)) = controller.blend() {
+        if let Some(previous_region) = animations.frame_rect(sprite, kind, controller.variant(), elapsed_ms) {
+            let [r, g, b, a] = color.unwrap_or([1.0, 1.0, 1.0, 1.0]);
+            renderer.draw_sprite_region(sprite, previous_region, rect, rotation, Some([r, g, b, a * weight]), blend);
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+
+    // remembers the sprites drawn, in pixels
+    struct RecordingRenderer {
+        camera: Option<Camera>,
+        sprites: Vec<(String, [f64; 4])>
+    }
+
+    impl Renderer for RecordingRenderer {
+        fn size(&self) -> [f64; 2] {
+            [320.0, 240.0]
+        }
+        fn clear(&mut self, _color: renderer::Color) {}
+        fn set_camera(&mut self, camera: Option<Camera>) {
+            self.camera = camera;
+        }
+        fn draw_sprite(&mut self, sprite: &str, rect: [f64; 4], _rotation: f64, _color: Option<renderer::Color>, _blend: Blend) -> bool {
+            let rect = self.camera.map_or(rect, |camera| camera.to_screen(rect));
+            self.sprites.push((sprite.to_string(), rect));
+            true
+        }
+        fn draw_rect(&mut self, _color: renderer::Color, _rect: [f64; 4]) {}
+        fn draw_text(&mut self, _text: &str, _x: f64, _y: f64, _size: u32, _color: renderer::Color) {}
+    }
+
+    #[test]
+    fn test_render_game_centers_focused_entity() {
+        let mut ecs_ = ecs::ECS::new();
+        let tile = dungeon::create_floor_tile(&mut ecs_, 3.0, 4.0);
+        dungeon::create_floor_tile(&mut ecs_, 100.0, 4.0);
+        let conf = RenderConfig {
+            scale: 20.0,
+            window_xs: 320,
+            window_ys: 240,
+            focused_entity: Some(tile),
+            focused_point: None,
+            show_minimap: false
+        };
+        let mut renderer = RecordingRenderer { camera: None, sprites: Vec::new() };
+        render_game(&mut renderer, &mut ecs_, &conf, 1.0);
+
+        // the tile far outside of the window is skipped
+        let sprite = ecs_.render_component.get(tile).unwrap().base_sprite.clone();
+        assert!(sprite.starts_with("floor_tile"));
+        assert_eq!(renderer.sprites, vec![(sprite, [150.0, 110.0, 20.0, 20.0])]);
+    }
+}
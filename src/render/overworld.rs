@@ -0,0 +1,93 @@
+use crate::ecs;
+use crate::gamelogic::overworld::{is_unlocked, party_position};
+use crate::gamestate::overworld::{self, Destination, TravelEvent};
+use crate::gamestate::LocationVec;
+use crate::render::renderer::Renderer;
+
+const MARGIN: f64 = 40.0;
+const TITLE_SIZE: u32 = 24;
+const TEXT_SIZE: u32 = 14;
+const NODE_SIZE: f64 = 20.0;
+// width of the frame around the selected node
+const FRAME: f64 = 3.0;
+const PARTY_SIZE: f64 = 10.0;
+const ROAD_DOT_SIZE: f64 = 3.0;
+const ROAD_DOT_SPACING: f64 = 10.0;
+
+const BACKGROUND: [f32; 4] = [0.12, 0.15, 0.1, 1.0];
+const TEXT_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 1.0];
+const ROAD_COLOR: [f32; 4] = [0.55, 0.45, 0.3, 1.0];
+const DUNGEON_COLOR: [f32; 4] = [0.6, 0.2, 0.2, 1.0];
+const TOWN_COLOR: [f32; 4] = [0.3, 0.6, 0.3, 1.0];
+const LOCKED_COLOR: [f32; 4] = [0.35, 0.35, 0.35, 1.0];
+const SELECTED_COLOR: [f32; 4] = [1.0, 0.9, 0.3, 1.0];
+const PARTY_COLOR: [f32; 4] = [0.3, 0.5, 1.0, 1.0];
+const EVENT_COLOR: [f32; 4] = [1.0, 0.85, 0.5, 1.0];
+
+/// Renders the overworld with its nodes and roads, replacing the dungeon view while
+/// `GameMode::Overworld` is active. The players are a marker walking the roads
+///
+/// ### Arguments
+/// * `alpha` - Time since the last update, relative to the time between updates
+///
+pub fn render_overworld(renderer: &mut dyn Renderer, ecs_: &ecs::ECS, alpha: f64) {
+    let nodes = overworld::world();
+    let [width, height] = renderer.size();
+    let top = 2.0 * MARGIN + TITLE_SIZE as f64;
+    let to_screen = |position: LocationVec| [
+        MARGIN + position.x * (width - 2.0 * MARGIN),
+        top + position.y * (height - top - 2.0 * MARGIN)
+    ];
+
+    renderer.set_camera(None);
+    renderer.clear(BACKGROUND);
+    renderer.draw_text(&ecs_.locale.text("overworld.title"), MARGIN, MARGIN, TITLE_SIZE, TEXT_COLOR);
+
+    // roads as dotted lines, each drawn once
+    for (i, node) in nodes.iter().enumerate() {
+        for road in node.roads.iter().filter(|road| **road > i) {
+            let (from, to) = (to_screen(node.position), to_screen(nodes[*road].position));
+            let length = ((to[0] - from[0]).powi(2) + (to[1] - from[1]).powi(2)).sqrt();
+            let dots = (length / ROAD_DOT_SPACING) as usize;
+            for dot in 0..=dots {
+                let t = dot as f64 / dots.max(1) as f64;
+                let [x, y] = [from[0] + (to[0] - from[0]) * t, from[1] + (to[1] - from[1]) * t];
+                renderer.draw_rect(ROAD_COLOR, [x - ROAD_DOT_SIZE / 2.0, y - ROAD_DOT_SIZE / 2.0, ROAD_DOT_SIZE, ROAD_DOT_SIZE]);
+            }
+        }
+    }
+
+    for (i, node) in nodes.iter().enumerate() {
+        let [x, y] = to_screen(node.position);
+        let unlocked = is_unlocked(ecs_, node);
+        if i == ecs_.overworld.selected {
+            let size = NODE_SIZE + 2.0 * FRAME;
+            renderer.draw_rect(SELECTED_COLOR, [x - size / 2.0, y - size / 2.0, size, size]);
+        }
+        let color = match node.destination {
+            _ if !unlocked => LOCKED_COLOR,
+            Destination::Dungeon { .. } => DUNGEON_COLOR,
+            Destination::Town => TOWN_COLOR
+        };
+        renderer.draw_rect(color, [x - NODE_SIZE / 2.0, y - NODE_SIZE / 2.0, NODE_SIZE, NODE_SIZE]);
+        let name = if unlocked {
+            ecs_.locale.text(node.name)
+        } else {
+            ecs_.locale.format("overworld.locked", &[("name", &ecs_.locale.text(node.name))])
+        };
+        renderer.draw_text(&name, x - NODE_SIZE / 2.0, y + NODE_SIZE / 2.0 + FRAME, TEXT_SIZE, TEXT_COLOR);
+    }
+
+    let [x, y] = to_screen(party_position(ecs_, alpha));
+    renderer.draw_rect(PARTY_COLOR, [x - PARTY_SIZE / 2.0, y - PARTY_SIZE / 2.0, PARTY_SIZE, PARTY_SIZE]);
+
+    if let Some(event) = ecs_.overworld.last_event {
+        let text = match event {
+            TravelEvent::Treasure { gold } => ecs_.locale.format(event.locale_key(), &[("gold", &gold)]),
+            TravelEvent::Ambush { damage } => ecs_.locale.format(event.locale_key(), &[("damage", &damage)]),
+            TravelEvent::Campfire { health } => ecs_.locale.format(event.locale_key(), &[("health", &health)])
+        };
+        renderer.draw_text(&text, MARGIN, height - 2.0 * MARGIN, TEXT_SIZE, EVENT_COLOR);
+    }
+    renderer.draw_text(&ecs_.locale.text("overworld.hint"), MARGIN, height - MARGIN, TEXT_SIZE, TEXT_COLOR);
+}
@@ -0,0 +1,123 @@
+extern crate serde;
+
+use serde::{Serialize, Deserialize};
+
+/// Colors of the interface that carry meaning, e.g. friend or foe. Color-blind palettes
+/// replace the ones that are hard to tell apart with the color vision deficiency
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Standard,
+    // red and green are hard to tell apart
+    Deuteranopia,
+    // like deuteranopia, but reds also look darker
+    Protanopia,
+    // blue and yellow are hard to tell apart
+    Tritanopia,
+}
+
+/// What a color of the palette is used for
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PaletteColor {
+    Health,
+    Mana,
+    Hunger,
+    Rest,
+    // the player on the maps
+    Player,
+    // hostile creatures on the maps
+    Hostile,
+    // damage previewed on a target
+    Damage,
+    // damage previewed that kills the target
+    Lethal,
+    // fields an aimed ability can be used on
+    Target,
+    // the aiming cursor on a field the ability can or can not be used on
+    ValidCursor,
+    InvalidCursor,
+}
+
+impl Palette {
+    pub fn next(&self) -> Palette {
+        match self {
+            Palette::Standard => Palette::Deuteranopia,
+            Palette::Deuteranopia => Palette::Protanopia,
+            Palette::Protanopia => Palette::Tritanopia,
+            Palette::Tritanopia => Palette::Standard,
+        }
+    }
+
+    /// The color used for something in this palette
+    pub fn color(&self, color: PaletteColor) -> [f32; 4] {
+        match (self, color) {
+            // grey is told apart from the other colors in every palette
+            (_, PaletteColor::InvalidCursor) => [0.5, 0.5, 0.5, 1.0],
+
+            (Palette::Standard, PaletteColor::Health) => [0.8, 0.1, 0.1, 1.0],
+            (Palette::Standard, PaletteColor::Mana) => [0.1, 0.3, 0.9, 1.0],
+            (Palette::Standard, PaletteColor::Hunger) => [0.8, 0.5, 0.1, 1.0],
+            (Palette::Standard, PaletteColor::Rest) => [0.2, 0.8, 0.3, 1.0],
+            (Palette::Standard, PaletteColor::Player) => [0.1, 0.9, 0.2, 1.0],
+            (Palette::Standard, PaletteColor::Hostile) => [0.9, 0.1, 0.1, 1.0],
+            (Palette::Standard, PaletteColor::Damage) => [1.0, 0.9, 0.2, 1.0],
+            (Palette::Standard, PaletteColor::Lethal) => [1.0, 0.3, 0.1, 1.0],
+            (Palette::Standard, PaletteColor::Target) => [1.0, 0.3, 0.1, 1.0],
+            (Palette::Standard, PaletteColor::ValidCursor) => [1.0, 0.9, 0.2, 1.0],
+
+            // blue against orange instead of green against red
+            (Palette::Deuteranopia, PaletteColor::Health) => [0.85, 0.4, 0.0, 1.0],
+            (Palette::Deuteranopia, PaletteColor::Mana) => [0.3, 0.2, 0.8, 1.0],
+            (Palette::Deuteranopia, PaletteColor::Hunger) => [0.9, 0.8, 0.3, 1.0],
+            (Palette::Deuteranopia, PaletteColor::Rest) => [0.3, 0.7, 1.0, 1.0],
+            (Palette::Deuteranopia, PaletteColor::Player) => [0.2, 0.6, 1.0, 1.0],
+            (Palette::Deuteranopia, PaletteColor::Hostile) => [0.95, 0.5, 0.0, 1.0],
+            (Palette::Deuteranopia, PaletteColor::Damage) => [1.0, 1.0, 0.6, 1.0],
+            (Palette::Deuteranopia, PaletteColor::Lethal) => [0.9, 0.4, 0.9, 1.0],
+            (Palette::Deuteranopia, PaletteColor::Target) => [0.95, 0.5, 0.0, 1.0],
+            (Palette::Deuteranopia, PaletteColor::ValidCursor) => [0.3, 0.7, 1.0, 1.0],
+
+            (Palette::Protanopia, PaletteColor::Health) => [1.0, 0.55, 0.1, 1.0],
+            (Palette::Protanopia, PaletteColor::Mana) => [0.3, 0.3, 0.9, 1.0],
+            (Palette::Protanopia, PaletteColor::Hunger) => [0.95, 0.85, 0.4, 1.0],
+            (Palette::Protanopia, PaletteColor::Rest) => [0.3, 0.75, 1.0, 1.0],
+            (Palette::Protanopia, PaletteColor::Player) => [0.2, 0.6, 1.0, 1.0],
+            (Palette::Protanopia, PaletteColor::Hostile) => [1.0, 0.65, 0.1, 1.0],
+            (Palette::Protanopia, PaletteColor::Damage) => [1.0, 1.0, 0.6, 1.0],
+            (Palette::Protanopia, PaletteColor::Lethal) => [0.95, 0.5, 1.0, 1.0],
+            (Palette::Protanopia, PaletteColor::Target) => [1.0, 0.65, 0.1, 1.0],
+            (Palette::Protanopia, PaletteColor::ValidCursor) => [0.3, 0.75, 1.0, 1.0],
+
+            // red against teal instead of yellow against blue
+            (Palette::Tritanopia, PaletteColor::Health) => [0.85, 0.1, 0.2, 1.0],
+            (Palette::Tritanopia, PaletteColor::Mana) => [0.1, 0.6, 0.65, 1.0],
+            (Palette::Tritanopia, PaletteColor::Hunger) => [0.9, 0.45, 0.6, 1.0],
+            (Palette::Tritanopia, PaletteColor::Rest) => [0.2, 0.75, 0.7, 1.0],
+            (Palette::Tritanopia, PaletteColor::Player) => [0.1, 0.8, 0.8, 1.0],
+            (Palette::Tritanopia, PaletteColor::Hostile) => [0.9, 0.1, 0.2, 1.0],
+            (Palette::Tritanopia, PaletteColor::Damage) => [1.0, 0.6, 0.7, 1.0],
+            (Palette::Tritanopia, PaletteColor::Lethal) => [1.0, 0.2, 0.3, 1.0],
+            (Palette::Tritanopia, PaletteColor::Target) => [0.9, 0.1, 0.2, 1.0],
+            (Palette::Tritanopia, PaletteColor::ValidCursor) => [0.1, 0.8, 0.8, 1.0],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palettes_keep_friend_and_foe_apart() {
+        let mut palette = Palette::Standard;
+        loop {
+            let (player, hostile) = (palette.color(PaletteColor::Player), palette.color(PaletteColor::Hostile));
+            let difference: f32 = player.iter().zip(hostile.iter()).map(|(a, b)| (a - b).abs()).sum();
+            assert!(difference > 0.5, "{:?}", palette);
+            palette = palette.next();
+            if palette == Palette::Standard {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+extern crate rand;
+
+use rand::Rng;
+
+use crate::gamestate::LocationVec;
+use crate::render::{sprite, renderer::{Blend, Renderer}};
+
+/// A single particle, lives in ingame units like entities but is not part of the ECS
+struct Particle {
+    location: LocationVec,
+    // fields per second
+    velocity: LocationVec,
+    // seconds left to live
+    lifetime: f64,
+    max_lifetime: f64,
+    color: [f32; 4],
+    sprite: Option<sprite::SpriteId>,
+    // in fields
+    size: f64,
+    // fields per second squared, pulling downwards
+    gravity: f64
+}
+
+/// Describes a burst of particles emitted at once from one location
+#[derive(Debug, Clone)]
+pub struct Burst {
+    pub count: usize,
+    // fields per second, each particle gets a random speed in this range
+    pub speed: (f64, f64),
+    // seconds, each particle gets a random lifetime in this range
+    pub lifetime: (f64, f64),
+    pub color: [f32; 4],
+    pub sprite: Option<sprite::SpriteId>,
+    pub size: f64,
+    pub gravity: f64
+}
+
+impl Burst {
+    /// Small red burst for entities taking damage
+    pub fn hit() -> Burst {
+        Burst {
+            count: 12,
+            speed: (1.0, 3.0),
+            lifetime: (0.2, 0.4),
+            color: [0.8, 0.05, 0.05, 1.0],
+            sprite: None,
+            size: 0.06,
+            gravity: 6.0
+        }
+    }
+
+    /// Bright burst for spells hitting their target
+    pub fn spell_impact() -> Burst {
+        Burst {
+            count: 24,
+            speed: (0.5, 2.5),
+            lifetime: (0.3, 0.7),
+            color: [0.5, 0.6, 1.0, 1.0],
+            sprite: None,
+            size: 0.08,
+            gravity: -1.0
+        }
+    }
+
+    /// Large, slow burst for dying entities
+    pub fn death() -> Burst {
+        Burst {
+            count: 40,
+            speed: (0.5, 2.0),
+            lifetime: (0.5, 1.2),
+            color: [0.4, 0.4, 0.4, 1.0],
+            sprite: None,
+            size: 0.1,
+            gravity: 2.0
+        }
+    }
+}
+
+/// Simulates and renders short lived cosmetic particles. Particles are kept in a
+/// plain vector instead of being entities to keep spawning them cheap
+pub struct ParticleSystem {
+    particles: Vec<Particle>
+}
+
+impl ParticleSystem {
+    pub fn new() -> ParticleSystem {
+        ParticleSystem {
+            particles: Vec::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// Spawns a burst of particles flying into random directions
+    ///
+    /// ### Arguments
+    /// * `location` - The location (in ingame units) to spawn at
+    /// * `burst`    - Description of the particles
+    ///
+    pub fn spawn_burst(&mut self, location: LocationVec, burst: &Burst) {
+        let mut rng = rand::thread_rng();
+        for _ in 0..burst.count {
+            let angle = rng.gen_range(0.0..2.0 * std::f64::consts::PI);
+            let speed = random_in(&mut rng, burst.speed);
+            let lifetime = random_in(&mut rng, burst.lifetime);
+            self.particles.push(Particle {
+                location,
+                velocity: LocationVec { x: angle.cos() * speed, y: angle.sin() * speed },
+                lifetime,
+                max_lifetime: lifetime,
+                color: burst.color,
+                sprite: burst.sprite.clone(),
+                size: burst.size,
+                gravity: burst.gravity
+            });
+        }
+    }
+
+    /// Moves all particles and removes dead ones
+    ///
+    /// ### Arguments
+    /// * `dt` - Passed time in seconds
+    ///
+    pub fn update(&mut self, dt: f64) {
+        for particle in &mut self.particles {
+            particle.velocity.y += particle.gravity * dt;
+            particle.location.x += particle.velocity.x * dt;
+            particle.location.y += particle.velocity.y * dt;
+            particle.lifetime -= dt;
+        }
+        self.particles.retain(|particle| particle.lifetime > 0.0);
+    }
+
+    /// Renders all particles, fading them out over their lifetime. The camera has to be
+    /// set already, the same as used for entities
+    pub fn render(&self, renderer: &mut dyn Renderer) {
+        for particle in &self.particles {
+            let rect = [particle.location.x - particle.size / 2.0, particle.location.y - particle.size / 2.0, particle.size, particle.size];
+            let mut color = particle.color;
+            color[3] *= (particle.lifetime / particle.max_lifetime) as f32;
+
+            let drawn = particle.sprite.as_ref().is_some_and(|sprite| renderer.draw_sprite(sprite, rect, 0.0, Some(color), Blend::Alpha));
+            if !drawn {
+                renderer.draw_rect(color, rect);
+            }
+        }
+    }
+}
+
+impl Default for ParticleSystem {
+    fn default() -> ParticleSystem {
+        ParticleSystem::new()
+    }
+}
+
+fn random_in<R: Rng>(rng: &mut R, (min, max): (f64, f64)) -> f64 {
+    if max > min {
+        rng.gen_range(min..max)
+    } else {
+        min
+    }
+}
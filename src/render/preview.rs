@@ -0,0 +1,61 @@
+use crate::ecs;
+use crate::gamelogic::{combat, cutscene, targeting};
+use crate::gamestate::LocationVec;
+use crate::render::{game_camera, palette::PaletteColor, renderer::Renderer, RenderConfig};
+
+const TEXT_SIZE: u32 = 12;
+// space between the preview and the top of the target
+const MARGIN: f64 = 4.0;
+const BLOCKED_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+
+/// Renders what attacking the creature under the cursor does above it: the damage range of
+/// the ability aimed, else of a melee attack on the hovered creature
+///
+/// ### Arguments
+/// * `hovered` - The location under the mouse, None without a mouse
+/// * `alpha`   - Time since the last update, relative to the time between updates
+///
+pub fn render_damage_preview(renderer: &mut dyn Renderer, ecs_: &mut ecs::ECS, conf: &RenderConfig, alpha: f64, hovered: Option<LocationVec>) {
+    let player = match ecs_.get_player_entity() {
+        Some(player) => player,
+        None => return
+    };
+    // nothing is attacked during cutscenes
+    if cutscene::is_playing(ecs_) {
+        return;
+    }
+    // while aiming the cursor decides, even if it was moved with the keyboard
+    let (location, ability) = match (ecs_.targeting, hovered) {
+        (Some(targeting), _) => (targeting.cursor, Some(targeting.ability)),
+        (None, Some(location)) => (location, None),
+        _ => return
+    };
+    let target = match targeting::target_at(ecs_, player, location) {
+        Some(target) => target,
+        None => return
+    };
+    let target_location = match ecs_.location_component.get(target) {
+        Some(location_c) => location_c.interpolated(alpha),
+        None => return
+    };
+
+    let preview = combat::preview(ecs_, player, target, ability);
+    let (text, color) = match preview.damage {
+        Some((min, max)) => {
+            let mut text = ecs_.locale.format("preview.damage", &[("min", &min), ("max", &max)]);
+            if preview.damage_per_turn > 0 {
+                text = format!("{} {}", text, ecs_.locale.format("preview.per_turn", &[("damage", &preview.damage_per_turn)]));
+            }
+            if preview.lethal {
+                (format!("{} ({})", text, ecs_.locale.text("preview.lethal")), ecs_.settings.accessibility.palette.color(PaletteColor::Lethal))
+            } else {
+                (text, ecs_.settings.accessibility.palette.color(PaletteColor::Damage))
+            }
+        },
+        None => (ecs_.locale.text("preview.blocked"), BLOCKED_COLOR)
+    };
+
+    let [x, y, _, _] = game_camera(ecs_, conf, alpha).to_screen([target_location.x - 0.5, target_location.y - 0.5, 0.0, 0.0]);
+    renderer.set_camera(None);
+    renderer.draw_text(&text, x, y - MARGIN - TEXT_SIZE as f64, TEXT_SIZE, color);
+}
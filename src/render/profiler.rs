@@ -0,0 +1,41 @@
+use crate::ecs;
+use crate::render::renderer::Renderer;
+
+const TEXT_SIZE: u32 = 12;
+const LINE_HEIGHT: f64 = 16.0;
+const MARGIN: f64 = 6.0;
+const PANEL_WIDTH: f64 = 300.0;
+const TEXT_COLOR: [f32; 4] = [0.6, 1.0, 0.6, 1.0];
+const PANEL_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.7];
+
+/// Renders the timings of the game loop and the number of entities into the top right
+/// corner, if the profiler is shown
+pub fn render_profiler(renderer: &mut dyn Renderer, ecs_: &ecs::ECS) {
+    if !ecs_.profiler.visible {
+        return;
+    }
+    let profiler = &ecs_.profiler;
+    let ms = |value: f64| format!("{:.2}", value);
+
+    let mut lines = vec![ecs_.locale.format("profiler.update", &[("ms", &ms(profiler.update_ms()))])];
+    for timing in profiler.systems() {
+        lines.push(ecs_.locale.format("profiler.system", &[("name", &timing.name), ("ms", &ms(timing.average_ms))]));
+    }
+    lines.push(ecs_.locale.format("profiler.frame", &[("ms", &ms(profiler.frame_ms()))]));
+
+    let entities = ecs_.allocator.live_indices();
+    let count = |has: &dyn Fn(ecs::Entity) -> bool| entities.iter().filter(|entity| has(**entity)).count();
+    lines.push(ecs_.locale.format("profiler.entities", &[("count", &entities.len())]));
+    lines.push(ecs_.locale.format("profiler.components", &[
+        ("actors", &count(&|entity| ecs_.actor_component.get(entity).is_some())),
+        ("located", &count(&|entity| ecs_.location_component.get(entity).is_some())),
+        ("drawn", &count(&|entity| ecs_.render_component.get(entity).is_some_and(|render_c| render_c.visible)))
+    ]));
+
+    renderer.set_camera(None);
+    let x = renderer.size()[0] - PANEL_WIDTH - MARGIN;
+    renderer.draw_rect(PANEL_COLOR, [x, MARGIN, PANEL_WIDTH, lines.len() as f64 * LINE_HEIGHT + MARGIN]);
+    for (i, line) in lines.iter().enumerate() {
+        renderer.draw_text(line, x + MARGIN, MARGIN + i as f64 * LINE_HEIGHT, TEXT_SIZE, TEXT_COLOR);
+    }
+}
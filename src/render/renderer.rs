@@ -0,0 +1,83 @@
+use crate::gamestate::LocationVec;
+
+pub type Color = [f32; 4];
+
+/// How something drawn is combined with what was drawn before
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Blend {
+    // covers what is below depending on the alpha
+    Alpha,
+    // lightens what is below, e.g. for flashes
+    Add,
+}
+
+/// Maps ingame units to pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    // added to ingame locations before scaling
+    pub offset: LocationVec,
+    // pixels per ingame unit
+    pub scale: f64
+}
+
+impl Camera {
+    /// Converts a rectangle in ingame units to pixels
+    pub fn to_screen(&self, rect: [f64; 4]) -> [f64; 4] {
+        [(rect[0] + self.offset.x) * self.scale,
+         (rect[1] + self.offset.y) * self.scale,
+         rect[2] * self.scale,
+         rect[3] * self.scale]
+    }
+
+    /// Converts a point in pixels to ingame units
+    pub fn to_world(&self, point: [f64; 2]) -> LocationVec {
+        LocationVec {
+            x: point[0] / self.scale - self.offset.x,
+            y: point[1] / self.scale - self.offset.y
+        }
+    }
+}
+
+/// A backend the game can be drawn with. Rectangles are given as x, y of the top left
+/// corner, width and height, in ingame units while a camera is set, else in pixels
+pub trait Renderer {
+    /// Width and height of the drawing area in pixels
+    fn size(&self) -> [f64; 2];
+
+    /// Fills the whole drawing area with one color
+    fn clear(&mut self, color: Color);
+
+    /// Sets the camera used by all following calls, None draws in pixels
+    fn set_camera(&mut self, camera: Option<Camera>);
+
+    /// Draws a sprite stretched over a rectangle
+    ///
+    /// ### Arguments
+    /// * `sprite`   - Id of the sprite
+    /// * `rect`     - The rectangle to draw in
+    /// * `rotation` - Clockwise rotation in degrees around the center of the rectangle
+    /// * `color`    - Color the sprite is multiplied with, None draws it unchanged
+    /// * `blend`    - How the sprite is combined with what is below
+    ///
+    /// ### Returns
+    /// False if the backend does not know the sprite, nothing is drawn then
+    fn draw_sprite(&mut self, sprite: &str, rect: [f64; 4], rotation: f64, color: Option<Color>, blend: Blend) -> bool;
+
+    /// Like `draw_sprite`, but only draws a part of the sprite's image, e.g. one frame
+    /// of a sprite sheet. Backends without images draw the whole sprite
+    ///
+    /// ### Arguments
+    /// * `region` - x, y, width and height of the part within the image, in pixels
+    ///
+    fn draw_sprite_region(&mut self, sprite: &str, region: [u32; 4], rect: [f64; 4], rotation: f64, color: Option<Color>, blend: Blend) -> bool {
+        let _ = region;
+        self.draw_sprite(sprite, rect, rotation, color, blend)
+    }
+
+    /// Fills a rectangle with a color
+    fn draw_rect(&mut self, color: Color, rect: [f64; 4]);
+
+    /// Draws a line of text with its top left corner at `x`, `y`. The font size is
+    /// always in pixels
+    fn draw_text(&mut self, text: &str, x: f64, y: f64, size: u32, color: Color);
+}
@@ -0,0 +1,55 @@
+use crate::ecs;
+use crate::render::{RenderConfig, renderer::Renderer};
+
+const MARGIN: f64 = 40.0;
+const LINE_HEIGHT: f64 = 22.0;
+const TITLE_SIZE: u32 = 24;
+const TEXT_SIZE: u32 = 16;
+// x offset of the save descriptions from the left margin
+const VALUE_OFFSET: f64 = 200.0;
+
+const TEXT_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 1.0];
+const EMPTY_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+const SELECTED_COLOR: [f32; 4] = [1.0, 0.9, 0.3, 1.0];
+const SELECTED_BACKGROUND: [f32; 4] = [0.2, 0.2, 0.25, 1.0];
+
+/// Renders the list of save slots, replacing the dungeon view while `GameMode::LoadMenu` is active
+pub fn render_load_menu(renderer: &mut dyn Renderer, ecs_: &mut ecs::ECS, conf: &RenderConfig) {
+    let menu = &ecs_.load_menu;
+    let lines: Vec<(String, Option<String>)> = menu.slots.iter().map(|info| {
+        let description = info.metadata.as_ref().map(|metadata| ecs_.locale.format("saves.entry", &[
+            ("name", &metadata.character_name),
+            ("floor", &metadata.floor),
+            ("playtime", &metadata.playtime_text())
+        ]));
+        (info.slot.describe(ecs_), description)
+    }).collect();
+    let title = ecs_.locale.text("saves.title");
+    let hint = ecs_.locale.text("saves.hint");
+    let empty = ecs_.locale.text("saves.empty");
+
+    // scroll so the selected line stays visible
+    let visible_lines = ((conf.window_ys as f64 - 3.0 * MARGIN) / LINE_HEIGHT).max(1.0) as usize;
+    let first = (menu.selected + 1).saturating_sub(visible_lines);
+
+    renderer.set_camera(None);
+    renderer.clear([0.05, 0.05, 0.08, 1.0]);
+    renderer.draw_text(&title, MARGIN, MARGIN, TITLE_SIZE, TEXT_COLOR);
+
+    for (row, (i, (name, description))) in lines.iter().enumerate().skip(first).take(visible_lines).enumerate() {
+        let y = 2.0 * MARGIN + row as f64 * LINE_HEIGHT;
+        let color = if i == menu.selected {
+            renderer.draw_rect(SELECTED_BACKGROUND, [MARGIN - 4.0, y - 2.0, conf.window_xs as f64 - 2.0 * MARGIN, LINE_HEIGHT]);
+            SELECTED_COLOR
+        } else {
+            TEXT_COLOR
+        };
+        renderer.draw_text(name, MARGIN, y, TEXT_SIZE, color);
+        match description {
+            Some(description) => renderer.draw_text(description, MARGIN + VALUE_OFFSET, y, TEXT_SIZE, color),
+            None => renderer.draw_text(&empty, MARGIN + VALUE_OFFSET, y, TEXT_SIZE, EMPTY_COLOR)
+        }
+    }
+
+    renderer.draw_text(&hint, MARGIN, conf.window_ys as f64 - MARGIN, TEXT_SIZE, TEXT_COLOR);
+}
@@ -0,0 +1,52 @@
+use crate::config;
+use crate::ecs;
+use crate::render::{RenderConfig, renderer::Renderer};
+
+const MARGIN: f64 = 40.0;
+const LINE_HEIGHT: f64 = 22.0;
+const TITLE_SIZE: u32 = 24;
+const TEXT_SIZE: u32 = 16;
+// x offset of the values from the left margin
+const VALUE_OFFSET: f64 = 300.0;
+
+const TEXT_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 1.0];
+const SELECTED_COLOR: [f32; 4] = [1.0, 0.9, 0.3, 1.0];
+const SELECTED_BACKGROUND: [f32; 4] = [0.2, 0.2, 0.25, 1.0];
+
+/// Renders the settings menu, replacing the dungeon view while `GameMode::Settings` is active
+pub fn render_settings(renderer: &mut dyn Renderer, ecs_: &mut ecs::ECS, conf: &RenderConfig) {
+    let entries = config::settings_entries();
+    let menu = &ecs_.settings_menu;
+    let lines: Vec<(String, String)> = entries.iter().enumerate().map(|(i, entry)| {
+        let (name, value) = ecs_.settings.describe(*entry, &ecs_.locale);
+        if i == menu.selected && menu.rebinding {
+            (name, ecs_.locale.text("settings.press_key"))
+        } else {
+            (name, value)
+        }
+    }).collect();
+    let title = ecs_.locale.text("settings.title");
+    let hint = ecs_.locale.text("settings.hint");
+
+    // scroll so the selected line stays visible
+    let visible_lines = ((conf.window_ys as f64 - 3.0 * MARGIN) / LINE_HEIGHT).max(1.0) as usize;
+    let first = (menu.selected + 1).saturating_sub(visible_lines);
+
+    renderer.set_camera(None);
+    renderer.clear([0.05, 0.05, 0.08, 1.0]);
+    renderer.draw_text(&title, MARGIN, MARGIN, TITLE_SIZE, TEXT_COLOR);
+
+    for (row, (i, (name, value))) in lines.iter().enumerate().skip(first).take(visible_lines).enumerate() {
+        let y = 2.0 * MARGIN + row as f64 * LINE_HEIGHT;
+        let color = if i == menu.selected {
+            renderer.draw_rect(SELECTED_BACKGROUND, [MARGIN - 4.0, y - 2.0, conf.window_xs as f64 - 2.0 * MARGIN, LINE_HEIGHT]);
+            SELECTED_COLOR
+        } else {
+            TEXT_COLOR
+        };
+        renderer.draw_text(name, MARGIN, y, TEXT_SIZE, color);
+        renderer.draw_text(value, MARGIN + VALUE_OFFSET, y, TEXT_SIZE, color);
+    }
+
+    renderer.draw_text(&hint, MARGIN, conf.window_ys as f64 - MARGIN, TEXT_SIZE, TEXT_COLOR);
+}
@@ -0,0 +1,77 @@
+use crate::gamestate::LocationVec;
+
+// strength of the shake with `AccessibilitySettings::reduce_screen_shake`, relative to the normal one
+pub const REDUCED_SHAKE_SCALE: f64 = 0.25;
+
+/// Shaking of the game camera, e.g. while the player is hit. Purely cosmetic, so it
+/// does not use the random numbers of the game
+#[derive(Debug, Clone, Default)]
+pub struct ScreenShake {
+    // largest offset of the camera, in fields
+    strength: f64,
+    // event loop updates left and the updates the shake lasts in total
+    remaining: u32,
+    duration: u32
+}
+
+impl ScreenShake {
+    /// Shakes the camera, a weaker shake does not replace a stronger one in progress
+    ///
+    /// ### Arguments
+    /// * `strength` - Largest offset of the camera, in fields
+    /// * `updates`  - Number of event loop updates the shake lasts
+    ///
+    pub fn start(&mut self, strength: f64, updates: u32) {
+        if strength > 0.0 && updates > 0 && strength >= self.current_strength() {
+            self.strength = strength;
+            self.remaining = updates;
+            self.duration = updates;
+        }
+    }
+
+    // the strength fades out over the duration
+    fn current_strength(&self) -> f64 {
+        if self.duration == 0 {
+            0.0
+        } else {
+            self.strength * self.remaining as f64 / self.duration as f64
+        }
+    }
+
+    /// Advances by one event loop update
+    pub fn update(&mut self) {
+        self.remaining = self.remaining.saturating_sub(1);
+    }
+
+    /// Offset of the camera, zero while not shaking
+    ///
+    /// ### Arguments
+    /// * `alpha` - Time since the last update, relative to the time between updates
+    ///
+    pub fn offset(&self, alpha: f64) -> LocationVec {
+        if self.remaining == 0 {
+            return LocationVec { x: 0.0, y: 0.0 };
+        }
+        let time = self.remaining as f64 - alpha;
+        let strength = self.strength * time.max(0.0) / self.duration as f64;
+        LocationVec { x: (time * 2.3).sin() * strength, y: (time * 3.1).cos() * strength }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shake_fades_out() {
+        let mut shake = ScreenShake::default();
+        shake.start(0.2, 10);
+        shake.start(0.1, 10);
+        for _ in 0..10 {
+            let offset = shake.offset(0.5);
+            assert!(offset.x.abs() <= 0.2 && offset.y.abs() <= 0.2);
+            shake.update();
+        }
+        assert_eq!(shake.offset(0.0), LocationVec { x: 0.0, y: 0.0 });
+    }
+}
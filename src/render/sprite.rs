@@ -1,27 +1,97 @@
-use std::collections::HashMap;
+#[cfg(feature = "window")]
 use std::path::Path;
 
-extern crate opengl_graphics;
-
+#[cfg(feature = "window")]
 use opengl_graphics:: {Texture, TextureSettings} ;
 
+#[cfg(feature = "window")]
+use crate::assets::{AssetScope, Assets};
+#[cfg(feature = "window")]
+use crate::error::{Error, Result};
+#[cfg(feature = "window")]
+use crate::render::animation::AnimationLibrary;
+
 pub type SpriteId = String;
 
-pub type SpriteTextures = HashMap<SpriteId, Texture>;
+// sprites drawn with one of several textures, and how many there are. The first one is
+// named like the sprite, the others `<sprite>_2` and up. Animated sprites vary their
+// clips instead, see `AnimationLibrary::clip_for`
+const SPRITE_VARIANTS: &[(&str, u32)] = &[
+    ("floor_tile", 3),
+];
+
+/// One of the variants of a sprite, sprites without variants are kept
+///
+/// ### Arguments
+/// * `sprite`  - The sprite to vary
+/// * `variant` - Picks one of the variants, any number is fine
+///
+pub fn variant(sprite: &str, variant: u32) -> SpriteId {
+    let count = SPRITE_VARIANTS.iter().find(|(varied, _)| *varied == sprite).map_or(1, |(_, count)| *count);
+    match variant % count {
+        0 => sprite.to_string(),
+        index => format!("{}_{}", sprite, index + 1)
+    }
+}
+
+#[cfg(feature = "window")]
+pub type SpriteTextures = Assets<Texture>;
 
-pub fn setup_sprite_textures() -> SpriteTextures {
-    let mut sprite_textures = SpriteTextures::new();
+// texture file of every sprite within `assets/textures/` and whether it is only
+// needed by the contents of a floor
+#[cfg(feature = "window")]
+const SPRITE_FILES: &[(&str, &str, AssetScope)] = &[
+    ("default", "default.png", AssetScope::Global),
+    ("floor_tile", "floor_tile.png", AssetScope::Floor),
+    ("floor_tile_2", "floor_tile_2.png", AssetScope::Floor),
+    ("floor_tile_3", "floor_tile_3.png", AssetScope::Floor),
+    ("wall_tile", "wall_tile.png", AssetScope::Floor),
+    ("player", "player.png", AssetScope::Global),
+    ("dummy", "dummy.png", AssetScope::Floor),
+];
 
+/// Registers the textures of all sprites, they are loaded when first drawn. Textures
+/// belong to the GL context of the window, so they can not be loaded in the
+/// background. Sprites whose texture can not be loaded are drawn with "default"
+///
+/// ### Arguments
+/// * `animations` - Animated sprites use the image of their sprite sheet as texture
+///
+#[cfg(feature = "window")]
+pub fn setup_sprite_textures(animations: &AnimationLibrary) -> SpriteTextures {
+    let mut sprite_textures = SpriteTextures::new(Path::new("./assets/textures/"), load_texture);
+
+    for (sprite, file, scope) in SPRITE_FILES.iter() {
+        let handle = sprite_textures.register(sprite, file, *scope);
+        if *sprite == "default" {
+            sprite_textures.set_placeholder(handle);
+        }
+    }
+    for (sprite, sheet) in animations.sheets() {
+        let scope = SPRITE_FILES.iter()
+                                .find(|(known, _, _)| known == sprite)
+                                .map_or(AssetScope::Floor, |(_, _, scope)| *scope);
+        sprite_textures.register(sprite, &sheet.image, scope);
+    }
 
-    sprite_textures.insert("default".to_string(), load_texture("default.png"));
-    sprite_textures.insert("floor_tile".to_string(), load_texture("floor_tile.png"));
-    sprite_textures.insert("wall_tile".to_string(), load_texture("wall_tile.png"));
-    sprite_textures.insert("player".to_string(), load_texture("player.png"));
-    sprite_textures.insert("dummy".to_string(), load_texture("dummy.png"));
-    
     sprite_textures
 }
 
-fn load_texture(name: &str) -> Texture {
-    Texture::from_path(Path::new("./assets/textures/").join(name), &TextureSettings::new()).unwrap()
-}
\ No newline at end of file
+/// Loads a texture file again for all sprites using it. If it can not be loaded, the
+/// sprites keep their previous texture
+///
+/// ### Arguments
+/// * `sprite_textures` - The textures to update
+/// * `file`            - Name of the changed file within `assets/textures/`
+///
+#[cfg(feature = "window")]
+pub fn reload_texture(sprite_textures: &mut SpriteTextures, file: &str) {
+    if sprite_textures.reload(file) == 0 {
+        debug!("No sprite uses the texture {}", file);
+    }
+}
+
+#[cfg(feature = "window")]
+fn load_texture(path: &Path) -> Result<Texture> {
+    Texture::from_path(path, &TextureSettings::new()).map_err(|reason| Error::Asset { path: path.to_path_buf(), reason })
+}
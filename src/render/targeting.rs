@@ -0,0 +1,48 @@
+use crate::ecs;
+use crate::gamelogic::targeting::{self, SightMap};
+use crate::render::{game_camera, palette::PaletteColor, renderer::Renderer, RenderConfig};
+
+const TEXT_SIZE: u32 = 14;
+const MARGIN: f64 = 6.0;
+// distance of the hint from the bottom, above the hotbar
+const HINT_OFFSET: f64 = 64.0;
+// opacity of the highlighted targets and the cursor, their colors come from the palette
+const TARGET_ALPHA: f32 = 0.35;
+const CURSOR_ALPHA: f32 = 0.5;
+const HINT_COLOR: [f32; 4] = [1.0, 0.9, 0.2, 1.0];
+
+/// Renders the ability the player is aiming: all valid targets are highlighted, the cursor
+/// shows if the ability can be used on its field
+///
+/// ### Arguments
+/// * `alpha` - Time since the last update, relative to the time between updates
+///
+pub fn render_targeting(renderer: &mut dyn Renderer, ecs_: &mut ecs::ECS, conf: &RenderConfig, alpha: f64) {
+    let (targeting, player) = match (ecs_.targeting, ecs_.get_player_entity()) {
+        (Some(targeting), Some(player)) => (targeting, player),
+        _ => return
+    };
+
+    let palette = ecs_.settings.accessibility.palette;
+    let translucent = |color: PaletteColor, alpha: f32| {
+        let [r, g, b, _] = palette.color(color);
+        [r, g, b, alpha]
+    };
+
+    renderer.set_camera(Some(game_camera(ecs_, conf, alpha)));
+    for target in targeting::valid_targets(ecs_, player, targeting.ability) {
+        if let Some(location_c) = ecs_.location_component.get(target) {
+            let location = location_c.interpolated(alpha);
+            renderer.draw_rect(translucent(PaletteColor::Target, TARGET_ALPHA), [location.x - 0.5, location.y - 0.5, 1.0, 1.0]);
+        }
+    }
+    let valid = targeting::target_at(ecs_, player, targeting.cursor).is_some()
+                && targeting::in_reach(ecs_, &SightMap::build(ecs_), player, targeting.ability, targeting.cursor);
+    let cursor_color = translucent(if valid { PaletteColor::ValidCursor } else { PaletteColor::InvalidCursor }, CURSOR_ALPHA);
+    renderer.draw_rect(cursor_color, [targeting.cursor.x - 0.5, targeting.cursor.y - 0.5, 1.0, 1.0]);
+
+    renderer.set_camera(None);
+    let hint = ecs_.locale.format("targeting.hint", &[("ability", &ecs_.locale.text(&targeting.ability.locale_key()))]);
+    let y = renderer.size()[1] - HINT_OFFSET - TEXT_SIZE as f64;
+    renderer.draw_text(&hint, MARGIN, y, TEXT_SIZE, HINT_COLOR);
+}
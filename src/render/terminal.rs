@@ -0,0 +1,245 @@
+use std::io::{self, Write};
+
+use crossterm::{cursor, queue, style, terminal};
+
+use crate::render::renderer::{Blend, Camera, Color, Renderer};
+
+/// Pixels one character cell stands for, so everything laid out in pixels for the
+/// window keeps its proportions. With a scale of `CELL_SIZE` one field is one cell
+pub const CELL_SIZE: f64 = 16.0;
+
+// glyph and color of every sprite, sprites not listed are drawn with the first
+// character of their id
+const GLYPHS: &[(&str, char, [f32; 3])] = &[
+    ("default", '?', [1.0, 0.0, 1.0]),
+    ("floor_tile", '.', [0.35, 0.35, 0.35]),
+    ("wall_tile", '#', [0.65, 0.6, 0.55]),
+    ("floor_tile_2", '.', [0.35, 0.35, 0.35]),
+    ("floor_tile_3", '.', [0.35, 0.35, 0.35]),
+    ("player", '@', [1.0, 0.85, 0.3]),
+    ("dummy", 'd', [0.8, 0.25, 0.25]),
+    ("item", '!', [0.3, 0.8, 1.0]),
+    ("door", '+', [0.7, 0.45, 0.2]),
+    ("door_open", '\'', [0.7, 0.45, 0.2]),
+    ("chest", '=', [0.85, 0.7, 0.2]),
+    ("lever", '/', [0.6, 0.6, 0.7]),
+    ("shopkeeper", 's', [0.4, 0.8, 0.4]),
+    ("rat", 'r', [0.6, 0.45, 0.3]),
+    ("skeleton", 'S', [0.9, 0.9, 0.8]),
+    ("mercenary", 'm', [0.4, 0.6, 1.0]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    glyph: char,
+    foreground: [f32; 3],
+    background: [f32; 3]
+}
+
+const EMPTY_CELL: Cell = Cell {
+    glyph: ' ',
+    foreground: [1.0, 1.0, 1.0],
+    background: [0.0, 0.0, 0.0]
+};
+
+/// Draws into a grid of character cells which is written to a terminal with `present`.
+/// Sprites become glyphs, rectangles color the background of the cells they cover
+pub struct TerminalRenderer {
+    columns: u16,
+    rows: u16,
+    cells: Vec<Cell>,
+    // what the terminal shows, only cells that differ from it are written
+    shown: Vec<Cell>,
+    camera: Option<Camera>
+}
+
+impl TerminalRenderer {
+    pub fn new(columns: u16, rows: u16) -> TerminalRenderer {
+        let mut renderer = TerminalRenderer {
+            columns: 0,
+            rows: 0,
+            cells: Vec::new(),
+            shown: Vec::new(),
+            camera: None
+        };
+        renderer.resize(columns, rows);
+        renderer
+    }
+
+    /// Takes over a new terminal size, the next `present` redraws everything
+    pub fn resize(&mut self, columns: u16, rows: u16) {
+        self.columns = columns;
+        self.rows = rows;
+        self.cells = vec![EMPTY_CELL; columns as usize * rows as usize];
+        self.shown.clear();
+    }
+
+    /// Writes all cells that changed since the last call to the terminal
+    pub fn present<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        if self.shown.len() != self.cells.len() {
+            queue!(out, terminal::Clear(terminal::ClearType::All))?;
+            self.shown = vec![Cell { glyph: '\0', ..EMPTY_CELL }; self.cells.len()];
+        }
+        // the cursor moves on by itself while printing a row, colors stay until changed
+        let mut cursor = None;
+        let mut colors = None;
+        for (i, cell) in self.cells.iter().enumerate() {
+            if self.shown[i] == *cell {
+                continue;
+            }
+            let position = ((i % self.columns as usize) as u16, (i / self.columns as usize) as u16);
+            if cursor != Some(position) {
+                queue!(out, cursor::MoveTo(position.0, position.1))?;
+            }
+            if colors != Some((cell.foreground, cell.background)) {
+                queue!(out,
+                       style::SetForegroundColor(terminal_color(cell.foreground)),
+                       style::SetBackgroundColor(terminal_color(cell.background)))?;
+                colors = Some((cell.foreground, cell.background));
+            }
+            queue!(out, style::Print(cell.glyph))?;
+            cursor = Some((position.0 + 1, position.1));
+        }
+        self.shown.copy_from_slice(&self.cells);
+        out.flush()
+    }
+
+    fn to_screen(&self, rect: [f64; 4]) -> [f64; 4] {
+        match &self.camera {
+            Some(camera) => camera.to_screen(rect),
+            None => rect
+        }
+    }
+
+    /// The cell a point (in pixels) lies in, None outside of the terminal
+    fn cell_mut(&mut self, x: f64, y: f64) -> Option<&mut Cell> {
+        let column = (x / CELL_SIZE).floor();
+        let row = (y / CELL_SIZE).floor();
+        if column < 0.0 || row < 0.0 || column >= self.columns as f64 || row >= self.rows as f64 {
+            return None;
+        }
+        let index = row as usize * self.columns as usize + column as usize;
+        self.cells.get_mut(index)
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    fn size(&self) -> [f64; 2] {
+        [self.columns as f64 * CELL_SIZE, self.rows as f64 * CELL_SIZE]
+    }
+
+    fn clear(&mut self, color: Color) {
+        let background = [color[0], color[1], color[2]];
+        for cell in self.cells.iter_mut() {
+            *cell = Cell { background, ..EMPTY_CELL };
+        }
+    }
+
+    fn set_camera(&mut self, camera: Option<Camera>) {
+        self.camera = camera;
+    }
+
+    fn draw_sprite(&mut self, sprite: &str, rect: [f64; 4], _rotation: f64, color: Option<Color>, blend: Blend) -> bool {
+        let (glyph, sprite_color) = glyph_for(sprite);
+        let rect = self.to_screen(rect);
+        let cell = match self.cell_mut(rect[0] + rect[2] / 2.0, rect[1] + rect[3] / 2.0) {
+            Some(cell) => cell,
+            None => return true
+        };
+        match blend {
+            Blend::Alpha => {
+                let [r, g, b, a] = color.unwrap_or([1.0, 1.0, 1.0, 1.0]);
+                let foreground = [sprite_color[0] * r, sprite_color[1] * g, sprite_color[2] * b];
+                // faded out sprites leave what is below
+                if a >= 0.5 {
+                    cell.glyph = glyph;
+                }
+                cell.foreground = mix(cell.foreground, foreground, a);
+            },
+            Blend::Add => {
+                let [r, g, b, a] = color.unwrap_or([0.0, 0.0, 0.0, 0.0]);
+                cell.foreground = [(cell.foreground[0] + r * a).min(1.0),
+                                   (cell.foreground[1] + g * a).min(1.0),
+                                   (cell.foreground[2] + b * a).min(1.0)];
+            }
+        }
+        true
+    }
+
+    fn draw_rect(&mut self, color: Color, rect: [f64; 4]) {
+        let rect = self.to_screen(rect);
+        let [r, g, b, a] = color;
+        // rectangles smaller than a cell, e.g. particles, still color the cell they are in
+        let first_column = (rect[0] / CELL_SIZE).floor() as i64;
+        let first_row = (rect[1] / CELL_SIZE).floor() as i64;
+        let last_column = (((rect[0] + rect[2]) / CELL_SIZE).ceil() as i64 - 1).max(first_column);
+        let last_row = (((rect[1] + rect[3]) / CELL_SIZE).ceil() as i64 - 1).max(first_row);
+        for row in first_row.max(0)..=last_row.min(self.rows as i64 - 1) {
+            for column in first_column.max(0)..=last_column.min(self.columns as i64 - 1) {
+                let cell = &mut self.cells[row as usize * self.columns as usize + column as usize];
+                if a >= 1.0 {
+                    *cell = Cell { background: [r, g, b], ..EMPTY_CELL };
+                } else {
+                    cell.background = mix(cell.background, [r, g, b], a);
+                    cell.foreground = mix(cell.foreground, [r, g, b], a);
+                }
+            }
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, x: f64, y: f64, _size: u32, color: Color) {
+        let [x, y, _, _] = self.to_screen([x, y, 0.0, 0.0]);
+        for (i, glyph) in text.chars().enumerate() {
+            if let Some(cell) = self.cell_mut(x + i as f64 * CELL_SIZE, y) {
+                cell.glyph = glyph;
+                cell.foreground = mix(cell.background, [color[0], color[1], color[2]], color[3]);
+            }
+        }
+    }
+}
+
+fn glyph_for(sprite: &str) -> (char, [f32; 3]) {
+    match GLYPHS.iter().find(|(id, _, _)| *id == sprite) {
+        Some((_, glyph, color)) => (*glyph, *color),
+        None => (sprite.chars().next().unwrap_or('?'), [1.0, 1.0, 1.0])
+    }
+}
+
+fn mix(below: [f32; 3], above: [f32; 3], alpha: f32) -> [f32; 3] {
+    let alpha = alpha.max(0.0).min(1.0);
+    [below[0] + (above[0] - below[0]) * alpha,
+     below[1] + (above[1] - below[1]) * alpha,
+     below[2] + (above[2] - below[2]) * alpha]
+}
+
+fn terminal_color(color: [f32; 3]) -> style::Color {
+    let channel = |value: f32| (value.max(0.0).min(1.0) * 255.0).round() as u8;
+    style::Color::Rgb { r: channel(color[0]), g: channel(color[1]), b: channel(color[2]) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamestate::LocationVec;
+
+    #[test]
+    fn test_sprites_become_glyphs() {
+        let mut renderer = TerminalRenderer::new(10, 4);
+        renderer.set_camera(Some(Camera { offset: LocationVec { x: 1.0, y: 0.0 }, scale: CELL_SIZE }));
+        renderer.draw_sprite("player", [2.0, 1.0, 1.0, 1.0], 0.0, None, Blend::Alpha);
+        renderer.set_camera(None);
+        renderer.draw_text("hp", 0.0, 0.0, 11, [1.0, 1.0, 1.0, 1.0]);
+
+        assert_eq!(renderer.cells[1 * 10 + 3].glyph, '@');
+        assert_eq!(renderer.cells[0].glyph, 'h');
+        assert_eq!(renderer.cells[1].glyph, 'p');
+
+        // only changed cells are written again
+        let mut first = Vec::new();
+        renderer.present(&mut first).unwrap();
+        let mut second = Vec::new();
+        renderer.present(&mut second).unwrap();
+        assert!(!first.is_empty());
+        assert!(second.is_empty());
+    }
+}
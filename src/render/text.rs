@@ -0,0 +1,78 @@
+use std::path::Path;
+
+extern crate graphics;
+extern crate opengl_graphics;
+
+use opengl_graphics::{GlGraphics, GlyphCache, TextureSettings};
+use graphics::{Text, Transformed, types::Matrix2d, draw_state::DrawState};
+
+const DEFAULT_FONT: &str = "DejaVuSansMono.ttf";
+
+/// How a line of text is drawn
+#[derive(Debug, Clone, Copy)]
+pub struct TextStyle {
+    // font size in pixels
+    pub size: u32,
+    pub color: [f32; 4]
+}
+
+/// Renders text with a TrueType font loaded from the assets. If the font can not be
+/// loaded, drawing text does nothing instead of failing
+pub struct TextRenderer {
+    glyphs: Option<GlyphCache<'static>>
+}
+
+impl TextRenderer {
+    pub fn new() -> TextRenderer {
+        TextRenderer::from_font(DEFAULT_FONT)
+    }
+
+    pub fn from_font(name: &str) -> TextRenderer {
+        let path = Path::new("./assets/fonts/").join(name);
+        let glyphs = match GlyphCache::new(&path, (), TextureSettings::new()) {
+            Ok(glyphs) => Some(glyphs),
+            Err(err) => {
+                warn!("Could not load font {:?}: {}", path, err);
+                None
+            }
+        };
+        TextRenderer {
+            glyphs
+        }
+    }
+
+    /// Draws a line of text
+    ///
+    /// ### Arguments
+    /// * `text`      - The text to draw
+    /// * `x`         - The x coordinate (in pixels) of the left edge of the text
+    /// * `y`         - The y coordinate (in pixels) of the top edge of the text
+    /// * `style`     - The font size and color of the text
+    /// * `transform` - The transform of the current drawing context
+    /// * `gl`        - The graphics backend to draw with
+    ///
+    pub fn draw_text(&mut self, text: &str, x: f64, y: f64, style: TextStyle, transform: Matrix2d, gl: &mut GlGraphics) {
+        if let Some(glyphs) = &mut self.glyphs {
+            // text is drawn on its baseline, move it down to draw from the top edge
+            let transform = transform.trans(x, y + style.size as f64);
+            if let Err(err) = Text::new_color(style.color, style.size).draw(text, glyphs, &DrawState::default(), transform, gl) {
+                warn!("Could not draw text {:?}: {}", text, err);
+            }
+        }
+    }
+
+    /// Computes the width (in pixels) the text would have when drawn
+    pub fn text_width(&mut self, text: &str, size: u32) -> f64 {
+        use graphics::CharacterCache;
+        match &mut self.glyphs {
+            Some(glyphs) => glyphs.width(size, text).unwrap_or(0.0),
+            None => 0.0
+        }
+    }
+}
+
+impl Default for TextRenderer {
+    fn default() -> TextRenderer {
+        TextRenderer::new()
+    }
+}
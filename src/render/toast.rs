@@ -0,0 +1,58 @@
+use crate::ecs;
+use crate::render::renderer::Renderer;
+use crate::UPDATES_PER_SECOND;
+
+// seconds a toast is shown
+const TOAST_SECONDS: u64 = 4;
+// toasts shown at once, later ones wait for their turn
+const VISIBLE_TOASTS: usize = 3;
+const TOAST_WIDTH: f64 = 280.0;
+const TOAST_HEIGHT: f64 = 28.0;
+const MARGIN: f64 = 6.0;
+const TEXT_SIZE: u32 = 14;
+const BACKGROUND: [f32; 4] = [0.1, 0.1, 0.15, 0.85];
+const TEXT_COLOR: [f32; 4] = [1.0, 0.85, 0.3, 1.0];
+
+/// A short notification, e.g. about an unlocked achievement
+#[derive(Debug, Clone)]
+struct Toast {
+    text: String,
+    // game updates left to show it
+    remaining: u64
+}
+
+/// Notifications shown for a few seconds in the top right corner
+#[derive(Debug, Clone, Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>
+}
+
+impl Toasts {
+    pub fn push(&mut self, text: String) {
+        self.toasts.push(Toast { text, remaining: TOAST_SECONDS * UPDATES_PER_SECOND });
+    }
+
+    /// Counts down the shown toasts by one update and removes expired ones
+    pub fn update(&mut self) {
+        for toast in self.toasts.iter_mut().take(VISIBLE_TOASTS) {
+            toast.remaining = toast.remaining.saturating_sub(1);
+        }
+        self.toasts.retain(|toast| toast.remaining > 0);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+}
+
+/// Renders the current toasts on top of any screen
+pub fn render_toasts(renderer: &mut dyn Renderer, ecs_: &ecs::ECS) {
+    let width = renderer.size()[0];
+    renderer.set_camera(None);
+    for (i, toast) in ecs_.toasts.toasts.iter().take(VISIBLE_TOASTS).enumerate() {
+        let x = width - TOAST_WIDTH - MARGIN;
+        let y = MARGIN + i as f64 * (TOAST_HEIGHT + MARGIN);
+        renderer.draw_rect(BACKGROUND, [x, y, TOAST_WIDTH, TOAST_HEIGHT]);
+        renderer.draw_text(&toast.text, x + MARGIN, y + (TOAST_HEIGHT - TEXT_SIZE as f64) / 2.0, TEXT_SIZE, TEXT_COLOR);
+    }
+}
@@ -0,0 +1,26 @@
+use crate::ecs;
+use crate::gamestate::mode::TransitionEffect;
+use crate::render::{RenderConfig, renderer::Renderer};
+
+/// Renders the current transition as a full screen quad on top of everything else
+pub fn render_transition(renderer: &mut dyn Renderer, ecs_: &ecs::ECS, conf: &RenderConfig) {
+    let transition = match &ecs_.transition {
+        Some(transition) => transition,
+        None => return
+    };
+    let coverage = transition.coverage();
+    let width = conf.window_xs as f64;
+    let height = conf.window_ys as f64;
+
+    renderer.set_camera(None);
+    match transition.effect {
+        TransitionEffect::Fade => {
+            renderer.draw_rect([0.0, 0.0, 0.0, coverage as f32], [0.0, 0.0, width, height]);
+        },
+        TransitionEffect::Wipe => {
+            // covers from the left and uncovers towards the right
+            let x = if transition.progress() < 0.5 { 0.0 } else { width * (1.0 - coverage) };
+            renderer.draw_rect([0.0, 0.0, 0.0, 1.0], [x, 0.0, width * coverage, height]);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+extern crate piston;
+extern crate graphics;
+extern crate opengl_graphics;
+extern crate rand;
+
+use piston::input::RenderArgs;
+use opengl_graphics::GlGraphics;
+use graphics::{line, rectangle};
+use rand::Rng;
+
+use crate::gamestate::weather::Weather;
+use crate::render::RenderConfig;
+
+// maximum amount of rain drops or snow flakes on screen
+const MAX_DROPS: usize = 300;
+// drops spawned per update
+const DROPS_PER_UPDATE: usize = 20;
+
+/// A rain drop or snow flake in screen (pixel) coordinates
+struct Drop {
+    x: f64,
+    y: f64,
+    dx: f64,
+    dy: f64
+}
+
+/// Screen space overlay rendering the weather of the current floor on top of the game.
+/// The drops are purely cosmetic and not part of the game state
+pub struct WeatherOverlay {
+    drops: Vec<Drop>,
+    weather: Weather
+}
+
+impl WeatherOverlay {
+    pub fn new() -> WeatherOverlay {
+        WeatherOverlay {
+            drops: Vec::new(),
+            weather: Weather::Clear
+        }
+    }
+
+    /// Moves all drops and spawns new ones. Should be called once per game update
+    ///
+    /// ### Arguments
+    /// * `weather` - The weather of the current floor
+    /// * `conf`    - The render config, used for the window size
+    ///
+    pub fn update(&mut self, weather: Weather, conf: &RenderConfig) {
+        if weather != self.weather {
+            self.drops.clear();
+            self.weather = weather;
+        }
+
+        let width = conf.window_xs as f64;
+        let height = conf.window_ys as f64;
+
+        for drop in &mut self.drops {
+            drop.x += drop.dx;
+            drop.y += drop.dy;
+        }
+        self.drops.retain(|drop| drop.y < height && drop.x > -20.0 && drop.x < width + 20.0);
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..DROPS_PER_UPDATE {
+            if self.drops.len() >= MAX_DROPS {
+                break;
+            }
+            match weather {
+                Weather::Rain => self.drops.push(Drop {
+                    x: rng.gen_range(0.0..width + 100.0),
+                    y: rng.gen_range(-50.0..0.0),
+                    dx: -4.0,
+                    dy: rng.gen_range(25.0..35.0)
+                }),
+                Weather::Snow => self.drops.push(Drop {
+                    x: rng.gen_range(0.0..width),
+                    y: rng.gen_range(-20.0..0.0),
+                    dx: rng.gen_range(-1.0..1.0),
+                    dy: rng.gen_range(1.5..3.0)
+                }),
+                _ => break
+            }
+        }
+    }
+
+    pub fn render(&self, gl: &mut GlGraphics, args: &RenderArgs, conf: &RenderConfig) {
+        let weather = self.weather;
+        let drops = &self.drops;
+        gl.draw(args.viewport(), |c, gl| {
+            match weather {
+                Weather::Rain => {
+                    for drop in drops {
+                        line([0.6, 0.7, 0.9, 0.6], 1.0, [drop.x, drop.y, drop.x + drop.dx, drop.y + drop.dy / 2.0], c.transform, gl);
+                    }
+                },
+                Weather::Snow => {
+                    for drop in drops {
+                        rectangle([1.0, 1.0, 1.0, 0.8], [drop.x, drop.y, 3.0, 3.0], c.transform, gl);
+                    }
+                },
+                Weather::Fog => {
+                    rectangle([0.7, 0.7, 0.75, 0.35], [0.0, 0.0, conf.window_xs as f64, conf.window_ys as f64], c.transform, gl);
+                },
+                Weather::Clear => {}
+            }
+        });
+    }
+}
+
+impl Default for WeatherOverlay {
+    fn default() -> WeatherOverlay {
+        WeatherOverlay::new()
+    }
+}
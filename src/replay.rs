@@ -0,0 +1,240 @@
+extern crate serde;
+extern crate serde_yaml;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Serialize, Deserialize};
+
+use crate::config::{Difficulty, InputAction};
+use crate::ecs::ECS;
+use crate::error::Result;
+use crate::gamelogic::targeting;
+use crate::gamestate::actor::Scheduling;
+use crate::gamestate::class::Class;
+use crate::gamestate::item::Item;
+use crate::gamestate::movement::DiagonalRule;
+use crate::gamestate::LocationVec;
+
+/// An input action performed by the player and the game update it was performed after
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedInput {
+    pub tick: u64,
+    pub action: InputAction,
+    // the targeting cursor, for actions performed while aiming. The mouse moves it without
+    // an action, so it is recorded with the actions that depend on it
+    #[serde(default)]
+    pub cursor: Option<LocationVec>
+}
+
+impl RecordedInput {
+    /// An action performed now, the cursor is where it is
+    pub fn new(tick: u64, action: InputAction) -> RecordedInput {
+        RecordedInput {
+            tick,
+            action,
+            cursor: None
+        }
+    }
+
+    /// Puts the targeting cursor back where it was when the action was recorded, before
+    /// the action is performed again
+    pub fn restore_cursor(&self, ecs_: &mut ECS) {
+        if let Some(cursor) = self.cursor {
+            targeting::set_cursor(ecs_, cursor);
+        }
+    }
+}
+
+/// The options a game was started with. They come from the settings, the arguments and
+/// the profile of whoever recorded the replay, so playback must not read them locally
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameOptions {
+    pub difficulty: Difficulty,
+    pub diagonal_rule: DiagonalRule,
+    pub scheduling: Scheduling,
+    pub permadeath: bool,
+    pub dev_mode: bool,
+    /// The equipment kept from the last won run, if it is a new-game-plus run
+    pub new_game_plus: Option<Vec<Item>>,
+    pub class: Option<Class>
+}
+
+/// Everything needed to play a session again: the seed of the game's randomness, the
+/// options of the game and all inputs of the player. Inputs are stored as actions, so
+/// replays do not depend on key bindings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    #[serde(default)]
+    pub options: GameOptions,
+    pub inputs: Vec<RecordedInput>
+}
+
+impl Replay {
+    pub fn new(seed: u64) -> Replay {
+        Replay {
+            seed,
+            options: GameOptions::default(),
+            inputs: Vec::new()
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Replay> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_yaml::from_str(&content)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_yaml::to_string(self)?;
+        Ok(fs::write(path, content)?)
+    }
+}
+
+/// Records the inputs of a session. The replay is written when the recorder is
+/// dropped, which also happens when the game panics, so crashes can be reproduced
+pub struct ReplayRecorder {
+    replay: Replay,
+    path: PathBuf
+}
+
+impl ReplayRecorder {
+    pub fn new(seed: u64, options: GameOptions, path: PathBuf) -> ReplayRecorder {
+        ReplayRecorder {
+            replay: Replay { options, ..Replay::new(seed) },
+            path
+        }
+    }
+
+    /// Records an action before it is performed, together with the targeting cursor.
+    /// Opening the settings is not recorded, as the settings menu is not part of the game
+    pub fn record(&mut self, input: &RecordedInput, ecs_: &ECS) {
+        if input.action == InputAction::Settings {
+            return;
+        }
+        let cursor = ecs_.targeting.map(|targeting| targeting.cursor);
+        self.replay.inputs.push(RecordedInput { cursor, ..input.clone() });
+    }
+}
+
+impl Drop for ReplayRecorder {
+    fn drop(&mut self) {
+        match self.replay.save(&self.path) {
+            Ok(()) => info!("Saved replay with {} inputs to {:?}", self.replay.inputs.len(), self.path),
+            Err(err) => error!("Could not save replay to {:?}: {}", self.path, err)
+        }
+    }
+}
+
+/// Feeds the inputs of a replay back in at the game updates they were recorded after
+pub struct ReplayPlayer {
+    replay: Replay,
+    // index of the next input to play
+    next: usize
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> ReplayPlayer {
+        ReplayPlayer {
+            replay,
+            next: 0
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.replay.seed
+    }
+
+    pub fn options(&self) -> &GameOptions {
+        &self.replay.options
+    }
+
+    /// Takes all inputs recorded up to and including a game update, in recording order
+    pub fn inputs_until(&mut self, tick: u64) -> Vec<RecordedInput> {
+        let mut inputs = Vec::new();
+        while let Some(input) = self.replay.inputs.get(self.next) {
+            if input.tick > tick {
+                break;
+            }
+            inputs.push(input.clone());
+            self.next += 1;
+        }
+        inputs
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.replay.inputs.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::dungeon;
+    use crate::gamestate::{components::CasterComponent, spell::Ability};
+
+    fn aiming_game() -> ECS {
+        let mut ecs_ = ECS::new();
+        dungeon::create_test_floor(&mut ecs_);
+        let player = dungeon::create_test_player(&mut ecs_, 1.0, 5.0);
+        ecs_.caster_component.set(player, CasterComponent { current_mana: 5, maximum_mana: 5, spells: Vec::new(), cooldowns: Vec::new() });
+        assert!(targeting::start_targeting(&mut ecs_, Ability::Firebolt));
+        ecs_
+    }
+
+    #[test]
+    fn test_replay_roundtrip() {
+        let mut replay = Replay::new(42);
+        replay.options.difficulty = Difficulty::Hard;
+        replay.options.permadeath = true;
+        replay.options.class = Some(Class::Mage);
+        replay.inputs.push(RecordedInput::new(3, InputAction::MoveUp));
+        replay.inputs.push(RecordedInput { cursor: Some(LocationVec { x: 4.0, y: 3.0 }), ..RecordedInput::new(3, InputAction::ConfirmTarget) });
+        let content = serde_yaml::to_string(&replay).unwrap();
+        assert_eq!(serde_yaml::from_str::<Replay>(&content).unwrap(), replay);
+    }
+
+    #[test]
+    fn test_replays_without_options_use_the_defaults() {
+        let replay: Replay = serde_yaml::from_str("seed: 7\ninputs: []\n").unwrap();
+        assert_eq!(replay, Replay::new(7));
+    }
+
+    #[test]
+    fn test_player_feeds_actions_by_tick() {
+        let mut replay = Replay::new(1);
+        replay.inputs.push(RecordedInput::new(2, InputAction::MoveUp));
+        replay.inputs.push(RecordedInput::new(2, InputAction::Wait));
+        replay.inputs.push(RecordedInput::new(5, InputAction::Attack));
+        let mut player = ReplayPlayer::new(replay);
+
+        let actions = |inputs: Vec<RecordedInput>| inputs.into_iter().map(|input| input.action).collect::<Vec<_>>();
+        assert!(player.inputs_until(1).is_empty());
+        assert_eq!(actions(player.inputs_until(2)), vec![InputAction::MoveUp, InputAction::Wait]);
+        assert!(player.inputs_until(4).is_empty());
+        assert!(!player.is_finished());
+        assert_eq!(actions(player.inputs_until(5)), vec![InputAction::Attack]);
+        assert!(player.is_finished());
+    }
+
+    #[test]
+    fn test_replays_aim_where_the_mouse_was() {
+        let path = std::env::temp_dir().join("rustac_test_mouse_aiming.yaml");
+        let mut ecs_ = aiming_game();
+        let aimed = LocationVec { x: 3.0, y: 4.0 };
+        targeting::set_cursor(&mut ecs_, aimed);
+        let mut recorder = ReplayRecorder::new(1, GameOptions::default(), path.clone());
+        recorder.record(&RecordedInput::new(0, InputAction::ConfirmTarget), &ecs_);
+        drop(recorder);
+
+        let mut player = ReplayPlayer::new(Replay::load(&path).unwrap());
+        let mut replayed = aiming_game();
+        assert_ne!(replayed.targeting.unwrap().cursor, aimed);
+        for input in player.inputs_until(0) {
+            input.restore_cursor(&mut replayed);
+        }
+        assert_eq!(replayed.targeting.unwrap().cursor, aimed);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::{cursor, event, execute, terminal};
+use crossterm::event::{Event, KeyCode};
+use piston::input::{Button, Key};
+
+use crate::ecs::ECS;
+use crate::event::bus::GameEvent;
+use crate::gamelogic;
+use crate::gamestate::mode::GameMode;
+#[cfg(feature = "hot-reload")]
+use crate::hotreload::{self, AssetChange, AssetWatcher};
+use crate::input;
+#[cfg(feature = "hot-reload")]
+use crate::locale::Locale;
+use crate::logging;
+use crate::persistence::{profile, slots};
+use crate::render::{render_game, console, cutscene, gameover, hud, inspector, overworld, preview, profiler, saves, settings, targeting, toast, transition, RenderConfig};
+use crate::render::terminal::{TerminalRenderer, CELL_SIZE};
+use crate::replay::{RecordedInput, ReplayPlayer, ReplayRecorder};
+use crate::UPDATES_PER_SECOND;
+
+/// Runs the game in the terminal until Escape is pressed. The map and the minimap are
+/// only drawn in the window, the dungeon is shown instead
+///
+/// ### Arguments
+/// * `ecs_`            - The game to run
+/// * `replay_player`   - Replay whose inputs are used instead of the keyboard's
+/// * `replay_recorder` - Records the inputs of the session
+///
+pub fn run(ecs_: &mut ECS, mut replay_player: Option<ReplayPlayer>, mut replay_recorder: Option<ReplayRecorder>) {
+    let mut stdout = io::stdout();
+    if let Err(err) = enter(&mut stdout) {
+        error!("Could not set up the terminal: {}", err);
+        let _ = leave(&mut stdout);
+        return;
+    }
+    // log lines would be written over the game, they only go to the log file
+    let console_log = logging::set_console(false);
+
+    let (columns, rows) = terminal::size().unwrap_or((80, 24));
+    let mut renderer = TerminalRenderer::new(columns, rows);
+    // only texts are shown in the terminal, so only they are reloaded
+    #[cfg(feature = "hot-reload")]
+    let asset_watcher = AssetWatcher::new(std::path::Path::new(hotreload::ASSETS_DIR))
+        .map_err(|err| warn!("Assets are not reloaded when they change: {}", err))
+        .ok();
+    let mut render_conf = RenderConfig {
+        scale: CELL_SIZE,
+        window_xs: (columns as f64 * CELL_SIZE) as u32,
+        window_ys: (rows as f64 * CELL_SIZE) as u32,
+        focused_entity: ecs_.get_player_entity(),
+        focused_point: None,
+        show_minimap: false
+    };
+
+    let update_duration = Duration::from_secs_f64(1.0 / UPDATES_PER_SECOND as f64);
+    let mut next_update = Instant::now();
+    // number of game updates so far, recorded inputs refer to it
+    let mut tick: u64 = 0;
+
+    'game: loop {
+        // keys are read until the next update is due
+        let mut actions = Vec::new();
+        loop {
+            let timeout = next_update.saturating_duration_since(Instant::now());
+            match event::poll(timeout) {
+                Ok(true) => {},
+                Ok(false) => break,
+                Err(err) => {
+                    error!("Could not read from the terminal: {}", err);
+                    break 'game;
+                }
+            }
+            match event::read() {
+                Ok(Event::Key(key_event)) if key_event.code == KeyCode::Esc => break 'game,
+                Ok(Event::Key(key_event)) => {
+                    if replay_player.is_some() {
+                        continue;
+                    }
+                    if let Some(key) = piston_key(key_event.code) {
+                        actions.extend(input::keys::button_action(&Button::Keyboard(key), ecs_).map(|action| RecordedInput::new(tick, action)));
+                    }
+                },
+                Ok(Event::Resize(columns, rows)) => {
+                    renderer.resize(columns, rows);
+                    render_conf.resize([columns as f64 * CELL_SIZE, rows as f64 * CELL_SIZE]);
+                },
+                Ok(_) => {},
+                Err(err) => {
+                    error!("Could not read from the terminal: {}", err);
+                    break 'game;
+                }
+            }
+        }
+        next_update += update_duration;
+        let _span = logging::span("update", tick, update_duration);
+
+        // while a replay is played, only its inputs are used
+        if let Some(player) = &mut replay_player {
+            actions = player.inputs_until(tick);
+            if player.is_finished() {
+                replay_player = None;
+            }
+        }
+        for recorded in actions {
+            recorded.restore_cursor(ecs_);
+            if let Some(recorder) = &mut replay_recorder {
+                recorder.record(&recorded, ecs_);
+            }
+            input::handle_action(recorded.action, ecs_, &mut render_conf);
+        }
+        render_conf.update_focus(ecs_);
+
+        #[cfg(feature = "hot-reload")]
+        for change in asset_watcher.iter().flat_map(|watcher| watcher.changes()) {
+            if let AssetChange::Locale(language) = change {
+                if language == ecs_.locale.language {
+                    ecs_.locale = Locale::load(&language);
+                }
+            }
+        }
+
+        let time_scale = ecs_.time_scale.scale();
+        let game_events = gamelogic::update(ecs_);
+        if gamelogic::is_simulating(ecs_) {
+            ecs_.particles.update(time_scale / UPDATES_PER_SECOND as f64);
+        }
+        if game_events.iter().any(|event| matches!(event, GameEvent::FloorChanged)) {
+            slots::autosave(ecs_);
+        }
+        if game_events.iter().any(|event| matches!(event, GameEvent::RunEnded { .. })) {
+            slots::on_run_ended(ecs_);
+            if let Err(err) = ecs_.profile.save() {
+                warn!("Could not save the profile to {:?}: {}", profile::profile_path(), err);
+            }
+        }
+        tick += 1;
+
+        let start = Instant::now();
+        match ecs_.game_mode {
+            GameMode::Dungeon | GameMode::Map => {
+                render_game(&mut renderer, ecs_, &render_conf, 1.0);
+                hud::render_hud(&mut renderer, ecs_);
+                targeting::render_targeting(&mut renderer, ecs_, &render_conf, 1.0);
+                preview::render_damage_preview(&mut renderer, ecs_, &render_conf, 1.0, None);
+                inspector::render_inspector(&mut renderer, ecs_, &render_conf, 1.0);
+                cutscene::render_cutscene(&mut renderer, ecs_);
+            },
+            GameMode::Settings => settings::render_settings(&mut renderer, ecs_, &render_conf),
+            GameMode::LoadMenu => saves::render_load_menu(&mut renderer, ecs_, &render_conf),
+            GameMode::GameOver => gameover::render_game_over(&mut renderer, ecs_, &render_conf),
+            GameMode::Overworld => overworld::render_overworld(&mut renderer, ecs_, 1.0)
+        }
+        toast::render_toasts(&mut renderer, ecs_);
+        transition::render_transition(&mut renderer, ecs_, &render_conf);
+        console::render_cheat_console(&mut renderer, ecs_);
+        ecs_.profiler.record_frame(start.elapsed());
+        profiler::render_profiler(&mut renderer, ecs_);
+        if let Err(err) = renderer.present(&mut stdout) {
+            error!("Could not draw to the terminal: {}", err);
+            break;
+        }
+    }
+
+    if let Err(err) = leave(&mut stdout) {
+        error!("Could not restore the terminal: {}", err);
+    }
+    logging::set_console(console_log);
+    slots::compact_autosave(ecs_);
+}
+
+fn enter<W: Write>(out: &mut W) -> io::Result<()> {
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)
+}
+
+fn leave<W: Write>(out: &mut W) -> io::Result<()> {
+    execute!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()
+}
+
+/// The key of the window a terminal key corresponds to, so key bindings work the same
+fn piston_key(code: KeyCode) -> Option<Key> {
+    let key = match code {
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        KeyCode::Left => Key::Left,
+        KeyCode::Right => Key::Right,
+        KeyCode::Enter => Key::Return,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Tab => Key::Tab,
+        KeyCode::F(number) => match number {
+            1 => Key::F1,
+            2 => Key::F2,
+            3 => Key::F3,
+            4 => Key::F4,
+            5 => Key::F5,
+            6 => Key::F6,
+            7 => Key::F7,
+            8 => Key::F8,
+            9 => Key::F9,
+            10 => Key::F10,
+            11 => Key::F11,
+            12 => Key::F12,
+            _ => return None
+        },
+        // piston's codes of printable keys are their lower case ASCII characters
+        KeyCode::Char(c) if c.is_ascii() => Key::from(c.to_ascii_lowercase() as u32),
+        _ => return None
+    };
+    Some(key)
+}
@@ -0,0 +1,327 @@
+use std::time::{Duration, Instant};
+
+use piston::window::{Window as _, WindowSettings};
+use glutin_window::GlutinWindow as Window;
+use opengl_graphics::{GlGraphics, OpenGL};
+use piston::event_loop::*;
+use piston::input::*;
+
+use crate::assets::AssetScope;
+#[cfg(feature = "audio")]
+use crate::audio;
+use crate::config;
+use crate::ecs::ECS;
+use crate::error::{Error, Result};
+use crate::event;
+use crate::gamelogic;
+use crate::gamestate::mode::GameMode;
+#[cfg(feature = "hot-reload")]
+use crate::hotreload::{self, AssetChange, AssetWatcher};
+use crate::input;
+#[cfg(feature = "hot-reload")]
+use crate::locale::Locale;
+use crate::logging;
+use crate::net::{client::Client, RemoteAction};
+use crate::persistence::{profile, slots};
+use crate::render::{game_camera, render_game, gl::GlRenderer, console, cutscene, fps, gameover, hud, inspector, map, minimap, overworld, preview, profiler, saves, settings, sprite, targeting, text, toast, transition, weather, RenderConfig};
+use crate::replay::{RecordedInput, ReplayPlayer, ReplayRecorder};
+use crate::UPDATES_PER_SECOND;
+
+/// Runs the game in a window until it is closed
+///
+/// ### Arguments
+/// * `ecs_`            - The game to run
+/// * `replay_player`   - Replay whose inputs are used instead of the keyboard's
+/// * `replay_recorder` - Records the inputs of the session
+/// * `net_client`      - Connection to the server in multiplayer games
+///
+pub fn run(ecs_: &mut ECS, mut replay_player: Option<ReplayPlayer>, mut replay_recorder: Option<ReplayRecorder>, mut net_client: Option<Client>) {
+    let mut render_conf = RenderConfig {
+        scale: ecs_.settings.display.scale,
+        window_xs: ecs_.settings.display.window_width,
+        window_ys: ecs_.settings.display.window_height,
+        focused_entity: ecs_.get_player_entity(),
+        focused_point: None,
+        show_minimap: true
+    };
+
+    info!("Creating window with {:?}", render_conf);
+    
+    // setup of opengl window
+    let opengl  = OpenGL::V3_2;
+    let mut window = match create_window(&ecs_.settings.display, opengl) {
+        Ok(window) => window,
+        Err(err) => {
+            error!("{}", err);
+            return;
+        }
+    };
+    // display settings the window was created with
+    let mut window_display = ecs_.settings.display.clone();
+
+    let mut gl = GlGraphics::new(opengl);
+    let mut sprite_textures = sprite::setup_sprite_textures(&ecs_.animations);
+    let mut weather_overlay = weather::WeatherOverlay::new();
+    let mut text_renderer = text::TextRenderer::new();
+    #[cfg(feature = "audio")]
+    let mut audio_system = audio::AudioSystem::new(audio::sound::setup_sound_registry());
+    let mut fps_counter = fps::FpsCounter::new();
+    // changed textures, sounds and texts are loaded again while playing
+    #[cfg(feature = "hot-reload")]
+    let asset_watcher = AssetWatcher::new(std::path::Path::new(hotreload::ASSETS_DIR))
+        .map_err(|err| warn!("Assets are not reloaded when they change: {}", err))
+        .ok();
+
+    //Main loop
+
+    // rendering runs at its own rate, game logic always at UPDATES_PER_SECOND
+    let mut events = Events::new(EventSettings::new());
+    events.set_ups(UPDATES_PER_SECOND);
+    events.set_max_fps(ecs_.settings.display.max_fps);
+
+    let mut alt_held = false;
+    let mut move_chord = input::keys::MoveChord::default();
+    // position of the mouse in the window, in pixels
+    let mut cursor = [0.0, 0.0];
+    // number of game updates so far, recorded inputs refer to it
+    let mut tick: u64 = 0;
+    let mut frame: u64 = 0;
+
+    while let Some(e) = events.next(&mut window) {
+
+        // Alt+Enter toggles fullscreen, independent of the game state
+        match (e.press_args(), e.release_args()) {
+            (Some(Button::Keyboard(Key::LAlt)), _) | (Some(Button::Keyboard(Key::RAlt)), _) => alt_held = true,
+            (_, Some(Button::Keyboard(Key::LAlt))) | (_, Some(Button::Keyboard(Key::RAlt))) => alt_held = false,
+            (Some(Button::Keyboard(Key::Return)), _) if alt_held => {
+                ecs_.settings.display.fullscreen = !ecs_.settings.display.fullscreen;
+                continue;
+            },
+            _ => {}
+        }
+
+        if let Some(position) = e.mouse_cursor_args() {
+            cursor = position;
+            // while aiming, the mouse moves the cursor as well
+            if ecs_.targeting.is_some() {
+                gamelogic::targeting::set_cursor(ecs_, game_camera(ecs_, &render_conf, 1.0).to_world(cursor));
+            }
+        }
+        // clicking an entity shows its components in the inspector, while aiming it confirms the target
+        let mut clicked = false;
+        if let Some(Button::Mouse(MouseButton::Left)) = e.press_args() {
+            clicked = ecs_.targeting.is_some();
+            if ecs_.inspector.visible && ecs_.game_mode == GameMode::Dungeon && !clicked {
+                let location = game_camera(ecs_, &render_conf, 1.0).to_world(cursor);
+                inspector::select_at(ecs_, location);
+            }
+        }
+
+        // while a replay is played, only its inputs are used
+        let mut actions = Vec::new();
+        if let Some(player) = &mut replay_player {
+            actions = player.inputs_until(tick);
+            if player.is_finished() {
+                info!("Replay finished");
+                replay_player = None;
+            }
+        } else if let Some(p) = e.press_args() {
+            actions.extend(input::keys::button_action(&p, ecs_).map(|action| RecordedInput::new(tick, move_chord.press(action))));
+        } else if let Some(Button::Keyboard(key)) = e.release_args() {
+            if let Some(action) = ecs_.settings.action_for(key) {
+                move_chord.release(action);
+            }
+        } else if e.update_args().is_some() {
+            // held move keys keep the player walking
+            actions.extend(move_chord.repeat(ecs_).map(|action| RecordedInput::new(tick, action)));
+        }
+        if clicked && replay_player.is_none() {
+            actions.push(RecordedInput::new(tick, config::InputAction::ConfirmTarget));
+        }
+        for recorded in actions {
+            let action = recorded.action;
+            debug!("{:?}", action);
+            // in multiplayer games the server performs the actions of the character
+            if let Some(client) = &mut net_client {
+                if action.is_game_action() && ecs_.targeting.is_none() {
+                    client.send_action(RemoteAction::Input(action));
+                    continue;
+                }
+                // aiming is local, the server gets the aimed ability once it is confirmed
+                if ecs_.game_mode == GameMode::Dungeon && !ecs_.paused && input::is_aiming_action(action, ecs_) {
+                    if let Some(remote_action) = input::aiming_controls(action, ecs_).and_then(RemoteAction::of) {
+                        client.send_action(remote_action);
+                    }
+                    continue;
+                }
+            }
+            // replayed actions aim where they were recorded, the mouse moves the cursor without an action
+            recorded.restore_cursor(ecs_);
+            if let Some(recorder) = &mut replay_recorder {
+                recorder.record(&recorded, ecs_);
+            }
+            input::handle_action(action, ecs_, &mut render_conf);
+        }
+        render_conf.update_focus(ecs_);
+
+        // fullscreen or vsync was changed, either by Alt+Enter or in the settings menu
+        if ecs_.settings.display.needs_new_window(&window_display) {
+            window_display = ecs_.settings.display.clone();
+            info!("Recreating window with {:?}", window_display);
+            // everything uploaded to the GL context of the old window is freed while that
+            // context is still current, then loaded again into the context of the new one
+            sprite_textures.unload_all();
+            drop(text_renderer);
+            drop(gl);
+            drop(window);
+            window = match create_window(&ecs_.settings.display, opengl) {
+                Ok(window) => window,
+                Err(err) => {
+                    error!("{}", err);
+                    break;
+                }
+            };
+            gl = GlGraphics::new(opengl);
+            text_renderer = text::TextRenderer::new();
+            let size = window.size();
+            render_conf.resize([size.width, size.height]);
+            continue;
+        }
+
+        if let Some(args) = e.resize_args() {
+            render_conf.resize(args.window_size);
+            // the windowed size is remembered for the next start
+            if !window_display.fullscreen {
+                ecs_.settings.display.window_width = render_conf.window_xs;
+                ecs_.settings.display.window_height = render_conf.window_ys;
+            }
+        }
+
+        if e.update_args().is_some() {
+            let _span = logging::span("update", tick, Duration::from_secs_f64(1.0 / UPDATES_PER_SECOND as f64));
+            // settings may have been changed in the settings menu
+            render_conf.scale = ecs_.settings.display.scale;
+            #[cfg(feature = "audio")]
+            audio_system.set_volumes(&ecs_.settings.volume);
+            if events.get_event_settings().max_fps != ecs_.settings.display.max_fps {
+                events.set_max_fps(ecs_.settings.display.max_fps);
+            }
+            fps_counter.update();
+            #[cfg(feature = "hot-reload")]
+            for change in asset_watcher.iter().flat_map(|watcher| watcher.changes()) {
+                match change {
+                    AssetChange::Texture(file) => {
+                        sprite::reload_texture(&mut sprite_textures, &file);
+                        ecs_.animations.reload(&file);
+                    },
+                    #[cfg(feature = "audio")]
+                    AssetChange::Sound(file) => audio_system.registry_mut().reload(&file),
+                    AssetChange::Locale(language) if language == ecs_.locale.language => ecs_.locale = Locale::load(&language),
+                    _ => {}
+                }
+            }
+
+            // the dungeon is paused in all other modes and during transitions
+            let time_scale = ecs_.time_scale.scale();
+            let game_events = match &mut net_client {
+                Some(client) => {
+                    if !client.update(ecs_) {
+                        error!("Lost connection to the server");
+                        break;
+                    }
+                    // only local screens and the map of the mirrored world are updated
+                    gamelogic::update_transition(ecs_);
+                    gamelogic::update_explored(ecs_);
+                    gamelogic::process_events(ecs_)
+                },
+                None => gamelogic::update(ecs_)
+            };
+            if gamelogic::is_simulating(ecs_) {
+                weather_overlay.update(ecs_.weather, &render_conf);
+                ecs_.particles.update(time_scale / UPDATES_PER_SECOND as f64);
+            }
+            if game_events.iter().any(|event| matches!(event, event::bus::GameEvent::FloorChanged)) {
+                slots::autosave(ecs_);
+                // textures of the previous floor are loaded again once something uses them
+                sprite_textures.unload(AssetScope::Floor);
+            }
+            if game_events.iter().any(|event| matches!(event, event::bus::GameEvent::RunEnded { .. })) {
+                slots::on_run_ended(ecs_);
+                if let Err(err) = ecs_.profile.save() {
+                    warn!("Could not save the profile to {:?}: {}", profile::profile_path(), err);
+                }
+            }
+            #[cfg(feature = "audio")]
+            {
+                audio_system.play_events(ecs_, &game_events, render_conf.focused_entity);
+                audio_system.update_music(ecs_, 1.0 / UPDATES_PER_SECOND as f32);
+            }
+            tick += 1;
+        }
+
+        if let Some(r) = e.render_args() {
+            let _span = logging::span("frame", frame, Duration::from_secs_f64(1.0 / ecs_.settings.display.max_fps.max(1) as f64));
+            frame += 1;
+            let start = Instant::now();
+            let gl = &mut gl;
+            match ecs_.game_mode {
+                GameMode::Dungeon => {
+                    // time since the last update, relative to the time between updates
+                    let alpha = (r.ext_dt * UPDATES_PER_SECOND as f64).min(1.0);
+                    render_game(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_, &render_conf, alpha);
+                    weather_overlay.render(gl, &r, &render_conf);
+                    hud::render_hud(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_);
+                    minimap::render_minimap(gl, &r, ecs_, &render_conf);
+                    targeting::render_targeting(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_, &render_conf, alpha);
+                    let hovered = game_camera(ecs_, &render_conf, alpha).to_world(cursor);
+                    preview::render_damage_preview(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_, &render_conf, alpha, Some(hovered));
+                    inspector::render_inspector(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_, &render_conf, alpha);
+                    cutscene::render_cutscene(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_);
+                },
+                GameMode::Map => {
+                    map::render_map(gl, &r, ecs_, &render_conf);
+                },
+                GameMode::Settings => {
+                    settings::render_settings(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_, &render_conf);
+                },
+                GameMode::LoadMenu => {
+                    saves::render_load_menu(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_, &render_conf);
+                },
+                GameMode::GameOver => {
+                    gameover::render_game_over(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_, &render_conf);
+                },
+                GameMode::Overworld => {
+                    let alpha = (r.ext_dt * UPDATES_PER_SECOND as f64).min(1.0);
+                    overworld::render_overworld(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_, alpha);
+                }
+            }
+            toast::render_toasts(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_);
+            transition::render_transition(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_, &render_conf);
+            fps_counter.frame();
+            if ecs_.settings.display.show_fps {
+                fps_counter.render(gl, &r, &render_conf, &ecs_.locale, &mut text_renderer);
+            }
+            console::render_cheat_console(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_);
+            ecs_.profiler.record_frame(start.elapsed());
+            profiler::render_profiler(&mut GlRenderer::new(gl, &r, &mut sprite_textures, &mut text_renderer), ecs_);
+        }
+
+    }
+
+    // the world of a client is only a partial copy of the server's
+    match &mut net_client {
+        Some(client) => client.leave(),
+        None => slots::compact_autosave(ecs_)
+    }
+}
+
+/// Creates the game window as configured in the display settings
+fn create_window(display: &config::DisplaySettings, opengl: OpenGL) -> Result<Window> {
+    WindowSettings::new("rustac", [display.window_width, display.window_height])
+        .graphics_api(opengl)
+        .fullscreen(display.fullscreen)
+        .vsync(display.vsync)
+        .exit_on_esc(true)
+        .build()
+        .map_err(|err| Error::Window(err.to_string()))
+}
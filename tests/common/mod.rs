@@ -0,0 +1,109 @@
+//! Runs games without a window for end-to-end tests. Inputs take the same way as
+//! keyboard inputs in the game loop: they are played back by `headless::run` as a
+//! replay, which hands them to `input::handle_action`
+
+use rustac::builder;
+use rustac::config::InputAction;
+use rustac::ecs::{ECS, Entity};
+use rustac::gamestate::LocationVec;
+use rustac::gamestate::rng::GameRng;
+use rustac::headless::{self, HeadlessOptions};
+use rustac::replay::{RecordedInput, Replay, ReplayPlayer};
+
+pub use rustac::UPDATES_PER_SECOND;
+
+// tests must not depend on chance, so every game uses the same seed
+pub const TEST_SEED: u64 = 4;
+
+/// A game driven by scripted inputs
+pub struct TestGame {
+    pub ecs: ECS,
+    // inputs not played yet, their ticks count from the start of the next `run`
+    script: Vec<RecordedInput>
+}
+
+impl TestGame {
+    /// An empty world
+    pub fn new() -> TestGame {
+        let mut ecs_ = ECS::new();
+        ecs_.rng = GameRng::new(TEST_SEED);
+        TestGame {
+            ecs: ecs_,
+            script: Vec::new()
+        }
+    }
+
+    /// The floor of `builder::dungeon::create_test_floor` with its attack dummy at
+    /// (3, 4) and the test player at the given location
+    pub fn on_test_floor(player_x: f64, player_y: f64) -> TestGame {
+        let mut game = TestGame::new();
+        builder::dungeon::create_test_floor(&mut game.ecs);
+        game.spawn(builder::dungeon::create_test_player, player_x, player_y);
+        game
+    }
+
+    /// Creates an entity with one of the builder functions
+    pub fn spawn(&mut self, prefab: fn(&mut ECS, f64, f64) -> Entity, x: f64, y: f64) -> Entity {
+        prefab(&mut self.ecs, x, y)
+    }
+
+    pub fn player(&mut self) -> Entity {
+        self.ecs.get_player_entity().expect("the game has no player")
+    }
+
+    /// Presses a key bound to the action before the first update of the next run
+    pub fn input(&mut self, action: InputAction) -> &mut TestGame {
+        self.input_at(0, action)
+    }
+
+    /// Presses a key bound to the action after some updates of the next run
+    pub fn input_at(&mut self, tick: u64, action: InputAction) -> &mut TestGame {
+        self.script.push(RecordedInput::new(tick, action));
+        self.script.sort_by_key(|input| input.tick);
+        self
+    }
+
+    /// Runs a number of game updates, playing the scripted inputs due in them.
+    /// Without inputs the player does nothing, so turns do not end
+    pub fn run(&mut self, ticks: u64) -> &mut TestGame {
+        let mut replay = Replay::new(TEST_SEED);
+        let later = self.script.iter().position(|input| input.tick >= ticks).unwrap_or(self.script.len());
+        replay.inputs = self.script.drain(..later).collect();
+        for input in self.script.iter_mut() {
+            input.tick -= ticks;
+        }
+
+        let player = if replay.inputs.is_empty() { None } else { Some(ReplayPlayer::new(replay)) };
+        let options = HeadlessOptions { ticks: Some(ticks), realtime: false, player_waits: false };
+        headless::run(&mut self.ecs, player, None, options);
+        self
+    }
+
+    /// Runs one update after another until a condition holds
+    ///
+    /// ### Returns
+    /// True if the condition held within `max_ticks` updates
+    pub fn run_until<F: FnMut(&mut ECS) -> bool>(&mut self, max_ticks: u64, mut condition: F) -> bool {
+        for _ in 0..max_ticks {
+            self.run(1);
+            if condition(&mut self.ecs) {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn location(&self, entity: Entity) -> LocationVec {
+        self.ecs.location_component.get(entity).expect("the entity has no location").location
+    }
+
+    pub fn health(&self, entity: Entity) -> i32 {
+        self.ecs.health_component.get(entity).expect("the entity has no health").current
+    }
+
+    /// The turn the player is in, it advances once every actor is done
+    pub fn turn(&mut self) -> u64 {
+        let player = self.player();
+        self.ecs.actor_component.get(player).expect("the player is no actor").turn
+    }
+}
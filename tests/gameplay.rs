@@ -0,0 +1,155 @@
+extern crate rustac;
+
+mod common;
+
+use common::{TestGame, UPDATES_PER_SECOND};
+use rustac::builder;
+use rustac::config::InputAction;
+use rustac::gamelogic::{self, PlayerAction};
+use rustac::gamestate::{components::{CasterComponent, NpcBehaviorComponent, StatusComponent}, LocationVec};
+use rustac::gamestate::{duration::Duration, spell::Ability, status::{Status, StatusType}};
+use rustac::gamestate::item::{Equipment, Item, ItemType};
+
+#[test]
+fn test_player_moves_one_field() {
+    let mut game = TestGame::on_test_floor(1.0, 1.0);
+    let player = game.player();
+
+    game.input(InputAction::MoveRight).run(UPDATES_PER_SECOND);
+    assert_eq!(game.location(player), LocationVec { x: 2.0, y: 1.0 });
+}
+
+#[test]
+fn test_players_pick_up_equipment_they_walk_over() {
+    let mut game = TestGame::on_test_floor(1.0, 1.0);
+    let player = game.player();
+    let helm = Item { item: ItemType::Equipment(Equipment::Helm), amount: 1 };
+    gamelogic::items::spawn_ground_item(&mut game.ecs, helm.clone(), LocationVec { x: 2.0, y: 1.0 });
+
+    game.input(InputAction::MoveRight).run(UPDATES_PER_SECOND);
+    assert_eq!(game.ecs.inventory_component.get(player).unwrap().items, vec![helm]);
+}
+
+#[test]
+fn test_walls_block_the_player() {
+    let mut game = TestGame::on_test_floor(1.0, 1.0);
+    let player = game.player();
+
+    game.input(InputAction::MoveUp).run(UPDATES_PER_SECOND);
+    assert_eq!(game.location(player), LocationVec { x: 1.0, y: 1.0 });
+    // a blocked move is no action, the turn goes on
+    assert_eq!(game.turn(), 0);
+}
+
+#[test]
+fn test_attack_damages_the_faced_dummy() {
+    // the player faces down, towards the dummy
+    let mut game = TestGame::on_test_floor(6.0, 3.0);
+    let dummy = game.spawn(builder::dungeon::create_attack_dummy, 6.0, 4.0);
+
+    game.input(InputAction::Attack).run(1);
+    assert!(game.health(dummy) < 100);
+}
+
+#[test]
+fn test_moving_into_creatures_attacks_only_hostile_ones() {
+    let mut game = TestGame::on_test_floor(6.0, 3.0);
+    let player = game.player();
+    let dummy = game.spawn(builder::dungeon::create_attack_dummy, 7.0, 3.0);
+
+    // a peaceful creature is just in the way
+    game.input(InputAction::MoveRight).run(UPDATES_PER_SECOND);
+    assert_eq!(game.location(player), LocationVec { x: 6.0, y: 3.0 });
+    assert_eq!(game.health(dummy), 100);
+
+    game.ecs.npc_behavior_component.set(dummy, NpcBehaviorComponent { hostile: true });
+    game.input(InputAction::MoveRight).run(UPDATES_PER_SECOND);
+    assert_eq!(game.location(player), LocationVec { x: 6.0, y: 3.0 });
+    assert!(game.health(dummy) < 100);
+}
+
+#[test]
+fn test_turn_ends_once_the_player_acted() {
+    let mut game = TestGame::on_test_floor(1.0, 1.0);
+    let clock_before = game.ecs.world_clock.turns;
+
+    // without input the dungeon waits for the player
+    game.run(UPDATES_PER_SECOND);
+    assert_eq!(game.turn(), 0);
+
+    game.input(InputAction::MoveRight);
+    assert!(game.run_until(UPDATES_PER_SECOND * 2, |ecs_| {
+        let player = ecs_.get_player_entity().unwrap();
+        ecs_.actor_component.get(player).unwrap().turn == 1
+    }));
+    assert_eq!(game.ecs.world_clock.turns, clock_before + 1);
+
+    // inputs while the player is still acting are performed once it is its turn again
+    game.input(InputAction::MoveRight).input_at(1, InputAction::MoveDown).run(UPDATES_PER_SECOND * 2);
+    let player = game.player();
+    assert_eq!(game.location(player), LocationVec { x: 3.0, y: 2.0 });
+}
+
+#[test]
+fn test_every_player_rests_on_their_own() {
+    let mut game = TestGame::on_test_floor(1.0, 1.0);
+    let host = game.player();
+    let guest = game.spawn(builder::dungeon::create_test_player, 2.0, 1.0);
+    for player in [host, guest] {
+        game.ecs.health_component.get_mut(player).unwrap().current = 50;
+    }
+
+    // turns only pass faster once every player rests
+    gamelogic::perform_action(&mut game.ecs, guest, PlayerAction::Rest);
+    assert!(gamelogic::is_resting(&game.ecs, guest));
+    assert_eq!(game.ecs.time_scale.base, 1.0);
+    gamelogic::perform_action(&mut game.ecs, host, PlayerAction::Rest);
+    assert!(game.ecs.time_scale.base > 1.0);
+
+    game.run(5);
+    assert!(game.health(host) > 50 && game.health(guest) > 50);
+    gamelogic::stop_resting(&mut game.ecs, host);
+    assert!(gamelogic::is_resting(&game.ecs, guest));
+    assert_eq!(game.ecs.time_scale.base, 1.0);
+}
+
+#[test]
+fn test_burning_creatures_die_and_leave_corpses() {
+    let mut game = TestGame::on_test_floor(1.0, 1.0);
+    let rat = game.spawn(builder::dungeon::create_rat, 6.0, 5.0);
+    game.ecs.health_component.get_mut(rat).unwrap().current = gamelogic::BURN_DAMAGE;
+    game.ecs.status_component.set(rat, StatusComponent {
+        status: vec![Status { type_: StatusType::Burning, duration: Duration::Updates(100) }]
+    });
+
+    gamelogic::pass_turn(&mut game.ecs);
+    assert_eq!(game.health(rat), 0);
+    assert!(game.run_until(UPDATES_PER_SECOND * 2, |ecs_| !ecs_.allocator.is_live(rat)));
+    let corpses = game.ecs.get_entities_by_location(LocationVec { x: 6.0, y: 5.0 })
+                      .into_iter()
+                      .filter(|entity| game.ecs.corpse_component.get(*entity).is_some())
+                      .count();
+    assert_eq!(corpses, 1);
+}
+
+#[test]
+fn test_firebolt_targets_burn_to_death() {
+    let mut game = TestGame::on_test_floor(1.0, 5.0);
+    let player = game.player();
+    game.ecs.caster_component.set(player, CasterComponent { current_mana: 5, maximum_mana: 5, spells: Vec::new(), cooldowns: Vec::new() });
+    let rat = game.spawn(builder::dungeon::create_rat, 4.0, 3.0);
+    // the bolt itself does not kill
+    game.ecs.health_component.get_mut(rat).unwrap().current = 1000;
+
+    gamelogic::perform_action(&mut game.ecs, player, PlayerAction::UseAbility(Ability::Firebolt, LocationVec { x: 4.0, y: 3.0 }));
+    assert!(game.health(rat) > 0);
+    game.ecs.health_component.get_mut(rat).unwrap().current = gamelogic::BURN_DAMAGE;
+    gamelogic::pass_turn(&mut game.ecs);
+    assert_eq!(game.health(rat), 0);
+    assert!(game.run_until(UPDATES_PER_SECOND * 2, |ecs_| !ecs_.allocator.is_live(rat)));
+    let corpses = game.ecs.allocator.live_indices()
+                      .into_iter()
+                      .filter(|entity| game.ecs.corpse_component.get(*entity).is_some())
+                      .count();
+    assert_eq!(corpses, 1);
+}